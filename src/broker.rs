@@ -0,0 +1,448 @@
+//! In-process secrets broker, serving a provider over a Unix domain socket.
+//!
+//! Sidecars written in other languages, or forked workers, often need a few secrets but should
+//! not hold cloud credentials of their own. A [SecretsBroker] serves the secrets of an
+//! already-configured provider over a Unix socket, so these processes fetch them through the
+//! credentials of the parent. [BrokerSecretsProvider] is the client of a broker, for Rust
+//! processes.
+//!
+//! The socket is created readable and writable by its owner only (mode `0600`). It is bound in a
+//! private staging directory next to its path and then moved there, so it is never reachable by
+//! other users with looser permissions. Any process able to connect can read every secret of
+//! the served provider. Wrap the provider (for example
+//! in a [ScopedProvider](crate::namespace::ScopedProvider)) to restrict what the broker serves.
+//!
+//! # Protocol
+//!
+//! Each connection carries any number of requests, each one followed by its response. Requests
+//! and responses are frames: a 4 bytes big endian length, followed by that many bytes, at most
+//! 16 MiB. Strings are UTF-8, and `bytes` fields are a 4 bytes big endian length followed by the
+//! content.
+//!
+//! Requests start with an operation byte:
+//!
+//! | Operation | Fields |
+//! |-----------|--------|
+//! | `1`: find the current version | name (`bytes`) |
+//! | `2`: find a version | name (`bytes`), version (`bytes`) |
+//!
+//! Responses start with a status byte:
+//!
+//! | Status | Fields |
+//! |--------|--------|
+//! | `0`: found | type (`0` string, `1` binary), version (`bytes`), value (`bytes`) |
+//! | `1`: not found | |
+//! | `2`: error | error code, message (`bytes`) |
+//!
+//! Error codes are `0` for backend failures, `1` invalid type, `2` invalid name, `3` invalid
+//! version, `4` unsupported operation, `5` quota exceeded, `6` unknown type, `7` already exists
+//! and `8` initialization errors. Malformed requests get an error response, and the connection
+//! is closed.
+use std::io::ErrorKind;
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::task::JoinSet;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, SecretData};
+use crate::{Result, SecretsProvider};
+
+/// Backend of the versions returned by the client.
+const BACKEND: &str = "Secrets broker";
+
+/// Maximum length of a frame.
+const MAX_FRAME_LENGTH: usize = 16 * 1024 * 1024;
+
+const FIND: u8 = 1;
+const FIND_WITH_VERSION: u8 = 2;
+
+const FOUND: u8 = 0;
+const NOT_FOUND: u8 = 1;
+const ERROR: u8 = 2;
+
+const STRING: u8 = 0;
+const BINARY: u8 = 1;
+
+/// Serves the secrets of a provider over a Unix domain socket.
+#[derive(Debug)]
+pub struct SecretsBroker<P> {
+    provider: Arc<P>,
+}
+
+impl<P: SecretsProvider + Send + Sync + 'static> SecretsBroker<P> {
+    /// Creates a broker serving the secrets of a provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Served Secrets Provider.
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider: Arc::new(provider),
+        }
+    }
+
+    /// Served Secrets Provider.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Creates the socket, readable and writable by its owner only, and serves requests until
+    /// the future is dropped. A socket left at the path by a previous broker is replaced; any
+    /// other file fails with an [Initialization](SecretsProviderError::Initialization) error.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the socket.
+    pub async fn listen(self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let init_error = |e: std::io::Error| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to create broker socket {}: {}",
+                path.display(),
+                e
+            ))
+        };
+
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => {
+                std::fs::remove_file(path).map_err(init_error)?
+            }
+            Ok(_) => {
+                return Err(init_error(std::io::Error::new(
+                    ErrorKind::AlreadyExists,
+                    "the path exists and is not a socket",
+                )))
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return Err(init_error(e)),
+        }
+
+        let listener = bind_private(path).map_err(init_error)?;
+        self.serve(listener).await
+    }
+
+    /// Serves requests on a listening socket until the future is dropped, which also closes the
+    /// open connections. Fails with a [ProviderFailed](SecretsProviderError::ProviderFailed)
+    /// error if the socket can not accept connections anymore.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - Listening socket.
+    pub async fn serve(self, listener: UnixListener) -> Result<()> {
+        let mut connections = JoinSet::new();
+        loop {
+            let (stream, _) = listener.accept().await.map_err(|e| {
+                SecretsProviderError::ProviderFailed(format!("Broker socket failed: {}", e))
+            })?;
+            // Forget the connections that were closed
+            while connections.try_join_next().is_some() {}
+
+            let provider = self.provider.clone();
+            connections.spawn(async move { serve_connection(provider.as_ref(), stream).await });
+        }
+    }
+}
+
+/// Binds a socket readable and writable by its owner only.
+///
+/// Sockets are created with permissions derived from the umask, so the socket is bound in a new
+/// directory only its owner can enter, made private, and then moved to its path. Other users can
+/// not connect before its permissions are restricted.
+fn bind_private(path: &Path) -> std::io::Result<UnixListener> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "the path has no file name"))?;
+    let mut staging_name = std::ffi::OsString::from(".");
+    staging_name.push(file_name);
+    staging_name.push(format!(".{}.tmp", std::process::id()));
+    let staging = path.with_file_name(staging_name);
+
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let staged = staging.join("socket");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    // The directory is empty once the socket was moved, or left behind by a failure
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&staging);
+    bound
+}
+
+/// Serves the requests of a connection, until it is closed or a request is malformed.
+async fn serve_connection<P: SecretsProvider + Sync>(provider: &P, mut stream: UnixStream) {
+    while let Ok(Some(request)) = read_frame(&mut stream).await {
+        let (response, close) = match handle_request(provider, &request).await {
+            Ok(response) => (response, false),
+            Err(response) => (response, true),
+        };
+        if write_frame(&mut stream, &response).await.is_err() || close {
+            return;
+        }
+    }
+}
+
+/// Looks up the secret of a request. Malformed requests get an error response as `Err`.
+async fn handle_request<P: SecretsProvider + Sync>(
+    provider: &P,
+    request: &[u8],
+) -> std::result::Result<Vec<u8>, Vec<u8>> {
+    let mut reader = FrameReader(request);
+    let lookup = match reader.u8() {
+        Some(FIND) => match reader.string() {
+            Some(name) if reader.is_empty() => provider.find::<SecretData>(name).await,
+            _ => return Err(malformed_request()),
+        },
+        Some(FIND_WITH_VERSION) => match (reader.string(), reader.string()) {
+            (Some(name), Some(version)) if reader.is_empty() => {
+                provider
+                    .find_with_version::<SecretData>(name, version)
+                    .await
+            }
+            _ => return Err(malformed_request()),
+        },
+        _ => return Err(malformed_request()),
+    };
+
+    let mut response = Vec::new();
+    match lookup {
+        Ok(Some(secret)) => {
+            response.push(FOUND);
+            let version = secret.version.clone();
+            let (kind, value) = match secret.reveal() {
+                SecretData::Str(s) => (STRING, s.into_bytes()),
+                SecretData::Bytes(b) => (BINARY, b.into()),
+            };
+            response.push(kind);
+            put_bytes(&mut response, version.as_bytes());
+            put_bytes(&mut response, &value);
+        }
+        Ok(None) => response.push(NOT_FOUND),
+        Err(e) => encode_error(&mut response, e),
+    }
+
+    Ok(response)
+}
+
+fn malformed_request() -> Vec<u8> {
+    let mut response = Vec::new();
+    encode_error(
+        &mut response,
+        SecretsProviderError::ProviderFailed("Malformed broker request".to_string()),
+    );
+    response
+}
+
+fn encode_error(response: &mut Vec<u8>, error: SecretsProviderError) {
+    let (code, message) = match error {
+        SecretsProviderError::ProviderFailed(m) => (0, m),
+        SecretsProviderError::InvalidType(m) => (1, m),
+        SecretsProviderError::InvalidName(m) => (2, m),
+        SecretsProviderError::InvalidVersion(m) => (3, m),
+        SecretsProviderError::Unsupported(m) => (4, m),
+        SecretsProviderError::QuotaExceeded(m) => (5, m),
+        SecretsProviderError::UnknownType(m) => (6, m),
+        SecretsProviderError::AlreadyExists(m) => (7, m),
+        SecretsProviderError::Initialization(m) => (8, m),
+    };
+    response.push(ERROR);
+    response.push(code);
+    put_bytes(response, message.as_bytes());
+}
+
+fn decode_error(code: u8, message: String) -> SecretsProviderError {
+    match code {
+        1 => SecretsProviderError::InvalidType(message),
+        2 => SecretsProviderError::InvalidName(message),
+        3 => SecretsProviderError::InvalidVersion(message),
+        4 => SecretsProviderError::Unsupported(message),
+        5 => SecretsProviderError::QuotaExceeded(message),
+        6 => SecretsProviderError::UnknownType(message),
+        7 => SecretsProviderError::AlreadyExists(message),
+        8 => SecretsProviderError::Initialization(message),
+        _ => SecretsProviderError::ProviderFailed(message),
+    }
+}
+
+/// Reads a frame, or `None` if the connection was closed before a new frame.
+async fn read_frame(stream: &mut UnixStream) -> std::io::Result<Option<Vec<u8>>> {
+    let mut length = [0; 4];
+    match stream.read_exact(&mut length).await {
+        Ok(_) => (),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_LENGTH {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("frame of {} bytes", length),
+        ));
+    }
+    let mut frame = vec![0; length];
+    stream.read_exact(&mut frame).await?;
+    Ok(Some(frame))
+}
+
+async fn write_frame(stream: &mut UnixStream, frame: &[u8]) -> std::io::Result<()> {
+    let length = u32::try_from(frame.len())
+        .ok()
+        .filter(|length| *length as usize <= MAX_FRAME_LENGTH)
+        .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "frame too long"))?;
+    stream.write_all(&length.to_be_bytes()).await?;
+    stream.write_all(frame).await
+}
+
+fn put_bytes(buffer: &mut Vec<u8>, bytes: &[u8]) {
+    // Frames are limited to 16 MiB, so fields always fit
+    buffer.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+/// Reads the fields of a frame.
+struct FrameReader<'a>(&'a [u8]);
+
+impl<'a> FrameReader<'a> {
+    fn u8(&mut self) -> Option<u8> {
+        let (first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(*first)
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let length = self.0.get(..4)?;
+        let length = u32::from_be_bytes(length.try_into().ok()?) as usize;
+        let bytes = self.0.get(4..4 + length)?;
+        self.0 = &self.0[4 + length..];
+        Some(bytes)
+    }
+
+    fn string(&mut self) -> Option<&'a str> {
+        std::str::from_utf8(self.bytes()?).ok()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Client of a [SecretsBroker], connecting to its socket for every lookup.
+#[derive(Clone, Debug)]
+pub struct BrokerSecretsProvider {
+    path: PathBuf,
+}
+
+impl BrokerSecretsProvider {
+    /// Creates a client of the broker listening on a socket. The socket is not opened until the
+    /// first lookup.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the broker socket.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the broker socket.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let mut request = Vec::new();
+        match version {
+            Some(version) => {
+                request.push(FIND_WITH_VERSION);
+                put_bytes(&mut request, name.as_bytes());
+                put_bytes(&mut request, version.as_bytes());
+            }
+            None => {
+                request.push(FIND);
+                put_bytes(&mut request, name.as_bytes());
+            }
+        }
+
+        let broker_error = |e: String| {
+            SecretsProviderError::ProviderFailed(format!(
+                "Broker {} failed: {}",
+                self.path.display(),
+                e
+            ))
+        };
+        let mut stream = UnixStream::connect(&self.path)
+            .await
+            .map_err(|e| broker_error(e.to_string()))?;
+        write_frame(&mut stream, &request)
+            .await
+            .map_err(|e| broker_error(e.to_string()))?;
+        let response = read_frame(&mut stream)
+            .await
+            .map_err(|e| broker_error(e.to_string()))?
+            .ok_or_else(|| broker_error("connection closed".to_string()))?;
+
+        let malformed = || broker_error("malformed response".to_string());
+        let mut reader = FrameReader(&response);
+        match reader.u8() {
+            Some(FOUND) => {
+                let kind = reader.u8().ok_or_else(malformed)?;
+                let version = reader.string().ok_or_else(malformed)?.to_string();
+                let value = reader.bytes().ok_or_else(malformed)?.to_vec();
+                let data = match kind {
+                    STRING => SecretData::Str(String::from_utf8(value).map_err(|_| malformed())?),
+                    BINARY => SecretData::Bytes(value.into()),
+                    _ => return Err(malformed()),
+                };
+
+                Ok(Some(Secret {
+                    secret: T::decode(name, data)?,
+                    name: name.to_string(),
+                    version,
+                    version_stages: Vec::new(),
+                    is_current: None,
+                    backend: BACKEND,
+                    tags: None,
+                    created_at: None,
+                    last_rotated_at: None,
+                    metadata: None,
+                }))
+            }
+            Some(NOT_FOUND) => Ok(None),
+            Some(ERROR) => {
+                let code = reader.u8().ok_or_else(malformed)?;
+                let message = reader.string().ok_or_else(malformed)?.to_string();
+                Err(decode_error(code, message))
+            }
+            _ => Err(malformed()),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for BrokerSecretsProvider {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(secret_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(secret_name, Some(version)).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
@@ -0,0 +1,89 @@
+//! Compile-time capabilities of the Secrets Provider implementations.
+//!
+//! Implementations do not support every operation of [SecretsProvider](crate::SecretsProvider):
+//! some backends keep a single version of each secret, others can not enumerate or write their
+//! secrets, and these operations fail at runtime (or never find anything). Each implementation
+//! declares what it supports with [ProviderCapabilities], so applications relying on a capability
+//! can check it at compile time with [static_assert_supports](crate::static_assert_supports), and
+//! switching to a backend lacking it fails the build instead of the lookups.
+//!
+//! Capabilities are declared by the implementations, not by the wrappers: assert the
+//! capabilities of the wrapped implementation.
+
+/// Operations supported by a Secrets Provider implementation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Previous versions of a secret can be read with
+    /// [find_with_version](crate::SecretsProvider::find_with_version), not only the current one.
+    pub versions: bool,
+
+    /// Versions can be read by stage with
+    /// [find_with_stage](crate::SecretsProvider::find_with_stage).
+    pub stages: bool,
+
+    /// Secrets can be enumerated with [list_secrets](crate::SecretsProvider::list_secrets).
+    pub list: bool,
+
+    /// Secrets can be written through [SecretsWriter](crate::SecretsWriter).
+    pub write: bool,
+
+    /// [batch_find](crate::SecretsProvider::batch_find) reads every secret in a single request.
+    pub batch: bool,
+}
+
+impl Capabilities {
+    /// No capability beyond looking up the current version of a secret.
+    pub const NONE: Self = Self {
+        versions: false,
+        stages: false,
+        list: false,
+        write: false,
+        batch: false,
+    };
+}
+
+/// Capabilities of a Secrets Provider implementation, known at compile time.
+pub trait ProviderCapabilities {
+    /// Operations supported by the implementation.
+    const CAPABILITIES: Capabilities;
+}
+
+/// Fails the compilation if a Secrets Provider implementation lacks any of the listed
+/// capabilities, named after the fields of [Capabilities].
+///
+/// This example uses the `memory` feature
+#[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+/// ```rust
+/// use secrets_provider::implementations::memory::MemorySecretsProvider;
+/// use secrets_provider::static_assert_supports;
+///
+/// static_assert_supports!(MemorySecretsProvider, versions, write);
+/// ```
+///
+/// Environment variables are not versioned, so this example, using the `env` feature, does not
+/// compile:
+#[cfg_attr(not(feature = "env"), doc = "```ignore")]
+/// ```rust,compile_fail
+/// use secrets_provider::implementations::env::EnvSecretsProvider;
+/// use secrets_provider::static_assert_supports;
+///
+/// static_assert_supports!(EnvSecretsProvider, versions);
+/// ```
+#[macro_export]
+macro_rules! static_assert_supports {
+    ($provider:ty $(, $capability:ident)+ $(,)?) => {
+        const _: () = {
+            $(
+                assert!(
+                    <$provider as $crate::capabilities::ProviderCapabilities>::CAPABILITIES
+                        .$capability,
+                    concat!(
+                        stringify!($provider),
+                        " does not support ",
+                        stringify!($capability)
+                    )
+                );
+            )+
+        };
+    };
+}
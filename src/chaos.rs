@@ -0,0 +1,354 @@
+//! Chaos testing wrapper for Secrets Providers.
+//!
+//! [ChaosProvider] wraps another provider and randomly misbehaves, so game-day exercises can
+//! check how an application copes with a failing secrets backend. Every kind of misbehavior has
+//! its own probability, and they are all disabled by default:
+//!
+//! - Latency spikes: lookups are delayed before reaching the wrapped provider.
+//! - Errors: lookups fail with [ProviderFailed](crate::SecretsProviderError::ProviderFailed).
+//! - Stale versions: [find](crate::SecretsProvider::find) returns a previous version of the
+//!   secret, if the wrapper has returned one before.
+//! - Truncated values: secrets are cut to half of their length before being decoded.
+//!
+//! The random number generator can be seeded, so a misbehavior sequence can be replayed.
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, SecretData, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::{ResolveTrace, TraceOutcome, TraceStep};
+use crate::{ListFilter, Result, SecretsPage, SecretsProvider, VersionStage};
+
+/// Chaos testing wrapper for Secrets Providers.
+pub struct ChaosProvider<P> {
+    inner: P,
+
+    /// Probability and duration of latency spikes.
+    latency: (f64, Duration),
+
+    /// Probability of failed lookups.
+    error_probability: f64,
+
+    /// Probability of returning a previous version.
+    stale_probability: f64,
+
+    /// Probability of truncating the secret value.
+    truncate_probability: f64,
+
+    rng: Mutex<StdRng>,
+
+    /// Versions returned for each secret, the most recent last.
+    seen_versions: Mutex<HashMap<String, Vec<String>>>,
+}
+
+impl<P> ChaosProvider<P> {
+    /// Wraps a provider. Every misbehavior is disabled until configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Wrapped Secrets Provider.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            latency: (0.0, Duration::ZERO),
+            error_probability: 0.0,
+            stale_probability: 0.0,
+            truncate_probability: 0.0,
+            rng: Mutex::new(StdRng::from_entropy()),
+            seen_versions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Seeds the random number generator, so the same misbehaviors happen on every run given the
+    /// same sequence of lookups.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Random number generator seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Delays lookups.
+    ///
+    /// # Arguments
+    ///
+    /// * `probability` - Probability of a latency spike, between `0.0` and `1.0`.
+    /// * `latency` - Delay added to the lookup.
+    pub fn with_latency(mut self, probability: f64, latency: Duration) -> Self {
+        self.latency = (probability, latency);
+        self
+    }
+
+    /// Makes lookups fail.
+    ///
+    /// # Arguments
+    ///
+    /// * `probability` - Probability of a failed lookup, between `0.0` and `1.0`.
+    pub fn with_errors(mut self, probability: f64) -> Self {
+        self.error_probability = probability;
+        self
+    }
+
+    /// Returns previous versions of the secrets instead of the current ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `probability` - Probability of a stale version, between `0.0` and `1.0`.
+    pub fn with_stale_versions(mut self, probability: f64) -> Self {
+        self.stale_probability = probability;
+        self
+    }
+
+    /// Truncates secret values to half of their length.
+    ///
+    /// # Arguments
+    ///
+    /// * `probability` - Probability of a truncated value, between `0.0` and `1.0`.
+    pub fn with_truncated_values(mut self, probability: f64) -> Self {
+        self.truncate_probability = probability;
+        self
+    }
+
+    /// Wrapped Secrets Provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Randomly decides whether a misbehavior happens.
+    fn roll(&self, probability: f64) -> bool {
+        // Probabilities out of range (or NaN) are not validated: they just never or always match
+        probability > 0.0 && lock(&self.rng).gen::<f64>() < probability
+    }
+
+    /// Records the version returned for a secret, and returns the latest different version seen
+    /// before it.
+    fn record_version(&self, name: &str, version: &str) -> Option<String> {
+        let mut seen_versions = lock(&self.seen_versions);
+        let versions = seen_versions.entry(name.to_string()).or_default();
+        if versions.last().map(String::as_str) != Some(version) {
+            versions.retain(|v| v != version);
+            versions.push(version.to_string());
+        }
+
+        versions.iter().rev().nth(1).cloned()
+    }
+}
+
+impl<P: SecretsProvider + Send + Sync> ChaosProvider<P> {
+    /// Applies latency spikes and errors before a lookup, describing them in `details`.
+    async fn before_lookup(&self, name: &str, details: &mut Vec<String>) -> Result<()> {
+        let (latency_probability, latency) = self.latency;
+        if self.roll(latency_probability) {
+            tokio::time::sleep(latency).await;
+            details.push(format!("Injected a latency spike of {:?}", latency));
+        }
+
+        if self.roll(self.error_probability) {
+            details.push("Injected a failure".to_string());
+            return Err(SecretsProviderError::ProviderFailed(format!(
+                "Chaos: injected failure looking up secret {}",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Looks up a secret in the wrapped provider, truncating its value if needed.
+    async fn lookup<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let secret = if self.roll(self.truncate_probability) {
+            match version {
+                Some(v) => self.inner.find_with_version::<Truncated<T>>(name, v).await,
+                None => self.inner.find::<Truncated<T>>(name).await,
+            }?
+            .map(|s| Secret {
+                secret: s.secret.0,
+                name: s.name,
+                version: s.version,
+                version_stages: s.version_stages,
+                is_current: s.is_current,
+                backend: s.backend,
+                tags: s.tags,
+                created_at: s.created_at,
+                last_rotated_at: s.last_rotated_at,
+                metadata: s.metadata,
+            })
+        } else {
+            match version {
+                Some(v) => self.inner.find_with_version::<T>(name, v).await,
+                None => self.inner.find::<T>(name).await,
+            }?
+        };
+
+        Ok(secret)
+    }
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> SecretsProvider for ChaosProvider<P> {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        self.before_lookup(secret_name, &mut Vec::new()).await?;
+
+        let Some(secret) = self.lookup::<T>(secret_name, None).await? else {
+            return Ok(None);
+        };
+
+        let previous = self.record_version(secret_name, &secret.version);
+        if let Some(previous) = previous.filter(|_| self.roll(self.stale_probability)) {
+            if let Some(stale) = self.lookup::<T>(secret_name, Some(&previous)).await? {
+                return Ok(Some(stale));
+            }
+        }
+
+        Ok(Some(secret))
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.before_lookup(secret_name, &mut Vec::new()).await?;
+
+        let secret = self.lookup::<T>(secret_name, Some(version)).await?;
+        if let Some(secret) = &secret {
+            self.record_version(secret_name, &secret.version);
+        }
+
+        Ok(secret)
+    }
+
+    /// Stage lookups get latency spikes and failures, but their values are never truncated nor
+    /// stale.
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        self.before_lookup(secret_name, &mut Vec::new()).await?;
+        self.inner.find_with_stage(secret_name, stage).await
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        self.before_lookup(secret_name, &mut Vec::new()).await?;
+        self.inner.exists(secret_name).await
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        self.inner.list_secret_versions(secret_name).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.inner.backend()
+    }
+
+    /// Listings are delegated to the wrapped provider, without any misbehavior.
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        self.inner.list_secrets(filter, page_token).await
+    }
+
+    /// Self-tests are delegated to the wrapped provider, without any misbehavior.
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.inner.self_test(canary).await
+    }
+
+    /// Traces the lookup like [find](SecretsProvider::find) makes it, misbehaviors included. The
+    /// trace does not hold the value, so truncations are only reported.
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        let start = Instant::now();
+        let mut details = Vec::new();
+        if let Err(e) = self.before_lookup(secret_name, &mut details).await {
+            return ResolveTrace {
+                name: secret_name.to_string(),
+                steps: vec![TraceStep {
+                    layer: "ChaosProvider".to_string(),
+                    details,
+                    duration: start.elapsed(),
+                }],
+                outcome: TraceOutcome::Failed(e.to_string()),
+            };
+        }
+
+        let mut truncated = self.roll(self.truncate_probability);
+        let mut trace = self.inner.resolve_trace(secret_name).await;
+        if let TraceOutcome::Found { version, .. } = &trace.outcome {
+            let previous = self.record_version(secret_name, version);
+            if let Some(previous) = previous.filter(|_| self.roll(self.stale_probability)) {
+                let stale_truncated = self.roll(self.truncate_probability);
+                let stale = self
+                    .inner
+                    .find_with_version::<SecretData>(secret_name, &previous)
+                    .await;
+                if !matches!(stale, Ok(None)) {
+                    details.push(format!("Returned the stale version {}", previous));
+                    trace.outcome = TraceOutcome::from(stale);
+                    truncated = stale_truncated;
+                }
+            }
+        }
+        if truncated && matches!(trace.outcome, TraceOutcome::Found { .. }) {
+            details.push("Truncated the value to half of its length".to_string());
+        }
+
+        trace.wrap("ChaosProvider", secret_name, details, start)
+    }
+}
+
+// The protected data is always left consistent, so a poisoned lock can still be used
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Decodes a secret after cutting it to half of its length.
+struct Truncated<T>(T);
+
+impl<T: Decode> Decode for Truncated<T> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        let truncated = match secret_data {
+            SecretData::Str(s) => SecretData::Str(truncate_str(s)),
+            SecretData::Bytes(b) => SecretData::Bytes(b.slice(..b.len() / 2)),
+        };
+
+        T::decode(secret_name, truncated).map(Truncated)
+    }
+
+    fn decode_raw(secret_name: &str, raw: Vec<u8>) -> Result<Self> {
+        // Text is truncated on a character boundary, so it is still read as text
+        let truncated = match String::from_utf8(raw) {
+            Ok(s) => truncate_str(s).into_bytes(),
+            Err(e) => truncate_bytes(e.into_bytes()),
+        };
+
+        T::decode_raw(secret_name, truncated).map(Truncated)
+    }
+}
+
+fn truncate_str(mut s: String) -> String {
+    let mut len = s.len() / 2;
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    s.truncate(len);
+    s
+}
+
+fn truncate_bytes(mut b: Vec<u8>) -> Vec<u8> {
+    b.truncate(b.len() / 2);
+    b
+}
@@ -0,0 +1,156 @@
+//! Cryptographic primitives used by the crate's own subsystems.
+//!
+//! Signing keys and client-side decryption go through these functions, so the algorithms and
+//! the library implementing them are chosen in a single place. By default they are implemented
+//! with the RustCrypto crates. With the `fips` feature, they are implemented with aws-lc-rs, and
+//! features relying on algorithms that are not FIPS approved do not compile.
+//!
+//! The application selects the FIPS validated module of aws-lc-rs by enabling its own `fips`
+//! feature. aws-lc-rs does not compile without a module, and these functions panic if the linked
+//! one is not running in FIPS mode, so a misconfigured build never falls back to another
+//! implementation.
+//!
+//! Only FIPS approved algorithms are exposed here: HMAC-SHA256, AES-256-GCM and AES-256-CTR.
+
+/// Panics if the linked aws-lc module is not running in FIPS mode.
+#[cfg(feature = "fips")]
+fn assert_fips_mode() {
+    if let Err(e) = aws_lc_rs::try_fips_mode() {
+        panic!(
+            "The fips feature requires the FIPS module of aws-lc-rs (enable its fips feature): {}",
+            e
+        );
+    }
+}
+
+/// Computes the HMAC-SHA256 of a message.
+///
+/// # Arguments
+///
+/// * `key` - HMAC key, of any length.
+/// * `message` - Authenticated message.
+#[cfg(all(feature = "keyset", not(feature = "fips")))]
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    use hmac::{Hmac, Mac};
+
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts any key");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(all(feature = "keyset", feature = "fips"))]
+pub(crate) fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    use aws_lc_rs::hmac;
+
+    assert_fips_mode();
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::sign(&key, message).as_ref().to_vec()
+}
+
+/// Returns `true` if the HMAC-SHA256 of a message is the given tag. Tags are compared in
+/// constant time.
+///
+/// # Arguments
+///
+/// * `key` - HMAC key, of any length.
+/// * `message` - Authenticated message.
+/// * `tag` - Expected tag.
+#[cfg(all(
+    any(feature = "keyset", feature = "aws-dynamodb-kms"),
+    not(feature = "fips")
+))]
+pub(crate) fn verify_hmac_sha256(key: &[u8], message: &[u8], tag: &[u8]) -> bool {
+    use hmac::{Hmac, Mac};
+
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key).expect("HMAC accepts any key");
+    mac.update(message);
+    mac.verify_slice(tag).is_ok()
+}
+
+#[cfg(all(
+    any(feature = "keyset", feature = "aws-dynamodb-kms"),
+    feature = "fips"
+))]
+pub(crate) fn verify_hmac_sha256(key: &[u8], message: &[u8], tag: &[u8]) -> bool {
+    use aws_lc_rs::hmac;
+
+    assert_fips_mode();
+    let key = hmac::Key::new(hmac::HMAC_SHA256, key);
+    hmac::verify(&key, message, tag).is_ok()
+}
+
+/// Decrypts and authenticates an AES-256-GCM ciphertext, with no additional data. Returns
+/// `None` if the key or nonce lengths are invalid, or if authentication failed.
+///
+/// # Arguments
+///
+/// * `key` - 32 bytes key.
+/// * `nonce` - 12 bytes nonce.
+/// * `ciphertext` - Ciphertext, followed by the 16 bytes authentication tag.
+#[cfg(all(feature = "aws-s3-kms", not(feature = "fips")))]
+pub(crate) fn aes_256_gcm_open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+    use aes_gcm::aead::Aead;
+    use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+
+    if nonce.len() != 12 {
+        return None;
+    }
+    Aes256Gcm::new_from_slice(key)
+        .ok()?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .ok()
+}
+
+#[cfg(all(feature = "aws-s3-kms", feature = "fips"))]
+pub(crate) fn aes_256_gcm_open(key: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+    use aws_lc_rs::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
+
+    assert_fips_mode();
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).ok()?);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext_length = key
+        .open_in_place(nonce, Aad::empty(), &mut in_out)
+        .ok()?
+        .len();
+    in_out.truncate(plaintext_length);
+    Some(in_out)
+}
+
+/// Applies the AES-256-CTR keystream to data, in place, with a 128 bits big endian counter.
+/// Returns `false` if the key length is invalid.
+///
+/// # Arguments
+///
+/// * `key` - 32 bytes key.
+/// * `counter_block` - Initial counter block.
+/// * `data` - Data to encrypt or decrypt.
+#[cfg(all(feature = "aws-dynamodb-kms", not(feature = "fips")))]
+pub(crate) fn aes_256_ctr_apply(key: &[u8], counter_block: &[u8; 16], data: &mut [u8]) -> bool {
+    use aes::cipher::{KeyIvInit, StreamCipher};
+
+    match ctr::Ctr128BE::<aes::Aes256>::new_from_slices(key, counter_block) {
+        Ok(mut cipher) => {
+            cipher.apply_keystream(data);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(all(feature = "aws-dynamodb-kms", feature = "fips"))]
+pub(crate) fn aes_256_ctr_apply(key: &[u8], counter_block: &[u8; 16], data: &mut [u8]) -> bool {
+    use aws_lc_rs::cipher::{DecryptingKey, DecryptionContext, UnboundCipherKey, AES_256};
+    use aws_lc_rs::iv::FixedLength;
+
+    assert_fips_mode();
+    let Ok(key) = UnboundCipherKey::new(&AES_256, key).and_then(DecryptingKey::ctr) else {
+        return false;
+    };
+    key.decrypt(
+        data,
+        DecryptionContext::Iv128(FixedLength::from(*counter_block)),
+    )
+    .is_ok()
+}
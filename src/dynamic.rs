@@ -0,0 +1,285 @@
+//! Object-safe Secrets Providers.
+//!
+//! The lookups of [SecretsProvider] are generic over the type of the secret, so it can not be
+//! used as a trait object. [DynSecretsProvider] returns untyped [SecretData] instead, and is
+//! implemented by every Secrets Provider, so providers chosen at runtime can be stored as
+//! `Box<dyn DynSecretsProvider>` or `Arc<dyn DynSecretsProvider>`. Secrets are decoded afterwards
+//! with [Secret::decode].
+//!
+//! Boxed providers implement [SecretsProvider] again, so they can be used (and wrapped) like any
+//! other provider:
+//!
+//! ```rust,ignore
+//! let secrets_provider: Box<dyn DynSecretsProvider> = match config.backend {
+//!     Backend::Aws => Box::new(AwsSecretsProvider::new(config.region).await),
+//!     Backend::Env => Box::new(EnvSecretsProvider::new()),
+//! };
+//! let password = secrets_provider.find::<String>("database-password").await?;
+//! ```
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::find::FindOptions;
+use crate::secret::{Decode, Secret, SecretData, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::ResolveTrace;
+use crate::{ListFilter, Result, SecretsPage, SecretsProvider, VersionStage};
+
+/// Object-safe version of [SecretsProvider], returning untyped secrets.
+///
+/// Methods are the ones of [SecretsProvider], prefixed with `dyn_` so they never clash with them
+/// when both traits are in scope.
+#[async_trait]
+pub trait DynSecretsProvider: Send + Sync {
+    /// Retrieves a secret, like [SecretsProvider::find].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    async fn dyn_find(&self, secret_name: &str) -> Result<Option<Secret<SecretData>>>;
+
+    /// Retrieves a specific version of a secret, like [SecretsProvider::find_with_version].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `version` - The secret's version to retrieve.
+    async fn dyn_find_with_version(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<SecretData>>>;
+
+    /// Retrieves the version of a secret at a stage of its rotation, like
+    /// [SecretsProvider::find_with_stage].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `stage` - Stage of the version to retrieve.
+    async fn dyn_find_with_stage(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<SecretData>>>;
+
+    /// Retrieves a secret with lookup options, like [SecretsProvider::find_with_options].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `options` - Version to retrieve, decoding and timeout of the lookup.
+    async fn dyn_find_with_options(
+        &self,
+        secret_name: &str,
+        options: &FindOptions,
+    ) -> Result<Option<Secret<SecretData>>>;
+
+    /// Checks whether a secret exists, like [SecretsProvider::exists].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    async fn dyn_exists(&self, secret_name: &str) -> Result<bool>;
+
+    /// Retrieves a group of secrets, like [SecretsProvider::batch_find].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_names` - List of secret names that will be retrieved
+    async fn dyn_batch_find<'n>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<SecretData>>>;
+
+    /// Lists the secrets of the provider, like [SecretsProvider::list_secrets].
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Secrets to list, and size of the pages.
+    /// * `page_token` - Token of the page to list, or `None` for the first page.
+    async fn dyn_list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage>;
+
+    /// Lists the versions of a secret, like [SecretsProvider::list_secret_versions].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    async fn dyn_list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>>;
+
+    /// Backend of the versions returned by the provider, like [SecretsProvider::backend].
+    fn dyn_backend(&self) -> Option<&'static str>;
+
+    /// Checks that the provider is usable, like [SecretsProvider::self_test].
+    ///
+    /// # Arguments
+    ///
+    /// * `canary` - Name of a secret that must be readable.
+    async fn dyn_self_test(&self, canary: Option<&str>) -> SelfTestReport;
+
+    /// Looks up a secret, returning a trace of the lookup, like
+    /// [SecretsProvider::resolve_trace].
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    async fn dyn_resolve_trace(&self, secret_name: &str) -> ResolveTrace;
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> DynSecretsProvider for P {
+    async fn dyn_find(&self, secret_name: &str) -> Result<Option<Secret<SecretData>>> {
+        self.find(secret_name).await
+    }
+
+    async fn dyn_find_with_version(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<SecretData>>> {
+        self.find_with_version(secret_name, version).await
+    }
+
+    async fn dyn_find_with_stage(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<SecretData>>> {
+        self.find_with_stage(secret_name, stage).await
+    }
+
+    async fn dyn_find_with_options(
+        &self,
+        secret_name: &str,
+        options: &FindOptions,
+    ) -> Result<Option<Secret<SecretData>>> {
+        self.find_with_options(secret_name, options).await
+    }
+
+    async fn dyn_exists(&self, secret_name: &str) -> Result<bool> {
+        self.exists(secret_name).await
+    }
+
+    async fn dyn_batch_find<'n>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<SecretData>>> {
+        self.batch_find(secret_names).await
+    }
+
+    async fn dyn_list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        self.list_secrets(filter, page_token).await
+    }
+
+    async fn dyn_list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        self.list_secret_versions(secret_name).await
+    }
+
+    fn dyn_backend(&self) -> Option<&'static str> {
+        self.backend()
+    }
+
+    async fn dyn_self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.self_test(canary).await
+    }
+
+    async fn dyn_resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        self.resolve_trace(secret_name).await
+    }
+}
+
+/// Decodes the secret returned by a [DynSecretsProvider], if there is one.
+fn decode<T: Decode>(secret: Option<Secret<SecretData>>) -> Result<Option<Secret<T>>> {
+    secret.map(Secret::decode).transpose()
+}
+
+/// Implements [SecretsProvider] for a pointer to a [DynSecretsProvider].
+macro_rules! impl_secrets_provider {
+    ($pointer:ty) => {
+        // The pointer is also a DynSecretsProvider, through the blanket implementation: calls go
+        // to the provider it points to, or they would recurse
+        #[async_trait]
+        impl SecretsProvider for $pointer {
+            async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+                decode((**self).dyn_find(secret_name).await?)
+            }
+
+            async fn find_with_version<T: Decode>(
+                &self,
+                secret_name: &str,
+                version: &str,
+            ) -> Result<Option<Secret<T>>> {
+                decode((**self).dyn_find_with_version(secret_name, version).await?)
+            }
+
+            async fn find_with_stage<T: Decode>(
+                &self,
+                secret_name: &str,
+                stage: &VersionStage,
+            ) -> Result<Option<Secret<T>>> {
+                decode((**self).dyn_find_with_stage(secret_name, stage).await?)
+            }
+
+            async fn find_with_options<T: Decode>(
+                &self,
+                secret_name: &str,
+                options: &FindOptions,
+            ) -> Result<Option<Secret<T>>> {
+                decode((**self).dyn_find_with_options(secret_name, options).await?)
+            }
+
+            async fn exists(&self, secret_name: &str) -> Result<bool> {
+                (**self).dyn_exists(secret_name).await
+            }
+
+            async fn batch_find<'n, T: Decode>(
+                &self,
+                secret_names: &[&'n str],
+            ) -> Result<HashMap<&'n str, Secret<T>>> {
+                (**self)
+                    .dyn_batch_find(secret_names)
+                    .await?
+                    .into_iter()
+                    .map(|(name, secret)| Ok((name, secret.decode()?)))
+                    .collect()
+            }
+
+            async fn list_secrets(
+                &self,
+                filter: &ListFilter,
+                page_token: Option<&str>,
+            ) -> Result<SecretsPage> {
+                (**self).dyn_list_secrets(filter, page_token).await
+            }
+
+            async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+                (**self).dyn_list_secret_versions(secret_name).await
+            }
+
+            fn backend(&self) -> Option<&'static str> {
+                (**self).dyn_backend()
+            }
+
+            async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+                (**self).dyn_self_test(canary).await
+            }
+
+            async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+                (**self).dyn_resolve_trace(secret_name).await
+            }
+        }
+    };
+}
+
+impl_secrets_provider!(Box<dyn DynSecretsProvider>);
+impl_secrets_provider!(Arc<dyn DynSecretsProvider>);
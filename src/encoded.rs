@@ -0,0 +1,121 @@
+//! Structured secrets packed in a binary serialization format.
+//!
+//! Some secrets are packed binary config blobs rather than plain strings. [Encoded] decodes a
+//! binary secret with a serialization [Format] into any type implementing
+//! [DeserializeOwned](serde::de::DeserializeOwned), so it can be requested directly:
+//!
+//! ```rust,ignore
+//! let config = secrets_provider
+//!     .find::<Encoded<EdgeConfig, Cbor>>("edge-config")
+//!     .await?
+//!     .expect("Secret not found")
+//!     .reveal()
+//!     .into_inner();
+//! ```
+//!
+//! The available formats depend on the enabled features:
+//!
+//! - `cbor`: [Cbor](crate::encoded::Cbor), [RFC 8949](https://www.rfc-editor.org/rfc/rfc8949).
+//! - `msgpack`: [MessagePack](crate::encoded::MessagePack).
+//!
+//! Payloads are binary, so they are only decoded from binary secrets. Implementations that can
+//! not tell string secrets apart from binary ones (such as mounted files) always hand the raw
+//! bytes to the format.
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use serde::de::DeserializeOwned;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, Decode, SecretData};
+use crate::Result;
+
+/// Serialization format of an [Encoded] secret.
+pub trait Format {
+    /// Name of the format, used in error messages.
+    const NAME: &'static str;
+
+    /// Deserializes a payload, returning a description of the error if it is not valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload` - Serialized value.
+    fn deserialize<T: DeserializeOwned>(payload: &[u8]) -> std::result::Result<T, String>;
+}
+
+/// [CBOR](https://cbor.io) format.
+#[cfg(feature = "cbor")]
+#[derive(Clone, Copy, Debug)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Format for Cbor {
+    const NAME: &'static str = "CBOR";
+
+    fn deserialize<T: DeserializeOwned>(payload: &[u8]) -> std::result::Result<T, String> {
+        ciborium::de::from_reader(payload).map_err(|e| e.to_string())
+    }
+}
+
+/// [MessagePack](https://msgpack.org) format.
+#[cfg(feature = "msgpack")]
+#[derive(Clone, Copy, Debug)]
+pub struct MessagePack;
+
+#[cfg(feature = "msgpack")]
+impl Format for MessagePack {
+    const NAME: &'static str = "MessagePack";
+
+    fn deserialize<T: DeserializeOwned>(payload: &[u8]) -> std::result::Result<T, String> {
+        rmp_serde::from_slice(payload).map_err(|e| e.to_string())
+    }
+}
+
+/// Secret value deserialized from a binary payload in the format `F`.
+pub struct Encoded<T, F> {
+    value: T,
+    format: PhantomData<fn() -> F>,
+}
+
+impl<T, F> Encoded<T, F> {
+    /// Returns the deserialized value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T, F> Deref for Encoded<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: DeserializeOwned + Send, F: Format> Decode for Encoded<T, F> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        match secret_data {
+            SecretData::Bytes(b) => Self::decode_raw(secret_name, b.into()),
+            _ => Err(SecretsProviderError::InvalidType(secret_name.to_string())),
+        }
+    }
+
+    // Payloads may happen to be valid UTF-8, so they are never read as string secrets
+    fn decode_raw(secret_name: &str, mut raw: Vec<u8>) -> Result<Self> {
+        let value = F::deserialize(&raw);
+        wipe(&mut raw);
+        let value = value.map_err(|e| {
+            SecretsProviderError::InvalidType(format!(
+                "{} (invalid {} payload: {})",
+                secret_name,
+                F::NAME,
+                e
+            ))
+        })?;
+
+        Ok(Self {
+            value,
+            format: PhantomData,
+        })
+    }
+}
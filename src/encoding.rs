@@ -0,0 +1,123 @@
+//! Binary secrets stored as text.
+//!
+//! Backends like AWS Secrets Manager are mostly used with string secrets, so binary values such
+//! as keys are often stored text-encoded. [Hex] and, with the `base64` feature, `Base64` decode
+//! them, so they can be requested directly as bytes:
+//!
+//! ```rust,ignore
+//! let key = secrets_provider
+//!     .find::<Base64>("signing-key")
+//!     .await?
+//!     .expect("Secret not found")
+//!     .reveal()
+//!     .into_inner();
+//! ```
+//!
+//! The decoded bytes are then decoded as `T` (`Vec<u8>` by default), like the content of a
+//! mounted file: `Hex<String>` reads hex-encoded UTF-8 text, for example. Only string
+//! secrets are decoded. Leading and trailing whitespace, like the line feed ending a file, is
+//! ignored.
+use std::ops::Deref;
+
+#[cfg(feature = "base64")]
+use base64::engine::general_purpose::STANDARD;
+#[cfg(feature = "base64")]
+use base64::Engine;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, Decode, SecretData};
+use crate::Result;
+
+/// Secret value decoded from a base64 string, with the standard alphabet and padding.
+#[cfg(feature = "base64")]
+pub struct Base64<T = Vec<u8>> {
+    value: T,
+}
+
+#[cfg(feature = "base64")]
+impl<T> Base64<T> {
+    /// Returns the decoded value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+#[cfg(feature = "base64")]
+impl<T> Deref for Base64<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+#[cfg(feature = "base64")]
+impl<T: Decode> Decode for Base64<T> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        let SecretData::Str(mut encoded) = secret_data else {
+            return Err(SecretsProviderError::InvalidType(secret_name.to_string()));
+        };
+
+        let decoded = STANDARD.decode(encoded.trim());
+        wipe(&mut encoded);
+        // base64 errors quote the invalid byte, so they are not part of the error
+        let decoded = decoded.map_err(|_| {
+            SecretsProviderError::InvalidType(format!("{} (invalid base64 string)", secret_name))
+        })?;
+
+        Ok(Self {
+            value: T::decode_raw(secret_name, decoded)?,
+        })
+    }
+}
+
+/// Secret value decoded from a hex string. Both lowercase and uppercase digits are accepted.
+pub struct Hex<T = Vec<u8>> {
+    value: T,
+}
+
+impl<T> Hex<T> {
+    /// Returns the decoded value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Hex<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: Decode> Decode for Hex<T> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        let SecretData::Str(mut encoded) = secret_data else {
+            return Err(SecretsProviderError::InvalidType(secret_name.to_string()));
+        };
+
+        let decoded = decode_hex(encoded.trim().as_bytes());
+        wipe(&mut encoded);
+        let decoded = decoded.ok_or_else(|| {
+            SecretsProviderError::InvalidType(format!("{} (invalid hex string)", secret_name))
+        })?;
+
+        Ok(Self {
+            value: T::decode_raw(secret_name, decoded)?,
+        })
+    }
+}
+
+/// Decodes a hex string, or returns `None` if it has an odd length or characters other than hex
+/// digits.
+pub(crate) fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    hex.chunks(2)
+        .map(|pair| Some((digit(pair[0])? << 4) | digit(pair[1])?))
+        .collect()
+}
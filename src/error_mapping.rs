@@ -0,0 +1,226 @@
+//! Reclassification of backend errors.
+//!
+//! Implementations map their backend's errors to [SecretsProviderError] the same way for every
+//! secret, but legacy backends do not always fit these semantics: Vault answers 403 instead of
+//! 404 on some mounts, and some proxies fail instead of answering that a secret does not exist.
+//! An [ErrorMapper] reclassifies these errors before they reach the application, and
+//! [ErrorMappingProvider] applies it to every lookup of a wrapped provider.
+//!
+//! Mappers are given the error and the [ErrorContext] of the lookup, and return a
+//! [MappedError]: the secret is reported as not found, or the lookup fails with the same or
+//! another error.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::ResolveTrace;
+use crate::{canary_check, ListFilter, Result, SecretsPage, SecretsProvider, VersionStage};
+
+/// Lookup that failed.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorContext<'a> {
+    /// Names of the secrets looked up: a single one, all the secrets of a
+    /// [batch_find](crate::SecretsProvider::batch_find), or none for a
+    /// [list_secrets](crate::SecretsProvider::list_secrets).
+    pub secret_names: &'a [&'a str],
+
+    /// Version looked up, if any.
+    pub version: Option<&'a str>,
+
+    /// Backend of the wrapped provider, if it tags its versions. For example:
+    /// `AWS Secrets Manager`.
+    pub backend: Option<&'static str>,
+}
+
+/// Result of an [ErrorMapper].
+#[derive(Debug)]
+pub enum MappedError {
+    /// The secrets are reported as not found: [find](crate::SecretsProvider::find) returns
+    /// `None`, and [batch_find](crate::SecretsProvider::batch_find) and
+    /// [list_secrets](crate::SecretsProvider::list_secrets) return no secrets.
+    NotFound,
+
+    /// The lookup fails with the given error, the original one or another one.
+    Error(SecretsProviderError),
+}
+
+/// Reclassifies the errors of a provider.
+///
+/// It is implemented by closures taking the [ErrorContext] and the error:
+/// ```rust
+/// use secrets_provider::error_mapping::{ErrorContext, MappedError};
+/// use secrets_provider::SecretsProviderError;
+///
+/// let mapper = |context: &ErrorContext<'_>, error: SecretsProviderError| match &error {
+///     SecretsProviderError::ProviderFailed(message)
+///         if message.contains("403")
+///             && context.secret_names.iter().all(|n| n.starts_with("legacy/")) =>
+///     {
+///         MappedError::NotFound
+///     }
+///     _ => MappedError::Error(error),
+/// };
+/// ```
+pub trait ErrorMapper: Send + Sync {
+    /// Reclassifies an error returned by the wrapped provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - Lookup that failed.
+    /// * `error` - Error returned by the wrapped provider.
+    fn map_error(&self, context: &ErrorContext<'_>, error: SecretsProviderError) -> MappedError;
+}
+
+impl<F> ErrorMapper for F
+where
+    F: Fn(&ErrorContext<'_>, SecretsProviderError) -> MappedError + Send + Sync,
+{
+    fn map_error(&self, context: &ErrorContext<'_>, error: SecretsProviderError) -> MappedError {
+        self(context, error)
+    }
+}
+
+/// Secrets Provider wrapper reclassifying the errors of lookups with an [ErrorMapper].
+///
+/// Traces returned by [resolve_trace](crate::SecretsProvider::resolve_trace) keep the outcome of
+/// the wrapped provider, before errors are mapped, so they show the original error.
+#[derive(Clone)]
+pub struct ErrorMappingProvider<P> {
+    inner: P,
+    mapper: Arc<dyn ErrorMapper>,
+}
+
+impl<P> ErrorMappingProvider<P> {
+    /// Wraps a provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Wrapped Secrets Provider.
+    /// * `mapper` - Reclassifies the errors of the wrapped provider.
+    pub fn new(inner: P, mapper: impl ErrorMapper + 'static) -> Self {
+        Self {
+            inner,
+            mapper: Arc::new(mapper),
+        }
+    }
+
+    /// Wrapped Secrets Provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: SecretsProvider> ErrorMappingProvider<P> {
+    /// Maps the error of a lookup, if it failed.
+    fn map_result<T: Default>(
+        &self,
+        result: Result<T>,
+        secret_names: &[&str],
+        version: Option<&str>,
+    ) -> Result<T> {
+        let Err(error) = result else {
+            return result;
+        };
+
+        let context = ErrorContext {
+            secret_names,
+            version,
+            backend: self.inner.backend(),
+        };
+        match self.mapper.map_error(&context, error) {
+            MappedError::NotFound => Ok(T::default()),
+            MappedError::Error(error) => Err(error),
+        }
+    }
+}
+
+impl<P: Debug> Debug for ErrorMappingProvider<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ErrorMappingProvider")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> SecretsProvider for ErrorMappingProvider<P> {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        let result = self.inner.find(secret_name).await;
+        self.map_result(result, &[secret_name], None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        let result = self.inner.find_with_version(secret_name, version).await;
+        self.map_result(result, &[secret_name], Some(version))
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        let result = self.inner.find_with_stage(secret_name, stage).await;
+        self.map_result(result, &[secret_name], None)
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        let result = self.inner.exists(secret_name).await;
+        self.map_result(result, &[secret_name], None)
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        let result = self.inner.list_secret_versions(secret_name).await;
+        self.map_result(result, &[secret_name], None)
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        let result = self.inner.batch_find(secret_names).await;
+        self.map_result(result, secret_names, None)
+    }
+
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        let result = self.inner.list_secrets(filter, page_token).await;
+        self.map_result(result, &[], None)
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.inner.backend()
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = self.inner.self_test(None).await;
+        // The canary is read with mapped errors, like the application would
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        let start = Instant::now();
+        self.inner.resolve_trace(secret_name).await.wrap(
+            "ErrorMappingProvider",
+            secret_name,
+            Vec::new(),
+            start,
+        )
+    }
+}
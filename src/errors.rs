@@ -8,6 +8,21 @@ pub enum SecretsProviderError {
     #[error("Incorrect typecast for secret {0}")]
     InvalidType(String),
 
+    #[error("Invalid secret name: {0}")]
+    InvalidName(String),
+
+    #[error("Invalid secret version: {0}")]
+    InvalidVersion(String),
+
+    #[error("Secret {0} already exists")]
+    AlreadyExists(String),
+
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
     #[error("Unknown secret type for secret {0}")]
     UnknownType(String),
 
@@ -0,0 +1,401 @@
+//! Export to the [External Secrets Operator](https://external-secrets.io) format.
+//!
+//! Services declare the secrets they read in a [SecretManifest], which can be kept in the
+//! repository next to the service:
+//!
+//! ```yaml
+//! secrets:
+//!   - name: database/password
+//!     key: DATABASE_PASSWORD
+//!   - name: api-token
+//!     version: AWSPREVIOUS
+//! ```
+//!
+//! The same manifest gives the names to look up with
+//! [batch_find](crate::SecretsProvider::batch_find), and, through [EsoExport], the `SecretStore`
+//! and `ExternalSecret` manifests that make the operator sync those secrets into a Kubernetes
+//! Secret. Each declared secret becomes a key of that Kubernetes Secret, so workloads that only
+//! see Kubernetes Secrets and services using this crate read the same values.
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::SecretsProviderError;
+use crate::Result;
+
+/// API version of the generated resources.
+pub const ESO_API_VERSION: &str = "external-secrets.io/v1beta1";
+
+/// Default interval between two syncs of the Kubernetes Secret.
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Secrets read by a service.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SecretManifest {
+    pub secrets: Vec<DeclaredSecret>,
+}
+
+/// A secret read by a service.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DeclaredSecret {
+    /// Secret name in the backend.
+    pub name: String,
+
+    /// Key of the Kubernetes Secret the value is synced to. Defaults to the secret name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+
+    /// Version to sync, instead of the current one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl SecretManifest {
+    /// Parses a manifest from a YAML (or JSON) document.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - Content of the manifest.
+    pub fn from_yaml(document: &str) -> Result<Self> {
+        serde_yaml::from_str(document).map_err(|e| {
+            SecretsProviderError::Initialization(format!("Unable to parse secret manifest: {}", e))
+        })
+    }
+
+    /// Reads a manifest from a YAML (or JSON) file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the manifest file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let document = std::fs::read_to_string(path).map_err(|e| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to read secret manifest {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_yaml(&document)
+    }
+
+    /// Names of the declared secrets, to look them up with
+    /// [batch_find](crate::SecretsProvider::batch_find).
+    pub fn names(&self) -> Vec<&str> {
+        self.secrets.iter().map(|s| s.name.as_str()).collect()
+    }
+}
+
+impl DeclaredSecret {
+    /// Key of the Kubernetes Secret the value is synced to.
+    pub fn key(&self) -> &str {
+        self.key.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Backend the operator reads the secrets from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EsoProvider {
+    /// AWS Secrets Manager. The operator authenticates with its own credentials (for example: the
+    /// IAM role of its service account).
+    AwsSecretsManager {
+        /// AWS region. For example: `us-west-2`.
+        region: String,
+    },
+
+    /// Doppler. The token is read from a Kubernetes Secret of the namespace.
+    Doppler {
+        /// Name of the Kubernetes Secret holding the Doppler token.
+        token_secret_name: String,
+
+        /// Key of the Doppler token in that Kubernetes Secret.
+        token_secret_key: String,
+
+        /// Doppler project, for personal and service account tokens.
+        project: Option<String>,
+
+        /// Doppler config, for personal and service account tokens.
+        config: Option<String>,
+    },
+}
+
+/// Generates the `SecretStore` and `ExternalSecret` manifests syncing a [SecretManifest] into a
+/// Kubernetes Secret.
+#[derive(Clone, Debug)]
+pub struct EsoExport {
+    name: String,
+    namespace: Option<String>,
+    provider: EsoProvider,
+    target: Option<String>,
+    refresh_interval: Duration,
+}
+
+impl EsoExport {
+    /// Creates an export. The `SecretStore`, the `ExternalSecret` and the Kubernetes Secret are
+    /// all named after the export, unless configured otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the generated resources. For example: the service name.
+    /// * `provider` - Backend the operator reads the secrets from.
+    pub fn new(name: impl Into<String>, provider: EsoProvider) -> Self {
+        Self {
+            name: name.into(),
+            namespace: None,
+            provider,
+            target: None,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    /// Namespace of the generated resources. Without it, they are applied to the namespace
+    /// selected by `kubectl`.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Kubernetes namespace.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Name of the Kubernetes Secret created by the operator, instead of the export name.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Kubernetes Secret name.
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Interval between two syncs of the Kubernetes Secret, instead of
+    /// [DEFAULT_REFRESH_INTERVAL]. A zero interval syncs only once.
+    ///
+    /// # Arguments
+    ///
+    /// * `refresh_interval` - Sync interval, rounded down to seconds.
+    pub fn with_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        self.refresh_interval = refresh_interval;
+        self
+    }
+
+    /// Generates the `SecretStore` and `ExternalSecret` manifests, as a multi-document YAML
+    /// stream ready for `kubectl apply -f`.
+    ///
+    /// Fails with [InvalidName](crate::SecretsProviderError::InvalidName) if a declared secret
+    /// has no valid Kubernetes Secret key, or if two of them share the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest` - Secrets to sync.
+    pub fn generate(&self, manifest: &SecretManifest) -> Result<String> {
+        let mut keys: Vec<&str> = Vec::new();
+        for secret in &manifest.secrets {
+            let key = secret.key();
+            if !is_valid_key(key) {
+                return Err(SecretsProviderError::InvalidName(format!(
+                    r#""{}" is not a valid Kubernetes Secret key for secret {}: {}"#,
+                    key, secret.name, "set a key made of alphanumerics, '-', '_' or '.'"
+                )));
+            }
+            if keys.contains(&key) {
+                return Err(SecretsProviderError::InvalidName(format!(
+                    r#"Kubernetes Secret key "{}" is declared more than once"#,
+                    key
+                )));
+            }
+            keys.push(key);
+        }
+
+        let metadata = Metadata {
+            name: &self.name,
+            namespace: self.namespace.as_deref(),
+        };
+        let store = Resource {
+            api_version: ESO_API_VERSION,
+            kind: "SecretStore",
+            metadata: &metadata,
+            spec: StoreSpec {
+                provider: self.store_provider(),
+            },
+        };
+        let external_secret = Resource {
+            api_version: ESO_API_VERSION,
+            kind: "ExternalSecret",
+            metadata: &metadata,
+            spec: ExternalSecretSpec {
+                refresh_interval: format!("{}s", self.refresh_interval.as_secs()),
+                secret_store_ref: StoreRef {
+                    name: &self.name,
+                    kind: "SecretStore",
+                },
+                target: Target {
+                    name: self.target.as_deref().unwrap_or(&self.name),
+                    creation_policy: "Owner",
+                },
+                data: manifest
+                    .secrets
+                    .iter()
+                    .map(|secret| Data {
+                        secret_key: secret.key(),
+                        remote_ref: RemoteRef {
+                            key: &secret.name,
+                            version: secret.version.as_deref(),
+                        },
+                    })
+                    .collect(),
+            },
+        };
+
+        let serialization_error = |e: serde_yaml::Error| {
+            SecretsProviderError::ProviderFailed(format!("Unable to generate manifests: {}", e))
+        };
+        Ok(format!(
+            "{}---\n{}",
+            serde_yaml::to_string(&store).map_err(serialization_error)?,
+            serde_yaml::to_string(&external_secret).map_err(serialization_error)?
+        ))
+    }
+
+    fn store_provider(&self) -> StoreProvider<'_> {
+        match &self.provider {
+            EsoProvider::AwsSecretsManager { region } => StoreProvider {
+                aws: Some(AwsStore {
+                    service: "SecretsManager",
+                    region,
+                }),
+                doppler: None,
+            },
+            EsoProvider::Doppler {
+                token_secret_name,
+                token_secret_key,
+                project,
+                config,
+            } => StoreProvider {
+                aws: None,
+                doppler: Some(DopplerStore {
+                    auth: DopplerAuth {
+                        secret_ref: DopplerSecretRef {
+                            doppler_token: SecretKeySelector {
+                                name: token_secret_name,
+                                key: token_secret_key,
+                            },
+                        },
+                    },
+                    project: project.as_deref(),
+                    config: config.as_deref(),
+                }),
+            },
+        }
+    }
+}
+
+/// Whether a string is a valid key of a Kubernetes Secret.
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 253
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Resource<'a, S> {
+    api_version: &'a str,
+    kind: &'a str,
+    metadata: &'a Metadata<'a>,
+    spec: S,
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct StoreSpec<'a> {
+    provider: StoreProvider<'a>,
+}
+
+/// Provider of a store. Exactly one of the fields is set.
+#[derive(Serialize)]
+struct StoreProvider<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aws: Option<AwsStore<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doppler: Option<DopplerStore<'a>>,
+}
+
+#[derive(Serialize)]
+struct AwsStore<'a> {
+    service: &'a str,
+    region: &'a str,
+}
+
+#[derive(Serialize)]
+struct DopplerStore<'a> {
+    auth: DopplerAuth<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DopplerAuth<'a> {
+    secret_ref: DopplerSecretRef<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DopplerSecretRef<'a> {
+    doppler_token: SecretKeySelector<'a>,
+}
+
+#[derive(Serialize)]
+struct SecretKeySelector<'a> {
+    name: &'a str,
+    key: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalSecretSpec<'a> {
+    refresh_interval: String,
+    secret_store_ref: StoreRef<'a>,
+    target: Target<'a>,
+    data: Vec<Data<'a>>,
+}
+
+#[derive(Serialize)]
+struct StoreRef<'a> {
+    name: &'a str,
+    kind: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Target<'a> {
+    name: &'a str,
+    creation_policy: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Data<'a> {
+    secret_key: &'a str,
+    remote_ref: RemoteRef<'a>,
+}
+
+#[derive(Serialize)]
+struct RemoteRef<'a> {
+    key: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'a str>,
+}
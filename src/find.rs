@@ -0,0 +1,354 @@
+//! Options of the lookups made with
+//! [find_with_options](crate::SecretsProvider::find_with_options).
+use std::collections::BTreeMap;
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, Decode, Secret, SecretData};
+use crate::{Result, SecretsProvider, VersionStage};
+
+/// How values are decoded by [find_with_options](crate::SecretsProvider::find_with_options).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeHint {
+    /// Values are decoded as the backend returns them. Backends that can not tell string values
+    /// apart from binary ones decode valid UTF-8 as strings.
+    #[default]
+    Auto,
+
+    /// Values are strings: binary values are decoded as UTF-8, and fail with an
+    /// [InvalidType](SecretsProviderError::InvalidType) error if they are not valid UTF-8.
+    Str,
+
+    /// Values are binary: string values are decoded as their UTF-8 bytes.
+    Bytes,
+}
+
+/// Version looked up by [find_with_options](crate::SecretsProvider::find_with_options).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Target {
+    #[default]
+    Current,
+    Version(String),
+    Stage(VersionStage),
+}
+
+/// Options of a lookup made with [find_with_options](crate::SecretsProvider::find_with_options).
+/// By default, the current version is looked up, decoded as the backend returns it, without
+/// timeout.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use secrets_provider::{DecodeHint, FindOptions};
+///
+/// let options = FindOptions::new()
+///     .with_version("v2")
+///     .with_decode_hint(DecodeHint::Bytes)
+///     .with_timeout(Duration::from_secs(2));
+/// assert_eq!(Some("v2"), options.version());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    target: Target,
+    decode_hint: DecodeHint,
+    timeout: Option<Duration>,
+}
+
+impl FindOptions {
+    /// Looks up the current version, decoded as the backend returns it, without timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks up a specific version instead of the current one. Replaces the stage, if one was
+    /// set.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The secret's version to retrieve.
+    pub fn with_version(mut self, version: impl Into<String>) -> Self {
+        self.target = Target::Version(version.into());
+        self
+    }
+
+    /// Looks up the version at a stage of its rotation instead of the current one. Replaces the
+    /// version, if one was set.
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - Stage of the version to retrieve.
+    pub fn with_stage(mut self, stage: VersionStage) -> Self {
+        self.target = Target::Stage(stage);
+        self
+    }
+
+    /// Sets how the value is decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `decode_hint` - Type of the value.
+    pub fn with_decode_hint(mut self, decode_hint: DecodeHint) -> Self {
+        self.decode_hint = decode_hint;
+        self
+    }
+
+    /// Fails the lookup with a [ProviderFailed](SecretsProviderError::ProviderFailed) error if
+    /// it did not complete in time. The lookup is dropped: see the cancellation guarantees of
+    /// the [crate].
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - Maximum duration of the lookup.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Version to look up, if one was set.
+    pub fn version(&self) -> Option<&str> {
+        match &self.target {
+            Target::Version(version) => Some(version),
+            _ => None,
+        }
+    }
+
+    /// Stage of the version to look up, if one was set.
+    pub fn stage(&self) -> Option<&VersionStage> {
+        match &self.target {
+            Target::Stage(stage) => Some(stage),
+            _ => None,
+        }
+    }
+
+    /// How the value is decoded.
+    pub fn decode_hint(&self) -> DecodeHint {
+        self.decode_hint
+    }
+
+    /// Maximum duration of the lookup, if one was set.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+}
+
+/// Looks up a secret with the methods of the provider matching the options.
+pub(crate) async fn find_with_options<P, T>(
+    provider: &P,
+    secret_name: &str,
+    options: &FindOptions,
+) -> Result<Option<Secret<T>>>
+where
+    P: SecretsProvider + Sync + ?Sized,
+    T: Decode,
+{
+    let lookup = async {
+        if options.decode_hint == DecodeHint::Auto {
+            return find_target(provider, secret_name, &options.target).await;
+        }
+
+        let Some(mut secret) =
+            find_target::<P, SecretData>(provider, secret_name, &options.target).await?
+        else {
+            return Ok(None);
+        };
+        secret.secret = match (options.decode_hint, secret.secret) {
+            (DecodeHint::Str, SecretData::Bytes(b)) => {
+                SecretData::Str(String::from_utf8(b.into()).map_err(|e| {
+                    wipe(&mut e.into_bytes());
+                    SecretsProviderError::InvalidType(secret_name.to_string())
+                })?)
+            }
+            (DecodeHint::Bytes, SecretData::Str(s)) => SecretData::Bytes(s.into_bytes().into()),
+            (_, data) => data,
+        };
+
+        secret.decode().map(Some)
+    };
+
+    let Some(timeout) = options.timeout else {
+        return lookup.await;
+    };
+    let mut lookup = pin!(lookup);
+    let mut deadline = Deadline::after(timeout)?;
+    poll_fn(|cx| match lookup.as_mut().poll(cx) {
+        Poll::Ready(result) => Poll::Ready(result),
+        Poll::Pending if Pin::new(&mut deadline).poll(cx).is_ready() => {
+            Poll::Ready(Err(SecretsProviderError::ProviderFailed(format!(
+                "Lookup of {} timed out after {:?}",
+                secret_name, timeout
+            ))))
+        }
+        Poll::Pending => Poll::Pending,
+    })
+    .await
+}
+
+async fn find_target<P, T>(
+    provider: &P,
+    secret_name: &str,
+    target: &Target,
+) -> Result<Option<Secret<T>>>
+where
+    P: SecretsProvider + Sync + ?Sized,
+    T: Decode,
+{
+    match target {
+        Target::Current => provider.find(secret_name).await,
+        Target::Version(version) => provider.find_with_version(secret_name, version).await,
+        Target::Stage(stage) => provider.find_with_stage(secret_name, stage).await,
+    }
+}
+
+#[derive(Default)]
+struct DeadlineState {
+    expired: bool,
+    waker: Option<Waker>,
+}
+
+/// Deadlines waited for by the timer thread, by end time.
+type Deadlines = BTreeMap<(Instant, u64), Arc<Mutex<DeadlineState>>>;
+
+/// Timer thread shared by every deadline, started with the first one.
+struct Timer {
+    deadlines: Mutex<Deadlines>,
+    changed: Condvar,
+    next_id: AtomicU64,
+}
+
+impl Timer {
+    /// Returns the timer, starting its thread if it is not running yet.
+    fn get() -> Result<Arc<Timer>> {
+        static TIMER: Mutex<Option<Arc<Timer>>> = Mutex::new(None);
+
+        let mut timer = TIMER.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(timer) = timer.as_ref() {
+            return Ok(Arc::clone(timer));
+        }
+
+        let started = Arc::new(Timer {
+            deadlines: Mutex::new(BTreeMap::new()),
+            changed: Condvar::new(),
+            next_id: AtomicU64::new(0),
+        });
+        let thread_timer = Arc::clone(&started);
+        std::thread::Builder::new()
+            .name("secrets-provider-timer".to_string())
+            .spawn(move || thread_timer.run())
+            .map_err(|e| {
+                SecretsProviderError::ProviderFailed(format!(
+                    "Unable to start the lookup timer: {}",
+                    e
+                ))
+            })?;
+        *timer = Some(Arc::clone(&started));
+        Ok(started)
+    }
+
+    /// Expires the deadlines as their end times are reached.
+    fn run(&self) {
+        let mut deadlines = self.lock_deadlines();
+        loop {
+            let now = Instant::now();
+            let mut expired = Vec::new();
+            while let Some(entry) = deadlines.first_entry() {
+                if entry.key().0 > now {
+                    break;
+                }
+                expired.push(entry.remove());
+            }
+            if !expired.is_empty() {
+                drop(deadlines);
+                for state in expired {
+                    let waker = {
+                        let mut state = lock_state(&state);
+                        state.expired = true;
+                        state.waker.take()
+                    };
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+                deadlines = self.lock_deadlines();
+                continue;
+            }
+
+            deadlines = match deadlines.first_key_value().map(|((end, _), _)| *end) {
+                Some(end) => {
+                    self.changed
+                        .wait_timeout(deadlines, end - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(deadlines)
+                    .unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+    }
+
+    fn lock_deadlines(&self) -> MutexGuard<'_, Deadlines> {
+        // Deadlines are added and removed in a single step, so a poisoned lock still holds
+        // consistent data
+        self.deadlines.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Completes when a timeout expired. Deadlines are expired by a single timer thread, so lookups
+/// can be timed out with any async runtime; a deadline is removed from it as soon as it is
+/// dropped.
+struct Deadline {
+    state: Arc<Mutex<DeadlineState>>,
+    key: (Instant, u64),
+    timer: Arc<Timer>,
+}
+
+impl Deadline {
+    fn after(timeout: Duration) -> Result<Self> {
+        let timer = Timer::get()?;
+        let state = Arc::new(Mutex::new(DeadlineState::default()));
+        let key = (
+            Instant::now() + timeout,
+            timer.next_id.fetch_add(1, Ordering::Relaxed),
+        );
+
+        let mut deadlines = timer.lock_deadlines();
+        deadlines.insert(key, Arc::clone(&state));
+        // The timer only needs to wake up if this deadline is the next one
+        if deadlines.first_key_value().map(|(first, _)| first) == Some(&key) {
+            timer.changed.notify_one();
+        }
+        drop(deadlines);
+
+        Ok(Self { state, key, timer })
+    }
+}
+
+impl Future for Deadline {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = lock_state(&self.state);
+        if state.expired {
+            return Poll::Ready(());
+        }
+
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Deadline {
+    fn drop(&mut self) {
+        self.timer.lock_deadlines().remove(&self.key);
+    }
+}
+
+fn lock_state(state: &Mutex<DeadlineState>) -> MutexGuard<'_, DeadlineState> {
+    // Flags are updated in a single step, so a poisoned lock still holds consistent data
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,225 @@
+//! age-encrypted bundle Secret Provider implementation.
+//!
+//! Every secret is read from a single [age](https://age-encryption.org) encrypted JSON or YAML
+//! document, with the same layout as the [secrets file](crate::implementations::file)
+//! implementation. Both binary and armored (`age --armor`) bundles are supported.
+//!
+//! The bundle is decrypted once, when the provider is created, and lookups are served from
+//! memory, so no file or network access happens afterwards. Secrets have a single version,
+//! derived from the bundle's modification time.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ::age::armor::ArmoredReader;
+use ::age::{Decryptor, Identity, IdentityFile};
+use async_trait::async_trait;
+
+use super::file::{parse_secrets, FileFormat, FileSecretType};
+use super::file_version;
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, Decode, Secret};
+use crate::{Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "age bundle";
+
+/// Extension of age-encrypted files.
+const AGE_EXTENSION: &str = "age";
+
+/// Where the identities decrypting the bundle are read from. Both sources use the age identity
+/// file format: one `AGE-SECRET-KEY-1...` key per line, with `#` comments.
+#[derive(Clone, Debug)]
+pub enum AgeIdentitySource {
+    /// Identity file path.
+    File(PathBuf),
+
+    /// Name of an environment variable holding the identities.
+    Env(String),
+}
+
+impl AgeIdentitySource {
+    /// Reads the identities, in the age identity file format.
+    pub(crate) fn read_to_string(&self) -> Result<String> {
+        match self {
+            AgeIdentitySource::File(path) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Unable to read identity file {}: {}", path.display(), e)),
+            AgeIdentitySource::Env(variable) => std::env::var(variable)
+                .map_err(|e| format!("Unable to read identity variable {}: {}", variable, e)),
+        }
+        .map_err(SecretsProviderError::Initialization)
+    }
+}
+
+/// age-encrypted bundle Secrets Provider implementation.
+pub struct AgeSecretsProvider {
+    path: PathBuf,
+    version: String,
+    secrets: HashMap<String, FileSecretType>,
+}
+
+impl AgeSecretsProvider {
+    /// Creates a new Secrets Provider decrypting a secrets bundle. The format is guessed from
+    /// the bundle extension, without the `.age` suffix.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the encrypted bundle. For example: `secrets.yaml.age`.
+    /// * `identity` - Where the decryption identities are read from.
+    pub fn new(path: impl Into<PathBuf>, identity: AgeIdentitySource) -> Result<Self> {
+        let path = path.into();
+        let format = path
+            .extension()
+            .filter(|e| *e == AGE_EXTENSION)
+            .and_then(|_| path.file_stem())
+            .and_then(|stem| FileFormat::from_path(Path::new(stem)))
+            .ok_or_else(|| {
+                SecretsProviderError::Initialization(format!(
+                    "Unable to guess the format of secrets bundle {}",
+                    path.display()
+                ))
+            })?;
+
+        Self::with_format(path, identity, format)
+    }
+
+    /// Creates a new Secrets Provider decrypting a secrets bundle with the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the encrypted bundle.
+    /// * `identity` - Where the decryption identities are read from.
+    /// * `format` - Format of the decrypted bundle.
+    pub fn with_format(
+        path: impl Into<PathBuf>,
+        identity: AgeIdentitySource,
+        format: FileFormat,
+    ) -> Result<Self> {
+        let path = path.into();
+        let identities = load_identities(&identity)?;
+
+        let read_error = |e: std::io::Error| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to read secrets bundle {}: {}",
+                path.display(),
+                e
+            ))
+        };
+        let version = std::fs::metadata(&path)
+            .and_then(|m| file_version(&m))
+            .map_err(read_error)?;
+        let file = std::fs::File::open(&path).map_err(read_error)?;
+
+        let decrypt_error = |e: String| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to decrypt secrets bundle {}: {}",
+                path.display(),
+                e
+            ))
+        };
+        let mut content = Vec::new();
+        Decryptor::new(ArmoredReader::new(file))
+            .and_then(|d| d.decrypt(identities.iter().map(|i| i.as_ref())))
+            .map_err(|e| decrypt_error(e.to_string()))?
+            .read_to_end(&mut content)
+            .map_err(|e| decrypt_error(e.to_string()))?;
+
+        let secrets = parse_secrets(&content, format, &path);
+        wipe(&mut content);
+        let secrets = secrets.map_err(|e| match e {
+            SecretsProviderError::ProviderFailed(e) => SecretsProviderError::Initialization(e),
+            other => other,
+        })?;
+
+        Ok(Self {
+            path,
+            version,
+            secrets,
+        })
+    }
+
+    /// Path of the encrypted bundle.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        if version.is_some_and(|v| v != self.version) {
+            return Ok(None);
+        }
+
+        match self.secrets.get(name) {
+            Some(secret) => Ok(Some(Secret {
+                secret: secret.decode(name)?,
+                name: name.to_string(),
+                version: self.version.clone(),
+                version_stages: Vec::new(),
+                tags: None,
+                created_at: None,
+                last_rotated_at: None,
+                metadata: None,
+                backend: BACKEND,
+                is_current: Some(true),
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Debug for AgeSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgeSecretsProvider")
+            .field("path", &self.path)
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reads the identities decrypting the bundle.
+fn load_identities(source: &AgeIdentitySource) -> Result<Vec<Box<dyn Identity>>> {
+    let identity_file =
+        IdentityFile::from_buffer(source.read_to_string()?.as_bytes()).map_err(|e| {
+            SecretsProviderError::Initialization(format!("Unable to parse age identities: {}", e))
+        })?;
+
+    let identities = identity_file.into_identities().map_err(|e| {
+        SecretsProviderError::Initialization(format!("Invalid age identities: {}", e))
+    })?;
+
+    if identities.is_empty() {
+        return Err(SecretsProviderError::Initialization(
+            "No age identities found".to_string(),
+        ));
+    }
+
+    Ok(identities)
+}
+
+impl ProviderCapabilities for AgeSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for AgeSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version))
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
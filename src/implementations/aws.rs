@@ -6,24 +6,94 @@
 //! * `AWS_ROLE_ARN` - ARN of the role to assume.
 //! * `AWS_ROLE_SESSION_NAME` - **(optional)** name applied to the assume-role session.
 //!
+//! Credentials are looked up with the default chain of the SDK. IAM Identity Center profiles
+//! require the `aws-sso` feature, enabled by the `aws` feature; services using other credentials
+//! can enable `aws-core` alone instead, to build fewer dependencies.
+//!
 //! For more information:
 //! `<https://docs.aws.amazon.com/sdk-for-rust/latest/dg/environment-variables.html>`
+pub mod hedging;
+pub mod policy;
+pub mod replication;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
 use async_trait::async_trait;
 use aws_config::{BehaviorVersion, Region};
-use aws_sdk_secretsmanager::error::SdkError;
+use aws_sdk_secretsmanager::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
+use aws_sdk_secretsmanager::operation::batch_get_secret_value::BatchGetSecretValueOutput;
+use aws_sdk_secretsmanager::operation::create_secret::CreateSecretError;
+use aws_sdk_secretsmanager::operation::delete_secret::DeleteSecretError;
+use aws_sdk_secretsmanager::operation::describe_secret::DescribeSecretError;
+use aws_sdk_secretsmanager::operation::get_resource_policy::GetResourcePolicyError;
 use aws_sdk_secretsmanager::operation::get_secret_value::{
     GetSecretValueError, GetSecretValueOutput,
 };
+use aws_sdk_secretsmanager::operation::list_secret_version_ids::ListSecretVersionIdsError;
+use aws_sdk_secretsmanager::operation::put_secret_value::PutSecretValueError;
+use aws_sdk_secretsmanager::operation::remove_regions_from_replication::RemoveRegionsFromReplicationError;
+use aws_sdk_secretsmanager::operation::replicate_secret_to_regions::ReplicateSecretToRegionsError;
+use aws_sdk_secretsmanager::operation::restore_secret::RestoreSecretError;
+use aws_sdk_secretsmanager::operation::tag_resource::TagResourceError;
+use aws_sdk_secretsmanager::operation::untag_resource::UntagResourceError;
+use aws_sdk_secretsmanager::primitives::{Blob, DateTime};
+use aws_sdk_secretsmanager::types::{
+    Filter, FilterNameStringType, SecretListEntry, SecretValueEntry, Tag,
+};
 use aws_sdk_secretsmanager::Client;
+use aws_smithy_runtime::client::http::hyper_014::HyperClientBuilder;
+use hyper_rustls::HttpsConnectorBuilder;
 
+use self::hedging::{Hedger, HedgingPolicy, HedgingStats};
+use self::policy::ResourcePolicy;
+use self::replication::{replica_statuses, ReplicaRegion, ReplicaStatus};
+use crate::capabilities::{Capabilities, ProviderCapabilities};
 use crate::errors::SecretsProviderError;
-use crate::secret::{Decode, Secret, SecretData};
-use crate::{Result, SecretsProvider};
+use crate::naming::NameRules;
+use crate::resolver::{self, ResolveHost};
+use crate::secret::{
+    Decode, Secret, SecretData, SecretDescription, SecretMetadata, VersionInfo, VersionStage,
+};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{
+    canary_check, DeleteOptions, ListFilter, Result, SecretsPage, SecretsProvider, SecretsWriter,
+    WriteOptions, DEFAULT_BATCH_CONCURRENCY,
+};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "AWS Secrets Manager";
+
+/// Staging label of the current version of a secret.
+const CURRENT_STAGE: &str = "AWSCURRENT";
+
+/// Staging label of the version that was current before the current one.
+const PREVIOUS_STAGE: &str = "AWSPREVIOUS";
+
+/// Staging label of the version being rotated in.
+const PENDING_STAGE: &str = "AWSPENDING";
+
+/// Maximum number of values of a ListSecrets filter.
+const MAX_FILTER_VALUES: usize = 10;
+
+/// Maximum number of secrets of a ListSecrets page.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Maximum number of secrets of a BatchGetSecretValue request.
+const MAX_BATCH_SECRETS: usize = 20;
 
 /// Amazon Web Services Secrets Provider implementation.
 #[derive(Clone)]
 pub struct AwsSecretsProvider {
     client: Client,
+    hedger: Option<Arc<Hedger>>,
+    fetch_tags: bool,
+    fetch_rotation_dates: bool,
+    fetch_metadata: bool,
+    // Set once the endpoint failed a BatchGetSecretValue request as unimplemented
+    batch_unavailable: Arc<AtomicBool>,
 }
 
 impl AwsSecretsProvider {
@@ -41,6 +111,11 @@ impl AwsSecretsProvider {
                     .load()
                     .await,
             ),
+            hedger: None,
+            fetch_tags: false,
+            fetch_rotation_dates: false,
+            fetch_metadata: false,
+            batch_unavailable: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -61,20 +136,342 @@ impl AwsSecretsProvider {
                     .load()
                     .await,
             ),
+            hedger: None,
+            fetch_tags: false,
+            fetch_rotation_dates: false,
+            fetch_metadata: false,
+            batch_unavailable: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Creates a new Secrets Provider for Amazon Web Services resolving host names with a custom
+    /// resolver. This method can be used in networks that reach AWS through private endpoints
+    /// with split-horizon DNS.
+    ///
+    /// The resolver is also used by the credentials providers (for example, to reach STS).
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - String representing the AWS Region. Must be formatted with all lowercases
+    ///   letters and hyphens. For example: `us-west-2`.
+    /// * `endpoint_url` - Optional URL overriding the service endpoint. Example:
+    ///   `https://vpce-0123.secretsmanager.us-west-2.vpce.amazonaws.com`.
+    /// * `resolver` - Host name resolver. For example, a
+    ///   [StaticResolver](crate::resolver::StaticResolver).
+    pub async fn new_with_resolver(
+        region: String,
+        endpoint_url: Option<&str>,
+        resolver: impl ResolveHost + 'static,
+    ) -> Self {
+        let connector = HttpsConnectorBuilder::new()
+            .with_native_roots()
+            .https_or_http()
+            .enable_http1()
+            .enable_http2()
+            .wrap_connector(resolver::http_connector(Arc::new(resolver)));
+
+        // The default credentials chain sends its requests through the HTTP client of the loader
+        let mut config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region))
+            .http_client(HyperClientBuilder::new().build(connector));
+        if let Some(endpoint_url) = endpoint_url {
+            config = config.endpoint_url(endpoint_url);
+        }
+
+        Self {
+            client: Client::new(&config.load().await),
+            hedger: None,
+            fetch_tags: false,
+            fetch_rotation_dates: false,
+            fetch_metadata: false,
+            batch_unavailable: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Hedges lookups: a lookup that did not complete within the usual latency of the service
+    /// sends a second request, and the first answer is used. See the [hedging] module for the
+    /// caps applied to second requests.
+    ///
+    /// Only [find](SecretsProvider::find), [find_with_version](SecretsProvider::find_with_version)
+    /// and [find_with_stage](SecretsProvider::find_with_stage) are hedged. Clones of the provider
+    /// share the observed latencies and the budget of second requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - When lookups are hedged, and how many of them.
+    pub fn with_hedging(mut self, policy: HedgingPolicy) -> Self {
+        self.hedger = Some(Arc::new(Hedger::new(policy)));
+        self
+    }
+
+    /// Retrieves the tags of the secrets with their values (see [Secret::tags]), for example to
+    /// check that a secret belongs to the tenant using it. Lookups send a DescribeSecret request
+    /// after the value is retrieved, and fail if the tags can not be read.
+    pub fn with_tags(mut self) -> Self {
+        self.fetch_tags = true;
+        self
+    }
+
+    /// Retrieves the last rotation time of the secrets with their values (see
+    /// [Secret::last_rotated_at]). Lookups send a DescribeSecret request after the value is
+    /// retrieved, shared with [with_tags](Self::with_tags), and fail if it can not be read.
+    pub fn with_rotation_dates(mut self) -> Self {
+        self.fetch_rotation_dates = true;
+        self
+    }
+
+    /// Retrieves the description, KMS key, primary region and owning service of the secrets with
+    /// their values (see [Secret::metadata]). Lookups send a DescribeSecret request after the
+    /// value is retrieved, shared with [with_tags](Self::with_tags), and fail if it can not be
+    /// read. The ARN of the secrets is always returned, without this request.
+    pub fn with_metadata(mut self) -> Self {
+        self.fetch_metadata = true;
+        self
+    }
+
+    /// Counters of the hedged lookups, if hedging is enabled.
+    pub fn hedging_stats(&self) -> Option<HedgingStats> {
+        self.hedger.as_ref().map(|h| h.stats())
+    }
+
+    /// Retrieves and parses the resource policy attached to a secret.
+    ///
+    /// Returns `None` if the secret does not exist or does not have a resource policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub async fn get_resource_policy(&self, secret_name: &str) -> Result<Option<ResourcePolicy>> {
+        validate_secret_id(secret_name)?;
+
+        match self
+            .client
+            .get_resource_policy()
+            .secret_id(secret_name)
+            .send()
+            .await
+        {
+            Ok(response) => response
+                .resource_policy()
+                .map(ResourcePolicy::parse)
+                .transpose(),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                GetResourcePolicyError::ResourceNotFoundException(_) => Ok(None),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+        }
+    }
+
+    /// Replicates a secret to other regions, and returns the replication status of all its
+    /// replicas. Replication is asynchronous: new replicas start
+    /// [InProgress](replication::ReplicationState::InProgress).
+    ///
+    /// Returns `None` if the secret does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `regions` - Regions to replicate the secret to. For example:
+    ///   `&[ReplicaRegion::new("eu-west-1")]`.
+    pub async fn replicate_secret(
+        &self,
+        secret_name: &str,
+        regions: &[ReplicaRegion],
+    ) -> Result<Option<Vec<ReplicaStatus>>> {
+        validate_secret_id(secret_name)?;
+
+        match self
+            .client
+            .replicate_secret_to_regions()
+            .secret_id(secret_name)
+            .set_add_replica_regions(Some(regions.iter().map(ReplicaRegion::to_sdk).collect()))
+            .send()
+            .await
+        {
+            Ok(response) => Ok(Some(replica_statuses(response.replication_status()))),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                ReplicateSecretToRegionsError::ResourceNotFoundException(_) => Ok(None),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
         }
     }
 
+    /// Deletes the replicas of a secret in some regions, and returns the replication status of
+    /// the remaining replicas.
+    ///
+    /// Returns `None` if the secret does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `regions` - Regions of the replicas to delete. For example: `&["eu-west-1"]`.
+    pub async fn remove_replication(
+        &self,
+        secret_name: &str,
+        regions: &[&str],
+    ) -> Result<Option<Vec<ReplicaStatus>>> {
+        validate_secret_id(secret_name)?;
+
+        match self
+            .client
+            .remove_regions_from_replication()
+            .secret_id(secret_name)
+            .set_remove_replica_regions(Some(regions.iter().map(|r| r.to_string()).collect()))
+            .send()
+            .await
+        {
+            Ok(response) => Ok(Some(replica_statuses(response.replication_status()))),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                RemoveRegionsFromReplicationError::ResourceNotFoundException(_) => Ok(None),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
+    }
+
+    /// Retrieves the version of a secret that was current at a given time: the most recent
+    /// version created at or before that time.
+    ///
+    /// Versions are selected by their creation date, so rollbacks (moving `AWSCURRENT` back to a
+    /// previous version) are not taken into account. Versions already removed by AWS can not be
+    /// selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `timestamp` - Point in time. For example: the start of an incident.
+    pub async fn find_as_of<T: Decode>(
+        &self,
+        secret_name: &str,
+        timestamp: SystemTime,
+    ) -> Result<Option<Secret<T>>> {
+        validate_secret_id(secret_name)?;
+
+        let mut as_of: Option<(SystemTime, String)> = None;
+        let mut next_token = None;
+        loop {
+            let response = match self
+                .client
+                .list_secret_version_ids()
+                .secret_id(secret_name)
+                .include_deprecated(true)
+                .set_next_token(next_token)
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                Err(SdkError::ServiceError(e)) => match e.err() {
+                    ListSecretVersionIdsError::ResourceNotFoundException(_) => return Ok(None),
+                    other => return Err(SecretsProviderError::ProviderFailed(other.to_string())),
+                },
+                Err(other) => return Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            };
+
+            for version in response.versions() {
+                let (Some(version_id), Some(created)) = (
+                    version.version_id(),
+                    version
+                        .created_date()
+                        .and_then(|d| SystemTime::try_from(*d).ok()),
+                ) else {
+                    continue;
+                };
+
+                if created <= timestamp && as_of.as_ref().is_none_or(|(c, _)| created > *c) {
+                    as_of = Some((created, version_id.to_string()));
+                }
+            }
+
+            next_token = response.next_token().map(String::from);
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        match as_of {
+            Some((_, version)) => self.find_secret(secret_name, Some(&version), None).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Describes many secrets at once, without reading their values. Missing secrets are not
+    /// included in the result.
+    ///
+    /// Secrets are looked up with ListSecrets, filtering by name, so a single request describes
+    /// up to ten secrets instead of a DescribeSecret request per secret. Only names are accepted,
+    /// not ARNs.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_names` - List of secret names to describe.
+    pub async fn batch_describe<'n>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, SecretDescription>> {
+        for name in secret_names {
+            NameRules::AWS.validate(name)?;
+        }
+
+        let mut described = HashMap::new();
+        for chunk in secret_names.chunks(MAX_FILTER_VALUES) {
+            let filter = Filter::builder()
+                .key(FilterNameStringType::Name)
+                .set_values(Some(chunk.iter().map(|n| n.to_string()).collect()))
+                .build();
+
+            let mut next_token = None;
+            loop {
+                let response = self
+                    .client
+                    .list_secrets()
+                    .filters(filter.clone())
+                    .set_next_token(next_token)
+                    .send()
+                    .await
+                    .map_err(|e| {
+                        SecretsProviderError::ProviderFailed(DisplayErrorContext(e).to_string())
+                    })?;
+
+                for entry in response.secret_list() {
+                    // The name filter matches prefixes, so other secrets may be listed too
+                    let Some(name) = chunk.iter().find(|n| Some(**n) == entry.name()) else {
+                        continue;
+                    };
+
+                    described.insert(*name, describe(entry));
+                }
+
+                next_token = response.next_token().map(String::from);
+                if next_token.is_none() {
+                    break;
+                }
+            }
+        }
+
+        Ok(described)
+    }
+
     fn parse_response<T: Decode>(
         secret_id: &str,
         response: GetSecretValueOutput,
     ) -> Result<Option<Secret<T>>> {
         let GetSecretValueOutput {
+            arn,
             version_id,
             name,
             secret_string,
             secret_binary,
+            version_stages,
+            created_date,
             ..
         } = response;
+        let version_stages = version_stages.unwrap_or_default();
 
         let name = name.unwrap_or_else(|| secret_id.to_string());
         Ok(Some(Secret {
@@ -84,40 +481,271 @@ impl AwsSecretsProvider {
                 if let Some(d) = secret_string {
                     Ok(SecretData::Str(d))
                 } else if let Some(d) = secret_binary {
-                    Ok(SecretData::Bytes(d.into_inner()))
+                    Ok(SecretData::Bytes(d.into_inner().into()))
                 } else {
                     Err(SecretsProviderError::UnknownType(name.clone()))
                 }?,
             )?,
             name,
+            is_current: Some(version_stages.iter().any(|s| s == CURRENT_STAGE)),
+            version_stages,
+            backend: BACKEND,
+            tags: None,
+            created_at: created_date.as_ref().and_then(to_system_time),
+            last_rotated_at: None,
+            metadata: arn.map(resource_metadata),
         }))
     }
 
+    fn parse_entry<T: Decode>(entry: SecretValueEntry) -> Result<Option<Secret<T>>> {
+        let SecretValueEntry {
+            arn,
+            name,
+            version_id,
+            secret_binary,
+            secret_string,
+            version_stages,
+            created_date,
+            ..
+        } = entry;
+
+        let secret_id = name.clone().or_else(|| arn.clone()).unwrap_or_default();
+        let response = GetSecretValueOutput::builder()
+            .set_arn(arn)
+            .set_name(name)
+            .set_version_id(version_id)
+            .set_secret_binary(secret_binary)
+            .set_secret_string(secret_string)
+            .set_version_stages(version_stages)
+            .set_created_date(created_date)
+            .build();
+        Self::parse_response(&secret_id, response)
+    }
+
+    /// Retrieves the current version of a group of secrets with BatchGetSecretValue requests, or
+    /// `None` if the endpoint does not implement them.
+    async fn batch_get_secret_values<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<Option<HashMap<&'n str, Secret<T>>>> {
+        let mut retrieved = HashMap::new();
+        for chunk in secret_names.chunks(MAX_BATCH_SECRETS) {
+            let mut next_token = None;
+            loop {
+                let response = self
+                    .client
+                    .batch_get_secret_value()
+                    .set_secret_id_list(Some(chunk.iter().map(|n| n.to_string()).collect()))
+                    .set_next_token(next_token)
+                    .send()
+                    .await;
+                let BatchGetSecretValueOutput {
+                    secret_values,
+                    next_token: token,
+                    ..
+                } = match response {
+                    Ok(response) => response,
+                    // Older emulators (Localstack, for example) do not implement the operation
+                    Err(SdkError::ServiceError(e))
+                        if e.raw().status().as_u16() == 501
+                            || matches!(
+                                e.err().code(),
+                                Some(
+                                    "UnknownOperationException"
+                                        | "InvalidAction"
+                                        | "NotImplemented"
+                                )
+                            ) =>
+                    {
+                        return Ok(None)
+                    }
+                    Err(e) => {
+                        return Err(SecretsProviderError::ProviderFailed(
+                            DisplayErrorContext(e).to_string(),
+                        ))
+                    }
+                };
+
+                // Secrets that could not be retrieved are reported as errors of the response, and
+                // left out like in the other implementations
+                for entry in secret_values.unwrap_or_default() {
+                    let Some(name) = chunk.iter().find(|n| {
+                        Some(**n) == entry.name.as_deref() || Some(**n) == entry.arn.as_deref()
+                    }) else {
+                        continue;
+                    };
+
+                    if let Some(mut secret) = Self::parse_entry::<T>(entry)? {
+                        self.add_description(name, &mut secret).await?;
+                        retrieved.insert(*name, secret);
+                    }
+                }
+
+                next_token = token;
+                if next_token.is_none() {
+                    break;
+                }
+            }
+        }
+
+        Ok(Some(retrieved))
+    }
+
+    /// Adds the metadata read with a DescribeSecret request to a secret, if any was requested.
+    async fn add_description<T>(&self, secret_id: &str, secret: &mut Secret<T>) -> Result<()> {
+        if !self.fetch_tags && !self.fetch_rotation_dates && !self.fetch_metadata {
+            return Ok(());
+        }
+
+        let response = self
+            .client
+            .describe_secret()
+            .secret_id(secret_id)
+            .send()
+            .await
+            .map_err(|e| SecretsProviderError::ProviderFailed(e.to_string()))?;
+
+        if self.fetch_tags {
+            secret.tags = Some(tag_map(response.tags()));
+        }
+        if self.fetch_rotation_dates {
+            secret.last_rotated_at = response.last_rotated_date().and_then(to_system_time);
+        }
+        if self.fetch_metadata {
+            let metadata = secret.metadata.get_or_insert_with(SecretMetadata::default);
+            metadata.description = response.description().map(String::from);
+            let extras = [
+                ("kms_key_id", response.kms_key_id()),
+                ("primary_region", response.primary_region()),
+                ("owning_service", response.owning_service()),
+            ];
+            for (key, value) in extras {
+                if let Some(value) = value {
+                    metadata.extras.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Retrieves a version of a secret, by identifier or staging label. The current version is
+    /// retrieved if there are none.
     async fn find_secret<T: Decode>(
         &self,
         name: &str,
         version: Option<&str>,
+        stage: Option<&str>,
     ) -> Result<Option<Secret<T>>> {
-        let mut request = self.client.get_secret_value().secret_id(name);
-        if let Some(version) = version {
-            request = request.version_id(version);
-        }
+        validate_secret_id(name)?;
 
-        match request.send().await {
+        let send = || {
+            let mut request = self.client.get_secret_value().secret_id(name);
+            if let Some(version) = version {
+                request = request.version_id(version);
+            }
+            if let Some(stage) = stage {
+                request = request.version_stage(stage);
+            }
+            request.send()
+        };
+        let response = match &self.hedger {
+            Some(hedger) => hedger.run(send).await,
+            None => send().await,
+        };
+
+        let mut secret = match response {
             Ok(response) => Self::parse_response(name, response),
             Err(SdkError::ServiceError(e)) => match e.err() {
                 GetSecretValueError::ResourceNotFoundException(_) => Ok(None),
                 other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
             },
             Err(other) => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+        }?;
+
+        if let Some(secret) = secret.as_mut() {
+            self.add_description(name, secret).await?;
         }
+        Ok(secret)
+    }
+}
+
+/// Staging label of a version stage.
+fn stage_label(stage: &VersionStage) -> &str {
+    match stage {
+        VersionStage::Current => CURRENT_STAGE,
+        VersionStage::Previous => PREVIOUS_STAGE,
+        VersionStage::Pending => PENDING_STAGE,
+        VersionStage::Label(label) => label,
+    }
+}
+
+/// Version stage of a staging label.
+fn label_stage(label: &str) -> VersionStage {
+    match label {
+        CURRENT_STAGE => VersionStage::Current,
+        PREVIOUS_STAGE => VersionStage::Previous,
+        PENDING_STAGE => VersionStage::Pending,
+        label => VersionStage::Label(label.to_string()),
+    }
+}
+
+/// Checks a secret name or ARN before sending any request.
+fn validate_secret_id(secret_id: &str) -> Result<()> {
+    if secret_id.starts_with("arn:") {
+        NameRules::AWS_ARN.validate(secret_id)
+    } else {
+        NameRules::AWS.validate(secret_id)
+    }
+}
+
+fn to_system_time(date: &DateTime) -> Option<SystemTime> {
+    SystemTime::try_from(*date).ok()
+}
+
+/// Metadata of a secret identified by its ARN.
+fn resource_metadata(arn: String) -> SecretMetadata {
+    SecretMetadata {
+        resource_id: Some(arn),
+        ..SecretMetadata::default()
+    }
+}
+
+/// Tags by key. Tags without key are skipped.
+fn tag_map(tags: &[Tag]) -> BTreeMap<String, String> {
+    tags.iter()
+        .filter_map(|tag| {
+            let key = tag.key()?.to_string();
+            Some((key, tag.value().unwrap_or_default().to_string()))
+        })
+        .collect()
+}
+
+fn describe(entry: &SecretListEntry) -> SecretDescription {
+    SecretDescription {
+        name: entry.name().unwrap_or_default().to_string(),
+        description: entry.description().map(String::from),
+        created_at: entry.created_date().and_then(to_system_time),
+        last_changed_at: entry.last_changed_date().and_then(to_system_time),
+        last_rotated_at: entry.last_rotated_date().and_then(to_system_time),
+        rotation_enabled: entry.rotation_enabled(),
+        tags: Some(tag_map(entry.tags())),
     }
 }
 
+impl ProviderCapabilities for AwsSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        stages: true,
+        list: true,
+        write: true,
+        batch: true,
+    };
+}
+
 #[async_trait]
 impl SecretsProvider for AwsSecretsProvider {
     async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
-        self.find_secret(key_name, None).await
+        self.find_secret(key_name, None, None).await
     }
 
     async fn find_with_version<T: Decode>(
@@ -125,19 +753,374 @@ impl SecretsProvider for AwsSecretsProvider {
         key_name: &str,
         version: &str,
     ) -> Result<Option<Secret<T>>> {
-        self.find_secret(key_name, Some(version)).await
+        self.find_secret(key_name, Some(version), None).await
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        key_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None, Some(stage_label(stage)))
+            .await
+    }
+
+    /// Uses a DescribeSecret request, which does not need `secretsmanager:GetSecretValue` and
+    /// does not decrypt the value. Secrets scheduled for deletion do not exist.
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        validate_secret_id(secret_name)?;
+
+        match self
+            .client
+            .describe_secret()
+            .secret_id(secret_name)
+            .send()
+            .await
+        {
+            Ok(response) => Ok(response.deleted_date().is_none()),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                DescribeSecretError::ResourceNotFoundException(_) => Ok(false),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+        }
+    }
+
+    /// Deprecated versions, without staging labels, are listed too until AWS removes them.
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        validate_secret_id(secret_name)?;
+
+        let mut versions = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = match self
+                .client
+                .list_secret_version_ids()
+                .secret_id(secret_name)
+                .include_deprecated(true)
+                .set_next_token(next_token)
+                .send()
+                .await
+            {
+                Ok(response) => response,
+                Err(SdkError::ServiceError(e)) => match e.err() {
+                    ListSecretVersionIdsError::ResourceNotFoundException(_) => {
+                        return Ok(Vec::new())
+                    }
+                    other => return Err(SecretsProviderError::ProviderFailed(other.to_string())),
+                },
+                Err(other) => return Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            };
+
+            versions.extend(response.versions().iter().filter_map(|version| {
+                Some(VersionInfo {
+                    id: version.version_id()?.to_string(),
+                    stages: version
+                        .version_stages()
+                        .iter()
+                        .map(|label| label_stage(label))
+                        .collect(),
+                    created_at: version.created_date().and_then(to_system_time),
+                })
+            }));
+
+            next_token = response.next_token().map(String::from);
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        versions.sort_by_key(|version| version.created_at);
+        Ok(versions)
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        // Listing secrets exercises both the credentials and the network path to the service
+        report.checks.push(
+            SelfTestCheck::run("list_secrets", async {
+                self.client
+                    .list_secrets()
+                    .max_results(1)
+                    .send()
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| DisplayErrorContext(e).to_string())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        let mut request = self
+            .client
+            .list_secrets()
+            .set_next_token(page_token.map(String::from));
+        if let Some(page_size) = filter.page_size {
+            request = request.max_results(page_size.clamp(1, MAX_PAGE_SIZE) as i32);
+        }
+        // An empty filter value is rejected by Secrets Manager, and every name matches anyway
+        if let Some(prefix) = filter.name_prefix.as_ref().filter(|p| !p.is_empty()) {
+            request = request.filters(
+                Filter::builder()
+                    .key(FilterNameStringType::Name)
+                    .values(prefix)
+                    .build(),
+            );
+        }
+
+        let response = request.send().await.map_err(|e| {
+            SecretsProviderError::ProviderFailed(DisplayErrorContext(e).to_string())
+        })?;
+
+        let prefix = filter.name_prefix.as_deref().unwrap_or_default();
+        Ok(SecretsPage {
+            // The name filter also matches words inside names, not only prefixes
+            secrets: response
+                .secret_list()
+                .iter()
+                .filter(|entry| entry.name().is_some_and(|n| n.starts_with(prefix)))
+                .map(describe)
+                .collect(),
+            next_page_token: response.next_token().map(String::from),
+        })
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+
+    /// Secrets are retrieved with BatchGetSecretValue requests, of up to 20 secrets each. When
+    /// the endpoint does not implement them (older Localstack versions, for example), every
+    /// secret is looked up with a GetSecretValue request instead, from then on.
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        for name in secret_names {
+            validate_secret_id(name)?;
+        }
+
+        if !self.batch_unavailable.load(Ordering::Relaxed) {
+            if let Some(retrieved) = self.batch_get_secret_values(secret_names).await? {
+                return Ok(retrieved);
+            }
+            self.batch_unavailable.store(true, Ordering::Relaxed);
+        }
+
+        self.batch_find_with_concurrency(secret_names, DEFAULT_BATCH_CONCURRENCY)
+            .await
+    }
+}
+
+#[async_trait]
+impl SecretsWriter for AwsSecretsProvider {
+    async fn create_secret(&self, secret_name: &str, value: SecretData) -> Result<String> {
+        self.create_secret_with_options(secret_name, value, &WriteOptions::default())
+            .await
+    }
+
+    async fn put_secret_value(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+    ) -> Result<Option<String>> {
+        self.put_secret_value_with_options(secret_name, value, &WriteOptions::default())
+            .await
+    }
+
+    /// The client request token becomes the identifier of the version.
+    async fn create_secret_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<String> {
+        NameRules::AWS.validate(secret_name)?;
+
+        let request = self
+            .client
+            .create_secret()
+            .name(secret_name)
+            .set_client_request_token(options.client_request_token().map(String::from));
+        let request = match value {
+            SecretData::Str(s) => request.secret_string(s),
+            SecretData::Bytes(b) => request.secret_binary(Blob::new(b)),
+        };
+
+        match request.send().await {
+            Ok(response) => Ok(response.version_id.unwrap_or_else(|| "unknown".to_string())),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                CreateSecretError::ResourceExistsException(_) => {
+                    Err(SecretsProviderError::AlreadyExists(secret_name.to_string()))
+                }
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
+    }
+
+    /// The client request token becomes the identifier of the version.
+    async fn put_secret_value_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<Option<String>> {
+        validate_secret_id(secret_name)?;
+
+        let request = self
+            .client
+            .put_secret_value()
+            .secret_id(secret_name)
+            .set_client_request_token(options.client_request_token().map(String::from));
+        let request = match value {
+            SecretData::Str(s) => request.secret_string(s),
+            SecretData::Bytes(b) => request.secret_binary(Blob::new(b)),
+        };
+
+        match request.send().await {
+            Ok(response) => Ok(Some(
+                response.version_id.unwrap_or_else(|| "unknown".to_string()),
+            )),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                PutSecretValueError::ResourceNotFoundException(_) => Ok(None),
+                // The client request token was already used with another value
+                PutSecretValueError::ResourceExistsException(e) => {
+                    Err(SecretsProviderError::InvalidVersion(e.to_string()))
+                }
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
     }
 
-    // NOTE: The official SDK provides the `batch_get_secret_value` method which would
-    // be a more efficient implementation of the `batch_find` method. However, it's
-    // still too recent to the point it's lacking support in localstack.
-    //
-    // We'll override the default implementation once we know localstack supports it so
-    // it doesn't block the development process / integration testing / pipelines.
+    async fn delete_secret(
+        &self,
+        secret_name: &str,
+        options: DeleteOptions,
+    ) -> Result<Option<SystemTime>> {
+        validate_secret_id(secret_name)?;
+
+        let request = self.client.delete_secret().secret_id(secret_name);
+        let request = match options {
+            DeleteOptions::DefaultRecoveryWindow => request,
+            DeleteOptions::RecoveryWindowDays(days) => {
+                request.recovery_window_in_days(i64::from(days))
+            }
+            DeleteOptions::Force => request.force_delete_without_recovery(true),
+        };
+
+        match request.send().await {
+            Ok(response) => Ok(Some(
+                response
+                    .deletion_date
+                    .as_ref()
+                    .and_then(to_system_time)
+                    .unwrap_or_else(SystemTime::now),
+            )),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                DeleteSecretError::ResourceNotFoundException(_) => Ok(None),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
+    }
+
+    async fn restore_secret(&self, secret_name: &str) -> Result<bool> {
+        validate_secret_id(secret_name)?;
+
+        match self
+            .client
+            .restore_secret()
+            .secret_id(secret_name)
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                RestoreSecretError::ResourceNotFoundException(_) => Ok(false),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
+    }
+
+    async fn tag_secret(&self, secret_name: &str, tags: BTreeMap<String, String>) -> Result<bool> {
+        validate_secret_id(secret_name)?;
+
+        let tags = tags
+            .into_iter()
+            .map(|(key, value)| Tag::builder().key(key).value(value).build())
+            .collect();
+        match self
+            .client
+            .tag_resource()
+            .secret_id(secret_name)
+            .set_tags(Some(tags))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                TagResourceError::ResourceNotFoundException(_) => Ok(false),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
+    }
+
+    async fn untag_secret(&self, secret_name: &str, tag_keys: &[&str]) -> Result<bool> {
+        validate_secret_id(secret_name)?;
+
+        match self
+            .client
+            .untag_resource()
+            .secret_id(secret_name)
+            .set_tag_keys(Some(tag_keys.iter().map(|key| key.to_string()).collect()))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                UntagResourceError::ResourceNotFoundException(_) => Ok(false),
+                other => Err(SecretsProviderError::ProviderFailed(other.to_string())),
+            },
+            Err(other) => Err(SecretsProviderError::ProviderFailed(
+                DisplayErrorContext(other).to_string(),
+            )),
+        }
+    }
 }
 
 impl From<Client> for AwsSecretsProvider {
     fn from(client: Client) -> Self {
-        Self { client }
+        Self {
+            client,
+            hedger: None,
+            fetch_tags: false,
+            fetch_rotation_dates: false,
+            fetch_metadata: false,
+            batch_unavailable: Arc::new(AtomicBool::new(false)),
+        }
     }
 }
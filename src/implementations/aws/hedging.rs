@@ -0,0 +1,229 @@
+//! Hedged requests of the official SDK implementation.
+//!
+//! Most lookups are answered quickly, but a few of them wait much longer (a slow connection, a
+//! throttled host...). With hedging, a lookup that did not complete within the usual latency of
+//! the service sends a second request, and the first answer is used. The hedging delay is a
+//! percentile (p95 by default) of the latencies observed by the provider.
+//!
+//! Hedging sends more requests to the service, so it is strictly capped:
+//!
+//! * A lookup sends at most one additional request.
+//! * Additional requests are a bounded share of the lookups (5% by default), plus a small burst.
+//! * The hedging delay stays within bounds, and the upper one is used until enough latencies were
+//!   observed.
+use std::collections::VecDeque;
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+use std::sync::{Mutex, MutexGuard};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+/// Latencies the hedging delay is computed from.
+const LATENCY_WINDOW: usize = 200;
+
+/// Latencies observed before the percentile is used as the hedging delay.
+const MIN_SAMPLES: usize = 20;
+
+/// When lookups are hedged, and how many of them.
+#[derive(Clone, Debug)]
+pub struct HedgingPolicy {
+    /// Percentile of the observed latencies after which a second request is sent, between `0`
+    /// and `1`. Defaults to `0.95`.
+    pub percentile: f64,
+
+    /// Lower bound of the hedging delay. Defaults to 10 milliseconds.
+    pub min_delay: Duration,
+
+    /// Upper bound of the hedging delay, also used until enough latencies were observed.
+    /// Defaults to 1 second.
+    pub max_delay: Duration,
+
+    /// Maximum share of the lookups sending a second request, between `0` and `1`. Defaults to
+    /// `0.05`.
+    pub max_hedged_ratio: f64,
+
+    /// Second requests allowed on top of the share, for example, right after a start. Defaults
+    /// to `10`.
+    pub burst: u32,
+}
+
+impl Default for HedgingPolicy {
+    fn default() -> Self {
+        Self {
+            percentile: 0.95,
+            min_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(1),
+            max_hedged_ratio: 0.05,
+            burst: 10,
+        }
+    }
+}
+
+/// Counters of the hedged lookups of a provider.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HedgingStats {
+    /// Lookups sent.
+    pub requests: u64,
+
+    /// Lookups that sent a second request.
+    pub hedged: u64,
+
+    /// Lookups answered by their second request.
+    pub hedges_won: u64,
+
+    /// Current hedging delay.
+    pub delay: Duration,
+}
+
+struct HedgerState {
+    latencies: VecDeque<Duration>,
+    tokens: f64,
+    stats: HedgingStats,
+}
+
+/// Hedges the requests of a provider, sharing the observed latencies and the budget of second
+/// requests between its clones.
+pub(super) struct Hedger {
+    policy: HedgingPolicy,
+    state: Mutex<HedgerState>,
+}
+
+impl Hedger {
+    pub(super) fn new(policy: HedgingPolicy) -> Self {
+        Self {
+            state: Mutex::new(HedgerState {
+                latencies: VecDeque::with_capacity(LATENCY_WINDOW),
+                tokens: f64::from(policy.burst),
+                stats: HedgingStats {
+                    delay: policy.max_delay,
+                    ..HedgingStats::default()
+                },
+            }),
+            policy,
+        }
+    }
+
+    pub(super) fn stats(&self) -> HedgingStats {
+        self.lock_state().stats
+    }
+
+    /// Sends a request, and a second one if the first did not complete within the hedging delay
+    /// and the budget allows it. The first success is returned. If a request fails while the
+    /// other one is still running, the other one is awaited.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Sends a request.
+    pub(super) async fn run<R, E, F, Fut>(&self, request: F) -> std::result::Result<R, E>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = std::result::Result<R, E>>,
+    {
+        let delay = {
+            let mut state = self.lock_state();
+            state.stats.requests += 1;
+            state.tokens =
+                (state.tokens + self.policy.max_hedged_ratio).min(f64::from(self.policy.burst));
+            state.stats.delay
+        };
+
+        let start = Instant::now();
+        let mut first = pin!(request());
+        let mut timer = pin!(tokio::time::sleep(delay));
+        let answered = poll_fn(|cx| match first.as_mut().poll(cx) {
+            Poll::Ready(result) => Poll::Ready(Some(result)),
+            Poll::Pending if timer.as_mut().poll(cx).is_ready() => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        })
+        .await;
+        if let Some(result) = answered {
+            self.record(&result, start.elapsed());
+            return result;
+        }
+
+        if !self.try_hedge() {
+            let result = first.await;
+            self.record(&result, start.elapsed());
+            return result;
+        }
+
+        let hedge_start = Instant::now();
+        let mut second = pin!(request());
+        let mut first_done = false;
+        let mut second_done = false;
+        poll_fn(|cx| {
+            if !first_done {
+                if let Poll::Ready(result) = first.as_mut().poll(cx) {
+                    first_done = true;
+                    self.record(&result, start.elapsed());
+                    if result.is_ok() || second_done {
+                        return Poll::Ready(result);
+                    }
+                }
+            }
+            if !second_done {
+                if let Poll::Ready(result) = second.as_mut().poll(cx) {
+                    second_done = true;
+                    self.record(&result, hedge_start.elapsed());
+                    if result.is_ok() {
+                        self.lock_state().stats.hedges_won += 1;
+                    }
+                    if result.is_ok() || first_done {
+                        return Poll::Ready(result);
+                    }
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Takes a second request from the budget, if there is one left.
+    fn try_hedge(&self) -> bool {
+        let mut state = self.lock_state();
+        if state.tokens < 1.0 {
+            return false;
+        }
+
+        state.tokens -= 1.0;
+        state.stats.hedged += 1;
+        true
+    }
+
+    /// Records the latency of a successful request and updates the hedging delay. Failures are
+    /// not recorded, so fast errors do not shorten the delay.
+    fn record<R, E>(&self, result: &std::result::Result<R, E>, latency: Duration) {
+        if result.is_err() {
+            return;
+        }
+
+        let mut state = self.lock_state();
+        if state.latencies.len() == LATENCY_WINDOW {
+            state.latencies.pop_front();
+        }
+        state.latencies.push_back(latency);
+
+        if state.latencies.len() >= MIN_SAMPLES {
+            let mut latencies: Vec<Duration> = state.latencies.iter().copied().collect();
+            latencies.sort_unstable();
+            let rank = (self.policy.percentile.clamp(0.0, 1.0) * latencies.len() as f64).ceil();
+            let index = (rank as usize).clamp(1, latencies.len()) - 1;
+            state.stats.delay = latencies[index]
+                .max(self.policy.min_delay)
+                .min(self.policy.max_delay);
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, HedgerState> {
+        // Counters are updated in a single step, so a poisoned lock still holds consistent data
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl std::fmt::Debug for Hedger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hedger")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
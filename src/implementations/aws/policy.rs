@@ -0,0 +1,209 @@
+//! Typed representation of AWS Secrets Manager resource policies.
+//!
+//! Resource policies are IAM policy documents attached to a secret. They are returned by
+//! [AwsSecretsProvider::get_resource_policy](crate::implementations::aws::AwsSecretsProvider::get_resource_policy),
+//! and can also be parsed from their JSON representation with
+//! [ResourcePolicy::parse](crate::implementations::aws::policy::ResourcePolicy::parse).
+//!
+//! For more information:
+//! `<https://docs.aws.amazon.com/IAM/latest/UserGuide/reference_policies_elements.html>`
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::errors::SecretsProviderError;
+use crate::Result;
+
+/// Resource policy attached to a secret.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ResourcePolicy {
+    /// Policy language version. For example: `2012-10-17`.
+    pub version: Option<String>,
+
+    /// Optional identifier of the policy.
+    pub id: Option<String>,
+
+    /// Statements of the policy.
+    #[serde(rename = "Statement", deserialize_with = "one_or_many")]
+    pub statements: Vec<PolicyStatement>,
+}
+
+impl ResourcePolicy {
+    /// Parses a resource policy from its JSON representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - JSON policy document.
+    pub fn parse(policy: &str) -> Result<Self> {
+        serde_json::from_str(policy).map_err(|e| {
+            SecretsProviderError::ProviderFailed(format!("Unable to parse resource policy: {}", e))
+        })
+    }
+
+    /// Returns the AWS principals (account ids, ARNs or `*`) granted access by the `Allow`
+    /// statements of the policy.
+    pub fn allowed_aws_principals(&self) -> impl Iterator<Item = &str> {
+        self.statements
+            .iter()
+            .filter(|s| s.effect == Effect::Allow)
+            .filter_map(|s| s.principal.as_ref())
+            .flat_map(|p| p.aws())
+    }
+}
+
+/// A single statement of a resource policy.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyStatement {
+    /// Optional identifier of the statement.
+    pub sid: Option<String>,
+
+    /// Whether the statement allows or denies access.
+    pub effect: Effect,
+
+    /// Principals the statement applies to.
+    pub principal: Option<Principal>,
+
+    /// Principals the statement does not apply to.
+    pub not_principal: Option<Principal>,
+
+    /// Actions the statement applies to. For example: `secretsmanager:GetSecretValue`.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub action: Vec<String>,
+
+    /// Actions the statement does not apply to.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub not_action: Vec<String>,
+
+    /// Resources the statement applies to.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub resource: Vec<String>,
+
+    /// Conditions of the statement, indexed by condition operator and then by condition key.
+    /// Values are kept as strings, so `true` and `"true"` are equivalent.
+    #[serde(default, deserialize_with = "conditions")]
+    pub condition: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+/// Effect of a policy statement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// Principals of a policy statement.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawPrincipal")]
+pub enum Principal {
+    /// Every principal (`"Principal": "*"`).
+    Any,
+
+    /// Principals indexed by type (`AWS`, `Service`, `Federated`...).
+    Typed(BTreeMap<String, Vec<String>>),
+}
+
+impl Principal {
+    /// Returns the AWS principals (account ids, ARNs or `*`). [Principal::Any] is returned as
+    /// `*`.
+    pub fn aws(&self) -> Vec<&str> {
+        match self {
+            Principal::Any => vec!["*"],
+            Principal::Typed(principals) => principals
+                .get("AWS")
+                .map(|p| p.iter().map(String::as_str).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawConditionValue {
+    Str(String),
+    Bool(bool),
+    Number(serde_json::Number),
+}
+
+impl From<RawConditionValue> for String {
+    fn from(value: RawConditionValue) -> Self {
+        match value {
+            RawConditionValue::Str(s) => s,
+            RawConditionValue::Bool(b) => b.to_string(),
+            RawConditionValue::Number(n) => n.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawPrincipal {
+    Wildcard(String),
+    Typed(BTreeMap<String, OneOrMany<String>>),
+}
+
+impl TryFrom<RawPrincipal> for Principal {
+    type Error = String;
+
+    fn try_from(value: RawPrincipal) -> std::result::Result<Self, Self::Error> {
+        match value {
+            RawPrincipal::Wildcard(w) if w == "*" => Ok(Principal::Any),
+            RawPrincipal::Wildcard(w) => Err(format!(r#"invalid principal "{}""#, w)),
+            RawPrincipal::Typed(principals) => Ok(Principal::Typed(
+                principals
+                    .into_iter()
+                    .map(|(kind, p)| (kind, p.into()))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// Most policy elements can be either a single value or a list of values.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> From<OneOrMany<T>> for Vec<T> {
+    fn from(value: OneOrMany<T>) -> Self {
+        match value {
+            OneOrMany::One(v) => vec![v],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+fn one_or_many<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    OneOrMany::deserialize(deserializer).map(Vec::from)
+}
+
+type RawConditions = BTreeMap<String, BTreeMap<String, OneOrMany<RawConditionValue>>>;
+
+fn conditions<'de, D>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, BTreeMap<String, Vec<String>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(RawConditions::deserialize(deserializer)?
+        .into_iter()
+        .map(|(operator, keys)| {
+            let keys = keys
+                .into_iter()
+                .map(|(key, values)| {
+                    let values: Vec<RawConditionValue> = values.into();
+                    (key, values.into_iter().map(String::from).collect())
+                })
+                .collect();
+            (operator, keys)
+        })
+        .collect())
+}
@@ -0,0 +1,120 @@
+//! Cross-region replication of AWS Secrets Manager secrets.
+//!
+//! Secrets are replicated to other regions with
+//! [AwsSecretsProvider::replicate_secret](crate::implementations::aws::AwsSecretsProvider::replicate_secret),
+//! and replicas are removed with
+//! [AwsSecretsProvider::remove_replication](crate::implementations::aws::AwsSecretsProvider::remove_replication).
+//! Both return the replication status of the remaining replicas.
+//!
+//! For more information:
+//! `<https://docs.aws.amazon.com/secretsmanager/latest/userguide/create-manage-multi-region-secrets.html>`
+use std::time::SystemTime;
+
+use aws_sdk_secretsmanager::types::{ReplicaRegionType, ReplicationStatusType, StatusType};
+
+/// Region a secret is replicated to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplicaRegion {
+    region: String,
+    kms_key_id: Option<String>,
+}
+
+impl ReplicaRegion {
+    /// Replica encrypted with the `aws/secretsmanager` managed key of the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - Region of the replica. For example: `eu-west-1`.
+    pub fn new(region: impl Into<String>) -> Self {
+        Self {
+            region: region.into(),
+            kms_key_id: None,
+        }
+    }
+
+    /// Encrypts the replica with a customer managed key of the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `kms_key_id` - ARN, key id or alias of the KMS key.
+    pub fn with_kms_key_id(mut self, kms_key_id: impl Into<String>) -> Self {
+        self.kms_key_id = Some(kms_key_id.into());
+        self
+    }
+
+    /// Region of the replica.
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// KMS key encrypting the replica, if not the managed key.
+    pub fn kms_key_id(&self) -> Option<&str> {
+        self.kms_key_id.as_deref()
+    }
+
+    pub(super) fn to_sdk(&self) -> ReplicaRegionType {
+        ReplicaRegionType::builder()
+            .region(&self.region)
+            .set_kms_key_id(self.kms_key_id.clone())
+            .build()
+    }
+}
+
+/// State of the replication to a region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplicationState {
+    /// The replica is up to date.
+    InSync,
+
+    /// The replica is being created or updated.
+    InProgress,
+
+    /// The replication failed. The status message explains why.
+    Failed,
+
+    /// State unknown to this version of the crate.
+    Unknown(String),
+}
+
+/// Replication status of a secret in a region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplicaStatus {
+    /// Region of the replica.
+    pub region: String,
+
+    /// KMS key encrypting the replica.
+    pub kms_key_id: Option<String>,
+
+    /// State of the replication.
+    pub state: ReplicationState,
+
+    /// Details about the state, for example why the replication failed.
+    pub message: Option<String>,
+
+    /// Last day the replica was accessed, if known.
+    pub last_accessed_at: Option<SystemTime>,
+}
+
+/// Statuses of the replicas. Statuses without region are skipped.
+pub(super) fn replica_statuses(statuses: &[ReplicationStatusType]) -> Vec<ReplicaStatus> {
+    statuses
+        .iter()
+        .filter_map(|status| {
+            Some(ReplicaStatus {
+                region: status.region()?.to_string(),
+                kms_key_id: status.kms_key_id().map(String::from),
+                state: match status.status() {
+                    Some(StatusType::InSync) => ReplicationState::InSync,
+                    Some(StatusType::InProgress) => ReplicationState::InProgress,
+                    Some(StatusType::Failed) => ReplicationState::Failed,
+                    Some(other) => ReplicationState::Unknown(other.as_str().to_string()),
+                    None => ReplicationState::Unknown(String::new()),
+                },
+                message: status.status_message().map(String::from),
+                last_accessed_at: status
+                    .last_accessed_date()
+                    .and_then(|date| SystemTime::try_from(*date).ok()),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,283 @@
+//! Directory tree Secret Provider implementation.
+//!
+//! Each secret is a file under a root directory, and the secret name is the file path relative to
+//! that root. This is the layout rendered by tools like Vault Agent or confd.
+//!
+//! Secrets are versioned with numbered suffixes: `name` is version `1`, and `name.v2`, `name.v3`
+//! ... are the following versions (`name.v1` can also be used for the first version). The
+//! current version is the highest one.
+//!
+//! Text files (valid UTF-8 content) can be read as [String](std::string::String), and any file
+//! can be read as [Vec<u8>](std::vec::Vec). Reading a binary file as a string fails with
+//! [InvalidType](crate::SecretsProviderError::InvalidType).
+//!
+//! Whole subtrees can be read at once with
+//! [find_tree](crate::implementations::directory::DirectorySecretsProvider::find_tree).
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use super::secret_file_path;
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, SecretTree, VersionInfo};
+use crate::{Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Directory tree";
+
+/// Separator between the secret name and its version number.
+const VERSION_SEPARATOR: &str = ".v";
+
+/// Directory tree Secrets Provider implementation.
+#[derive(Clone, Debug)]
+pub struct DirectorySecretsProvider {
+    root: PathBuf,
+}
+
+impl DirectorySecretsProvider {
+    /// Creates a new Secrets Provider reading the secrets under a given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory containing the secrets. For example: `/vault/secrets`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory where the secrets are read from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Lists the existing versions of a given secret, the most recent version last.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub fn list_secret_version_ids(&self, secret_name: &str) -> Result<Vec<String>> {
+        Ok(self
+            .secret_versions(secret_name)?
+            .into_keys()
+            .map(|v| v.to_string())
+            .collect())
+    }
+
+    /// Reads the current version of every secret under a path, including the subdirectories.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Path of the tree relative to the root, with `/` separated components. An
+    ///   empty prefix reads every secret.
+    pub fn find_tree<T: Decode>(&self, prefix: &str) -> Result<SecretTree<T>> {
+        let prefix = prefix.trim_matches('/');
+        let directory = if prefix.is_empty() {
+            self.root.clone()
+        } else {
+            secret_file_path(&self.root, prefix)?
+        };
+
+        self.read_tree(&directory, prefix)
+    }
+
+    fn read_tree<T: Decode>(&self, directory: &Path, prefix: &str) -> Result<SecretTree<T>> {
+        let read_error = |e: std::io::Error| {
+            SecretsProviderError::ProviderFailed(format!(
+                "Unable to read secrets directory {}: {}",
+                directory.display(),
+                e
+            ))
+        };
+
+        let mut tree = SecretTree::default();
+        if !directory.is_dir() {
+            return Ok(tree);
+        }
+
+        let mut secret_names = Vec::new();
+        for entry in std::fs::read_dir(directory).map_err(read_error)? {
+            let entry = entry.map_err(read_error)?;
+            let file_type = entry.file_type().map_err(read_error)?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+
+            if file_type.is_dir() {
+                let subtree = self.read_tree(&entry.path(), &join(prefix, &file_name))?;
+                if !subtree.is_empty() {
+                    tree.subtrees.insert(file_name, subtree);
+                }
+            } else if file_type.is_file() {
+                // Versions of the same secret are read once, with the secret base name
+                secret_names.push(strip_version(&file_name).to_string());
+            }
+        }
+
+        secret_names.sort_unstable();
+        secret_names.dedup();
+        for name in secret_names {
+            if let Some(secret) = self.read_secret(&join(prefix, &name), None)? {
+                tree.secrets.insert(name, secret);
+            }
+        }
+
+        Ok(tree)
+    }
+
+    /// Finds the files holding each version of a secret, indexed by version number.
+    fn secret_versions(&self, secret_name: &str) -> Result<BTreeMap<u64, PathBuf>> {
+        let path = secret_file_path(&self.root, secret_name)?;
+        let read_error = |e: std::io::Error| {
+            SecretsProviderError::ProviderFailed(format!(
+                "Unable to read secret {} from {}: {}",
+                secret_name,
+                self.root.display(),
+                e
+            ))
+        };
+
+        let mut versions = BTreeMap::new();
+        let (Some(directory), Some(base_name)) = (path.parent(), path.file_name()) else {
+            return Ok(versions);
+        };
+        let base_name = base_name.to_string_lossy();
+
+        let entries = match std::fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(versions),
+            Err(e) => return Err(read_error(e)),
+        };
+
+        for entry in entries {
+            let entry = entry.map_err(read_error)?;
+            if !entry.file_type().map_err(read_error)?.is_file() {
+                continue;
+            }
+
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            let version = if file_name == base_name {
+                // An explicit `name.v1` file takes precedence over `name`
+                if versions.contains_key(&1) {
+                    continue;
+                }
+                1
+            } else if let Some(version) = file_name
+                .strip_prefix(base_name.as_ref())
+                .and_then(|suffix| suffix.strip_prefix(VERSION_SEPARATOR))
+                .filter(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|v| v.parse::<u64>().ok())
+            {
+                version
+            } else {
+                continue;
+            };
+
+            versions.insert(version, entry.path());
+        }
+
+        Ok(versions)
+    }
+
+    fn read_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let mut versions = self.secret_versions(name)?;
+        let current = versions.last_key_value().map(|(v, _)| *v);
+        let found = match version {
+            Some(v) => v
+                .parse::<u64>()
+                .ok()
+                .and_then(|v| versions.remove_entry(&v)),
+            None => versions.pop_last(),
+        };
+
+        let Some((version, path)) = found else {
+            return Ok(None);
+        };
+
+        let content = match std::fs::read(&path) {
+            Ok(content) => content,
+            // The file was removed after listing the versions
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(SecretsProviderError::ProviderFailed(format!(
+                    "Unable to read secret file {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, content)?,
+            name: name.to_string(),
+            version: version.to_string(),
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(current == Some(version)),
+        }))
+    }
+}
+
+/// Removes the version suffix of a secret file name, if any.
+fn strip_version(file_name: &str) -> &str {
+    match file_name.rsplit_once(VERSION_SEPARATOR) {
+        Some((base_name, version))
+            if !base_name.is_empty()
+                && !version.is_empty()
+                && version.bytes().all(|b| b.is_ascii_digit()) =>
+        {
+            base_name
+        }
+        _ => file_name,
+    }
+}
+
+/// Joins a secret name to a path prefix.
+fn join(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    }
+}
+
+impl ProviderCapabilities for DirectorySecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for DirectorySecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, Some(version))
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        let versions = self.list_secret_version_ids(secret_name)?;
+        Ok(VersionInfo::last_current(
+            versions.into_iter().map(|id| (id, None)),
+        ))
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
@@ -0,0 +1,315 @@
+//! Doppler Secret Provider implementation.
+//!
+//! Secrets are read from a Doppler config through the Doppler API, authenticating with a token.
+//! Service tokens are bound to a single config, so the project and the config only need to be
+//! selected when using personal or service account tokens.
+//!
+//! Doppler versions configs as a whole, so every secret of a config shares the same version: the
+//! entity tag of the config, which changes whenever any of its secrets changes. Only the current
+//! version can be read; older versions must be restored with a rollback in Doppler.
+//!
+//! Doppler secrets are always strings, and their names are made of uppercase ASCII letters, digits
+//! and underscores. Other names are rejected before downloading the config.
+//!
+//! For more information:
+//! `<https://docs.doppler.com/reference/secrets-download>`
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use reqwest::header::ETAG;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::naming::NameRules;
+use crate::secret::{Decode, Secret, SecretData};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Doppler";
+
+/// Doppler API endpoint.
+pub const DOPPLER_API_ENDPOINT: &str = "https://api.doppler.com";
+
+/// Doppler Secrets Provider builder.
+pub struct DopplerSecretsProviderBuilder {
+    /// Service, service account or personal token.
+    token: String,
+
+    /// Doppler project.
+    project: Option<String>,
+
+    /// Config of the project. For example: `prd`.
+    config: Option<String>,
+
+    /// Endpoint of the Doppler API.
+    endpoint: String,
+}
+
+impl DopplerSecretsProviderBuilder {
+    /// Creates a new Doppler Secrets Provider builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Doppler token. For example, a service token: `dp.st.prd.xxxx`.
+    pub fn new(token: String) -> Self {
+        Self {
+            token,
+            project: None,
+            config: None,
+            endpoint: DOPPLER_API_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Selects the project to read secrets from. Not needed with service tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - Doppler project name.
+    pub fn project(mut self, project: String) -> Self {
+        self.project = Some(project);
+        self
+    }
+
+    /// Selects the config to read secrets from. Not needed with service tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Config name. For example: `prd`.
+    pub fn config(mut self, config: String) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Overrides the Doppler API endpoint.
+    ///
+    /// This is usually used for testing purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String represeting the endpoint. For example: `http://127.0.0.1:8080`.
+    pub fn endpoint_override(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Builds a [DopplerSecretsProvider](crate::implementations::doppler::DopplerSecretsProvider).
+    pub fn build(self) -> Result<DopplerSecretsProvider> {
+        let client = Client::builder().build().map_err(|e| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to build Doppler HTTP Client: {}",
+                e
+            ))
+        })?;
+
+        Ok(DopplerSecretsProvider {
+            client,
+            token: self.token,
+            project: self.project,
+            config: self.config,
+            endpoint: self.endpoint.trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+/// Doppler Secrets Provider implementation.
+#[derive(Clone)]
+pub struct DopplerSecretsProvider {
+    client: Client,
+    token: String,
+    project: Option<String>,
+    config: Option<String>,
+    endpoint: String,
+}
+
+/// Secrets of a config, with the config version.
+struct ConfigSecrets {
+    version: String,
+    secrets: HashMap<String, String>,
+}
+
+/// Error response of the Doppler API.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(default)]
+    messages: Vec<String>,
+}
+
+impl DopplerSecretsProvider {
+    /// Downloads every secret of the config.
+    async fn download_config(&self) -> Result<ConfigSecrets> {
+        let mut query = vec![("format", "json")];
+        if let Some(project) = &self.project {
+            query.push(("project", project));
+        }
+        if let Some(config) = &self.config {
+            query.push(("config", config));
+        }
+
+        let response = self
+            .client
+            .get(format!(
+                "{}/v3/configs/config/secrets/download",
+                self.endpoint
+            ))
+            .bearer_auth(&self.token)
+            .query(&query)
+            .send()
+            .await
+            .map_err(|e| {
+                SecretsProviderError::ProviderFailed(format!("Doppler request failed: {}", e))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let messages = response
+                .json::<ErrorResponse>()
+                .await
+                .map(|e| e.messages.join(", "))
+                .unwrap_or_default();
+            return Err(SecretsProviderError::ProviderFailed(match status {
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                    format!("Doppler denied access ({}): {}", status, messages)
+                }
+                _ => format!("Doppler request failed ({}): {}", status, messages),
+            }));
+        }
+
+        let version = response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(|etag| etag.trim_start_matches("W/").trim_matches('"').to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let secrets = response.json().await.map_err(|e| {
+            SecretsProviderError::ProviderFailed(format!("Invalid Doppler response: {}", e))
+        })?;
+
+        Ok(ConfigSecrets { version, secrets })
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        NameRules::DOPPLER.validate(name)?;
+
+        let ConfigSecrets {
+            version: config_version,
+            mut secrets,
+        } = self.download_config().await?;
+
+        if version.is_some_and(|v| v != config_version) {
+            return Ok(None);
+        }
+
+        match secrets.remove(name) {
+            Some(value) => Ok(Some(Secret {
+                secret: T::decode(name, SecretData::Str(value))?,
+                name: name.to_string(),
+                version: config_version,
+                version_stages: Vec::new(),
+                tags: None,
+                created_at: None,
+                last_rotated_at: None,
+                metadata: None,
+                backend: BACKEND,
+                is_current: Some(true),
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for DopplerSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DopplerSecretsProvider")
+            .field("project", &self.project)
+            .field("config", &self.config)
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for DopplerSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        batch: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for DopplerSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version)).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        for name in secret_names {
+            NameRules::DOPPLER.validate(name)?;
+        }
+
+        // The whole config is downloaded at once, so a single request is enough
+        let ConfigSecrets {
+            version,
+            mut secrets,
+        } = self.download_config().await?;
+
+        let mut retrieved = HashMap::new();
+        for name in secret_names {
+            if let Some(value) = secrets.remove(*name) {
+                retrieved.insert(
+                    *name,
+                    Secret {
+                        secret: T::decode(name, SecretData::Str(value))?,
+                        name: name.to_string(),
+                        version: version.clone(),
+                        version_stages: Vec::new(),
+                        tags: None,
+                        created_at: None,
+                        last_rotated_at: None,
+                        metadata: None,
+                        backend: BACKEND,
+                        is_current: Some(true),
+                    },
+                );
+            }
+        }
+
+        Ok(retrieved)
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        // Downloading the config exercises both the token and the network path to the API
+        report.checks.push(
+            SelfTestCheck::run("download_config", async {
+                self.download_config().await.map(|_| ())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
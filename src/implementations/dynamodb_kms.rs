@@ -0,0 +1,409 @@
+//! AWS DynamoDB + KMS Secret Provider implementation.
+//!
+//! Secrets are rows of a DynamoDB table, encrypted client-side with a KMS data key, in the layout
+//! written by [credstash](https://github.com/fugue/credstash) and its ports. This serverless
+//! pattern predates Secrets Manager, and many systems still keep their secrets this way. Each row
+//! holds one version of a secret:
+//!
+//! - `name` (hash key) and `version` (range key): the secret name, and its version as a number
+//!   zero-padded to 19 digits, so that versions sort numerically.
+//! - `key`: the 64 bytes data key, encrypted by KMS and base64-encoded. The first half is the
+//!   AES-256 key, the second half the HMAC key.
+//! - `contents`: the value, encrypted with AES-256 in CTR mode and base64-encoded.
+//! - `hmac`: the hex-encoded HMAC of the encrypted value, checked before decrypting it.
+//! - `digest`: the HMAC digest algorithm. Only the default `SHA256` is supported.
+//!
+//! The current version of a secret is the row with the highest version, read with a consistent
+//! query. Versions can be requested with or without their zero padding: `1` and
+//! `0000000000000000001` are the same version.
+//!
+//! Decrypted values do not say whether they hold a string or a binary secret, so the plaintext is
+//! returned as the requested type: [String](std::string::String) if it is valid UTF-8, or
+//! [Vec<u8>](std::vec::Vec) for any content.
+//!
+//! Credentials are read like for the [AWS implementation](crate::implementations::aws), and the
+//! role must be allowed to `dynamodb:Query` and `dynamodb:GetItem` the table, and `kms:Decrypt`
+//! the data keys.
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use aws_sdk_dynamodb::error::DisplayErrorContext;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_kms::primitives::Blob;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::crypto;
+use crate::encoding::decode_hex;
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "AWS DynamoDB + KMS";
+
+/// Default table of credstash.
+pub const DEFAULT_TABLE: &str = "credential-store";
+
+/// Attributes of the rows.
+const NAME_ATTRIBUTE: &str = "name";
+const VERSION_ATTRIBUTE: &str = "version";
+const KEY_ATTRIBUTE: &str = "key";
+const CONTENTS_ATTRIBUTE: &str = "contents";
+const HMAC_ATTRIBUTE: &str = "hmac";
+const DIGEST_ATTRIBUTE: &str = "digest";
+
+/// Width of the zero-padded versions.
+const VERSION_WIDTH: usize = 19;
+
+/// Only the default digest is supported. Rows written by old versions of credstash have no
+/// digest attribute, and use it too.
+const SHA256_DIGEST: &str = "SHA256";
+
+/// Values are encrypted with a fixed counter block: every version has its own data key.
+const COUNTER_BLOCK: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+
+/// AWS DynamoDB + KMS Secrets Provider implementation.
+#[derive(Clone)]
+pub struct DynamoDbKmsSecretsProvider {
+    dynamodb: aws_sdk_dynamodb::Client,
+    kms: aws_sdk_kms::Client,
+    table: String,
+    encryption_context: HashMap<String, String>,
+}
+
+impl DynamoDbKmsSecretsProvider {
+    /// Creates a new Secrets Provider reading the secrets of a DynamoDB table.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - String representing the AWS Region. Must be formatted with all lowercases
+    ///   letters and hyphens. For example: `us-west-2`.
+    /// * `table` - DynamoDB table of the secrets. credstash uses [DEFAULT_TABLE] by default.
+    pub async fn new(region: String, table: String) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region))
+            .load()
+            .await;
+        Self::from_config(&config, table)
+    }
+
+    /// Creates a new Secrets Provider reading the secrets of a DynamoDB table at a given URL.
+    /// This method can be used to connect to AWS emulators like Localstack.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - String representing the AWS Region. Must be formatted with all lowercases
+    ///   letters and hyphens. For example: `us-west-2`.
+    /// * `endpoint_url` - URL of the AWS emulator. Example: `http://localhost:4566`.
+    /// * `table` - DynamoDB table of the secrets.
+    pub async fn new_at_endpoint(region: &str, endpoint_url: &str, table: &str) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region.to_string()))
+            .endpoint_url(endpoint_url)
+            .load()
+            .await;
+        Self::from_config(&config, table.to_string())
+    }
+
+    /// Creates a new Secrets Provider from existing DynamoDB and KMS clients.
+    ///
+    /// # Arguments
+    ///
+    /// * `dynamodb` - DynamoDB client.
+    /// * `kms` - KMS client.
+    /// * `table` - DynamoDB table of the secrets.
+    pub fn from_clients(
+        dynamodb: aws_sdk_dynamodb::Client,
+        kms: aws_sdk_kms::Client,
+        table: String,
+    ) -> Self {
+        Self {
+            dynamodb,
+            kms,
+            table,
+            encryption_context: HashMap::new(),
+        }
+    }
+
+    fn from_config(config: &SdkConfig, table: String) -> Self {
+        Self::from_clients(
+            aws_sdk_dynamodb::Client::new(config),
+            aws_sdk_kms::Client::new(config),
+            table,
+        )
+    }
+
+    /// Decrypts the data keys with a KMS encryption context. It must be the context the secrets
+    /// were written with (the `context` arguments of `credstash put`).
+    ///
+    /// # Arguments
+    ///
+    /// * `encryption_context` - Encryption context of the data keys.
+    pub fn with_encryption_context(mut self, encryption_context: HashMap<String, String>) -> Self {
+        self.encryption_context = encryption_context;
+        self
+    }
+
+    /// DynamoDB table of the secrets.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        if name.is_empty() {
+            return Err(SecretsProviderError::InvalidName(
+                "DynamoDB keys can not be empty".to_string(),
+            ));
+        }
+
+        let row = match version {
+            Some(version) => self.get_row(name, &padded_version(version)).await?,
+            None => self.latest_row(name).await?,
+        };
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let row_version = string_attribute(name, &row, VERSION_ATTRIBUTE)?.to_string();
+        let plaintext = self.open_row(name, &row).await?;
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, plaintext)?,
+            name: name.to_string(),
+            version: row_version,
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            // The current version is only known when no version was requested
+            is_current: version.is_none().then_some(true),
+        }))
+    }
+
+    /// Reads the row with the highest version of a secret.
+    async fn latest_row(&self, name: &str) -> Result<Option<HashMap<String, AttributeValue>>> {
+        let response = self
+            .dynamodb
+            .query()
+            .table_name(&self.table)
+            .key_condition_expression("#name = :name")
+            .expression_attribute_names("#name", NAME_ATTRIBUTE)
+            .expression_attribute_values(":name", AttributeValue::S(name.to_string()))
+            .scan_index_forward(false)
+            .limit(1)
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(|e| dynamodb_error(name, DisplayErrorContext(e)))?;
+
+        Ok(response.items().first().cloned())
+    }
+
+    /// Reads the row of a version of a secret.
+    async fn get_row(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<HashMap<String, AttributeValue>>> {
+        let response = self
+            .dynamodb
+            .get_item()
+            .table_name(&self.table)
+            .key(NAME_ATTRIBUTE, AttributeValue::S(name.to_string()))
+            .key(VERSION_ATTRIBUTE, AttributeValue::S(version.to_string()))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(|e| dynamodb_error(name, DisplayErrorContext(e)))?;
+
+        Ok(response.item)
+    }
+
+    /// Decrypts the value of a row.
+    async fn open_row(&self, name: &str, row: &HashMap<String, AttributeValue>) -> Result<Vec<u8>> {
+        let decode = |attribute: &str| {
+            STANDARD
+                .decode(string_attribute(name, row, attribute)?)
+                .map_err(|e| decrypt_error(name, format!("invalid {} attribute: {}", attribute, e)))
+        };
+
+        let digest = match row.get(DIGEST_ATTRIBUTE) {
+            Some(_) => string_attribute(name, row, DIGEST_ATTRIBUTE)?,
+            None => SHA256_DIGEST,
+        };
+        if digest != SHA256_DIGEST {
+            return Err(decrypt_error(
+                name,
+                format!("unsupported digest {}", digest),
+            ));
+        }
+
+        // Recent versions of credstash store the hex-encoded HMAC as a binary attribute
+        let hmac = match row.get(HMAC_ATTRIBUTE) {
+            Some(AttributeValue::B(hmac)) => hmac.as_ref().to_vec(),
+            _ => string_attribute(name, row, HMAC_ATTRIBUTE)?
+                .as_bytes()
+                .to_vec(),
+        };
+        let hmac = decode_hex(&hmac)
+            .ok_or_else(|| decrypt_error(name, "invalid hmac attribute".to_string()))?;
+
+        let contents = decode(CONTENTS_ATTRIBUTE)?;
+        let mut key = self.kms_decrypt(name, decode(KEY_ATTRIBUTE)?).await?;
+        let plaintext = open(&key, contents, &hmac);
+        wipe(&mut key);
+        plaintext.map_err(|reason| decrypt_error(name, reason))
+    }
+
+    async fn kms_decrypt(&self, name: &str, ciphertext: Vec<u8>) -> Result<Vec<u8>> {
+        let response = self
+            .kms
+            .decrypt()
+            .ciphertext_blob(Blob::new(ciphertext))
+            .set_encryption_context(
+                (!self.encryption_context.is_empty()).then(|| self.encryption_context.clone()),
+            )
+            .send()
+            .await
+            .map_err(|e| {
+                SecretsProviderError::ProviderFailed(format!(
+                    "KMS was unable to decrypt the data key of secret {}: {}",
+                    name,
+                    DisplayErrorContext(e)
+                ))
+            })?;
+
+        response.plaintext.map(Blob::into_inner).ok_or_else(|| {
+            SecretsProviderError::ProviderFailed(format!(
+                "KMS returned no data key for secret {}",
+                name
+            ))
+        })
+    }
+}
+
+/// Checks the HMAC of an encrypted value, and decrypts it.
+///
+/// # Arguments
+///
+/// * `key` - Decrypted data key: the AES-256 key followed by the HMAC key.
+/// * `contents` - Encrypted value.
+/// * `hmac` - HMAC-SHA256 of the encrypted value.
+fn open(key: &[u8], mut contents: Vec<u8>, hmac: &[u8]) -> std::result::Result<Vec<u8>, String> {
+    if key.len() != 64 {
+        return Err(format!("invalid data key length {}", key.len()));
+    }
+    let (data_key, hmac_key) = key.split_at(32);
+
+    if !crypto::verify_hmac_sha256(hmac_key, &contents, hmac) {
+        return Err("HMAC verification failed".to_string());
+    }
+
+    if !crypto::aes_256_ctr_apply(data_key, &COUNTER_BLOCK, &mut contents) {
+        return Err("invalid data key length".to_string());
+    }
+    Ok(contents)
+}
+
+/// Version as stored in the table: numeric versions are zero-padded.
+fn padded_version(version: &str) -> String {
+    match version.parse::<u64>() {
+        Ok(number) => format!("{:0width$}", number, width = VERSION_WIDTH),
+        Err(_) => version.to_string(),
+    }
+}
+
+fn string_attribute<'a>(
+    name: &str,
+    row: &'a HashMap<String, AttributeValue>,
+    attribute: &str,
+) -> Result<&'a str> {
+    match row.get(attribute) {
+        Some(AttributeValue::S(value)) => Ok(value),
+        Some(_) => Err(decrypt_error(
+            name,
+            format!("{} attribute is not a string", attribute),
+        )),
+        None => Err(decrypt_error(
+            name,
+            format!("missing {} attribute", attribute),
+        )),
+    }
+}
+
+fn decrypt_error(name: &str, reason: String) -> SecretsProviderError {
+    SecretsProviderError::ProviderFailed(format!("Unable to decrypt secret {}: {}", name, reason))
+}
+
+fn dynamodb_error(name: &str, e: impl std::fmt::Display) -> SecretsProviderError {
+    SecretsProviderError::ProviderFailed(format!(
+        "Unable to read secret {} from DynamoDB: {}",
+        name, e
+    ))
+}
+
+impl std::fmt::Debug for DynamoDbKmsSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamoDbKmsSecretsProvider")
+            .field("table", &self.table)
+            .field("encryption_context", &self.encryption_context)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for DynamoDbKmsSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for DynamoDbKmsSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version)).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("describe_table", async {
+                self.dynamodb
+                    .describe_table()
+                    .table_name(&self.table)
+                    .send()
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| DisplayErrorContext(e).to_string())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
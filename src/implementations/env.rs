@@ -0,0 +1,197 @@
+//! Environment variables Secret Provider implementation.
+//!
+//! Each secret is read from an environment variable whose name is derived from the secret name.
+//! By default, the name is uppercased and every character that is not an ASCII letter or digit
+//! is replaced by an underscore (so `secret-1` is read from `SECRET_1`).
+//!
+//! Environment variables can only hold strings. Binary secrets can be stored base64-encoded
+//! with a `base64:` prefix (for example `base64:aGVsbG8=`), if enabled with
+//! [with_base64_binaries](crate::implementations::env::EnvSecretsProvider::with_base64_binaries).
+//!
+//! Use this for local development. Environment variables leak easily (to child processes, crash
+//! reports...) so they are not a good place for real secrets.
+use std::env::VarError;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, SecretData};
+use crate::{Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Environment variables";
+
+/// Version of every secret read from the environment. Environment variables are not versioned.
+pub const ENV_SECRET_VERSION: &str = "current";
+
+/// Prefix marking base64-encoded binary secrets.
+const BASE64_PREFIX: &str = "base64:";
+
+type NameTransform = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Environment variables Secrets Provider implementation.
+#[derive(Clone)]
+pub struct EnvSecretsProvider {
+    /// Prefix added to every variable name.
+    prefix: String,
+
+    /// Transforms a secret name into a variable name.
+    name_transform: NameTransform,
+
+    /// Whether `base64:` prefixed values are decoded as binary secrets.
+    base64_binaries: bool,
+}
+
+impl EnvSecretsProvider {
+    /// Creates a new Secrets Provider reading secrets from environment variables, using the
+    /// [default name transform](crate::implementations::env::default_name_transform).
+    pub fn new() -> Self {
+        Self {
+            prefix: String::new(),
+            name_transform: Arc::new(default_name_transform),
+            base64_binaries: false,
+        }
+    }
+
+    /// Adds a prefix to every variable name. The prefix is not transformed.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Variable name prefix. For example: `MY_APP_`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Overrides how secret names are transformed into variable names.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_transform` - Function transforming a secret name into a variable name.
+    pub fn with_name_transform(
+        mut self,
+        name_transform: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.name_transform = Arc::new(name_transform);
+        self
+    }
+
+    /// Decodes values prefixed with `base64:` as binary secrets.
+    pub fn with_base64_binaries(mut self) -> Self {
+        self.base64_binaries = true;
+        self
+    }
+
+    /// Returns the name of the environment variable holding a secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub fn variable_name(&self, secret_name: &str) -> String {
+        format!("{}{}", self.prefix, (self.name_transform)(secret_name))
+    }
+
+    fn read_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        if version.is_some_and(|v| v != ENV_SECRET_VERSION) {
+            return Ok(None);
+        }
+
+        let value = match std::env::var(self.variable_name(name)) {
+            Ok(value) => value,
+            Err(VarError::NotPresent) => return Ok(None),
+            Err(VarError::NotUnicode(_)) => {
+                return Err(SecretsProviderError::InvalidType(name.to_string()))
+            }
+        };
+
+        let secret_data = match value.strip_prefix(BASE64_PREFIX) {
+            Some(encoded) if self.base64_binaries => SecretData::Bytes(
+                STANDARD
+                    .decode(encoded)
+                    .map_err(|_| SecretsProviderError::InvalidType(name.to_string()))?
+                    .into(),
+            ),
+            _ => SecretData::Str(value),
+        };
+
+        Ok(Some(Secret {
+            secret: T::decode(name, secret_data)?,
+            name: name.to_string(),
+            version: ENV_SECRET_VERSION.to_string(),
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(true),
+        }))
+    }
+}
+
+impl Default for EnvSecretsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for EnvSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvSecretsProvider")
+            .field("prefix", &self.prefix)
+            .field("base64_binaries", &self.base64_binaries)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Default transform of secret names into variable names: the name is uppercased and every
+/// character that is not an ASCII letter or digit is replaced by an underscore. For example,
+/// `database/password-1` becomes `DATABASE_PASSWORD_1`.
+///
+/// # Arguments
+///
+/// * `secret_name` - A string that contains the secret name.
+pub fn default_name_transform(secret_name: &str) -> String {
+    secret_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+impl ProviderCapabilities for EnvSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for EnvSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, Some(version))
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
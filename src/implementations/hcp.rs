@@ -0,0 +1,474 @@
+//! HCP Vault Secrets Secret Provider implementation.
+//!
+//! Secrets are read from an application of HashiCorp Cloud Platform's Vault Secrets, the managed
+//! SaaS service (not to be confused with a self-hosted Vault KV engine). The provider
+//! authenticates as a service principal with OAuth client credentials, and keeps the access token
+//! until shortly before it expires.
+//!
+//! Secret versions are increasing integers, and every version can be read. HCP Vault Secrets
+//! values are always strings, and only static secrets can be read: rotating and dynamic secrets
+//! are rejected with an [InvalidType](crate::SecretsProviderError::InvalidType) error. Names are
+//! made of ASCII letters, digits and underscores, and other names are rejected before sending
+//! any request.
+//!
+//! For more information:
+//! `<https://developer.hashicorp.com/hcp/api-docs/vault-secrets>`
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::{Client, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::naming::NameRules;
+use crate::secret::{Decode, Secret, SecretData};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "HCP Vault Secrets";
+
+/// HCP API endpoint.
+pub const HCP_API_ENDPOINT: &str = "https://api.cloud.hashicorp.com";
+
+/// HCP OAuth endpoint, where service principals get their access tokens.
+pub const HCP_AUTH_ENDPOINT: &str = "https://auth.idp.hashicorp.com";
+
+/// Version of the Vault Secrets API.
+const API_VERSION: &str = "2023-11-28";
+
+/// Access tokens are renewed this long before they expire.
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// HCP Vault Secrets Provider builder.
+pub struct HcpVaultSecretsProviderBuilder {
+    /// Service principal client id.
+    client_id: String,
+
+    /// Service principal client secret.
+    client_secret: String,
+
+    /// HCP organization id.
+    organization_id: String,
+
+    /// HCP project id.
+    project_id: String,
+
+    /// Vault Secrets application.
+    app_name: String,
+
+    /// Endpoint of the HCP API.
+    endpoint: String,
+
+    /// Endpoint of the HCP OAuth server.
+    auth_endpoint: String,
+}
+
+impl HcpVaultSecretsProviderBuilder {
+    /// Creates a new HCP Vault Secrets Provider builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `client_id` - Client id of the service principal key.
+    /// * `client_secret` - Client secret of the service principal key.
+    /// * `organization_id` - Id of the HCP organization.
+    /// * `project_id` - Id of the HCP project.
+    /// * `app_name` - Vault Secrets application to read secrets from. For example: `my-app`.
+    pub fn new(
+        client_id: String,
+        client_secret: String,
+        organization_id: String,
+        project_id: String,
+        app_name: String,
+    ) -> Self {
+        Self {
+            client_id,
+            client_secret,
+            organization_id,
+            project_id,
+            app_name,
+            endpoint: HCP_API_ENDPOINT.to_string(),
+            auth_endpoint: HCP_AUTH_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Overrides the HCP API endpoint.
+    ///
+    /// This is usually used for testing purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String represeting the endpoint. For example: `http://127.0.0.1:8080`.
+    pub fn endpoint_override(mut self, endpoint: String) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Overrides the HCP OAuth endpoint.
+    ///
+    /// This is usually used for testing purposes.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - String represeting the endpoint. For example: `http://127.0.0.1:8080`.
+    pub fn auth_endpoint_override(mut self, endpoint: String) -> Self {
+        self.auth_endpoint = endpoint;
+        self
+    }
+
+    /// Builds a [HcpVaultSecretsProvider](crate::implementations::hcp::HcpVaultSecretsProvider).
+    pub fn build(self) -> Result<HcpVaultSecretsProvider> {
+        let client = Client::builder().build().map_err(|e| {
+            SecretsProviderError::Initialization(format!("Unable to build HCP HTTP Client: {}", e))
+        })?;
+
+        Ok(HcpVaultSecretsProvider {
+            client,
+            client_id: self.client_id,
+            client_secret: self.client_secret,
+            app_url: format!(
+                "{}/secrets/{}/organizations/{}/projects/{}/apps/{}",
+                self.endpoint.trim_end_matches('/'),
+                API_VERSION,
+                self.organization_id,
+                self.project_id,
+                self.app_name
+            ),
+            auth_endpoint: self.auth_endpoint.trim_end_matches('/').to_string(),
+            token: Arc::new(Mutex::new(None)),
+        })
+    }
+}
+
+/// HCP Vault Secrets Provider implementation.
+#[derive(Clone)]
+pub struct HcpVaultSecretsProvider {
+    client: Client,
+    client_id: String,
+    client_secret: String,
+
+    /// URL of the application in the Vault Secrets API.
+    app_url: String,
+
+    auth_endpoint: String,
+
+    /// Access token of the service principal, shared by the clones of the provider.
+    token: Arc<Mutex<Option<AccessToken>>>,
+}
+
+struct AccessToken {
+    value: String,
+    expires_at: Instant,
+}
+
+/// Response of the OAuth token endpoint.
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+/// Error response of the HCP APIs.
+#[derive(Deserialize)]
+struct ErrorResponse {
+    #[serde(default, alias = "error_description")]
+    message: String,
+}
+
+/// Response of the secret open endpoint.
+#[derive(Deserialize)]
+struct OpenSecretResponse {
+    secret: OpenSecret,
+}
+
+/// Response of the secret version open endpoint.
+#[derive(Deserialize)]
+struct OpenSecretVersionResponse {
+    static_version: Option<StaticVersion>,
+}
+
+/// Response of the secrets open endpoint.
+#[derive(Deserialize)]
+struct OpenSecretsResponse {
+    #[serde(default)]
+    secrets: Vec<OpenSecret>,
+    pagination: Option<Pagination>,
+}
+
+#[derive(Deserialize)]
+struct OpenSecret {
+    name: String,
+    latest_version: Option<u64>,
+    static_version: Option<StaticVersion>,
+}
+
+#[derive(Deserialize)]
+struct StaticVersion {
+    version: u64,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct Pagination {
+    #[serde(default)]
+    next_page_token: String,
+}
+
+impl HcpVaultSecretsProvider {
+    /// Returns a valid access token, requesting a new one if needed.
+    async fn access_token(&self) -> Result<String> {
+        if let Some(token) = self.token.lock().unwrap().as_ref() {
+            if token.expires_at > Instant::now() + TOKEN_EXPIRY_MARGIN {
+                return Ok(token.value.clone());
+            }
+        }
+
+        let response = self
+            .client
+            .post(format!("{}/oauth2/token", self.auth_endpoint))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("audience", HCP_API_ENDPOINT),
+            ])
+            .send()
+            .await
+            .map_err(request_error)?;
+        let TokenResponse {
+            access_token,
+            expires_in,
+        } = parse_response(check_status(response).await?).await?;
+
+        *self.token.lock().unwrap() = Some(AccessToken {
+            value: access_token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(expires_in),
+        });
+        Ok(access_token)
+    }
+
+    /// Sends an authenticated request to the application, returning `None` if it was not found.
+    async fn get<R: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Option<R>> {
+        let token = self.access_token().await?;
+        let response = self
+            .client
+            .get(format!("{}{}", self.app_url, path))
+            .bearer_auth(token)
+            .query(query)
+            .send()
+            .await
+            .map_err(request_error)?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        parse_response(check_status(response).await?)
+            .await
+            .map(Some)
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        NameRules::HCP_VAULT_SECRETS.validate(name)?;
+
+        let Some(version) = version else {
+            let response: Option<OpenSecretResponse> =
+                self.get(&format!("/secrets/{}:open", name), &[]).await?;
+            return response.map(|r| parse_secret(r.secret)).transpose();
+        };
+
+        // Versions are integers, so any other version can not exist. The secret is still opened,
+        // so that failures are reported like for any other lookup
+        let Ok(version) = version.parse::<u64>() else {
+            self.get::<OpenSecretResponse>(&format!("/secrets/{}:open", name), &[])
+                .await?;
+            return Ok(None);
+        };
+
+        let response: Option<OpenSecretVersionResponse> = self
+            .get(&format!("/secrets/{}/versions/{}:open", name, version), &[])
+            .await?;
+        match response {
+            Some(OpenSecretVersionResponse {
+                static_version: Some(v),
+            }) => Ok(Some(Secret {
+                secret: T::decode(name, SecretData::Str(v.value))?,
+                name: name.to_string(),
+                version: v.version.to_string(),
+                version_stages: Vec::new(),
+                tags: None,
+                created_at: None,
+                last_rotated_at: None,
+                metadata: None,
+                backend: BACKEND,
+                is_current: None,
+            })),
+            Some(_) => Err(unsupported_secret(name)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Creates a secret from an opened secret, with its current version.
+fn parse_secret<T: Decode>(secret: OpenSecret) -> Result<Secret<T>> {
+    let Some(version) = secret.static_version else {
+        return Err(unsupported_secret(&secret.name));
+    };
+
+    Ok(Secret {
+        secret: T::decode(&secret.name, SecretData::Str(version.value))?,
+        is_current: Some(
+            secret
+                .latest_version
+                .is_none_or(|latest| latest == version.version),
+        ),
+        name: secret.name,
+        version: version.version.to_string(),
+        version_stages: Vec::new(),
+        tags: None,
+        created_at: None,
+        last_rotated_at: None,
+        metadata: None,
+        backend: BACKEND,
+    })
+}
+
+fn unsupported_secret(name: &str) -> SecretsProviderError {
+    SecretsProviderError::InvalidType(format!(
+        "{} (only static HCP Vault Secrets secrets can be read)",
+        name
+    ))
+}
+
+/// Fails on error responses, with the message sent by HCP.
+async fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let message = response
+        .json::<ErrorResponse>()
+        .await
+        .map(|e| e.message)
+        .unwrap_or_default();
+    Err(SecretsProviderError::ProviderFailed(match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            format!("HCP denied access ({}): {}", status, message)
+        }
+        _ => format!("HCP request failed ({}): {}", status, message),
+    }))
+}
+
+async fn parse_response<R: DeserializeOwned>(response: Response) -> Result<R> {
+    response
+        .json()
+        .await
+        .map_err(|e| SecretsProviderError::ProviderFailed(format!("Invalid HCP response: {}", e)))
+}
+
+fn request_error(e: reqwest::Error) -> SecretsProviderError {
+    SecretsProviderError::ProviderFailed(format!("HCP request failed: {}", e))
+}
+
+impl std::fmt::Debug for HcpVaultSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HcpVaultSecretsProvider")
+            .field("client_id", &self.client_id)
+            .field("app_url", &self.app_url)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for HcpVaultSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        batch: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for HcpVaultSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version)).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        for name in secret_names {
+            NameRules::HCP_VAULT_SECRETS.validate(name)?;
+        }
+
+        // Every secret of the application is opened at once, a page at a time
+        let mut retrieved = HashMap::new();
+        let mut page_token = String::new();
+        loop {
+            let mut query = Vec::new();
+            if !page_token.is_empty() {
+                query.push(("pagination.next_page_token", page_token.as_str()));
+            }
+            let Some(page) = self
+                .get::<OpenSecretsResponse>("/secrets:open", &query)
+                .await?
+            else {
+                break;
+            };
+
+            for secret in page.secrets {
+                if let Some(name) = secret_names.iter().find(|n| **n == secret.name) {
+                    retrieved.insert(*name, parse_secret(secret)?);
+                }
+            }
+
+            match page.pagination {
+                Some(Pagination { next_page_token }) if !next_page_token.is_empty() => {
+                    page_token = next_page_token
+                }
+                _ => break,
+            }
+        }
+
+        Ok(retrieved)
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("authenticate", async {
+                self.access_token().await.map(|_| ())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
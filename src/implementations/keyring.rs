@@ -0,0 +1,165 @@
+//! OS keyring Secret Provider implementation.
+//!
+//! Secrets are read from the native credential store of the user (macOS Keychain, Windows
+//! Credential Manager, Secret Service...) through the `keyring` crate. Every secret of the
+//! provider shares a keyring service, and the secret name is the keyring user:
+//!
+//! ```text
+//! service: my-app, user: api-token
+//! ```
+//!
+//! The keyring store is selected by the features of the `keyring` crate enabled by the
+//! application (for example: `apple-native`, `windows-native` or `sync-secret-service`). Without
+//! any of them, `keyring` falls back to a mock store that keeps nothing.
+//!
+//! Keyrings do not version credentials, so every secret has a single version,
+//! [KEYRING_SECRET_VERSION](crate::implementations::keyring::KEYRING_SECRET_VERSION). Values are
+//! read like files: valid UTF-8 values can be read as [String](std::string::String), and any value
+//! can be read as [Vec<u8>](std::vec::Vec).
+//!
+//! Keyring stores are accessed synchronously, as they are local services.
+use async_trait::async_trait;
+use keyring::{Entry, Error as KeyringError};
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "OS keyring";
+
+/// Version of every secret read from the keyring. Keyring credentials are not versioned.
+pub const KEYRING_SECRET_VERSION: &str = "current";
+
+/// OS keyring Secrets Provider implementation.
+#[derive(Clone, Debug)]
+pub struct KeyringSecretsProvider {
+    service: String,
+}
+
+impl KeyringSecretsProvider {
+    /// Creates a new Secrets Provider reading secrets from a keyring service.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - Keyring service of the secrets. For example: the application name.
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    /// Keyring service of the secrets.
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// Saves a secret in the keyring, replacing its previous value.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `value` - Secret value. Strings are saved as their UTF-8 bytes.
+    pub fn set_secret(&self, secret_name: &str, value: impl AsRef<[u8]>) -> Result<()> {
+        self.entry(secret_name)?
+            .set_secret(value.as_ref())
+            .map_err(|e| keyring_error(secret_name, e))
+    }
+
+    /// Removes a secret from the keyring. Returns `false` if there was no such secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub fn delete_secret(&self, secret_name: &str) -> Result<bool> {
+        match self.entry(secret_name)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(KeyringError::NoEntry) => Ok(false),
+            Err(e) => Err(keyring_error(secret_name, e)),
+        }
+    }
+
+    fn entry(&self, secret_name: &str) -> Result<Entry> {
+        Entry::new(&self.service, secret_name).map_err(|e| keyring_error(secret_name, e))
+    }
+
+    fn read_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        if version.is_some_and(|v| v != KEYRING_SECRET_VERSION) {
+            return Ok(None);
+        }
+
+        let value = match self.entry(name)?.get_secret() {
+            Ok(value) => value,
+            Err(KeyringError::NoEntry) => return Ok(None),
+            Err(e) => return Err(keyring_error(name, e)),
+        };
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, value)?,
+            name: name.to_string(),
+            version: KEYRING_SECRET_VERSION.to_string(),
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(true),
+        }))
+    }
+}
+
+fn keyring_error(name: &str, e: KeyringError) -> SecretsProviderError {
+    match e {
+        KeyringError::TooLong(..) | KeyringError::Invalid(..) => {
+            SecretsProviderError::InvalidName(format!("{}: {}", name, e))
+        }
+        e => SecretsProviderError::ProviderFailed(format!("Keyring request failed: {}", e)),
+    }
+}
+
+impl ProviderCapabilities for KeyringSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for KeyringSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, Some(version))
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        // Reading any credential exercises the access to the keyring store, even if it is missing
+        report.checks.push(
+            SelfTestCheck::run("read_keyring", async {
+                self.read_secret::<Vec<u8>>("secrets-provider-self-test", None)
+                    .map(|_| ())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
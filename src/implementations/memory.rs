@@ -1,103 +1,328 @@
 //! Memory implementation of a Secrets Provider.
 //!
 //! Use this for testing purposes only!
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
+use bytes::Bytes;
 use indexmap::IndexMap;
 use uuid::Uuid;
 
 use crate::{
-    secret::{Decode, Secret, SecretData},
-    Result, SecretsProvider,
+    capabilities::{Capabilities, ProviderCapabilities},
+    errors::SecretsProviderError,
+    secret::{wipe, wipe_shared, Decode, Secret, SecretData, SecretDescription},
+    DeleteOptions, ListFilter, PutManyReport, PutResult, Result, SecretsPage, SecretsProvider,
+    SecretsWriter, VersionInfo, VersionStage, WriteOptions,
 };
 
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Memory";
+
+/// Recovery window of deleted secrets, unless another one is requested. The same as AWS Secrets
+/// Manager.
+const DEFAULT_RECOVERY_WINDOW_DAYS: u32 = 30;
+
+/// Secrets listed per page, unless another page size is requested.
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+#[derive(PartialEq)]
 enum MemorySecretType {
     Str(String),
-    Bytes(Vec<u8>),
+    Bytes(Bytes),
+}
+
+// Stored values are wiped once permanently deleted, or dropped with the provider
+impl Drop for MemorySecretType {
+    fn drop(&mut self) {
+        match self {
+            MemorySecretType::Str(s) => wipe(s),
+            MemorySecretType::Bytes(b) => wipe_shared(b),
+        }
+    }
+}
+
+struct MemorySecretVersion {
+    secret: MemorySecretType,
+    created_at: SystemTime,
+}
+
+impl MemorySecretVersion {
+    fn new(secret: MemorySecretType) -> Self {
+        Self {
+            secret,
+            created_at: SystemTime::now(),
+        }
+    }
 }
 
 type Version = String;
 
+/// Versions of a secret, the current one last, and its tags.
+#[derive(Default)]
+struct MemorySecret {
+    versions: IndexMap<String, MemorySecretVersion>,
+    tags: BTreeMap<String, String>,
+}
+
+type Secrets = HashMap<String, MemorySecret>;
+
+/// Secret scheduled for deletion, that can still be restored.
+struct DeletedSecret {
+    secret: MemorySecret,
+    deletion_date: SystemTime,
+}
+
 pub struct MemorySecretsProvider {
-    secrets: HashMap<String, IndexMap<String, MemorySecretType>>,
+    secrets: RwLock<Secrets>,
+    // Always locked after secrets
+    deleted: Mutex<HashMap<String, DeletedSecret>>,
+}
+
+/// Adds a version to a secret, creating the secret if needed. Returns the new version.
+fn insert_version(secrets: &mut Secrets, name: &str, secret: MemorySecretType) -> Version {
+    insert_version_with_id(secrets, name, secret, Uuid::new_v4().to_string())
+}
+
+/// Adds a version to a secret with a given identifier, creating the secret if needed. Returns
+/// the new version.
+fn insert_version_with_id(
+    secrets: &mut Secrets,
+    name: &str,
+    secret: MemorySecretType,
+    version: Version,
+) -> Version {
+    secrets
+        .entry(name.to_string())
+        .or_default()
+        .versions
+        .insert(version.clone(), MemorySecretVersion::new(secret));
+    version
+}
+
+/// Describes a secret. The creation time is the one of the first version, and the last change
+/// time is the one of the current version.
+fn describe(name: &str, secret: &MemorySecret) -> SecretDescription {
+    SecretDescription {
+        name: name.to_string(),
+        created_at: secret.versions.first().map(|(_, v)| v.created_at),
+        last_changed_at: secret.versions.last().map(|(_, v)| v.created_at),
+        tags: Some(secret.tags.clone()),
+        ..Default::default()
+    }
+}
+
+/// Stages of a version, given its position in the versions of its secret: the last version added
+/// is the current one, and the one before it the previous one.
+fn stages_of(index: usize, count: usize) -> Vec<VersionStage> {
+    match count - index {
+        1 => vec![VersionStage::Current],
+        2 => vec![VersionStage::Previous],
+        _ => Vec::new(),
+    }
+}
+
+/// Stages of a version as returned with its value.
+fn stage_names(index: usize, count: usize) -> Vec<String> {
+    stages_of(index, count)
+        .iter()
+        .map(ToString::to_string)
+        .collect()
 }
 
 impl MemorySecretsProvider {
     pub fn new() -> Self {
         Self {
-            secrets: HashMap::new(),
+            secrets: RwLock::new(HashMap::new()),
+            deleted: Mutex::new(HashMap::new()),
         }
     }
 
+    fn read_secrets(&self) -> RwLockReadGuard<'_, Secrets> {
+        // Versions are inserted in a single step, so a poisoned lock still holds consistent data
+        self.secrets.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_secrets(&self) -> RwLockWriteGuard<'_, Secrets> {
+        self.secrets.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Secrets scheduled for deletion, forgetting the ones past their recovery window.
+    fn deleted_secrets(&self) -> MutexGuard<'_, HashMap<String, DeletedSecret>> {
+        let mut deleted = self.deleted.lock().unwrap_or_else(|e| e.into_inner());
+        let now = SystemTime::now();
+        deleted.retain(|_, secret| secret.deletion_date > now);
+        deleted
+    }
+
     pub fn add_binary_secret(&mut self, name: String, secret: Vec<u8>) -> Secret<Vec<u8>> {
-        let version = Uuid::new_v4().to_string();
-        if let Some(saved_secret) = self.secrets.get_mut(&name) {
-            saved_secret.insert(
-                Uuid::new_v4().to_string(),
-                MemorySecretType::Bytes(secret.clone()),
-            );
-        } else {
-            self.secrets.insert(
-                name.clone(),
-                IndexMap::from([(version.clone(), MemorySecretType::Bytes(secret.clone()))]),
-            );
-        }
+        let secrets = self.secrets.get_mut().unwrap_or_else(|e| e.into_inner());
+        let version = insert_version(
+            secrets,
+            &name,
+            MemorySecretType::Bytes(secret.clone().into()),
+        );
+        let saved_secret = &secrets[&name];
+        let tags = saved_secret.tags.clone();
+        let created_at = saved_secret.versions[&version].created_at;
 
         Secret {
             name,
             version,
             secret,
+            version_stages: vec![VersionStage::Current.to_string()],
+            backend: BACKEND,
+            is_current: Some(true),
+            tags: Some(tags),
+            created_at: Some(created_at),
+            last_rotated_at: None,
+            metadata: None,
         }
     }
 
     pub fn add_string_secret(&mut self, name: String, secret: String) -> Secret<String> {
-        let version = Uuid::new_v4().to_string();
-        if let Some(saved_secret) = self.secrets.get_mut(&name) {
-            saved_secret.insert(
-                Uuid::new_v4().to_string(),
-                MemorySecretType::Str(secret.clone()),
-            );
-        } else {
-            self.secrets.insert(
-                name.clone(),
-                IndexMap::from([(version.clone(), MemorySecretType::Str(secret.clone()))]),
-            );
-        }
+        let secrets = self.secrets.get_mut().unwrap_or_else(|e| e.into_inner());
+        let version = insert_version(secrets, &name, MemorySecretType::Str(secret.clone()));
+        let saved_secret = &secrets[&name];
+        let tags = saved_secret.tags.clone();
+        let created_at = saved_secret.versions[&version].created_at;
 
         Secret {
             name,
             version,
             secret,
+            version_stages: vec![VersionStage::Current.to_string()],
+            backend: BACKEND,
+            is_current: Some(true),
+            tags: Some(tags),
+            created_at: Some(created_at),
+            last_rotated_at: None,
+            metadata: None,
+        }
+    }
+
+    /// Adds tags to a secret, replacing the values of the tags it already has. Returns `false`
+    /// if the secret does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string that contains the secret name.
+    /// * `tags` - Tag keys and values.
+    pub fn add_tags(
+        &mut self,
+        name: &str,
+        tags: impl IntoIterator<Item = (String, String)>,
+    ) -> bool {
+        let secrets = self.secrets.get_mut().unwrap_or_else(|e| e.into_inner());
+        match secrets.get_mut(name) {
+            Some(secret) => {
+                secret.tags.extend(tags);
+                true
+            }
+            None => false,
         }
     }
 
     pub fn list_secret_version_ids(&self, secret_name: &str) -> Option<Vec<Version>> {
-        if let Some(saved_secret) = self.secrets.get(secret_name) {
+        if let Some(saved_secret) = self.read_secrets().get(secret_name) {
             // Return the most recent version last
-            return Some(saved_secret.keys().cloned().collect());
+            return Some(saved_secret.versions.keys().cloned().collect());
         }
         None
     }
 
+    /// Describes many secrets at once. Missing secrets are not included in the result.
+    ///
+    /// The creation time is the one of the first version, and the last change time is the one
+    /// of the current version.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_names` - List of secret names to describe.
+    pub fn batch_describe<'n>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> HashMap<&'n str, SecretDescription> {
+        let secrets = self.read_secrets();
+        secret_names
+            .iter()
+            .filter_map(|name| Some((*name, describe(name, secrets.get(*name)?))))
+            .collect()
+    }
+
+    /// Retrieves the version of a secret that was current at a given time: the most recent
+    /// version added at or before that time.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `timestamp` - Point in time.
+    pub fn find_as_of<T: Decode>(
+        &self,
+        secret_name: &str,
+        timestamp: SystemTime,
+    ) -> Result<Option<Secret<T>>> {
+        let version = self
+            .read_secrets()
+            .get(secret_name)
+            .and_then(|saved_secret| {
+                saved_secret
+                    .versions
+                    .iter()
+                    .rev()
+                    .find(|(_, saved_version)| saved_version.created_at <= timestamp)
+                    .map(|(version, _)| version.clone())
+            });
+
+        match version {
+            Some(version) => self.get_secret_from_memory(secret_name, Some(version)),
+            None => Ok(None),
+        }
+    }
+
     fn get_secret_from_memory<T: Decode>(
         &self,
         name: &str,
         version: Option<String>,
     ) -> Result<Option<Secret<T>>> {
-        if let Some((secret, version)) = self.secrets.get(name).and_then(|saved_secret| {
-            let secret = version
-                .map(|v| saved_secret.get_key_value(&v))
-                .unwrap_or_else(|| saved_secret.last());
-            secret.map(|(version, secret)| match secret {
-                MemorySecretType::Bytes(s) => (SecretData::Bytes(s.to_vec()), version.to_owned()),
-                MemorySecretType::Str(s) => (SecretData::Str(s.to_string()), version.to_owned()),
+        if let Some((secret, version, version_stages, is_current, created_at, tags)) =
+            self.read_secrets().get(name).and_then(|saved_secret| {
+                let versions = &saved_secret.versions;
+                let (index, version, saved_version) = match version {
+                    Some(v) => versions.get_full(&v)?,
+                    None => {
+                        let index = versions.len().checked_sub(1)?;
+                        let (version, saved_version) = versions.get_index(index)?;
+                        (index, version, saved_version)
+                    }
+                };
+                let data = match &saved_version.secret {
+                    MemorySecretType::Bytes(b) => SecretData::Bytes(b.clone()),
+                    MemorySecretType::Str(s) => SecretData::Str(s.to_string()),
+                };
+                Some((
+                    data,
+                    version.to_owned(),
+                    stage_names(index, versions.len()),
+                    index + 1 == versions.len(),
+                    saved_version.created_at,
+                    saved_secret.tags.clone(),
+                ))
             })
-        }) {
+        {
             Ok(Some(Secret {
                 secret: T::decode(name, secret)?,
                 name: name.to_string(),
                 version,
+                version_stages,
+                backend: BACKEND,
+                is_current: Some(is_current),
+                tags: Some(tags),
+                created_at: Some(created_at),
+                last_rotated_at: None,
+                metadata: None,
             }))
         } else {
             Ok(None)
@@ -111,6 +336,16 @@ impl Default for MemorySecretsProvider {
     }
 }
 
+impl ProviderCapabilities for MemorySecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        stages: true,
+        list: true,
+        write: true,
+        ..Capabilities::NONE
+    };
+}
+
 #[async_trait]
 impl SecretsProvider for MemorySecretsProvider {
     async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
@@ -124,4 +359,312 @@ impl SecretsProvider for MemorySecretsProvider {
     ) -> Result<Option<Secret<T>>> {
         self.get_secret_from_memory(key_name, Some(version.into()))
     }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        Ok(self.read_secrets().contains_key(secret_name))
+    }
+
+    /// The previous stage is the version added before the current one. Versions are never
+    /// pending, and custom labels are not supported, so these stages are never found.
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        let version = match stage {
+            VersionStage::Current => return self.get_secret_from_memory(secret_name, None),
+            VersionStage::Previous => self.read_secrets().get(secret_name).and_then(|secret| {
+                let previous = secret.versions.len().checked_sub(2)?;
+                secret.versions.get_index(previous).map(|(v, _)| v.clone())
+            }),
+            VersionStage::Pending | VersionStage::Label(_) => None,
+        };
+
+        match version {
+            Some(version) => self.get_secret_from_memory(secret_name, Some(version)),
+            None => Ok(None),
+        }
+    }
+
+    /// The last version added is the current one, and the one before it the previous one.
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        let secrets = self.read_secrets();
+        let Some(secret) = secrets.get(secret_name) else {
+            return Ok(Vec::new());
+        };
+
+        let count = secret.versions.len();
+        Ok(secret
+            .versions
+            .iter()
+            .enumerate()
+            .map(|(i, (id, version))| VersionInfo {
+                id: id.clone(),
+                stages: stages_of(i, count),
+                created_at: Some(version.created_at),
+            })
+            .collect())
+    }
+
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        let page_size = filter.page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        let secrets = self.read_secrets();
+
+        let mut names: Vec<&String> = secrets
+            .keys()
+            .filter(|name| {
+                filter
+                    .name_prefix
+                    .as_deref()
+                    .is_none_or(|prefix| name.starts_with(prefix))
+            })
+            // Tokens are the last name of the previous page
+            .filter(|name| page_token.is_none_or(|token| name.as_str() > token))
+            .collect();
+        names.sort();
+
+        let next_page_token = (names.len() > page_size).then(|| names[page_size - 1].clone());
+        names.truncate(page_size);
+
+        Ok(SecretsPage {
+            secrets: names
+                .into_iter()
+                .map(|name| describe(name, &secrets[name]))
+                .collect(),
+            next_page_token,
+        })
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
+
+#[async_trait]
+impl SecretsWriter for MemorySecretsProvider {
+    async fn create_secret(&self, secret_name: &str, value: SecretData) -> Result<String> {
+        self.create_secret_with_options(secret_name, value, &WriteOptions::default())
+            .await
+    }
+
+    async fn put_secret_value(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+    ) -> Result<Option<String>> {
+        self.put_secret_value_with_options(secret_name, value, &WriteOptions::default())
+            .await
+    }
+
+    async fn delete_secret(
+        &self,
+        secret_name: &str,
+        options: DeleteOptions,
+    ) -> Result<Option<SystemTime>> {
+        let mut secrets = self.write_secrets();
+        let Some(secret) = secrets.remove(secret_name) else {
+            return Ok(None);
+        };
+
+        let recovery_window_days = match options {
+            DeleteOptions::DefaultRecoveryWindow => DEFAULT_RECOVERY_WINDOW_DAYS,
+            DeleteOptions::RecoveryWindowDays(days) => days,
+            DeleteOptions::Force => return Ok(Some(SystemTime::now())),
+        };
+        let deletion_date =
+            SystemTime::now() + Duration::from_secs(u64::from(recovery_window_days) * 86_400);
+        self.deleted_secrets().insert(
+            secret_name.to_string(),
+            DeletedSecret {
+                secret,
+                deletion_date,
+            },
+        );
+
+        Ok(Some(deletion_date))
+    }
+
+    /// Like AWS Secrets Manager, the client request token is the identifier of the version.
+    async fn create_secret_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<String> {
+        let value = value.into();
+        let mut secrets = self.write_secrets();
+        if let Some(secret) = secrets.get(secret_name) {
+            // A retried create returns the version of the first attempt
+            return match written_version(secret, options, &value)? {
+                Some(version) => Ok(version),
+                None => Err(SecretsProviderError::AlreadyExists(secret_name.to_string())),
+            };
+        }
+        // Names of deleted secrets are reserved until their recovery window ends
+        if self.deleted_secrets().contains_key(secret_name) {
+            return Err(SecretsProviderError::AlreadyExists(secret_name.to_string()));
+        }
+
+        Ok(insert_written_version(
+            &mut secrets,
+            secret_name,
+            value,
+            options,
+        ))
+    }
+
+    /// Like AWS Secrets Manager, the client request token is the identifier of the version.
+    async fn put_secret_value_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<Option<String>> {
+        let value = value.into();
+        let mut secrets = self.write_secrets();
+        let Some(secret) = secrets.get(secret_name) else {
+            return Ok(None);
+        };
+        if let Some(version) = written_version(secret, options, &value)? {
+            return Ok(Some(version));
+        }
+
+        Ok(Some(insert_written_version(
+            &mut secrets,
+            secret_name,
+            value,
+            options,
+        )))
+    }
+
+    async fn restore_secret(&self, secret_name: &str) -> Result<bool> {
+        let mut secrets = self.write_secrets();
+        if secrets.contains_key(secret_name) {
+            return Ok(true);
+        }
+
+        match self.deleted_secrets().remove(secret_name) {
+            Some(deleted) => {
+                secrets.insert(secret_name.to_string(), deleted.secret);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Entries are written all or nothing, holding the lock of the secrets: lookups see either
+    /// none or all of them.
+    async fn put_many(&self, entries: Vec<(String, SecretData)>) -> PutManyReport {
+        let mut secrets = self.write_secrets();
+        let deleted = self.deleted_secrets();
+
+        // Names of deleted secrets are reserved, so only creating those can fail
+        let reserved = |name: &str| !secrets.contains_key(name) && deleted.contains_key(name);
+        if entries.iter().any(|(name, _)| reserved(name)) {
+            let results = entries
+                .into_iter()
+                .map(|(name, _)| {
+                    let result = if reserved(&name) {
+                        PutResult::Failed(SecretsProviderError::AlreadyExists(name.clone()))
+                    } else {
+                        PutResult::NotAttempted
+                    };
+                    (name, result)
+                })
+                .collect();
+            return PutManyReport { results };
+        }
+
+        let results = entries
+            .into_iter()
+            .map(|(name, value)| {
+                let version = insert_version(&mut secrets, &name, value.into());
+                (name, PutResult::Written(version))
+            })
+            .collect();
+        PutManyReport { results }
+    }
+
+    async fn tag_secret(&self, secret_name: &str, tags: BTreeMap<String, String>) -> Result<bool> {
+        match self.write_secrets().get_mut(secret_name) {
+            Some(secret) => {
+                secret.tags.extend(tags);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn untag_secret(&self, secret_name: &str, tag_keys: &[&str]) -> Result<bool> {
+        match self.write_secrets().get_mut(secret_name) {
+            Some(secret) => {
+                for key in tag_keys {
+                    secret.tags.remove(*key);
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+/// Finds the version already written with the client request token of a write, if any. Fails if
+/// it was written with another value.
+fn written_version(
+    secret: &MemorySecret,
+    options: &WriteOptions,
+    value: &MemorySecretType,
+) -> Result<Option<Version>> {
+    let Some(token) = options.client_request_token() else {
+        return Ok(None);
+    };
+
+    match secret.versions.get(token) {
+        Some(version) if version.secret == *value => Ok(Some(token.to_string())),
+        Some(_) => Err(SecretsProviderError::InvalidVersion(format!(
+            "{} was already written with another value",
+            token
+        ))),
+        None => Ok(None),
+    }
+}
+
+/// Adds the version of a write, identified by its client request token if it has one.
+fn insert_written_version(
+    secrets: &mut Secrets,
+    name: &str,
+    value: MemorySecretType,
+    options: &WriteOptions,
+) -> Version {
+    match options.client_request_token() {
+        Some(token) => insert_version_with_id(secrets, name, value, token.to_string()),
+        None => insert_version(secrets, name, value),
+    }
+}
+
+impl From<SecretData> for MemorySecretType {
+    fn from(value: SecretData) -> Self {
+        match value {
+            SecretData::Str(s) => MemorySecretType::Str(s),
+            SecretData::Bytes(b) => MemorySecretType::Bytes(b),
+        }
+    }
+}
+
+#[cfg(feature = "testkit")]
+#[async_trait]
+impl crate::testkit::SecretsProviderTestExt for MemorySecretsProvider {
+    async fn add_string_secret(&mut self, name: &str, value: &str) -> Result<()> {
+        MemorySecretsProvider::add_string_secret(self, name.into(), value.into());
+        Ok(())
+    }
+
+    async fn add_binary_secret(&mut self, name: &str, value: &[u8]) -> Result<()> {
+        MemorySecretsProvider::add_binary_secret(self, name.into(), value.into());
+        Ok(())
+    }
 }
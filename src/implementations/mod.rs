@@ -1,7 +1,15 @@
 /// Use AWS official SDK
-#[cfg(feature = "aws")]
+#[cfg(feature = "aws-core")]
 pub mod aws;
 
+/// Use ciphertext objects of AWS S3, decrypted with AWS KMS (envelope encryption)
+#[cfg(feature = "aws-s3-kms")]
+pub mod s3_kms;
+
+/// Use a DynamoDB table encrypted with AWS KMS data keys, in the layout of credstash
+#[cfg(feature = "aws-dynamodb-kms")]
+pub mod dynamodb_kms;
+
 /// Use Rusoto SDK
 #[cfg(feature = "legacy-rusoto-aws")]
 pub mod rusoto;
@@ -9,3 +17,105 @@ pub mod rusoto;
 /// Use a dummy in-memory secrets provider
 #[cfg(feature = "memory")]
 pub mod memory;
+
+/// Use secrets mounted as files (Docker secrets, Kubernetes volumes)
+#[cfg(feature = "mounted")]
+pub mod mounted;
+
+/// Use environment variables (for local development)
+#[cfg(feature = "env")]
+pub mod env;
+
+/// Use a single JSON or YAML secrets file
+#[cfg(feature = "file")]
+pub mod file;
+
+/// Use files rendered by Vault Agent or consul-template, reloaded when they are rendered again
+#[cfg(feature = "vault-agent")]
+pub mod vault_agent;
+
+/// Use a directory tree where each secret is a file, with versions as suffixed files
+#[cfg(feature = "directory")]
+pub mod directory;
+
+/// Use an age-encrypted JSON or YAML secrets bundle, decrypted in memory
+#[cfg(feature = "age")]
+pub mod age;
+
+/// Use sealed secrets: values encrypted for an age recipient, safe to commit to git
+#[cfg(feature = "sealed")]
+pub mod sealed;
+
+/// Use Doppler
+#[cfg(feature = "doppler")]
+pub mod doppler;
+
+/// Use HCP Vault Secrets, HashiCorp's managed secrets service
+#[cfg(feature = "hcp")]
+pub mod hcp;
+
+/// Use a NATS JetStream KV bucket, with revisions as versions
+#[cfg(feature = "nats")]
+pub mod nats;
+
+/// Use Redis hashes, with one field per version (for test environments and edge caches)
+#[cfg(feature = "redis")]
+pub mod redis;
+
+/// Use a PostgreSQL table, with one row per version and optional pgcrypto encryption
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+/// Use a local SQLite database, optionally encrypted with SQLCipher (for desktop applications)
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Use the OS keyring of the user (for desktop tooling)
+#[cfg(feature = "keyring")]
+pub mod keyring;
+
+/// Use systemd credentials (`LoadCredential=`, `SetCredential=`...)
+#[cfg(feature = "systemd")]
+pub mod systemd;
+
+/// Resolves a secret name to a file path under a root directory.
+///
+/// Names that could escape the root directory (absolute paths or `..` components) and empty names
+/// are rejected with an [InvalidName](crate::SecretsProviderError::InvalidName) error.
+#[cfg(any(
+    feature = "mounted",
+    feature = "directory",
+    feature = "systemd",
+    feature = "sealed"
+))]
+pub(crate) fn secret_file_path(
+    root: &std::path::Path,
+    secret_name: &str,
+) -> crate::Result<std::path::PathBuf> {
+    use std::path::{Component, Path};
+
+    let relative = Path::new(secret_name);
+    if secret_name.is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return Err(crate::SecretsProviderError::InvalidName(format!(
+            r#"Secret name "{}" is not a relative path"#,
+            secret_name
+        )));
+    }
+
+    Ok(root.join(relative))
+}
+
+/// Version of a file based secret, derived from the file's modification time. It changes every
+/// time the file is written.
+#[cfg(any(feature = "mounted", feature = "file", feature = "systemd"))]
+pub(crate) fn file_version(metadata: &std::fs::Metadata) -> std::io::Result<String> {
+    Ok(metadata
+        .modified()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos().to_string())
+        .unwrap_or_else(|_| "unknown".to_string()))
+}
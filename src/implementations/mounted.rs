@@ -0,0 +1,141 @@
+//! Mounted files Secret Provider implementation.
+//!
+//! Each secret is a file under a root directory, and the secret name is the file path relative to
+//! that root. This is how Docker Swarm (`/run/secrets`), Kubernetes secret volumes and CSI secret
+//! stores expose secrets to containers, so no network call is ever made.
+//!
+//! Files do not say whether they hold a string or a binary secret, so the content is returned as
+//! the requested type: [String](std::string::String) if the file is valid UTF-8, or
+//! [Vec<u8>](std::vec::Vec) for any content.
+//!
+//! Mounted secrets have a single version, derived from the file's modification time. It changes
+//! every time the orchestrator updates the file.
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use super::{file_version, secret_file_path};
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Mounted files";
+
+/// Default directory where Docker mounts the secrets of a service.
+pub const DOCKER_SECRETS_ROOT: &str = "/run/secrets";
+
+/// Mounted files Secrets Provider implementation.
+#[derive(Clone, Debug)]
+pub struct MountedSecretsProvider {
+    root: PathBuf,
+}
+
+impl MountedSecretsProvider {
+    /// Creates a new Secrets Provider reading the secrets under a given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory where the secrets are mounted. For example: `/run/secrets`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory where the secrets are read from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn read_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let path = secret_file_path(&self.root, name)?;
+        let (content, current_version) = match read_file(&path) {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(SecretsProviderError::ProviderFailed(format!(
+                    "Unable to read secret file {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        if version.is_some_and(|v| v != current_version) {
+            return Ok(None);
+        }
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, content)?,
+            name: name.to_string(),
+            version: current_version,
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(true),
+        }))
+    }
+}
+
+impl Default for MountedSecretsProvider {
+    fn default() -> Self {
+        Self::new(DOCKER_SECRETS_ROOT)
+    }
+}
+
+/// Reads a file returning its content and its version.
+fn read_file(path: &Path) -> std::io::Result<(Vec<u8>, String)> {
+    let version = file_version(&std::fs::metadata(path)?)?;
+    Ok((std::fs::read(path)?, version))
+}
+
+impl ProviderCapabilities for MountedSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for MountedSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, Some(version))
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("read_root", async {
+                match std::fs::metadata(&self.root) {
+                    Ok(m) if m.is_dir() => Ok(()),
+                    Ok(_) => Err(format!("{} is not a directory", self.root.display())),
+                    Err(e) => Err(format!("Unable to read {}: {}", self.root.display(), e)),
+                }
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
@@ -0,0 +1,178 @@
+//! NATS JetStream KV Secret Provider implementation.
+//!
+//! Secrets are the keys of a JetStream key-value bucket, for teams already running NATS as their
+//! infrastructure bus. Versions are the revisions of the bucket: each put creates a new revision,
+//! and previous revisions can be read as long as the bucket keeps them in its history. Deleted
+//! and purged keys are not found.
+//!
+//! Values do not say whether they hold a string or a binary secret, so they are returned as the
+//! requested type: [String](std::string::String) if they are valid UTF-8, or
+//! [Vec<u8>](std::vec::Vec) for any content. Key names are checked against
+//! [NATS_KV](crate::naming::NameRules::NATS_KV) before sending any request.
+//!
+//! For more information:
+//! `<https://docs.nats.io/nats-concepts/jetstream/key-value-store>`
+use std::fmt::Debug;
+
+use async_nats::jetstream::kv::{Entry, EntryErrorKind, Operation, Store};
+use async_trait::async_trait;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::naming::NameRules;
+use crate::secret::{Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "NATS KV";
+
+/// NATS JetStream KV Secrets Provider implementation.
+#[derive(Clone)]
+pub struct NatsKvSecretsProvider {
+    store: Store,
+}
+
+impl NatsKvSecretsProvider {
+    /// Connects to a NATS server and creates a new Secrets Provider reading an existing KV bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - URL of the NATS server. For example: `nats://localhost:4222`.
+    /// * `bucket` - Name of the KV bucket.
+    pub async fn connect(url: &str, bucket: &str) -> Result<Self> {
+        let client = async_nats::connect(url).await.map_err(|e| {
+            SecretsProviderError::Initialization(format!("Unable to connect to NATS: {}", e))
+        })?;
+        let store = async_nats::jetstream::new(client)
+            .get_key_value(bucket)
+            .await
+            .map_err(|e| {
+                SecretsProviderError::Initialization(format!(
+                    "Unable to open KV bucket {}: {}",
+                    bucket, e
+                ))
+            })?;
+
+        Ok(Self::from_store(store))
+    }
+
+    /// Creates a new Secrets Provider from an existing KV bucket handle, for clients that need
+    /// custom connection options (credentials, TLS...).
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - Handle of the KV bucket.
+    pub fn from_store(store: Store) -> Self {
+        Self { store }
+    }
+
+    /// Name of the KV bucket.
+    pub fn bucket(&self) -> &str {
+        &self.store.name
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        NameRules::NATS_KV.validate(name)?;
+
+        let entry = match version {
+            // Revisions are sequence numbers, so other versions can not exist
+            Some(version) => match version.parse::<u64>() {
+                Ok(revision) => self.store.entry_for_revision(name, revision).await,
+                Err(_) => return Ok(None),
+            },
+            None => self.store.entry(name).await,
+        };
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) if e.kind() == EntryErrorKind::InvalidKey => {
+                return Err(SecretsProviderError::InvalidName(format!(
+                    "{:?} is not a valid NATS KV key",
+                    name
+                )))
+            }
+            Err(e) => {
+                return Err(SecretsProviderError::ProviderFailed(format!(
+                    "Unable to read secret {} from NATS KV bucket {}: {}",
+                    name, self.store.name, e
+                )))
+            }
+        };
+
+        match entry {
+            Some(Entry {
+                value,
+                revision,
+                operation: Operation::Put,
+                ..
+            }) => Ok(Some(Secret {
+                secret: T::decode_raw(name, value.to_vec())?,
+                name: name.to_string(),
+                version: revision.to_string(),
+                version_stages: Vec::new(),
+                tags: None,
+                created_at: None,
+                last_rotated_at: None,
+                metadata: None,
+                backend: BACKEND,
+                // The latest revision is only known when no version was requested
+                is_current: version.is_none().then_some(true),
+            })),
+            // Deletion and purge markers
+            _ => Ok(None),
+        }
+    }
+}
+
+impl Debug for NatsKvSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NatsKvSecretsProvider")
+            .field("bucket", &self.store.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for NatsKvSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for NatsKvSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version)).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("bucket_status", async {
+                self.store.status().await.map(|_| ())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
@@ -0,0 +1,282 @@
+//! Redis Secret Provider implementation.
+//!
+//! Each secret is a Redis hash, whose fields are the versions of the secret and whose values are
+//! the secret values. Versions are positive integers, and the current version is the highest one:
+//!
+//! ```text
+//! HSET database-password 1 "first password" 2 "second password"
+//! ```
+//!
+//! Fields that are not versions are ignored. Keys can be namespaced with a prefix, so secrets can
+//! share a Redis database with other data.
+//!
+//! Redis values are binary safe, so secrets are read like files: valid UTF-8 values can be read as
+//! [String](std::string::String), and any value can be read as [Vec<u8>](std::vec::Vec).
+//!
+//! This implementation is intended for ephemeral test environments and edge caches. Redis does
+//! not encrypt data at rest, so do not use it as the system of record of secrets.
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use redis::{AsyncCommands, RedisError};
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Redis";
+
+/// Connection attempts before giving up.
+const CONNECTION_RETRIES: usize = 2;
+
+/// Timeout of each connection attempt.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Redis Secrets Provider implementation.
+#[derive(Clone)]
+pub struct RedisSecretsProvider {
+    connection: ConnectionManager,
+    key_prefix: String,
+}
+
+impl RedisSecretsProvider {
+    /// Creates a new Secrets Provider connected to a Redis server. The connection is
+    /// re-established automatically if it is lost.
+    ///
+    /// Connecting is retried a few times, with a delay of one second between attempts, before
+    /// failing.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Redis connection URL. For example: `redis://127.0.0.1:6379/0`.
+    pub async fn new(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(|e| {
+            SecretsProviderError::Initialization(format!("Invalid Redis URL: {}", e))
+        })?;
+        let config = ConnectionManagerConfig::new()
+            .set_number_of_retries(CONNECTION_RETRIES)
+            .set_max_delay(1000)
+            .set_connection_timeout(CONNECTION_TIMEOUT);
+        let connection = ConnectionManager::new_with_config(client, config)
+            .await
+            .map_err(|e| {
+                SecretsProviderError::Initialization(format!("Unable to connect to Redis: {}", e))
+            })?;
+
+        Ok(Self {
+            connection,
+            key_prefix: String::new(),
+        })
+    }
+
+    /// Namespaces the secret keys: the secret `name` is read from the key `{prefix}name`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_prefix` - Prefix of the secret keys. For example: `secrets:`.
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Prefix of the secret keys.
+    pub fn key_prefix(&self) -> &str {
+        &self.key_prefix
+    }
+
+    /// Lists the existing versions of a given secret, the most recent version last.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub async fn list_secret_version_ids(&self, secret_name: &str) -> Result<Vec<String>> {
+        let fields: Vec<String> = self
+            .connection
+            .clone()
+            .hkeys(self.key(secret_name))
+            .await
+            .map_err(request_error)?;
+
+        let mut versions: Vec<u64> = fields.iter().filter_map(|f| parse_version(f)).collect();
+        versions.sort_unstable();
+
+        Ok(versions.into_iter().map(|v| v.to_string()).collect())
+    }
+
+    fn key(&self, secret_name: &str) -> String {
+        format!("{}{}", self.key_prefix, secret_name)
+    }
+}
+
+/// Parses a hash field as a version number.
+fn parse_version(field: &str) -> Option<u64> {
+    field
+        .parse::<u64>()
+        .ok()
+        .filter(|v| *v > 0 && v.to_string() == field)
+}
+
+/// Picks the current version among the fields of a secret hash.
+fn current_version<T: Decode>(
+    name: &str,
+    fields: HashMap<String, Vec<u8>>,
+) -> Result<Option<Secret<T>>> {
+    let versions: BTreeMap<u64, Vec<u8>> = fields
+        .into_iter()
+        .filter_map(|(field, value)| parse_version(&field).map(|v| (v, value)))
+        .collect();
+
+    match versions.into_iter().next_back() {
+        Some((version, value)) => Ok(Some(Secret {
+            secret: T::decode_raw(name, value)?,
+            name: name.to_string(),
+            version: version.to_string(),
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(true),
+        })),
+        None => Ok(None),
+    }
+}
+
+fn request_error(e: RedisError) -> SecretsProviderError {
+    SecretsProviderError::ProviderFailed(format!("Redis request failed: {}", e))
+}
+
+impl std::fmt::Debug for RedisSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The connection URL is not kept, as it may contain credentials
+        f.debug_struct("RedisSecretsProvider")
+            .field("key_prefix", &self.key_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for RedisSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        batch: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for RedisSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        let fields: HashMap<String, Vec<u8>> = self
+            .connection
+            .clone()
+            .hgetall(self.key(key_name))
+            .await
+            .map_err(request_error)?;
+
+        current_version(key_name, fields)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        if parse_version(version).is_none() {
+            return Ok(None);
+        }
+
+        let value: Option<Vec<u8>> = self
+            .connection
+            .clone()
+            .hget(self.key(key_name), version)
+            .await
+            .map_err(request_error)?;
+
+        match value {
+            Some(value) => Ok(Some(Secret {
+                secret: T::decode_raw(key_name, value)?,
+                name: key_name.to_string(),
+                version: version.to_string(),
+                version_stages: Vec::new(),
+                tags: None,
+                created_at: None,
+                last_rotated_at: None,
+                metadata: None,
+                backend: BACKEND,
+                // Telling would require reading the other versions
+                is_current: None,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the version fields, without their values.
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        Ok(!self.list_secret_version_ids(secret_name).await?.is_empty())
+    }
+
+    /// Versions have no creation time, Redis does not keep it.
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        let versions = self.list_secret_version_ids(secret_name).await?;
+        Ok(VersionInfo::last_current(
+            versions.into_iter().map(|id| (id, None)),
+        ))
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        if secret_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Every hash is read in a single round trip
+        let mut pipeline = redis::pipe();
+        for name in secret_names {
+            pipeline.hgetall(self.key(name));
+        }
+        let responses: Vec<HashMap<String, Vec<u8>>> = pipeline
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(request_error)?;
+
+        let mut retrieved = HashMap::new();
+        for (name, fields) in secret_names.iter().zip(responses) {
+            if let Some(secret) = current_version(name, fields)? {
+                retrieved.insert(*name, secret);
+            }
+        }
+
+        Ok(retrieved)
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("ping", async {
+                redis::cmd("PING")
+                    .query_async::<String>(&mut self.connection.clone())
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
@@ -9,18 +9,38 @@
 //! For more information:
 //! `<https://docs.rs/rusoto_sts/0.48.0/rusoto_sts/struct.WebIdentityProvider.html#method.from_k8s_env>`
 use async_trait::async_trait;
-use rusoto_core::Region;
-use rusoto_credential::AutoRefreshingProvider;
+use hyper_tls::HttpsConnector;
+use rusoto_core::request::{DispatchSignedRequest, HttpClient};
+use rusoto_core::{Client, Region};
+use rusoto_credential::{
+    AutoRefreshingProvider, AwsCredentials, CredentialsError, DefaultCredentialsProvider,
+    ProvideAwsCredentials,
+};
 use rusoto_secretsmanager::{
-    GetSecretValueError, GetSecretValueRequest, GetSecretValueResponse, SecretsManager,
-    SecretsManagerClient,
+    DescribeSecretError, DescribeSecretRequest, Filter, GetSecretValueError, GetSecretValueRequest,
+    GetSecretValueResponse, ListSecretVersionIdsError, ListSecretVersionIdsRequest,
+    ListSecretsRequest, SecretsManager, SecretsManagerClient, Tag,
+};
+use rusoto_sts::{
+    AssumeRoleWithWebIdentityRequest, NewAwsCredsForStsCreds, Sts, StsClient, WebIdentityProvider,
 };
-use rusoto_sts::WebIdentityProvider;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::capabilities::{Capabilities, ProviderCapabilities};
 use crate::errors::SecretsProviderError;
-use crate::secret::{Decode, Secret, SecretData};
-use crate::{Result, SecretsProvider};
+use crate::naming::NameRules;
+use crate::resolver::{self, ResolveHost};
+use crate::secret::{
+    Decode, Secret, SecretData, SecretDescription, SecretMetadata, VersionInfo, VersionStage,
+};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "AWS Secrets Manager";
 
 /// Amazon Web Services Secrets Provider builder.
 pub struct AwsSecretsProviderBuilder {
@@ -29,6 +49,9 @@ pub struct AwsSecretsProviderBuilder {
 
     /// Endpoint of the service.
     endpoint: Option<String>,
+
+    /// Custom host name resolution.
+    resolver: Option<Arc<dyn ResolveHost>>,
 }
 
 impl AwsSecretsProviderBuilder {
@@ -42,6 +65,7 @@ impl AwsSecretsProviderBuilder {
         Self {
             region,
             endpoint: None,
+            resolver: None,
         }
     }
 
@@ -57,12 +81,82 @@ impl AwsSecretsProviderBuilder {
         self
     }
 
+    /// Overrides how the host names of the service, and of STS when the credentials are obtained
+    /// from the k8s web identity environment, are resolved.
+    ///
+    /// This is usually used in networks that reach AWS through private endpoints with
+    /// split-horizon DNS.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolver` - Host name resolver. For example, a
+    ///   [StaticResolver](crate::resolver::StaticResolver).
+    pub fn resolver(mut self, resolver: impl ResolveHost + 'static) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
     /// Builds a [AwsSecretsProvider](crate::implementations::AwsSecretsProvider).
     pub fn build(self) -> Result<AwsSecretsProvider> {
-        AwsSecretsProvider::new(self.region, self.endpoint)
+        AwsSecretsProvider::new(self.region, self.endpoint, self.resolver)
     }
 }
 
+/// Web identity credentials obtained from a given STS client.
+///
+/// [WebIdentityProvider] always builds its STS client with a default HTTP client, which bypasses
+/// the resolver of the provider.
+struct WebIdentityCredentials {
+    web_identity: WebIdentityProvider,
+    sts: StsClient,
+}
+
+#[async_trait]
+impl ProvideAwsCredentials for WebIdentityCredentials {
+    async fn credentials(&self) -> std::result::Result<AwsCredentials, CredentialsError> {
+        let role_session_name = match &self.web_identity.role_session_name {
+            Some(name) => name.resolve()?,
+            None => None,
+        };
+        let request = AssumeRoleWithWebIdentityRequest {
+            role_arn: self.web_identity.role_arn.resolve()?,
+            web_identity_token: self
+                .web_identity
+                .web_identity_token
+                .resolve()?
+                .as_ref()
+                .to_string(),
+            role_session_name: role_session_name
+                .unwrap_or_else(|| "WebIdentitySession".to_string()),
+            ..Default::default()
+        };
+
+        let response = self
+            .sts
+            .assume_role_with_web_identity(request)
+            .await
+            .map_err(CredentialsError::new)?;
+        match response.credentials {
+            Some(credentials) => AwsCredentials::new_for_credentials(credentials),
+            None => Err(CredentialsError::new(
+                "No credentials found in AssumeRoleWithWebIdentityResponse",
+            )),
+        }
+    }
+}
+
+/// Staging label of the current version of a secret.
+const CURRENT_STAGE: &str = "AWSCURRENT";
+
+/// Staging label of the version that was current before the current one.
+const PREVIOUS_STAGE: &str = "AWSPREVIOUS";
+
+/// Staging label of the version being rotated in.
+const PENDING_STAGE: &str = "AWSPENDING";
+
+/// Maximum number of values of a ListSecrets filter.
+const MAX_FILTER_VALUES: usize = 10;
+
 /// Amazon Web Services Secrets Provider implementation.
 #[derive(Clone)]
 pub struct AwsSecretsProvider {
@@ -79,44 +173,107 @@ impl AwsSecretsProvider {
     /// * `endpoint` - String represeting the endpoint. For example: `http://127.0.0.1:4566`. This
     /// string is Optional because overriding the endpoint usually means the Secrets Provider will
     /// be used in a test environment.
-    fn new(region: String, endpoint: Option<String>) -> Result<Self> {
-        let secrets_manager_client = if let Some(ep) = endpoint {
-            SecretsManagerClient::new(Region::Custom {
-                name: region,
-                endpoint: ep,
-            })
+    /// * `resolver` - Custom host name resolution. When it is not set, the system resolver is used.
+    fn new(
+        region: String,
+        endpoint: Option<String>,
+        resolver: Option<Arc<dyn ResolveHost>>,
+    ) -> Result<Self> {
+        let secrets_manager_client = if let Some(resolver) = resolver {
+            let connector =
+                HttpsConnector::new_with_connector(resolver::http_connector(Arc::clone(&resolver)));
+            let sts_connector =
+                HttpsConnector::new_with_connector(resolver::http_connector(resolver));
+            // STS requests go through the resolver too, like the AWS SDK implementation
+            let sts = StsClient::new_with_client(
+                Client::new_not_signing(HttpClient::from_connector(sts_connector)),
+                Region::default(),
+            );
+            Self::new_client(
+                HttpClient::from_connector(connector),
+                Some(sts),
+                region,
+                endpoint,
+            )?
         } else {
-            let region = Region::from_str(&region).map_err(|e| {
+            let request_dispatcher = HttpClient::new().map_err(|e| {
                 SecretsProviderError::Initialization(format!(
-                    r#"Unable to parse AWS region "{}": {}"#,
-                    &region, e
+                    "Unable to build Rusoto HTTP Client: {}",
+                    e
                 ))
             })?;
 
-            let request_dispatcher = rusoto_core::request::HttpClient::new().map_err(|e| {
+            Self::new_client(request_dispatcher, None, region, endpoint)?
+        };
+
+        Self::new_with_secrets_manager_client(secrets_manager_client)
+    }
+
+    /// Creates the `SecretsManagerClient` sending the requests through the given dispatcher.
+    ///
+    /// When the endpoint is overridden the default credentials chain is used. Otherwise the
+    /// credentials are obtained from the k8s web identity environment, through the given STS
+    /// client if there is one.
+    fn new_client<D>(
+        request_dispatcher: D,
+        sts: Option<StsClient>,
+        region: String,
+        endpoint: Option<String>,
+    ) -> Result<SecretsManagerClient>
+    where
+        D: DispatchSignedRequest + Send + Sync + 'static,
+    {
+        if let Some(ep) = endpoint {
+            let credentials_provider = DefaultCredentialsProvider::new().map_err(|e| {
                 SecretsProviderError::Initialization(format!(
-                    "Unable to build Rusoto HTTP Client: {}",
+                    "Unable to construct the default credentials provider: {}",
                     e
                 ))
             })?;
 
-            // Create a WebIdentityProvider from the following environment variables:
-            // - AWS_WEB_IDENTITY_TOKEN_FILE path to the web identity token file.
-            // - AWS_ROLE_ARN ARN of the role to assume.
-            // - AWS_ROLE_SESSION_NAME (optional) name applied to the assume-role session.
-            // https://docs.rs/rusoto_sts/0.45.0/rusoto_sts/struct.WebIdentityProvider.html#method.from_k8s_env
-            let credentials_provider =
-                AutoRefreshingProvider::new(WebIdentityProvider::from_k8s_env()).map_err(|e| {
-                    SecretsProviderError::Initialization(format!(
-                        "Unable to construct the credentials provider from k8s environment: {}",
-                        e
-                    ))
-                })?;
-
-            SecretsManagerClient::new_with(request_dispatcher, credentials_provider, region)
+            return Ok(SecretsManagerClient::new_with(
+                request_dispatcher,
+                credentials_provider,
+                Region::Custom {
+                    name: region,
+                    endpoint: ep,
+                },
+            ));
+        }
+
+        let region = Region::from_str(&region).map_err(|e| {
+            SecretsProviderError::Initialization(format!(
+                r#"Unable to parse AWS region "{}": {}"#,
+                &region, e
+            ))
+        })?;
+
+        // Create a WebIdentityProvider from the following environment variables:
+        // - AWS_WEB_IDENTITY_TOKEN_FILE path to the web identity token file.
+        // - AWS_ROLE_ARN ARN of the role to assume.
+        // - AWS_ROLE_SESSION_NAME (optional) name applied to the assume-role session.
+        // https://docs.rs/rusoto_sts/0.45.0/rusoto_sts/struct.WebIdentityProvider.html#method.from_k8s_env
+        let web_identity = WebIdentityProvider::from_k8s_env();
+        let credentials_error = |e: CredentialsError| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to construct the credentials provider from k8s environment: {}",
+                e
+            ))
         };
 
-        Self::new_with_secrets_manager_client(secrets_manager_client)
+        Ok(match sts {
+            Some(sts) => SecretsManagerClient::new_with(
+                request_dispatcher,
+                AutoRefreshingProvider::new(WebIdentityCredentials { web_identity, sts })
+                    .map_err(credentials_error)?,
+                region,
+            ),
+            None => SecretsManagerClient::new_with(
+                request_dispatcher,
+                AutoRefreshingProvider::new(web_identity).map_err(credentials_error)?,
+                region,
+            ),
+        })
     }
 
     /// Creates a new Secrets Provider for Amazon Web Services using an instance of a `SecretsManagerClient`.
@@ -132,6 +289,137 @@ impl AwsSecretsProvider {
         })
     }
 
+    /// Retrieves the version of a secret that was current at a given time: the most recent
+    /// version created at or before that time.
+    ///
+    /// Versions are selected by their creation date, so rollbacks (moving `AWSCURRENT` back to a
+    /// previous version) are not taken into account. Versions already removed by AWS can not be
+    /// selected.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `timestamp` - Point in time. For example: the start of an incident.
+    pub async fn find_as_of<T: Decode>(
+        &self,
+        secret_name: &str,
+        timestamp: SystemTime,
+    ) -> Result<Option<Secret<T>>> {
+        validate_secret_id(secret_name)?;
+
+        let mut as_of: Option<(SystemTime, String)> = None;
+        let mut next_token = None;
+        loop {
+            let response = match SecretsManager::list_secret_version_ids(
+                &self.secrets_manager_client,
+                ListSecretVersionIdsRequest {
+                    secret_id: secret_name.to_string(),
+                    include_deprecated: Some(true),
+                    next_token,
+                    ..Default::default()
+                },
+            )
+            .await
+            {
+                Ok(response) => response,
+                Err(rusoto_core::RusotoError::Service(
+                    ListSecretVersionIdsError::ResourceNotFound(_),
+                )) => return Ok(None),
+                Err(e) => return Err(SecretsProviderError::ProviderFailed(e.to_string())),
+            };
+
+            for version in response.versions.unwrap_or_default() {
+                let (Some(version_id), Some(created)) = (
+                    version.version_id,
+                    version.created_date.and_then(to_system_time),
+                ) else {
+                    continue;
+                };
+
+                if created <= timestamp && as_of.as_ref().is_none_or(|(c, _)| created > *c) {
+                    as_of = Some((created, version_id));
+                }
+            }
+
+            next_token = response.next_token;
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        match as_of {
+            Some((_, version)) => self.find_secret(secret_name, Some(&version), None).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Describes many secrets at once, without reading their values. Missing secrets are not
+    /// included in the result.
+    ///
+    /// Secrets are looked up with ListSecrets, filtering by name, so a single request describes
+    /// up to ten secrets instead of a DescribeSecret request per secret. Only names are accepted,
+    /// not ARNs.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_names` - List of secret names to describe.
+    pub async fn batch_describe<'n>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, SecretDescription>> {
+        for name in secret_names {
+            NameRules::AWS.validate(name)?;
+        }
+
+        let mut described = HashMap::new();
+        for chunk in secret_names.chunks(MAX_FILTER_VALUES) {
+            let mut next_token = None;
+            loop {
+                let response = SecretsManager::list_secrets(
+                    &self.secrets_manager_client,
+                    ListSecretsRequest {
+                        filters: Some(vec![Filter {
+                            key: Some("name".to_string()),
+                            values: Some(chunk.iter().map(|n| n.to_string()).collect()),
+                        }]),
+                        next_token,
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(|e| SecretsProviderError::ProviderFailed(e.to_string()))?;
+
+                for entry in response.secret_list.unwrap_or_default() {
+                    // The name filter matches prefixes, so other secrets may be listed too
+                    let Some(name) = chunk.iter().find(|n| Some(**n) == entry.name.as_deref())
+                    else {
+                        continue;
+                    };
+
+                    described.insert(
+                        *name,
+                        SecretDescription {
+                            name: name.to_string(),
+                            description: entry.description,
+                            created_at: entry.created_date.and_then(to_system_time),
+                            last_changed_at: entry.last_changed_date.and_then(to_system_time),
+                            last_rotated_at: entry.last_rotated_date.and_then(to_system_time),
+                            rotation_enabled: entry.rotation_enabled,
+                            tags: Some(tag_map(entry.tags.unwrap_or_default())),
+                        },
+                    );
+                }
+
+                next_token = response.next_token;
+                if next_token.is_none() {
+                    break;
+                }
+            }
+        }
+
+        Ok(described)
+    }
+
     /// Processes an AWS Secret Manager response and creates a
     /// [SecretData](crate::secret::SecretData) value from the response.
     ///
@@ -144,12 +432,16 @@ impl AwsSecretsProvider {
         response: GetSecretValueResponse,
     ) -> Result<Secret<T>> {
         let GetSecretValueResponse {
+            arn,
             version_id,
             name,
             secret_string,
             secret_binary,
+            version_stages,
+            created_date,
             ..
         } = response;
+        let version_stages = version_stages.unwrap_or_default();
 
         let name = name.unwrap_or_else(|| secret_id.to_string());
         Ok(Secret {
@@ -159,26 +451,41 @@ impl AwsSecretsProvider {
                 if let Some(d) = secret_string {
                     Ok(SecretData::Str(d))
                 } else if let Some(d) = secret_binary {
-                    Ok(SecretData::Bytes(d.to_vec()))
+                    Ok(SecretData::Bytes(d))
                 } else {
                     Err(SecretsProviderError::UnknownType(name.clone()))
                 }?,
             )?,
             name,
+            is_current: Some(version_stages.iter().any(|s| s == CURRENT_STAGE)),
+            version_stages,
+            backend: BACKEND,
+            tags: None,
+            created_at: created_date.and_then(to_system_time),
+            last_rotated_at: None,
+            metadata: arn.map(|arn| SecretMetadata {
+                resource_id: Some(arn),
+                ..SecretMetadata::default()
+            }),
         })
     }
 
+    /// Retrieves a version of a secret, by identifier or staging label. The current version is
+    /// retrieved if there are none.
     async fn find_secret<T: Decode>(
         &self,
         name: &str,
         version: Option<&str>,
+        stage: Option<&str>,
     ) -> Result<Option<Secret<T>>> {
+        validate_secret_id(name)?;
+
         match SecretsManager::get_secret_value(
             &self.secrets_manager_client,
             GetSecretValueRequest {
                 secret_id: name.to_string(),
                 version_id: version.map(String::from),
-                version_stage: None,
+                version_stage: stage.map(String::from),
             },
         )
         .await
@@ -192,10 +499,61 @@ impl AwsSecretsProvider {
     }
 }
 
+/// Staging label of a version stage.
+fn stage_label(stage: &VersionStage) -> &str {
+    match stage {
+        VersionStage::Current => CURRENT_STAGE,
+        VersionStage::Previous => PREVIOUS_STAGE,
+        VersionStage::Pending => PENDING_STAGE,
+        VersionStage::Label(label) => label,
+    }
+}
+
+/// Version stage of a staging label.
+fn label_stage(label: &str) -> VersionStage {
+    match label {
+        CURRENT_STAGE => VersionStage::Current,
+        PREVIOUS_STAGE => VersionStage::Previous,
+        PENDING_STAGE => VersionStage::Pending,
+        label => VersionStage::Label(label.to_string()),
+    }
+}
+
+/// Tags by key. Tags without key are skipped.
+fn tag_map(tags: Vec<Tag>) -> BTreeMap<String, String> {
+    tags.into_iter()
+        .filter_map(|tag| Some((tag.key?, tag.value.unwrap_or_default())))
+        .collect()
+}
+
+/// Checks a secret name or ARN before sending any request.
+fn validate_secret_id(secret_id: &str) -> Result<()> {
+    if secret_id.starts_with("arn:") {
+        NameRules::AWS_ARN.validate(secret_id)
+    } else {
+        NameRules::AWS.validate(secret_id)
+    }
+}
+
+/// Converts an AWS date, in seconds since the epoch.
+fn to_system_time(date: f64) -> Option<SystemTime> {
+    Duration::try_from_secs_f64(date)
+        .ok()
+        .map(|d| UNIX_EPOCH + d)
+}
+
+impl ProviderCapabilities for AwsSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        stages: true,
+        ..Capabilities::NONE
+    };
+}
+
 #[async_trait]
 impl SecretsProvider for AwsSecretsProvider {
     async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
-        self.find_secret(key_name, None).await
+        self.find_secret(key_name, None, None).await
     }
 
     async fn find_with_version<T: Decode>(
@@ -203,7 +561,119 @@ impl SecretsProvider for AwsSecretsProvider {
         key_name: &str,
         version: &str,
     ) -> Result<Option<Secret<T>>> {
-        self.find_secret(key_name, Some(version)).await
+        self.find_secret(key_name, Some(version), None).await
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        key_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None, Some(stage_label(stage)))
+            .await
+    }
+
+    /// Uses a DescribeSecret request, which does not need `secretsmanager:GetSecretValue` and
+    /// does not decrypt the value. Secrets scheduled for deletion do not exist.
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        validate_secret_id(secret_name)?;
+
+        match SecretsManager::describe_secret(
+            &self.secrets_manager_client,
+            DescribeSecretRequest {
+                secret_id: secret_name.to_string(),
+            },
+        )
+        .await
+        {
+            Ok(response) => Ok(response.deleted_date.is_none()),
+            Err(rusoto_core::RusotoError::Service(DescribeSecretError::ResourceNotFound(_))) => {
+                Ok(false)
+            }
+            Err(e) => Err(SecretsProviderError::ProviderFailed(e.to_string())),
+        }
+    }
+
+    /// Deprecated versions, without staging labels, are listed too until AWS removes them.
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        validate_secret_id(secret_name)?;
+
+        let mut versions = Vec::new();
+        let mut next_token = None;
+        loop {
+            let response = match SecretsManager::list_secret_version_ids(
+                &self.secrets_manager_client,
+                ListSecretVersionIdsRequest {
+                    secret_id: secret_name.to_string(),
+                    include_deprecated: Some(true),
+                    next_token,
+                    ..Default::default()
+                },
+            )
+            .await
+            {
+                Ok(response) => response,
+                Err(rusoto_core::RusotoError::Service(
+                    ListSecretVersionIdsError::ResourceNotFound(_),
+                )) => return Ok(Vec::new()),
+                Err(e) => return Err(SecretsProviderError::ProviderFailed(e.to_string())),
+            };
+
+            versions.extend(
+                response
+                    .versions
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|version| {
+                        Some(VersionInfo {
+                            id: version.version_id?,
+                            stages: version
+                                .version_stages
+                                .unwrap_or_default()
+                                .iter()
+                                .map(|label| label_stage(label))
+                                .collect(),
+                            created_at: version.created_date.and_then(to_system_time),
+                        })
+                    }),
+            );
+
+            next_token = response.next_token;
+            if next_token.is_none() {
+                break;
+            }
+        }
+
+        versions.sort_by_key(|version| version.created_at);
+        Ok(versions)
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        // Listing secrets exercises both the credentials and the network path to the service
+        report.checks.push(
+            SelfTestCheck::run("list_secrets", async {
+                SecretsManager::list_secrets(
+                    &self.secrets_manager_client,
+                    ListSecretsRequest {
+                        max_results: Some(1),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map(|_| ())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
     }
 
     // NOTE: Rusoto does not support batch get secret value method, so we'll just
@@ -0,0 +1,391 @@
+//! AWS S3 + KMS envelope encryption Secret Provider implementation.
+//!
+//! Secrets are S3 objects holding ciphertext, decrypted with AWS KMS, for legacy systems that
+//! store their secrets this way instead of in Secrets Manager. The object key is the secret name,
+//! under an optional prefix. Two layouts are supported:
+//!
+//! - Envelope encryption, as written by the Amazon S3 Encryption Client (v2): the object is
+//!   encrypted with AES-256-GCM by a data key, and the data key, encrypted by KMS, is kept in the
+//!   object metadata (`x-amz-key-v2`, `x-amz-iv`, `x-amz-matdesc`...). The data key is decrypted
+//!   with KMS, using the stored encryption context, and the object is decrypted locally.
+//! - Direct KMS ciphertext: objects without envelope metadata are KMS ciphertext blobs, as
+//!   returned by `aws kms encrypt`, decrypted with a single KMS request. KMS only encrypts up to 4
+//!   KiB this way.
+//!
+//! Decrypted values do not say whether they hold a string or a binary secret, so the plaintext is
+//! returned as the requested type: [String](std::string::String) if it is valid UTF-8, or
+//! [Vec<u8>](std::vec::Vec) for any content.
+//!
+//! Versions are S3 object versions. Objects of buckets without versioning have the `null`
+//! version.
+//!
+//! Credentials are read like for the [AWS implementation](crate::implementations::aws), and the
+//! role must be allowed to `s3:GetObject` the secrets and `kms:Decrypt` with their keys.
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_config::{BehaviorVersion, Region, SdkConfig};
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_s3::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
+use aws_sdk_s3::operation::get_object::GetObjectError;
+use aws_sdk_s3::operation::head_object::HeadObjectError;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::crypto;
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "AWS S3 + KMS";
+
+/// Version of the objects of buckets without versioning.
+pub const UNVERSIONED_OBJECT_VERSION: &str = "null";
+
+/// Metadata of the envelope, as written by the Amazon S3 Encryption Client.
+const ENCRYPTED_KEY_METADATA: &str = "x-amz-key-v2";
+const IV_METADATA: &str = "x-amz-iv";
+const CONTENT_ALGORITHM_METADATA: &str = "x-amz-cek-alg";
+const WRAP_ALGORITHM_METADATA: &str = "x-amz-wrap-alg";
+const MATERIAL_DESCRIPTION_METADATA: &str = "x-amz-matdesc";
+const TAG_LENGTH_METADATA: &str = "x-amz-tag-len";
+
+/// Only envelopes encrypted with AES-256-GCM, with data keys wrapped by KMS, are supported.
+const AES_GCM_ALGORITHM: &str = "AES/GCM/NoPadding";
+const KMS_WRAP_ALGORITHMS: [&str; 2] = ["kms+context", "kms"];
+const AES_GCM_TAG_LENGTH: &str = "128";
+
+/// AWS S3 + KMS envelope encryption Secrets Provider implementation.
+#[derive(Clone)]
+pub struct S3KmsSecretsProvider {
+    s3: aws_sdk_s3::Client,
+    kms: aws_sdk_kms::Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3KmsSecretsProvider {
+    /// Creates a new Secrets Provider reading the secrets of an S3 bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - String representing the AWS Region. Must be formatted with all lowercases
+    ///   letters and hyphens. For example: `us-west-2`.
+    /// * `bucket` - S3 bucket of the secrets.
+    pub async fn new(region: String, bucket: String) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region))
+            .load()
+            .await;
+        Self::from_config(&config, bucket, false)
+    }
+
+    /// Creates a new Secrets Provider reading the secrets of an S3 bucket at a given URL. This
+    /// method can be used to connect to AWS emulators like Localstack.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - String representing the AWS Region. Must be formatted with all lowercases
+    ///   letters and hyphens. For example: `us-west-2`.
+    /// * `endpoint_url` - URL of the AWS emulator. Example: `http://localhost:4566`.
+    /// * `bucket` - S3 bucket of the secrets.
+    pub async fn new_at_endpoint(region: &str, endpoint_url: &str, bucket: &str) -> Self {
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(region.to_string()))
+            .endpoint_url(endpoint_url)
+            .load()
+            .await;
+        // Emulators do not serve buckets as subdomains
+        Self::from_config(&config, bucket.to_string(), true)
+    }
+
+    /// Creates a new Secrets Provider from existing S3 and KMS clients.
+    ///
+    /// # Arguments
+    ///
+    /// * `s3` - S3 client.
+    /// * `kms` - KMS client.
+    /// * `bucket` - S3 bucket of the secrets.
+    pub fn from_clients(s3: aws_sdk_s3::Client, kms: aws_sdk_kms::Client, bucket: String) -> Self {
+        Self {
+            s3,
+            kms,
+            bucket,
+            prefix: String::new(),
+        }
+    }
+
+    fn from_config(config: &SdkConfig, bucket: String, force_path_style: bool) -> Self {
+        let s3_config = aws_sdk_s3::config::Builder::from(config)
+            .force_path_style(force_path_style)
+            .build();
+        Self::from_clients(
+            aws_sdk_s3::Client::from_conf(s3_config),
+            aws_sdk_kms::Client::new(config),
+            bucket,
+        )
+    }
+
+    /// Reads the secrets under a prefix of the bucket. The object key of a secret is the prefix
+    /// followed by the secret name.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Prefix of the object keys. For example: `secrets/production/`.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        if name.is_empty() {
+            return Err(SecretsProviderError::InvalidName(
+                "S3 object keys can not be empty".to_string(),
+            ));
+        }
+
+        let response = match self
+            .s3
+            .get_object()
+            .bucket(&self.bucket)
+            .key(format!("{}{}", self.prefix, name))
+            .set_version_id(version.map(str::to_string))
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                GetObjectError::NoSuchKey(_) => return Ok(None),
+                // Unknown and malformed version ids
+                other
+                    if version.is_some()
+                        && matches!(other.code(), Some("NoSuchVersion" | "InvalidArgument")) =>
+                {
+                    return Ok(None)
+                }
+                other => return Err(s3_error(name, DisplayErrorContext(other))),
+            },
+            Err(other) => return Err(s3_error(name, DisplayErrorContext(other))),
+        };
+
+        let object_version = response
+            .version_id()
+            .unwrap_or(UNVERSIONED_OBJECT_VERSION)
+            .to_string();
+        let metadata = response.metadata().cloned().unwrap_or_default();
+        let body = response
+            .body
+            .collect()
+            .await
+            .map_err(|e| s3_error(name, e))?
+            .into_bytes()
+            .to_vec();
+
+        let plaintext = if metadata.contains_key(ENCRYPTED_KEY_METADATA) {
+            self.open_envelope(name, &metadata, &body).await?
+        } else {
+            self.kms_decrypt(name, body, HashMap::new()).await?
+        };
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, plaintext)?,
+            name: name.to_string(),
+            version: object_version,
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            // The current version is only known when no version was requested
+            is_current: version.is_none().then_some(true),
+        }))
+    }
+
+    /// Decrypts an object encrypted by the Amazon S3 Encryption Client.
+    async fn open_envelope(
+        &self,
+        name: &str,
+        metadata: &HashMap<String, String>,
+        body: &[u8],
+    ) -> Result<Vec<u8>> {
+        let envelope_error = |reason: String| {
+            SecretsProviderError::ProviderFailed(format!(
+                "Unable to decrypt secret {}: {}",
+                name, reason
+            ))
+        };
+        let get = |key: &str| {
+            metadata
+                .get(key)
+                .map(String::as_str)
+                .ok_or_else(|| envelope_error(format!("missing {} metadata", key)))
+        };
+        let decode = |key: &str| {
+            STANDARD
+                .decode(get(key)?)
+                .map_err(|e| envelope_error(format!("invalid {} metadata: {}", key, e)))
+        };
+
+        let content_algorithm = get(CONTENT_ALGORITHM_METADATA)?;
+        let wrap_algorithm = get(WRAP_ALGORITHM_METADATA)?;
+        if content_algorithm != AES_GCM_ALGORITHM || !KMS_WRAP_ALGORITHMS.contains(&wrap_algorithm)
+        {
+            return Err(envelope_error(format!(
+                "unsupported envelope {} with key wrapped by {}",
+                content_algorithm, wrap_algorithm
+            )));
+        }
+        if metadata
+            .get(TAG_LENGTH_METADATA)
+            .is_some_and(|length| length != AES_GCM_TAG_LENGTH)
+        {
+            return Err(envelope_error("unsupported tag length".to_string()));
+        }
+
+        // The material description is the encryption context of the data key
+        let encryption_context: HashMap<String, String> =
+            match metadata.get(MATERIAL_DESCRIPTION_METADATA) {
+                Some(description) => serde_json::from_str(description)
+                    .map_err(|e| envelope_error(format!("invalid material description: {}", e)))?,
+                None => HashMap::new(),
+            };
+        let iv = decode(IV_METADATA)?;
+        if iv.len() != 12 {
+            return Err(envelope_error(format!("invalid IV length {}", iv.len())));
+        }
+        let mut data_key = self
+            .kms_decrypt(name, decode(ENCRYPTED_KEY_METADATA)?, encryption_context)
+            .await?;
+
+        // The authentication tag is appended to the ciphertext
+        let plaintext = match data_key.len() {
+            32 => crypto::aes_256_gcm_open(&data_key, &iv, body)
+                .ok_or_else(|| envelope_error("authentication failed".to_string())),
+            _ => Err(envelope_error("invalid data key length".to_string())),
+        };
+        wipe(&mut data_key);
+        plaintext
+    }
+
+    async fn kms_decrypt(
+        &self,
+        name: &str,
+        ciphertext: Vec<u8>,
+        encryption_context: HashMap<String, String>,
+    ) -> Result<Vec<u8>> {
+        let response = self
+            .kms
+            .decrypt()
+            .ciphertext_blob(Blob::new(ciphertext))
+            .set_encryption_context((!encryption_context.is_empty()).then_some(encryption_context))
+            .send()
+            .await
+            .map_err(|e| {
+                SecretsProviderError::ProviderFailed(format!(
+                    "KMS was unable to decrypt secret {}: {}",
+                    name,
+                    DisplayErrorContext(e)
+                ))
+            })?;
+
+        response.plaintext.map(Blob::into_inner).ok_or_else(|| {
+            SecretsProviderError::ProviderFailed(format!(
+                "KMS returned no plaintext for secret {}",
+                name
+            ))
+        })
+    }
+}
+
+fn s3_error(name: &str, e: impl std::fmt::Display) -> SecretsProviderError {
+    SecretsProviderError::ProviderFailed(format!("Unable to read secret {} from S3: {}", name, e))
+}
+
+impl std::fmt::Debug for S3KmsSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("S3KmsSecretsProvider")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for S3KmsSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for S3KmsSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version)).await
+    }
+
+    /// Uses a HeadObject request, so neither the object nor KMS are read.
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        if secret_name.is_empty() {
+            return Err(SecretsProviderError::InvalidName(
+                "S3 object keys can not be empty".to_string(),
+            ));
+        }
+
+        match self
+            .s3
+            .head_object()
+            .bucket(&self.bucket)
+            .key(format!("{}{}", self.prefix, secret_name))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(SdkError::ServiceError(e)) => match e.err() {
+                HeadObjectError::NotFound(_) => Ok(false),
+                other => Err(s3_error(secret_name, DisplayErrorContext(other))),
+            },
+            Err(other) => Err(s3_error(secret_name, DisplayErrorContext(other))),
+        }
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("head_bucket", async {
+                self.s3
+                    .head_bucket()
+                    .bucket(&self.bucket)
+                    .send()
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| DisplayErrorContext(e).to_string())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
@@ -0,0 +1,279 @@
+//! Sealed secrets Secret Provider implementation.
+//!
+//! A lightweight take on Bitnami sealed secrets, without Kubernetes: secret values are encrypted
+//! ("sealed") with the public key of the deployment, an [age](https://age-encryption.org)
+//! recipient, so the sealed files can be committed to git. Only the deployment, holding the
+//! matching identity, can unseal them.
+//!
+//! Each secret is an armored age file under a root directory, named after the secret with a
+//! `.age` extension:
+//!
+//! ```text
+//! sealed
+//! ├── database
+//! │   └── password.age
+//! └── tls-key.age
+//! ```
+//!
+//! Secrets are sealed with [seal] or [seal_to_file], and unsealed on every read by
+//! [SealedSecretsProvider]. Only X25519 identities and recipients (`AGE-SECRET-KEY-1...` and
+//! `age1...`) are supported.
+//!
+//! Sealed values do not say whether they hold a string or a binary secret, so the content is
+//! returned as the requested type: [String](std::string::String) if it is valid UTF-8, or
+//! [Vec<u8>](std::vec::Vec) for any content. Secrets have a single version, derived from the
+//! sealed file's modification time.
+use std::fmt::Debug;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ::age::armor::{ArmoredReader, ArmoredWriter, Format};
+use ::age::x25519::{Identity, Recipient};
+use ::age::{Decryptor, Encryptor};
+use async_trait::async_trait;
+
+use super::age::AgeIdentitySource;
+use super::{file_version, secret_file_path};
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Sealed secrets";
+
+/// Extension of sealed secret files.
+const SEALED_EXTENSION: &str = ".age";
+
+/// Seals a secret value for the given recipients, returning an armored age file.
+///
+/// # Arguments
+///
+/// * `value` - Secret value. Strings are sealed as their UTF-8 bytes.
+/// * `recipients` - age recipients able to unseal the value. For example: `age1...`.
+pub fn seal(value: impl AsRef<[u8]>, recipients: &[&str]) -> Result<String> {
+    let recipients = recipients
+        .iter()
+        .map(|r| {
+            Recipient::from_str(r).map_err(|e| {
+                SecretsProviderError::ProviderFailed(format!("Invalid age recipient {}: {}", r, e))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let seal_error =
+        |e: String| SecretsProviderError::ProviderFailed(format!("Unable to seal secret: {}", e));
+    let encryptor = Encryptor::with_recipients(recipients.iter().map(|r| r as _))
+        .map_err(|e| seal_error(e.to_string()))?;
+
+    let mut sealed = Vec::new();
+    let mut writer = ArmoredWriter::wrap_output(&mut sealed, Format::AsciiArmor)
+        .and_then(|armored| encryptor.wrap_output(armored))
+        .map_err(|e| seal_error(e.to_string()))?;
+    writer
+        .write_all(value.as_ref())
+        .and_then(|_| writer.finish())
+        .and_then(|armored| armored.finish())
+        .map_err(|e| seal_error(e.to_string()))?;
+
+    String::from_utf8(sealed).map_err(|e| seal_error(e.to_string()))
+}
+
+/// Seals a secret value and writes it where [SealedSecretsProvider] reads it from. Returns the
+/// path of the sealed file.
+///
+/// # Arguments
+///
+/// * `root` - Directory of the sealed secrets.
+/// * `secret_name` - A string that contains the secret name.
+/// * `value` - Secret value. Strings are sealed as their UTF-8 bytes.
+/// * `recipients` - age recipients able to unseal the value. For example: `age1...`.
+pub fn seal_to_file(
+    root: impl AsRef<Path>,
+    secret_name: &str,
+    value: impl AsRef<[u8]>,
+    recipients: &[&str],
+) -> Result<PathBuf> {
+    let path = sealed_file_path(root.as_ref(), secret_name)?;
+    let sealed = seal(value, recipients)?;
+
+    path.parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, sealed))
+        .map_err(|e| {
+            SecretsProviderError::ProviderFailed(format!(
+                "Unable to write sealed secret {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+    Ok(path)
+}
+
+/// Sealed secrets Secrets Provider implementation.
+pub struct SealedSecretsProvider {
+    root: PathBuf,
+    identities: Vec<Identity>,
+}
+
+impl SealedSecretsProvider {
+    /// Creates a new Secrets Provider unsealing the secrets under a given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory of the sealed secrets.
+    /// * `identity` - Where the identities unsealing the secrets are read from.
+    pub fn new(root: impl Into<PathBuf>, identity: AgeIdentitySource) -> Result<Self> {
+        let identities = identity
+            .read_to_string()?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                Identity::from_str(line).map_err(|e| {
+                    SecretsProviderError::Initialization(format!(
+                        "Invalid age X25519 identity: {}",
+                        e
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if identities.is_empty() {
+            return Err(SecretsProviderError::Initialization(
+                "No age identities found".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            root: root.into(),
+            identities,
+        })
+    }
+
+    /// Directory where the sealed secrets are read from.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Recipients of the identities of the provider, to seal new secrets with.
+    pub fn recipients(&self) -> Vec<String> {
+        self.identities
+            .iter()
+            .map(|i| i.to_public().to_string())
+            .collect()
+    }
+
+    fn read_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let path = sealed_file_path(&self.root, name)?;
+        let read = std::fs::metadata(&path)
+            .and_then(|metadata| file_version(&metadata))
+            .and_then(|version| Ok((std::fs::File::open(&path)?, version)));
+        let (file, current_version) = match read {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(SecretsProviderError::ProviderFailed(format!(
+                    "Unable to read sealed secret {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        if version.is_some_and(|v| v != current_version) {
+            return Ok(None);
+        }
+
+        let unseal_error = |e: String| {
+            SecretsProviderError::ProviderFailed(format!(
+                "Unable to unseal secret {}: {}",
+                path.display(),
+                e
+            ))
+        };
+        let mut content = Vec::new();
+        Decryptor::new(ArmoredReader::new(file))
+            .and_then(|d| d.decrypt(self.identities.iter().map(|i| i as _)))
+            .map_err(|e| unseal_error(e.to_string()))?
+            .read_to_end(&mut content)
+            .map_err(|e| unseal_error(e.to_string()))?;
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, content)?,
+            name: name.to_string(),
+            version: current_version,
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(true),
+        }))
+    }
+}
+
+/// Path of the sealed file of a secret.
+fn sealed_file_path(root: &Path, secret_name: &str) -> Result<PathBuf> {
+    let mut path = secret_file_path(root, secret_name)?.into_os_string();
+    path.push(SEALED_EXTENSION);
+    Ok(path.into())
+}
+
+impl Debug for SealedSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SealedSecretsProvider")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for SealedSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for SealedSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, Some(version))
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("read_root", async {
+                match std::fs::metadata(&self.root) {
+                    Ok(m) if m.is_dir() => Ok(()),
+                    Ok(_) => Err(format!("{} is not a directory", self.root.display())),
+                    Err(e) => Err(format!("Unable to read {}: {}", self.root.display(), e)),
+                }
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
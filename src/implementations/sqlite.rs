@@ -0,0 +1,335 @@
+//! SQLite Secret Provider implementation.
+//!
+//! Secrets are stored in a local SQLite database, one row per version of each secret. Versions
+//! are positive integers, and the current version is the highest one. The database file and its
+//! table are created when the provider is opened, and new versions are written with
+//! [add_secret](crate::implementations::sqlite::SqliteSecretsProvider::add_secret), so desktop and
+//! command line applications can keep secrets offline.
+//!
+//! With the `sqlcipher` feature, SQLite is built with SQLCipher and the whole database file can
+//! be encrypted, either with a passphrase or, with the `keyring` feature too, with a master key
+//! kept in the OS keyring.
+//!
+//! Values are stored as bytes, so secrets are read like files: valid UTF-8 values can be read as
+//! [String](std::string::String), and any value can be read as [Vec<u8>](std::vec::Vec).
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqliteRow};
+use sqlx::Row;
+
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "SQLite";
+
+/// SQLite Secrets Provider implementation.
+#[derive(Clone)]
+pub struct SqliteSecretsProvider {
+    pool: SqlitePool,
+}
+
+impl SqliteSecretsProvider {
+    /// Opens a database of secrets, creating it if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the database file.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::connect(path.as_ref(), None).await
+    }
+
+    /// Opens a database of secrets encrypted with SQLCipher, creating it if it does not exist.
+    ///
+    /// Opening an existing database with another passphrase fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the database file.
+    /// * `passphrase` - Passphrase the encryption key is derived from.
+    #[cfg(feature = "sqlcipher")]
+    pub async fn open_encrypted(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        let key = format!("'{}'", passphrase.replace('\'', "''"));
+        Self::connect(path.as_ref(), Some(key)).await
+    }
+
+    /// Opens a database of secrets encrypted with SQLCipher, using a master key kept in the OS
+    /// keyring. The master key is generated and saved in the keyring the first time.
+    ///
+    /// The keyring store is chosen by the `keyring` crate features of the application (for
+    /// example: `apple-native`, `windows-native` or `sync-secret-service`). Opening fails if the
+    /// store does not keep the master key, as the database could not be read again.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the database file.
+    /// * `service` - Keyring service of the master key. For example: the application name.
+    /// * `user` - Keyring user of the master key.
+    #[cfg(all(feature = "sqlcipher", feature = "keyring"))]
+    pub async fn open_with_keyring(
+        path: impl AsRef<Path>,
+        service: &str,
+        user: &str,
+    ) -> Result<Self> {
+        let master_key = keyring_master_key(service, user)?;
+        // Raw keys skip the key derivation of SQLCipher
+        Self::connect(path.as_ref(), Some(format!("\"x'{}'\"", master_key))).await
+    }
+
+    async fn connect(path: &Path, key: Option<String>) -> Result<Self> {
+        let mut options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        if let Some(key) = key {
+            options = options.pragma("key", key);
+        }
+
+        let pool = SqlitePool::connect_with(options).await.map_err(|e| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to open SQLite database {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        // Creating the table also checks the encryption key of existing databases
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS secrets (
+                name TEXT NOT NULL,
+                version INTEGER NOT NULL CHECK (version > 0),
+                value BLOB NOT NULL,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (name, version)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            SecretsProviderError::Initialization(format!(
+                "Unable to read SQLite database {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self { pool })
+    }
+
+    /// Writes a new version of a secret, and returns it.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `value` - Secret value. Strings are written as their UTF-8 bytes.
+    pub async fn add_secret(&self, secret_name: &str, value: impl AsRef<[u8]>) -> Result<String> {
+        let version: i64 = sqlx::query_scalar(
+            "INSERT INTO secrets (name, version, value)
+            SELECT ?1, COALESCE(MAX(version), 0) + 1, ?2
+            FROM secrets WHERE name = ?1
+            RETURNING version",
+        )
+        .bind(secret_name)
+        .bind(value.as_ref())
+        .fetch_one(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(version.to_string())
+    }
+
+    /// Lists the existing versions of a given secret, the most recent version last.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub async fn list_secret_version_ids(&self, secret_name: &str) -> Result<Vec<String>> {
+        let versions = self.list_secret_versions(secret_name).await?;
+        Ok(versions.into_iter().map(|v| v.id).collect())
+    }
+
+    async fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let version = match version {
+            Some(version) => match version.parse::<i64>().ok().filter(|v| *v > 0) {
+                Some(version) => Some(version),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+
+        let row = sqlx::query(
+            "SELECT name, version, value,
+                version = (SELECT MAX(version) FROM secrets WHERE name = ?1) AS is_current,
+                CAST(strftime('%s', created_at) AS INTEGER)
+            FROM secrets
+            WHERE name = ?1 AND (?2 IS NULL OR version = ?2)
+            ORDER BY version DESC
+            LIMIT 1",
+        )
+        .bind(name)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        row.map(|row| parse_row(&row)).transpose()
+    }
+}
+
+/// Reads the master key from the OS keyring, generating it if there is none.
+#[cfg(all(feature = "sqlcipher", feature = "keyring"))]
+fn keyring_master_key(service: &str, user: &str) -> Result<String> {
+    use rand::RngCore;
+
+    let keyring_error = |e: keyring::Error| {
+        SecretsProviderError::Initialization(format!("Unable to read the OS keyring: {}", e))
+    };
+
+    let entry = keyring::Entry::new(service, user).map_err(keyring_error)?;
+    match entry.get_password() {
+        Ok(master_key) => return Ok(master_key),
+        Err(keyring::Error::NoEntry) => (),
+        Err(e) => return Err(keyring_error(e)),
+    }
+
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    let master_key: String = key.iter().map(|b| format!("{:02x}", b)).collect();
+    entry.set_password(&master_key).map_err(keyring_error)?;
+
+    // A store that does not keep the key (such as the mock one, used when no store was selected)
+    // would make the database unreadable once closed
+    let saved = keyring::Entry::new(service, user)
+        .and_then(|entry| entry.get_password())
+        .ok();
+    if saved.as_deref() != Some(master_key.as_str()) {
+        return Err(SecretsProviderError::Initialization(
+            "The OS keyring did not keep the master key, enable a persistent keyring store"
+                .to_string(),
+        ));
+    }
+
+    Ok(master_key)
+}
+
+/// Creates a secret from a `name, version, value, is_current, created_at` row. The creation
+/// time is in seconds since the epoch.
+fn parse_row<T: Decode>(row: &SqliteRow) -> Result<Secret<T>> {
+    let name: String = row.try_get(0).map_err(request_error)?;
+    let version: i64 = row.try_get(1).map_err(request_error)?;
+    let value: Vec<u8> = row.try_get(2).map_err(request_error)?;
+    let is_current: bool = row.try_get(3).map_err(request_error)?;
+    let created_at: Option<i64> = row.try_get(4).map_err(request_error)?;
+
+    Ok(Secret {
+        secret: T::decode_raw(&name, value)?,
+        name,
+        version: version.to_string(),
+        version_stages: Vec::new(),
+        tags: None,
+        created_at: created_at
+            .and_then(|secs| u64::try_from(secs).ok())
+            .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+        last_rotated_at: None,
+        metadata: None,
+        backend: BACKEND,
+        is_current: Some(is_current),
+    })
+}
+
+fn request_error(e: sqlx::Error) -> SecretsProviderError {
+    SecretsProviderError::ProviderFailed(format!("SQLite request failed: {}", e))
+}
+
+impl std::fmt::Debug for SqliteSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The connect options are not shown, as they may contain the encryption key
+        f.debug_struct("SqliteSecretsProvider")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for SqliteSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities {
+        versions: true,
+        ..Capabilities::NONE
+    };
+}
+
+#[async_trait]
+impl SecretsProvider for SqliteSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version)).await
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM secrets WHERE name = ?1)")
+            .bind(secret_name)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(request_error)
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        let versions: Vec<(i64, Option<i64>)> = sqlx::query_as(
+            "SELECT version, CAST(strftime('%s', created_at) AS INTEGER)
+            FROM secrets
+            WHERE name = ?1
+            ORDER BY version",
+        )
+        .bind(secret_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(request_error)?;
+
+        Ok(VersionInfo::last_current(versions.into_iter().map(
+            |(version, created_at)| {
+                (
+                    version.to_string(),
+                    created_at
+                        .and_then(|secs| u64::try_from(secs).ok())
+                        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+                )
+            },
+        )))
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("read_table", async {
+                sqlx::query("SELECT 1 FROM secrets LIMIT 1")
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
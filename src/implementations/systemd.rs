@@ -0,0 +1,165 @@
+//! systemd credentials Secret Provider implementation.
+//!
+//! systemd passes the credentials of a service (`LoadCredential=`, `LoadCredentialEncrypted=`,
+//! `SetCredential=`...) as files in a private directory, whose path is in the
+//! `$CREDENTIALS_DIRECTORY` environment variable. Each secret is a credential, and the secret name
+//! is the credential name. Encrypted credentials are decrypted by systemd before the service
+//! starts, so they are read like any other.
+//!
+//! Credential names are file names, so names containing `/` are rejected.
+//!
+//! Credentials do not say whether they hold a string or a binary secret, so the content is
+//! returned as the requested type: [String](std::string::String) if the file is valid UTF-8, or
+//! [Vec<u8>](std::vec::Vec) for any content. They have a single version, derived from the file's
+//! modification time.
+//!
+//! For more information:
+//! `<https://systemd.io/CREDENTIALS/>`
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use super::{file_version, secret_file_path};
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "systemd credentials";
+
+/// Environment variable holding the credentials directory of the service.
+pub const CREDENTIALS_DIRECTORY_VAR: &str = "CREDENTIALS_DIRECTORY";
+
+/// systemd credentials Secrets Provider implementation.
+#[derive(Clone, Debug)]
+pub struct SystemdCredentialsProvider {
+    directory: PathBuf,
+}
+
+impl SystemdCredentialsProvider {
+    /// Creates a new Secrets Provider reading the credentials of the running service, from the
+    /// directory in `$CREDENTIALS_DIRECTORY`.
+    ///
+    /// Fails if the variable is not set, which happens when the service has no credentials or
+    /// does not run under systemd.
+    pub fn from_env() -> Result<Self> {
+        match std::env::var_os(CREDENTIALS_DIRECTORY_VAR) {
+            Some(directory) if !directory.is_empty() => Ok(Self::new(directory)),
+            _ => Err(SecretsProviderError::Initialization(format!(
+                "{} is not set, the service has no systemd credentials",
+                CREDENTIALS_DIRECTORY_VAR
+            ))),
+        }
+    }
+
+    /// Creates a new Secrets Provider reading the credentials under a given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - Credentials directory. For example: `/run/credentials/app.service`.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    /// Directory where the credentials are read from.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn read_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        if name.contains('/') {
+            return Err(SecretsProviderError::InvalidName(format!(
+                r#""{}" is not a valid systemd credential name: it contains '/'"#,
+                name
+            )));
+        }
+
+        let path = secret_file_path(&self.directory, name)?;
+        let read = std::fs::metadata(&path)
+            .and_then(|metadata| file_version(&metadata))
+            .and_then(|version| Ok((std::fs::read(&path)?, version)));
+        let (content, current_version) = match read {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(SecretsProviderError::ProviderFailed(format!(
+                    "Unable to read credential file {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        if version.is_some_and(|v| v != current_version) {
+            return Ok(None);
+        }
+
+        Ok(Some(Secret {
+            secret: T::decode_raw(name, content)?,
+            name: name.to_string(),
+            version: current_version,
+            version_stages: Vec::new(),
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+            backend: BACKEND,
+            is_current: Some(true),
+        }))
+    }
+}
+
+impl ProviderCapabilities for SystemdCredentialsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for SystemdCredentialsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.read_secret(key_name, Some(version))
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("read_directory", async {
+                match std::fs::metadata(&self.directory) {
+                    Ok(m) if m.is_dir() => Ok(()),
+                    Ok(_) => Err(format!("{} is not a directory", self.directory.display())),
+                    Err(e) => Err(format!(
+                        "Unable to read {}: {}",
+                        self.directory.display(),
+                        e
+                    )),
+                }
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
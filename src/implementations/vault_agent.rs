@@ -0,0 +1,426 @@
+//! Vault Agent rendered files Secret Provider implementation.
+//!
+//! In sidecar deployments, [Vault Agent](https://developer.hashicorp.com/vault/docs/agent-and-proxy/agent)
+//! or [consul-template](https://github.com/hashicorp/consul-template) fetch the secrets and render
+//! them to files shared with the application. This implementation reads those files, so the
+//! application code keeps using the [SecretsProvider] trait instead of parsing them. Each
+//! template destination is declared as a [RenderedFile]:
+//!
+//! - [RenderedFile::value]: the whole file is the value of a single secret, for templates like
+//!   `{{ with secret "secret/data/db" }}{{ .Data.data.password }}{{ end }}`.
+//! - [RenderedFile::document]: the file is a JSON or YAML document mapping secret names to values,
+//!   in the format of the [secrets file implementation](crate::implementations::file).
+//!
+//! The files are loaded when the provider is created, and reloaded when the agent renders them
+//! again: their directories are watched for file system events (inotify on Linux), so renewed or
+//! rotated secrets are picked up without restarting the service. If a rendered file can not be
+//! read or parsed, the previously loaded secrets are kept.
+//!
+//! Changes are published to the receivers created with
+//! [subscribe](VaultAgentSecretsProvider::subscribe), following the semantics of the
+//! [watch](crate::watch) module.
+//!
+//! Secrets have a single version, derived from the rendered file's modification time. Values of
+//! [value](RenderedFile::value) files are returned as the requested type:
+//! [String](std::string::String) if they are valid UTF-8, or [Vec<u8>](std::vec::Vec) for any
+//! content.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use async_trait::async_trait;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::file::{parse_secrets, FileFormat, FileSecretType};
+use super::file_version;
+use crate::capabilities::{Capabilities, ProviderCapabilities};
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret};
+use crate::self_test::{SelfTestCheck, SelfTestReport};
+use crate::watch::{SecretUpdate, UpdateReceiver, UpdateSender};
+use crate::{canary_check, Result, SecretsProvider};
+
+/// Backend of the versions returned by the provider.
+const BACKEND: &str = "Vault Agent";
+
+/// A file rendered by Vault Agent or consul-template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedFile {
+    path: PathBuf,
+    content: RenderedContent,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum RenderedContent {
+    Value(String),
+    Document(FileFormat),
+}
+
+impl RenderedFile {
+    /// A file holding the value of a single secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - Name of the secret.
+    /// * `path` - Destination of the template.
+    pub fn value(secret_name: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            content: RenderedContent::Value(secret_name.into()),
+        }
+    }
+
+    /// A JSON or YAML document mapping secret names to values. Values prefixed with `binary:`
+    /// are base64-encoded binary secrets.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Destination of the template.
+    /// * `format` - Format of the rendered document.
+    pub fn document(path: impl Into<PathBuf>, format: FileFormat) -> Self {
+        Self {
+            path: path.into(),
+            content: RenderedContent::Document(format),
+        }
+    }
+
+    /// Destination of the template.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Secrets loaded from a rendered file.
+#[derive(PartialEq)]
+enum LoadedSecrets {
+    Value(Vec<u8>),
+    Document(HashMap<String, FileSecretType>),
+}
+
+struct LoadedFile {
+    version: String,
+    secrets: LoadedSecrets,
+}
+
+impl LoadedFile {
+    /// Names of the secrets of the file.
+    fn names<'a>(&'a self, file: &'a RenderedFile) -> Vec<&'a str> {
+        match (&file.content, &self.secrets) {
+            (RenderedContent::Value(name), _) => vec![name.as_str()],
+            (_, LoadedSecrets::Document(secrets)) => secrets.keys().map(String::as_str).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Rendered files and their loaded secrets, in the same order.
+struct State {
+    files: Vec<RenderedFile>,
+    loaded: RwLock<Vec<LoadedFile>>,
+    updates: UpdateSender,
+}
+
+/// Vault Agent rendered files Secrets Provider implementation.
+pub struct VaultAgentSecretsProvider {
+    state: Arc<State>,
+
+    // Watches the directories of the rendered files while the provider is alive
+    _watcher: RecommendedWatcher,
+}
+
+impl VaultAgentSecretsProvider {
+    /// Creates a new Secrets Provider reading the given rendered files, and starts watching them.
+    ///
+    /// Fails with an [Initialization](crate::SecretsProviderError::Initialization) error if a
+    /// file can not be read or parsed (the agent must have rendered them before the service
+    /// starts), or if a secret is declared by more than one file.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - Template destinations.
+    pub fn new(files: Vec<RenderedFile>) -> Result<Self> {
+        let init_error = |e: SecretsProviderError| match e {
+            SecretsProviderError::ProviderFailed(e) => SecretsProviderError::Initialization(e),
+            other => other,
+        };
+
+        // Events report absolute paths, so the files are matched by their canonical paths
+        let files = files
+            .into_iter()
+            .map(|file| {
+                Ok(RenderedFile {
+                    path: canonical_path(&file.path)?,
+                    ..file
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let loaded = files
+            .iter()
+            .map(load_file)
+            .collect::<Result<Vec<_>>>()
+            .map_err(init_error)?;
+        check_duplicates(&files, &loaded)?;
+
+        let state = Arc::new(State {
+            files,
+            loaded: RwLock::new(loaded),
+            updates: UpdateSender::new(),
+        });
+
+        let watched = Arc::clone(&state);
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                watched.reload(&event.paths);
+            }
+        })
+        .map_err(|e| {
+            SecretsProviderError::Initialization(format!("Unable to watch rendered files: {}", e))
+        })?;
+
+        // Agents replace rendered files with a rename, so directories are watched instead of files
+        let mut directories: Vec<&Path> =
+            state.files.iter().filter_map(|f| f.path.parent()).collect();
+        directories.sort();
+        directories.dedup();
+        for directory in directories {
+            watcher
+                .watch(directory, RecursiveMode::NonRecursive)
+                .map_err(|e| {
+                    SecretsProviderError::Initialization(format!(
+                        "Unable to watch directory {}: {}",
+                        directory.display(),
+                        e
+                    ))
+                })?;
+        }
+
+        Ok(Self {
+            state,
+            _watcher: watcher,
+        })
+    }
+
+    /// Rendered files read by the provider.
+    pub fn files(&self) -> &[RenderedFile] {
+        &self.state.files
+    }
+
+    /// Creates a receiver of the changes of the secrets, published when rendered files are
+    /// reloaded with a new version. Secrets removed from a rendered document are published with
+    /// no version.
+    pub fn subscribe(&self) -> UpdateReceiver {
+        self.state.updates.subscribe()
+    }
+
+    fn find_secret<T: Decode>(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<Secret<T>>> {
+        let loaded = self.state.read_loaded();
+        let found = self
+            .state
+            .files
+            .iter()
+            .zip(loaded.iter())
+            .find_map(|(file, loaded)| match (&file.content, &loaded.secrets) {
+                (RenderedContent::Value(n), LoadedSecrets::Value(value)) if n == name => {
+                    Some((loaded, T::decode_raw(name, value.clone())))
+                }
+                (RenderedContent::Document(_), LoadedSecrets::Document(secrets)) => {
+                    secrets.get(name).map(|s| (loaded, s.decode(name)))
+                }
+                _ => None,
+            });
+
+        match found {
+            Some((loaded, _)) if version.is_some_and(|v| v != loaded.version) => Ok(None),
+            Some((loaded, secret)) => Ok(Some(Secret {
+                secret: secret?,
+                name: name.to_string(),
+                version: loaded.version.clone(),
+                version_stages: Vec::new(),
+                tags: None,
+                created_at: None,
+                last_rotated_at: None,
+                metadata: None,
+                backend: BACKEND,
+                is_current: Some(true),
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+impl State {
+    fn read_loaded(&self) -> RwLockReadGuard<'_, Vec<LoadedFile>> {
+        // Loaded files are replaced as a whole, so a poisoned lock still holds consistent data
+        self.loaded.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reloads the rendered files among the given paths. Files that can not be loaded keep their
+    /// previous secrets, until they are rendered again.
+    fn reload(&self, paths: &[PathBuf]) {
+        for (index, file) in self.files.iter().enumerate() {
+            if !paths.contains(&file.path) {
+                continue;
+            }
+
+            let Ok(reloaded) = load_file(file) else {
+                continue;
+            };
+
+            let updates = {
+                let mut loaded = self.loaded.write().unwrap_or_else(|e| e.into_inner());
+                let previous = std::mem::replace(&mut loaded[index], reloaded);
+                changes(file, &previous, &loaded[index])
+            };
+            for update in updates {
+                self.updates.send(update);
+            }
+        }
+    }
+}
+
+/// Absolute path of a rendered file, resolving its directory but not the file itself: it may be
+/// a symbolic link replaced on every render.
+fn canonical_path(path: &Path) -> Result<PathBuf> {
+    let canonical = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(file_name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            parent.canonicalize().map(|p| p.join(file_name))
+        }
+        _ => Err(std::io::ErrorKind::InvalidInput.into()),
+    };
+
+    canonical.map_err(|e| {
+        SecretsProviderError::Initialization(format!(
+            "Invalid rendered file path {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Reads and parses a rendered file.
+fn load_file(file: &RenderedFile) -> Result<LoadedFile> {
+    let read_error = |e: std::io::Error| {
+        SecretsProviderError::ProviderFailed(format!(
+            "Unable to read rendered file {}: {}",
+            file.path.display(),
+            e
+        ))
+    };
+    let version = std::fs::metadata(&file.path)
+        .and_then(|m| file_version(&m))
+        .map_err(read_error)?;
+    let content = std::fs::read(&file.path).map_err(read_error)?;
+    let secrets = match file.content {
+        RenderedContent::Value(_) => LoadedSecrets::Value(content),
+        RenderedContent::Document(format) => {
+            LoadedSecrets::Document(parse_secrets(&content, format, &file.path)?)
+        }
+    };
+
+    Ok(LoadedFile { version, secrets })
+}
+
+/// Updates of the secrets of a reloaded file. Modification times may be too coarse to tell
+/// renders apart, so the content is compared too.
+fn changes(file: &RenderedFile, previous: &LoadedFile, reloaded: &LoadedFile) -> Vec<SecretUpdate> {
+    if previous.version == reloaded.version && previous.secrets == reloaded.secrets {
+        return Vec::new();
+    }
+
+    let current = reloaded.names(file);
+    let removed = previous
+        .names(file)
+        .into_iter()
+        .filter(|name| !current.contains(name))
+        .map(|name| SecretUpdate {
+            name: name.to_string(),
+            version: None,
+        });
+
+    current
+        .iter()
+        .map(|name| SecretUpdate {
+            name: name.to_string(),
+            version: Some(reloaded.version.clone()),
+        })
+        .chain(removed)
+        .collect()
+}
+
+/// Checks that every secret is declared by a single file.
+fn check_duplicates(files: &[RenderedFile], loaded: &[LoadedFile]) -> Result<()> {
+    let mut declared: HashMap<&str, &Path> = HashMap::new();
+    for (file, loaded) in files.iter().zip(loaded) {
+        for name in loaded.names(file) {
+            if let Some(other) = declared.insert(name, &file.path) {
+                return Err(SecretsProviderError::Initialization(format!(
+                    "Secret {} is rendered to both {} and {}",
+                    name,
+                    other.display(),
+                    file.path.display()
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Debug for VaultAgentSecretsProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("VaultAgentSecretsProvider")
+            .field("files", &self.state.files)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ProviderCapabilities for VaultAgentSecretsProvider {
+    const CAPABILITIES: Capabilities = Capabilities::NONE;
+}
+
+#[async_trait]
+impl SecretsProvider for VaultAgentSecretsProvider {
+    async fn find<T: Decode>(&self, key_name: &str) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, None)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        key_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find_secret(key_name, Some(version))
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        report.checks.push(
+            SelfTestCheck::run("read_rendered_files", async {
+                self.state
+                    .files
+                    .iter()
+                    .try_for_each(|f| load_file(f).map(|_| ()))
+            })
+            .await,
+        );
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        Some(BACKEND)
+    }
+}
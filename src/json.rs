@@ -0,0 +1,116 @@
+//! Structured secrets stored as JSON documents.
+//!
+//! Many secrets are JSON objects, like the `username` / `password` / `host` documents of AWS
+//! Secrets Manager database credentials. [Json] decodes them into any type implementing
+//! [DeserializeOwned], so they can be requested directly:
+//!
+//! ```rust,ignore
+//! let credentials = secrets_provider
+//!     .find::<Json<DatabaseCredentials>>("database")
+//!     .await?
+//!     .expect("Secret not found")
+//!     .reveal()
+//!     .into_inner();
+//! ```
+//!
+//! Key-value secrets, such as the ones created in the AWS console, can also be decoded into a
+//! `HashMap<String, String>` without declaring a type for them.
+//!
+//! Callers only needing one field of a document can fetch it with
+//! [find_field](crate::SecretsProvider::find_field) instead.
+//!
+//! Both string and binary secrets are decoded, binary ones as UTF-8 encoded JSON.
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use serde::de::DeserializeOwned;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, wipe_shared, Decode, SecretData};
+use crate::Result;
+
+/// Secret value deserialized from a JSON document.
+pub struct Json<T> {
+    value: T,
+}
+
+impl<T> Json<T> {
+    /// Returns the deserialized value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: DeserializeOwned + Send> Decode for Json<T> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        Ok(Self {
+            value: from_json(secret_name, secret_data)?,
+        })
+    }
+}
+
+/// Key-value secret: a JSON object with string values, like `{"username": "admin"}`.
+impl Decode for HashMap<String, String> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        from_json(secret_name, secret_data)
+    }
+}
+
+/// Extracts the field of a JSON secret at the given JSON pointer.
+pub(crate) fn field<T: DeserializeOwned>(
+    secret_name: &str,
+    secret_data: SecretData,
+    json_pointer: &str,
+) -> Result<T> {
+    let mut document: serde_json::Value = from_json(secret_name, secret_data)?;
+    let value = document
+        .pointer_mut(json_pointer)
+        .map(serde_json::Value::take)
+        .ok_or_else(|| {
+            SecretsProviderError::InvalidType(format!(
+                "{} (no field at {})",
+                secret_name, json_pointer
+            ))
+        })?;
+
+    // As for whole documents, the invalid value is not part of the error
+    serde_json::from_value(value).map_err(|_| {
+        SecretsProviderError::InvalidType(format!(
+            "{} (invalid field at {})",
+            secret_name, json_pointer
+        ))
+    })
+}
+
+fn from_json<T: DeserializeOwned>(secret_name: &str, secret_data: SecretData) -> Result<T> {
+    let value = match secret_data {
+        SecretData::Str(mut s) => {
+            let value = serde_json::from_str(&s);
+            wipe(&mut s);
+            value
+        }
+        SecretData::Bytes(mut b) => {
+            let value = serde_json::from_slice(&b);
+            wipe_shared(&mut b);
+            value
+        }
+    };
+
+    // serde_json messages may quote the invalid value, so only its position is reported
+    value.map_err(|e| {
+        SecretsProviderError::InvalidType(format!(
+            "{} (invalid JSON document at line {}, column {})",
+            secret_name,
+            e.line(),
+            e.column()
+        ))
+    })
+}
@@ -0,0 +1,343 @@
+//! Rotating signing keys.
+//!
+//! Services signing tokens or webhooks with a key stored as a secret need every key a live
+//! signature may have been made with: the current one, and the previous ones until their
+//! signatures expire. A [KeySet] keeps the current version of the secret and a bounded number of
+//! previous ones. It signs with the current key and verifies against all of them.
+//!
+//! Previous keys are the versions the key set replaced when it was refreshed, and the ones added
+//! with [add_previous_version](KeySet::add_previous_version) (for example, versions listed in the
+//! configuration of the service, so they are accepted right after a restart).
+//!
+//! A key set is refreshed explicitly with [refresh](KeySet::refresh), or when a signature does
+//! not verify with [verify_or_refresh](KeySet::verify_or_refresh): another instance may already
+//! sign with a rotated key. Refreshes triggered by signatures are rate limited, so invalid
+//! signatures can not flood the backend with requests.
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+use crate::crypto;
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, SecretData};
+use crate::{Result, SecretsProvider};
+
+/// Previous keys kept, unless another limit is set.
+const DEFAULT_MAX_PREVIOUS: usize = 1;
+
+/// Minimum time between refreshes triggered by signatures, unless another one is set.
+const DEFAULT_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Key of a [KeySet], decoded from a version of a secret.
+pub trait SigningKey: Decode + Send + Sync {
+    /// Signs a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Signed message.
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+
+    /// Returns `true` if the signature of the message was made with this key. Implementations
+    /// must compare signatures in constant time.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Signed message.
+    /// * `signature` - Signature to check.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// HMAC-SHA256 key. The key is the value of the secret: the UTF-8 bytes of a string secret, or
+/// the bytes of a binary one.
+pub struct HmacSha256Key(Vec<u8>);
+
+impl Decode for HmacSha256Key {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        let key = match secret_data {
+            SecretData::Str(s) => s.into_bytes(),
+            SecretData::Bytes(b) => b.into(),
+        };
+        if key.is_empty() {
+            return Err(SecretsProviderError::InvalidType(format!(
+                "{}: HMAC keys can not be empty",
+                secret_name
+            )));
+        }
+
+        Ok(Self(key))
+    }
+}
+
+impl SigningKey for HmacSha256Key {
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        crypto::hmac_sha256(&self.0, message)
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        crypto::verify_hmac_sha256(&self.0, message, signature)
+    }
+}
+
+impl std::fmt::Debug for HmacSha256Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HmacSha256Key").finish_non_exhaustive()
+    }
+}
+
+/// Signature made by a [KeySet].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    /// Version of the secret the signing key was read from.
+    pub key_version: String,
+
+    /// Signature bytes.
+    pub bytes: Vec<u8>,
+}
+
+/// Key read from a version of the secret.
+struct VersionedKey<T> {
+    version: String,
+    key: T,
+}
+
+/// Current key, and previous keys from the most recent to the oldest.
+struct Keys<T> {
+    current: VersionedKey<T>,
+    previous: VecDeque<VersionedKey<T>>,
+}
+
+/// Current and previous signing keys of a secret.
+pub struct KeySet<T, P> {
+    provider: P,
+    secret_name: String,
+    max_previous: usize,
+    min_refresh_interval: Duration,
+    keys: RwLock<Keys<T>>,
+    last_refresh: Mutex<Instant>,
+}
+
+impl<T: SigningKey, P: SecretsProvider + Sync> KeySet<T, P> {
+    /// Loads the current key of a secret. It keeps one previous key and refreshes at most once
+    /// a minute on unknown signatures, until configured.
+    ///
+    /// Fails with a [ProviderFailed](SecretsProviderError::ProviderFailed) error if the secret
+    /// does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Secrets Provider the keys are read from.
+    /// * `secret_name` - A string that contains the secret name.
+    pub async fn load(provider: P, secret_name: &str) -> Result<Self> {
+        let current = find_current(&provider, secret_name).await?;
+
+        Ok(Self {
+            provider,
+            secret_name: secret_name.to_string(),
+            max_previous: DEFAULT_MAX_PREVIOUS,
+            min_refresh_interval: DEFAULT_MIN_REFRESH_INTERVAL,
+            keys: RwLock::new(Keys {
+                current,
+                previous: VecDeque::new(),
+            }),
+            last_refresh: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Sets the number of previous keys kept. The oldest ones are dropped first.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_previous` - Maximum number of previous keys.
+    pub fn with_max_previous(mut self, max_previous: usize) -> Self {
+        self.max_previous = max_previous;
+        let keys = self.keys.get_mut().unwrap_or_else(|e| e.into_inner());
+        keys.previous.truncate(max_previous);
+        self
+    }
+
+    /// Sets the minimum time between refreshes triggered by
+    /// [verify_or_refresh](Self::verify_or_refresh).
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Minimum time between refreshes.
+    pub fn with_min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    /// Secrets Provider the keys are read from.
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Name of the secret the keys are read from.
+    pub fn secret_name(&self) -> &str {
+        &self.secret_name
+    }
+
+    /// Version of the current key.
+    pub fn current_version(&self) -> String {
+        self.read_keys().current.version.clone()
+    }
+
+    /// Versions of the keys, the current one first and then the previous ones from the most
+    /// recent to the oldest.
+    pub fn versions(&self) -> Vec<String> {
+        let keys = self.read_keys();
+        std::iter::once(&keys.current)
+            .chain(&keys.previous)
+            .map(|k| k.version.clone())
+            .collect()
+    }
+
+    /// Signs a message with the current key.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Signed message.
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let keys = self.read_keys();
+        Signature {
+            key_version: keys.current.version.clone(),
+            bytes: keys.current.key.sign(message),
+        }
+    }
+
+    /// Verifies a signature against every key, the current one first. Returns the version of
+    /// the key it was made with, or `None` if no key matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Signed message.
+    /// * `signature` - Signature bytes.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Option<String> {
+        let keys = self.read_keys();
+        std::iter::once(&keys.current)
+            .chain(&keys.previous)
+            .find(|k| k.key.verify(message, signature))
+            .map(|k| k.version.clone())
+    }
+
+    /// Verifies a signature like [verify](Self::verify). If no key matches, the key set is
+    /// refreshed (unless it was refreshed less than the minimum refresh interval ago) and the
+    /// signature is verified again.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Signed message.
+    /// * `signature` - Signature bytes.
+    pub async fn verify_or_refresh(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<Option<String>> {
+        if let Some(version) = self.verify(message, signature) {
+            return Ok(Some(version));
+        }
+
+        // Concurrent lookups with unknown signatures only trigger one refresh
+        let refresh_due = {
+            let mut last_refresh = self.lock_last_refresh();
+            let due = last_refresh.elapsed() >= self.min_refresh_interval;
+            if due {
+                *last_refresh = Instant::now();
+            }
+            due
+        };
+        if refresh_due && self.refresh().await? {
+            return Ok(self.verify(message, signature));
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the current version of the secret. If it changed, the key becomes the current one
+    /// and the replaced key is kept as a previous one. Returns `true` if the key changed.
+    pub async fn refresh(&self) -> Result<bool> {
+        let latest = find_current(&self.provider, &self.secret_name).await;
+        *self.lock_last_refresh() = Instant::now();
+        let latest = latest?;
+
+        let mut keys = self.write_keys();
+        if latest.version == keys.current.version {
+            return Ok(false);
+        }
+
+        // A rollback to a previous key makes it current again
+        keys.previous.retain(|k| k.version != latest.version);
+        let replaced = std::mem::replace(&mut keys.current, latest);
+        keys.previous.push_front(replaced);
+        keys.previous.truncate(self.max_previous);
+        Ok(true)
+    }
+
+    /// Adds a version of the secret to the previous keys, as the oldest one, if it is not already
+    /// in the key set. It is not added if the key set already keeps the maximum number of
+    /// previous keys. Returns `false` if the version does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - Version of the secret.
+    pub async fn add_previous_version(&self, version: &str) -> Result<bool> {
+        if self.versions().iter().any(|v| v == version) {
+            return Ok(true);
+        }
+
+        let Some(secret) = self
+            .provider
+            .find_with_version::<T>(&self.secret_name, version)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        let mut keys = self.write_keys();
+        if keys.previous.len() < self.max_previous {
+            keys.previous.push_back(VersionedKey {
+                version: secret.version,
+                key: secret.secret,
+            });
+        }
+        Ok(true)
+    }
+
+    fn read_keys(&self) -> RwLockReadGuard<'_, Keys<T>> {
+        // Keys are replaced in a single step, so a poisoned lock still holds consistent data
+        self.keys.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_keys(&self) -> RwLockWriteGuard<'_, Keys<T>> {
+        self.keys.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lock_last_refresh(&self) -> MutexGuard<'_, Instant> {
+        self.last_refresh.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T, P> std::fmt::Debug for KeySet<T, P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KeySet")
+            .field("secret_name", &self.secret_name)
+            .field("max_previous", &self.max_previous)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Reads the current key of a secret.
+async fn find_current<T: SigningKey, P: SecretsProvider + Sync>(
+    provider: &P,
+    secret_name: &str,
+) -> Result<VersionedKey<T>> {
+    match provider.find::<T>(secret_name).await? {
+        Some(secret) => Ok(VersionedKey {
+            version: secret.version,
+            key: secret.secret,
+        }),
+        None => Err(SecretsProviderError::ProviderFailed(format!(
+            "Signing key {} not found",
+            secret_name
+        ))),
+    }
+}
@@ -11,18 +11,104 @@
 //! This means that you have to explicitly type the function
 //! [get_secret](crate::SecretsProvider::get_secret) with turbofish (`::<T>`) or use it
 //! in a context where the type can be inferred.
+//!
+//! ## Cancellation
+//! Nothing takes a cancellation token: dropping a future, a stream or a receiver cancels it.
+//!
+//! Every future returned by a [SecretsProvider] can be dropped at any `.await` point (for
+//! example by `tokio::time::timeout` or a losing `tokio::select!` branch) without leaving the
+//! provider in an inconsistent state. Implementations only update their shared state (cached
+//! tokens, reloaded files, versions seen by wrappers) synchronously, after the awaited request
+//! completed, so a dropped lookup either had no effect or a complete one. The exception is a
+//! [CallerProvider](crate::quota::CallerProvider), which counts a lookup against the quota of its
+//! caller before it starts: a dropped lookup stays counted, as it may have reached the backend
+//! already. Dropping a [batch_find](crate::SecretsProvider::batch_find) discards the secrets
+//! retrieved so far, and the request already sent to the backend may still complete on the
+//! backend side, which is harmless for reads.
+//!
+//! Streams returned by [list_secrets_stream](crate::SecretsProvider::list_secrets_stream) only
+//! request pages while they are polled, so dropping one stops the listing. The polling task of a
+//! `SecretWatcher` stops, and releases its provider, as soon as every receiver of the watched
+//! secret is dropped.
+
+// Secrets encrypted with age use X25519 and ChaCha20-Poly1305, and SQLCipher databases are
+// decrypted by the bundled SQLCipher library: none of them can be restricted to FIPS algorithms
+#[cfg(all(
+    feature = "fips",
+    any(feature = "age", feature = "sealed", feature = "sqlcipher")
+))]
+compile_error!("The fips feature can not be enabled with the age, sealed or sqlcipher features");
+
+#[cfg(all(feature = "broker", unix))]
+pub mod broker;
+pub mod capabilities;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(any(
+    feature = "keyset",
+    feature = "aws-s3-kms",
+    feature = "aws-dynamodb-kms"
+))]
+mod crypto;
+mod dynamic;
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+pub mod encoded;
+pub mod encoding;
+pub mod error_mapping;
 mod errors;
+#[cfg(feature = "eso")]
+pub mod eso;
+mod find;
 pub mod implementations;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "keyset")]
+pub mod keyset;
+mod list;
+pub mod namespace;
+pub mod naming;
+#[cfg(feature = "pem")]
+pub mod pem;
+pub mod promote;
+pub mod quota;
+#[cfg(feature = "race")]
+pub mod race;
+#[cfg(any(feature = "aws-core", feature = "legacy-rusoto-aws"))]
+pub mod resolver;
 mod secret;
+pub mod self_test;
+#[cfg(feature = "testkit")]
+pub mod testkit;
+#[cfg(feature = "rustls")]
+pub mod tls;
+pub mod trace;
+#[cfg(feature = "watch")]
+pub mod watch;
+mod writer;
 
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+pub use dynamic::DynSecretsProvider;
 pub use errors::SecretsProviderError;
-pub use secret::{Decode, Secret};
+pub use find::{DecodeHint, FindOptions};
+use futures_util::stream::{self, Stream, StreamExt, TryStreamExt};
+pub use list::{ListFilter, SecretsPage};
+pub use secret::{
+    Decode, Secret, SecretData, SecretDescription, SecretMetadata, SecretTree, SecretVersion,
+    VersionInfo, VersionStage,
+};
+use self_test::{SelfTestCheck, SelfTestReport};
+use trace::ResolveTrace;
+pub use writer::{DeleteOptions, PutManyReport, PutResult, SecretsWriter, WriteOptions};
 
 type Result<T> = std::result::Result<T, SecretsProviderError>;
 
+/// Lookups run at the same time by the default implementations of
+/// [batch_find](SecretsProvider::batch_find) and
+/// [batch_find_detailed](SecretsProvider::batch_find_detailed).
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
 /// Secrets provider implementations interface.
 #[async_trait]
 pub trait SecretsProvider {
@@ -124,6 +210,285 @@ pub trait SecretsProvider {
         version: &str,
     ) -> Result<Option<Secret<T>>>;
 
+    /// Retrieves a specific version of a secret, tagged with its backend. Unlike
+    /// [find_with_version](Self::find_with_version), versions of another backend are rejected
+    /// with an [InvalidVersion](crate::SecretsProviderError::InvalidVersion) error instead of
+    /// being sent to this one. Versions are only checked by providers that tag them (see
+    /// [backend](Self::backend)).
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn find_with_secret_version<T: Decode>(
+    ///     &self,
+    ///     secret_name: &str,
+    ///     version: &SecretVersion,
+    /// ) -> Result<Option<Secret<T>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `version` - The secret's version to retrieve, as returned by
+    ///   [Secret::secret_version](crate::Secret::secret_version).
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{SecretsProvider, implementations::memory::MemorySecretsProvider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let secret = secrets_provider
+    ///         .find::<String>("master_key_of_everything")
+    ///         .await
+    ///         .expect("There was an error getting the Master Key of Everything")
+    ///         .expect("Secret not found");
+    ///
+    ///     let same_secret = secrets_provider
+    ///         .find_with_secret_version::<String>("master_key_of_everything", &secret.secret_version())
+    ///         .await
+    ///         .expect("There was an error getting the Master Key of Everything");
+    ///     assert!(same_secret.is_some());
+    /// }
+    /// ```
+    async fn find_with_secret_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &SecretVersion,
+    ) -> Result<Option<Secret<T>>> {
+        if let Some(backend) = self.backend().filter(|b| *b != version.backend()) {
+            return Err(SecretsProviderError::InvalidVersion(format!(
+                "version {} can not be used with {}",
+                version, backend
+            )));
+        }
+
+        self.find_with_version(secret_name, version.id()).await
+    }
+
+    /// Retrieves the version of a secret at a given stage of its rotation, instead of a version
+    /// identifier. For example, the previous version of a secret, to accept the credentials of
+    /// both versions while a rotation is in progress.
+    ///
+    /// The default implementation looks up the [Current](VersionStage::Current) stage with
+    /// [find](Self::find), and fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error for the other ones, for
+    /// backends without stages. Implementations with versions but without stages return `None`
+    /// for the stages they never have.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn find_with_stage<T: Decode>(
+    ///     &self,
+    ///     secret_name: &str,
+    ///     stage: &VersionStage,
+    /// ) -> Result<Option<Secret<T>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `stage` - Stage of the version to retrieve.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{SecretsProvider, VersionStage};
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let previous = secrets_provider
+    ///         .find_with_stage::<String>("master_key_of_everything", &VersionStage::Previous)
+    ///         .await
+    ///         .expect("There was an error getting the Master Key of Everything");
+    ///     if let Some(secret) = previous {
+    ///         println!("The previous Master Key of Everything is: {}", secret.reveal());
+    ///     }
+    /// }
+    /// ```
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        match stage {
+            VersionStage::Current => self.find(secret_name).await,
+            _ => Err(SecretsProviderError::Unsupported(format!(
+                "{} can not find {} versions",
+                trace::layer_name::<Self>(),
+                stage
+            ))),
+        }
+    }
+
+    /// Retrieves a secret with lookup options: a version or a stage instead of the current
+    /// version, how the value is decoded, and a timeout. [find](Self::find),
+    /// [find_with_version](Self::find_with_version) and [find_with_stage](Self::find_with_stage)
+    /// are shortcuts for the most common options.
+    ///
+    /// The default implementation looks up the secret with the method matching the options, so
+    /// it supports the same versions and stages as the provider. Timeouts do not depend on the
+    /// async runtime.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn find_with_options<T: Decode>(
+    ///     &self,
+    ///     secret_name: &str,
+    ///     options: &FindOptions,
+    /// ) -> Result<Option<Secret<T>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `options` - Version to retrieve, decoding and timeout of the lookup.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use secrets_provider::{FindOptions, SecretsProvider, VersionStage};
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let options = FindOptions::new()
+    ///         .with_stage(VersionStage::Previous)
+    ///         .with_timeout(Duration::from_secs(2));
+    ///     let previous = secrets_provider
+    ///         .find_with_options::<String>("master_key_of_everything", &options)
+    ///         .await
+    ///         .expect("There was an error getting the Master Key of Everything");
+    ///     if let Some(secret) = previous {
+    ///         println!("The previous Master Key of Everything is: {}", secret.reveal());
+    ///     }
+    /// }
+    /// ```
+    async fn find_with_options<T: Decode>(
+        &self,
+        secret_name: &str,
+        options: &FindOptions,
+    ) -> Result<Option<Secret<T>>> {
+        find::find_with_options(self, secret_name, options).await
+    }
+
+    /// Checks whether a secret exists, without retrieving its value where the backend allows it.
+    /// Startup validation can check every secret an application needs this way, instead of
+    /// reading and decrypting all of them.
+    ///
+    /// The default implementation retrieves the current version with [find](Self::find).
+    /// Implementations with metadata-only requests (such as DescribeSecret for AWS) override it.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn exists(&self, secret_name: &str) -> Result<bool>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::SecretsProvider;
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     for name in ["database/password", "api/token"] {
+    ///         let exists = secrets_provider
+    ///             .exists(name)
+    ///             .await
+    ///             .expect("There was an error checking the secret");
+    ///         assert!(exists, "Missing secret {}", name);
+    ///     }
+    /// }
+    /// ```
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        Ok(self.find::<SecretData>(secret_name).await?.is_some())
+    }
+
+    /// Retrieves one field of a JSON secret, for callers that do not need the whole document.
+    ///
+    /// Returns `None` if the secret does not exist, and fails with an
+    /// [InvalidType](SecretsProviderError::InvalidType) error if it is not a JSON document, has no
+    /// field at the pointer, or the field is not a valid `T`.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn find_field<T: DeserializeOwned + Send>(
+    ///     &self,
+    ///     secret_name: &str,
+    ///     json_pointer: &str,
+    /// ) -> Result<Option<Secret<T>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `json_pointer` - [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) of the field.
+    ///   For example: `/password` or `/replicas/0/host`.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::SecretsProvider;
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let password = secrets_provider
+    ///         .find_field::<String>("database", "/password")
+    ///         .await
+    ///         .expect("There was an error getting the database password")
+    ///         .expect("Secret not found")
+    ///         .reveal();
+    /// }
+    /// ```
+    #[cfg(feature = "json")]
+    async fn find_field<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        secret_name: &str,
+        json_pointer: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.find::<SecretData>(secret_name)
+            .await?
+            .map(|secret| secret.try_map(|name, data| json::field(name, data, json_pointer)))
+            .transpose()
+    }
+
+    /// Backend of the versions returned by the provider, used to tag them (see [SecretVersion]).
+    /// For example: `AWS Secrets Manager`. Wrappers return the backend of the provider they wrap.
+    ///
+    /// The default implementation returns `None`: versions are not tagged, and
+    /// [find_with_secret_version](Self::find_with_secret_version) accepts any version.
+    fn backend(&self) -> Option<&'static str> {
+        None
+    }
+
     /// Retrieves a group of secrets in a single request from the secrets provider.
     ///
     /// The last version of each secret will be retrieved. This method will try to
@@ -134,9 +499,10 @@ pub trait SecretsProvider {
     /// returned in cases where the complete request fails or for some other reason the
     /// secrets provider is unreacheable.
     ///
-    /// A naive default implementation of this method is provided that simply calls
-    /// `Self::find` in a loop. Implementations may override this implementation with
-    /// a more efficient version if they support it.
+    /// A default implementation of this method is provided that calls `Self::find` for each
+    /// secret, running up to [DEFAULT_BATCH_CONCURRENCY] lookups at the same time (see
+    /// [batch_find_with_concurrency](Self::batch_find_with_concurrency)). Implementations may
+    /// override this implementation with a more efficient version if they support it.
     ///
     /// Note that some implementations may impose a limit on how many secrets can be
     /// retrieved at once. AWS, for example, limits this to 20 secrets.
@@ -185,13 +551,417 @@ pub trait SecretsProvider {
         &self,
         secret_names: &[&'n str],
     ) -> Result<HashMap<&'n str, Secret<T>>> {
+        self.batch_find_with_concurrency(secret_names, DEFAULT_BATCH_CONCURRENCY)
+            .await
+    }
+
+    /// Retrieves a group of secrets like [batch_find](Self::batch_find), calling `Self::find` for
+    /// each secret with a limit on the lookups running at the same time. The first failed lookup
+    /// fails the whole batch, and cancels the lookups still running.
+    ///
+    /// Use it to read many secrets at cold start without exceeding the rate limits of the
+    /// backend. Implementations fetching every secret in a single request ignore the limit.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn batch_find_with_concurrency<T: Decode>(
+    ///     &self,
+    ///     secret_names: &[&str],
+    ///     concurrency: usize,
+    /// ) -> Result<HashMap<&str, Secret<T>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_names` - List of secret names that will be retrieved
+    /// * `concurrency` - Maximum lookups running at the same time. `0` is treated as `1`.
+    async fn batch_find_with_concurrency<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+        concurrency: usize,
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        let mut lookups =
+            stream::iter(batch_lookups(self, secret_names)).buffer_unordered(concurrency.max(1));
+
         let mut retrieved = HashMap::new();
-        for name in secret_names {
-            if let Some(secret) = self.find(name).await? {
-                retrieved.insert(*name, secret);
+        while let Some((name, result)) = lookups.next().await {
+            if let Some(secret) = result? {
+                retrieved.insert(name, secret);
             }
         }
 
         Ok(retrieved)
     }
+
+    /// Retrieves a group of secrets, with the outcome of the lookup of each one.
+    ///
+    /// Unlike [batch_find](Self::batch_find), a failed lookup does not hide the outcome of the
+    /// others: every name maps to the result [find](Self::find) would return for it, so secrets
+    /// that do not exist (`Ok(None)`) can be told apart from denied lookups or secrets of another
+    /// type (`Err`).
+    ///
+    /// The default implementation calls `Self::find` for each secret, running up to
+    /// [DEFAULT_BATCH_CONCURRENCY] lookups at the same time.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn batch_find_detailed<T: Decode>(
+    ///     &self,
+    ///     secret_names: &[&str],
+    /// ) -> HashMap<&str, Result<Option<Secret<T>>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_names` - List of secret names that will be retrieved
+    async fn batch_find_detailed<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> HashMap<&'n str, Result<Option<Secret<T>>>> {
+        stream::iter(batch_lookups(self, secret_names))
+            .buffer_unordered(DEFAULT_BATCH_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Lists the secrets of the provider, one page at a time, sorted as the backend returns them.
+    /// Values are never retrieved: only the names and the metadata the backend returns when
+    /// listing.
+    ///
+    /// The first page is listed without token, and the following ones with the
+    /// `next_page_token` of the previous page, until it is `None`. Tokens are specific to the
+    /// backend and the filter they were returned with.
+    ///
+    /// The default implementation fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error, for backends that can not
+    /// enumerate their secrets.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn list_secrets(
+    ///     &self,
+    ///     filter: &ListFilter,
+    ///     page_token: Option<&str>,
+    /// ) -> Result<SecretsPage>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Secrets to list, and size of the pages.
+    /// * `page_token` - Token of the page to list, or `None` for the first page.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{ListFilter, SecretsProvider};
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let filter = ListFilter {
+    ///         name_prefix: Some("payments/".to_string()),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let mut page_token = None;
+    ///     loop {
+    ///         let page = secrets_provider
+    ///             .list_secrets(&filter, page_token.as_deref())
+    ///             .await
+    ///             .expect("There was an error listing secrets");
+    ///         for secret in page.secrets {
+    ///             println!("{}", secret.name);
+    ///         }
+    ///
+    ///         page_token = page.next_page_token;
+    ///         if page_token.is_none() {
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    async fn list_secrets(
+        &self,
+        _filter: &ListFilter,
+        _page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        Err(SecretsProviderError::Unsupported(format!(
+            "{} can not list secrets",
+            trace::layer_name::<Self>()
+        )))
+    }
+
+    /// Lists the versions of a secret, oldest first, with their stages and creation time. This
+    /// is what rotation tooling needs to tell the current version from the previous and pending
+    /// ones. A missing secret has no versions.
+    ///
+    /// The default implementation fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error, for backends that do not
+    /// keep versions.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{SecretsProvider, VersionStage};
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let versions = secrets_provider
+    ///         .list_secret_versions("secret-name")
+    ///         .await
+    ///         .expect("There was an error listing the versions");
+    ///     for version in versions {
+    ///         if version.stages.contains(&VersionStage::Previous) {
+    ///             println!("Previous version: {}", version.id);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    async fn list_secret_versions(&self, _secret_name: &str) -> Result<Vec<VersionInfo>> {
+        Err(SecretsProviderError::Unsupported(format!(
+            "{} can not list secret versions",
+            trace::layer_name::<Self>()
+        )))
+    }
+
+    /// Lists the secrets of the provider as a stream, requesting the pages with
+    /// [list_secrets](Self::list_secrets) as they are consumed, so operators can enumerate
+    /// thousands of secrets without handling page tokens. The stream ends after the first error.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Secrets to list, and size of the pages requested to the backend.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use futures_util::TryStreamExt;
+    /// use secrets_provider::{ListFilter, SecretsProvider};
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let filter = ListFilter::default();
+    ///
+    ///     let mut secrets = std::pin::pin!(secrets_provider.list_secrets_stream(&filter));
+    ///     while let Some(secret) = secrets
+    ///         .try_next()
+    ///         .await
+    ///         .expect("There was an error listing secrets")
+    ///     {
+    ///         println!("{}", secret.name);
+    ///     }
+    /// }
+    /// ```
+    fn list_secrets_stream<'a>(
+        &'a self,
+        filter: &'a ListFilter,
+    ) -> impl Stream<Item = Result<SecretDescription>> + Send + 'a
+    where
+        Self: Sync,
+    {
+        // The state is the token of the next page, `None` once the last page was listed
+        stream::try_unfold(
+            Some(None),
+            move |page_token: Option<Option<String>>| async move {
+                let Some(page_token) = page_token else {
+                    return Ok(None);
+                };
+
+                let page = self.list_secrets(filter, page_token.as_deref()).await?;
+                Ok(Some((page.secrets, page.next_page_token.map(Some))))
+            },
+        )
+        .map_ok(|secrets| stream::iter(secrets.into_iter().map(Ok)))
+        .try_flatten()
+    }
+
+    /// Retrieves the current version of every secret whose name starts with a prefix, for
+    /// example to load a whole namespace like `payments/prod/` in one call. A trailing `*` is
+    /// ignored, so `payments/prod/*` is the same prefix.
+    ///
+    /// The default implementation lists the matching secrets with
+    /// [list_secrets](Self::list_secrets), then retrieves them with
+    /// [batch_find](Self::batch_find), so it fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error for backends that can not
+    /// enumerate their secrets. Secrets deleted between both steps are left out.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn find_by_prefix<T: Decode>(
+    ///     &self,
+    ///     prefix: &str,
+    /// ) -> Result<HashMap<String, Secret<T>>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Prefix of the secret names.
+    async fn find_by_prefix<T: Decode>(&self, prefix: &str) -> Result<HashMap<String, Secret<T>>> {
+        let filter = ListFilter {
+            name_prefix: Some(prefix.strip_suffix('*').unwrap_or(prefix).to_string()),
+            ..ListFilter::default()
+        };
+
+        let names: Vec<String> = self
+            .list_secrets_stream(&filter)
+            .map_ok(|secret| secret.name)
+            .try_collect()
+            .await?;
+
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        Ok(self
+            .batch_find(&names)
+            .await?
+            .into_iter()
+            .map(|(name, secret)| (name.to_string(), secret))
+            .collect())
+    }
+
+    /// Runs a self-test of the secrets provider, returning a structured report.
+    ///
+    /// Each implementation checks what makes sense for its backend. For example, the AWS
+    /// implementations make a `ListSecrets` call, exercising both the credentials and the
+    /// network path to the service. If a canary secret name is given, it is read as well (its
+    /// value is discarded). This is useful as a preflight check before rolling out a service.
+    ///
+    /// The default implementation only reads the canary secret.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn self_test(&self, canary: Option<&str>) -> SelfTestReport;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `canary` - Name of a secret that must be readable.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{SecretsProvider, implementations::memory::MemorySecretsProvider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let report = secrets_provider.self_test(Some("master_key_of_everything")).await;
+    ///
+    ///     for check in report.failed_checks() {
+    ///         println!("Check {} failed: {:?}", check.name, check.error);
+    ///     }
+    ///     assert!(report.is_healthy());
+    /// }
+    /// ```
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    /// Looks up a secret like [find](Self::find), returning a trace of the lookup instead of the
+    /// secret. The trace lists every layer of the provider stack the lookup went through (for
+    /// example: a [NamingProvider](crate::naming::NamingProvider) and the backend implementation
+    /// it wraps), what each layer did and how long it took, and the outcome of the lookup. The
+    /// secret value is never part of the trace.
+    ///
+    /// This is meant to debug reports of wrong or missing values. Wrappers add their own step
+    /// and delegate to the wrapped provider; the default implementation traces a single
+    /// [find](Self::find) of the implementation.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    ///
+    /// # Example
+    ///
+    /// This example uses the `memory` feature
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{SecretsProvider, implementations::memory::MemorySecretsProvider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///     let trace = secrets_provider.resolve_trace("master_key_of_everything").await;
+    ///
+    ///     for step in &trace.steps {
+    ///         println!("{} took {:?}: {:?}", step.layer, step.duration, step.details);
+    ///     }
+    ///     println!("Outcome: {:?}", trace.outcome);
+    /// }
+    /// ```
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        ResolveTrace::run(
+            trace::layer_name::<Self>(),
+            secret_name,
+            self.find::<secret::SecretData>(secret_name),
+        )
+        .await
+    }
+}
+
+/// Self-test check reading a canary secret. The secret must exist, but it can be of any type.
+pub(crate) async fn canary_check<P: SecretsProvider + Sync + ?Sized>(
+    provider: &P,
+    canary: &str,
+) -> SelfTestCheck {
+    SelfTestCheck::run("find_canary", async {
+        match provider.find::<secret::SecretData>(canary).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(format!("Canary secret {} not found", canary)),
+            Err(e) => Err(e.to_string()),
+        }
+    })
+    .await
+}
+
+/// Lookups of a group of secrets, run by the default implementations of the batch methods.
+fn batch_lookups<'a, 'n, P, T>(
+    provider: &'a P,
+    secret_names: &'a [&'n str],
+) -> Vec<impl std::future::Future<Output = (&'n str, Result<Option<Secret<T>>>)> + Send + 'a>
+where
+    P: SecretsProvider + Sync + ?Sized,
+    T: Decode,
+{
+    secret_names
+        .iter()
+        .map(|name| async move { (*name, provider.find(name).await) })
+        .collect()
 }
@@ -0,0 +1,25 @@
+//! Listing of the secrets of a provider.
+//!
+//! See [SecretsProvider::list_secrets](crate::SecretsProvider::list_secrets).
+use crate::secret::SecretDescription;
+
+/// Secrets listed by [list_secrets](crate::SecretsProvider::list_secrets).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ListFilter {
+    /// Only lists the secrets whose name starts with this prefix.
+    pub name_prefix: Option<String>,
+
+    /// Maximum number of secrets of a page. Backends use their own default if it is not set,
+    /// and may return fewer secrets, even when there are more pages.
+    pub page_size: Option<usize>,
+}
+
+/// Page of secrets returned by [list_secrets](crate::SecretsProvider::list_secrets).
+#[derive(Clone, Debug, Default)]
+pub struct SecretsPage {
+    /// Secrets of the page. Only the metadata the backend returns when listing is set.
+    pub secrets: Vec<SecretDescription>,
+
+    /// Token of the next page, or `None` if this is the last page.
+    pub next_page_token: Option<String>,
+}
@@ -0,0 +1,443 @@
+//! Hierarchical secret namespaces.
+//!
+//! Multi-team estates organize their secrets in projects and folders. A [SecretPath] names a
+//! secret as `project/folder/.../name`, and a [NamespaceLayout] maps it to the backend: AWS
+//! Secrets Manager name prefixes and Vault paths keep the whole path, while backends with a
+//! project per provider (GCP projects, Doppler projects...) only keep the folders and the name.
+//!
+//! A [ScopedProvider] restricts a provider to a [Scope] (a project, or a folder of a project).
+//! Lookups use names relative to the scope, and names that would escape it are rejected with an
+//! [InvalidName](crate::SecretsProviderError::InvalidName) error, so a team given a scoped
+//! provider can only read its own secrets.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::ResolveTrace;
+use crate::{canary_check, ListFilter, Result, SecretsPage, SecretsProvider, VersionStage};
+
+/// Separator of the components of a path.
+const PATH_SEPARATOR: char = '/';
+
+/// Full path of a secret: its project, folders and name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SecretPath {
+    project: String,
+    folders: Vec<String>,
+    name: String,
+}
+
+impl SecretPath {
+    /// Creates the path of a secret at the root of a project.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - Project of the secret.
+    /// * `name` - Name of the secret.
+    pub fn new(project: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            project: project.into(),
+            folders: Vec::new(),
+            name: name.into(),
+        }
+    }
+
+    /// Moves the secret into a folder, under the folders added before.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - Name of the folder.
+    pub fn with_folder(mut self, folder: impl Into<String>) -> Self {
+        self.folders.push(folder.into());
+        self
+    }
+
+    /// Parses a `/` separated path: the first component is the project and the last one the
+    /// name of the secret. For example: `payments/production/database-password`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the secret.
+    pub fn parse(path: &str) -> Result<Self> {
+        let mut components = split_components(path)?;
+        if components.len() < 2 {
+            return Err(SecretsProviderError::InvalidName(format!(
+                "{:?} is not a secret path: a project and a name are required",
+                path
+            )));
+        }
+
+        let name = components.pop().unwrap_or_default();
+        let project = components.remove(0);
+        Ok(Self {
+            project,
+            folders: components,
+            name,
+        })
+    }
+
+    /// Project of the secret.
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    /// Folders of the secret, the outermost first.
+    pub fn folders(&self) -> &[String] {
+        &self.folders
+    }
+
+    /// Name of the secret, without its project and folders.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the secret in a backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - How the backend organizes projects and folders.
+    pub fn backend_name(&self, layout: NamespaceLayout) -> String {
+        let (project, separator) = match layout {
+            NamespaceLayout::Path => (Some(self.project.as_str()), PATH_SEPARATOR),
+            NamespaceLayout::ProjectPerProvider(separator) => (None, separator),
+        };
+
+        project
+            .into_iter()
+            .chain(self.folders.iter().map(String::as_str))
+            .chain(std::iter::once(self.name.as_str()))
+            .collect::<Vec<_>>()
+            .join(&separator.to_string())
+    }
+}
+
+impl Display for SecretPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.backend_name(NamespaceLayout::Path))
+    }
+}
+
+/// How a backend organizes projects and folders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamespaceLayout {
+    /// The project and folders are `/` separated prefixes of the name:
+    /// `payments/production/database-password`. Used for AWS Secrets Manager names and Vault
+    /// paths.
+    Path,
+
+    /// The project is selected when creating the provider (a GCP project, a Doppler project...),
+    /// and the folders and name are joined with the given separator. For example, with `_`:
+    /// `production_database-password`.
+    ProjectPerProvider(char),
+}
+
+/// Part of a namespace: a project, or a folder of a project.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Scope {
+    project: String,
+    folders: Vec<String>,
+}
+
+impl Scope {
+    /// Scope of a whole project.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - Name of the project.
+    pub fn new(project: impl Into<String>) -> Self {
+        Self {
+            project: project.into(),
+            folders: Vec::new(),
+        }
+    }
+
+    /// Narrows the scope to a folder, under the folders added before.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - Name of the folder.
+    pub fn folder(mut self, folder: impl Into<String>) -> Self {
+        self.folders.push(folder.into());
+        self
+    }
+
+    /// Project of the scope.
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    /// Folders of the scope, the outermost first.
+    pub fn folders(&self) -> &[String] {
+        &self.folders
+    }
+
+    /// Returns `true` if the secret is in the scope, or in one of its subfolders.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the secret.
+    pub fn contains(&self, path: &SecretPath) -> bool {
+        path.project == self.project && path.folders.starts_with(&self.folders)
+    }
+
+    /// Prefix of the backend names of the secrets in the scope. For example, the
+    /// `payments/production` scope is `payments/production/` with [NamespaceLayout::Path], and
+    /// `production_` with [NamespaceLayout::ProjectPerProvider]`('_')`.
+    ///
+    /// # Arguments
+    ///
+    /// * `layout` - How the backend organizes projects and folders.
+    pub fn backend_prefix(&self, layout: NamespaceLayout) -> String {
+        let (project, separator) = match layout {
+            NamespaceLayout::Path => (Some(self.project.as_str()), PATH_SEPARATOR),
+            NamespaceLayout::ProjectPerProvider(separator) => (None, separator),
+        };
+
+        project
+            .into_iter()
+            .chain(self.folders.iter().map(String::as_str))
+            .map(|component| format!("{}{}", component, separator))
+            .collect()
+    }
+
+    /// Full path of a secret, from its path relative to the scope. For example, in the
+    /// `payments/production` scope, `database/password` is
+    /// `payments/production/database/password`.
+    ///
+    /// Fails with an [InvalidName](crate::SecretsProviderError::InvalidName) error if the
+    /// relative path has empty, `.` or `..` components.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative_path` - Path of the secret relative to the scope.
+    pub fn resolve(&self, relative_path: &str) -> Result<SecretPath> {
+        let mut components = split_components(relative_path)?;
+        let name = components.pop().unwrap_or_default();
+
+        Ok(SecretPath {
+            project: self.project.clone(),
+            folders: self.folders.iter().cloned().chain(components).collect(),
+            name,
+        })
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.project)?;
+        for folder in &self.folders {
+            write!(f, "{}{}", PATH_SEPARATOR, folder)?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits a path into components, rejecting components that could escape a scope.
+fn split_components(path: &str) -> Result<Vec<String>> {
+    path.split(PATH_SEPARATOR)
+        .map(|component| match component {
+            "" | "." | ".." => Err(SecretsProviderError::InvalidName(format!(
+                "{:?} is not a valid secret path: components can not be empty, . or ..",
+                path
+            ))),
+            component => Ok(component.to_string()),
+        })
+        .collect()
+}
+
+/// Secrets Provider wrapper restricting lookups to a [Scope].
+///
+/// Lookups use names relative to the scope, translated to backend names with a
+/// [NamespaceLayout]. Returned secrets keep the relative name they were looked up with.
+/// Listed secrets are the ones in the scope, and their names are relative to it.
+#[derive(Clone, Debug)]
+pub struct ScopedProvider<P> {
+    inner: P,
+    scope: Scope,
+    layout: NamespaceLayout,
+}
+
+impl<P> ScopedProvider<P> {
+    /// Wraps a provider. With [NamespaceLayout::ProjectPerProvider], the wrapped provider must
+    /// be the one of the scope's project.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Wrapped Secrets Provider.
+    /// * `scope` - Scope the lookups are restricted to.
+    /// * `layout` - How the wrapped provider's backend organizes projects and folders.
+    pub fn new(inner: P, scope: Scope, layout: NamespaceLayout) -> Self {
+        Self {
+            inner,
+            scope,
+            layout,
+        }
+    }
+
+    /// Wrapped Secrets Provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Scope the lookups are restricted to.
+    pub fn scope(&self) -> &Scope {
+        &self.scope
+    }
+
+    /// Full path of a secret, from its name relative to the scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - Name of the secret, relative to the scope.
+    pub fn path(&self, secret_name: &str) -> Result<SecretPath> {
+        self.scope.resolve(secret_name)
+    }
+
+    /// Name of a secret in the wrapped provider's backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - Name of the secret, relative to the scope.
+    pub fn backend_name(&self, secret_name: &str) -> Result<String> {
+        Ok(self.path(secret_name)?.backend_name(self.layout))
+    }
+}
+
+/// Restores the relative name of a secret.
+fn with_name<T>(secret: Secret<T>, name: &str) -> Secret<T> {
+    Secret {
+        name: name.to_string(),
+        ..secret
+    }
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> SecretsProvider for ScopedProvider<P> {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        Ok(self
+            .inner
+            .find(&self.backend_name(secret_name)?)
+            .await?
+            .map(|s| with_name(s, secret_name)))
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        Ok(self
+            .inner
+            .find_with_version(&self.backend_name(secret_name)?, version)
+            .await?
+            .map(|s| with_name(s, secret_name)))
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        Ok(self
+            .inner
+            .find_with_stage(&self.backend_name(secret_name)?, stage)
+            .await?
+            .map(|s| with_name(s, secret_name)))
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        self.inner.exists(&self.backend_name(secret_name)?).await
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        self.inner
+            .list_secret_versions(&self.backend_name(secret_name)?)
+            .await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        let translated = secret_names
+            .iter()
+            .map(|name| self.backend_name(name))
+            .collect::<Result<Vec<_>>>()?;
+        let translated_refs: Vec<&str> = translated.iter().map(String::as_str).collect();
+
+        let mut retrieved = self.inner.batch_find::<T>(&translated_refs).await?;
+
+        let mut found = HashMap::new();
+        for (name, translated) in secret_names.iter().zip(&translated) {
+            if let Some(secret) = retrieved.remove(translated.as_str()) {
+                found.insert(*name, with_name(secret, name));
+            }
+        }
+
+        Ok(found)
+    }
+
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        let scope_prefix = self.scope.backend_prefix(self.layout);
+        let scoped_filter = ListFilter {
+            name_prefix: Some(format!(
+                "{}{}",
+                scope_prefix,
+                filter.name_prefix.as_deref().unwrap_or_default()
+            )),
+            ..filter.clone()
+        };
+
+        let mut page = self.inner.list_secrets(&scoped_filter, page_token).await?;
+        page.secrets
+            .retain_mut(|secret| match secret.name.strip_prefix(&scope_prefix) {
+                Some(relative) => {
+                    secret.name = relative.to_string();
+                    true
+                }
+                None => false,
+            });
+
+        Ok(page)
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.inner.backend()
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = self.inner.self_test(None).await;
+        // The canary is a relative name, checked through the scope
+        if let Some(canary) = canary {
+            report.checks.push(canary_check(self, canary).await);
+        }
+
+        report
+    }
+
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        let start = Instant::now();
+        let translated = match self.backend_name(secret_name) {
+            Ok(translated) => translated,
+            Err(e) => {
+                return ResolveTrace::run("ScopedProvider", secret_name, async { Err(e) }).await
+            }
+        };
+
+        self.inner.resolve_trace(&translated).await.wrap(
+            "ScopedProvider",
+            secret_name,
+            vec![format!("Name scoped to {}", translated)],
+            start,
+        )
+    }
+}
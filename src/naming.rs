@@ -0,0 +1,516 @@
+//! Secret naming conventions.
+//!
+//! Backends accept different characters in secret names: AWS allows `/_+=.@-`, environment
+//! variables are usually uppercase with underscores, and Vault secrets are paths. A
+//! [NamingConvention] translates one logical name (for example `database.password`) into the
+//! name expected by a backend (`DATABASE_PASSWORD`, `database/password`...), and
+//! [NamingProvider] applies it to every lookup of a wrapped provider, so the same logical names
+//! can be used with any backend.
+//!
+//! A convention is applied in three steps, after an optional resolver (see
+//! [NamingConvention::with_resolver]) has computed the name from runtime context:
+//!
+//! 1. The name is split into words on `-`, `_`, `/`, `.` and spaces, and the words are joined
+//!    with the separator of the [NameStyle]. [NameStyle::Unchanged] skips this step.
+//! 2. The name is case folded.
+//! 3. Characters the backend does not allow are escaped.
+//!
+//! Backends also check names against [NameRules] before sending any request, so illegal names
+//! fail fast with an [InvalidName](crate::SecretsProviderError::InvalidName) error.
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::ResolveTrace;
+use crate::{Result, SecretsProvider, VersionStage};
+
+/// Characters splitting a logical name into words.
+const WORD_SEPARATORS: &[char] = &['-', '_', '/', '.', ' '];
+
+/// How the words of a name are joined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameStyle {
+    /// The name is not split into words.
+    Unchanged,
+
+    /// Words joined with hyphens: `database-password`.
+    Kebab,
+
+    /// Words joined with underscores: `database_password`.
+    Snake,
+
+    /// Words joined with slashes: `database/password`.
+    Path,
+}
+
+impl NameStyle {
+    fn separator(self) -> Option<char> {
+        match self {
+            NameStyle::Unchanged => None,
+            NameStyle::Kebab => Some('-'),
+            NameStyle::Snake => Some('_'),
+            NameStyle::Path => Some('/'),
+        }
+    }
+}
+
+/// Case folding applied to names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseFolding {
+    Unchanged,
+    Lower,
+    Upper,
+}
+
+/// How characters not allowed by the backend are escaped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Escape {
+    /// Each character is replaced by the given one. Different names may collide.
+    Replace(char),
+
+    /// Each UTF-8 byte of the character is written as two uppercase hex digits after the given
+    /// prefix. For example, with `%`, `é` becomes `%C3%A9`. The prefix should not be an allowed
+    /// character, or different names may collide.
+    Hex(char),
+}
+
+type AllowedChars = Arc<dyn Fn(char) -> bool + Send + Sync>;
+type Resolver = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Translates logical secret names into backend names.
+#[derive(Clone)]
+pub struct NamingConvention {
+    resolver: Option<Resolver>,
+    style: NameStyle,
+    case: CaseFolding,
+    allowed_chars: AllowedChars,
+    escape: Escape,
+}
+
+impl NamingConvention {
+    /// Creates a convention leaving names unchanged, until configured.
+    pub fn new() -> Self {
+        Self {
+            resolver: None,
+            style: NameStyle::Unchanged,
+            case: CaseFolding::Unchanged,
+            allowed_chars: Arc::new(|_| true),
+            escape: Escape::Replace('_'),
+        }
+    }
+
+    /// Convention for AWS Secrets Manager: names are kept, and characters other than ASCII
+    /// letters, digits and `/_+=.@-` are replaced by `-`.
+    pub fn aws() -> Self {
+        Self::new()
+            .with_allowed_chars(|c| c.is_ascii_alphanumeric() || "/_+=.@-".contains(c))
+            .with_escape(Escape::Replace('-'))
+    }
+
+    /// Convention for environment variables: `database.password` becomes `DATABASE_PASSWORD`.
+    pub fn env() -> Self {
+        Self::new()
+            .with_style(NameStyle::Snake)
+            .with_case(CaseFolding::Upper)
+            .with_allowed_chars(|c| c.is_ascii_alphanumeric() || c == '_')
+            .with_escape(Escape::Replace('_'))
+    }
+
+    /// Convention for path-oriented backends (like Vault): `database.password` becomes
+    /// `database/password`, and characters other than ASCII letters, digits and `/_-` are
+    /// replaced by `_`.
+    pub fn path() -> Self {
+        Self::new()
+            .with_style(NameStyle::Path)
+            .with_allowed_chars(|c| c.is_ascii_alphanumeric() || "/_-".contains(c))
+            .with_escape(Escape::Replace('_'))
+    }
+
+    /// Sets a resolver rewriting or computing names at call time, before the other steps.
+    ///
+    /// The resolver is called on every lookup, so it can read runtime context like a tenant ID
+    /// stored in a task-local or the region of the current request, instead of formatting names
+    /// by hand wherever secrets are used.
+    ///
+    /// # Arguments
+    ///
+    /// * `resolver` - Returns the name to look up for the given logical name.
+    pub fn with_resolver(
+        mut self,
+        resolver: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Sets how the words of a name are joined.
+    ///
+    /// # Arguments
+    ///
+    /// * `style` - Name style.
+    pub fn with_style(mut self, style: NameStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the case folding of names.
+    ///
+    /// # Arguments
+    ///
+    /// * `case` - Case folding.
+    pub fn with_case(mut self, case: CaseFolding) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Sets the characters allowed by the backend. The others are escaped.
+    ///
+    /// # Arguments
+    ///
+    /// * `allowed_chars` - Returns `true` for allowed characters.
+    pub fn with_allowed_chars(
+        mut self,
+        allowed_chars: impl Fn(char) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.allowed_chars = Arc::new(allowed_chars);
+        self
+    }
+
+    /// Sets how characters not allowed by the backend are escaped.
+    ///
+    /// # Arguments
+    ///
+    /// * `escape` - Escaping of characters.
+    pub fn with_escape(mut self, escape: Escape) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Translates a logical name into the backend name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Logical secret name.
+    pub fn apply(&self, name: &str) -> String {
+        let resolved;
+        let name = match &self.resolver {
+            Some(resolver) => {
+                resolved = resolver(name);
+                resolved.as_str()
+            }
+            None => name,
+        };
+
+        let joined = match self.style.separator() {
+            Some(separator) => name
+                .split(WORD_SEPARATORS)
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+                .join(&separator.to_string()),
+            None => name.to_string(),
+        };
+
+        let folded = match self.case {
+            CaseFolding::Unchanged => joined,
+            CaseFolding::Lower => joined.to_lowercase(),
+            CaseFolding::Upper => joined.to_uppercase(),
+        };
+
+        let mut escaped = String::with_capacity(folded.len());
+        for c in folded.chars() {
+            if (self.allowed_chars)(c) {
+                escaped.push(c);
+                continue;
+            }
+
+            match self.escape {
+                Escape::Replace(replacement) => escaped.push(replacement),
+                Escape::Hex(prefix) => {
+                    for byte in c.to_string().bytes() {
+                        escaped.push(prefix);
+                        escaped.push_str(&format!("{:02X}", byte));
+                    }
+                }
+            }
+        }
+
+        escaped
+    }
+}
+
+impl Default for NamingConvention {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for NamingConvention {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NamingConvention")
+            .field("resolver", &self.resolver.is_some())
+            .field("style", &self.style)
+            .field("case", &self.case)
+            .field("escape", &self.escape)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Constraints of a backend on secret names.
+#[derive(Clone, Copy, Debug)]
+pub struct NameRules {
+    backend: &'static str,
+    max_len: Option<usize>,
+    allowed_chars: fn(char) -> bool,
+    allowed_chars_description: &'static str,
+}
+
+impl NameRules {
+    /// Names of AWS Secrets Manager secrets: up to 512 ASCII letters, digits and `/_+=.@-`.
+    /// ARNs are not names, and must be checked with [AWS_ARN](Self::AWS_ARN).
+    pub const AWS: NameRules = NameRules::new(
+        "AWS Secrets Manager",
+        Some(512),
+        |c| c.is_ascii_alphanumeric() || "/_+=.@-".contains(c),
+        "ASCII letters, digits and /_+=.@-",
+    );
+
+    /// ARNs of AWS Secrets Manager secrets, accepted wherever a secret name is.
+    pub const AWS_ARN: NameRules = NameRules::new(
+        "AWS Secrets Manager",
+        Some(2048),
+        |c| c.is_ascii_alphanumeric() || "/_+=.@-:".contains(c),
+        "ASCII letters, digits and /_+=.@-:",
+    );
+
+    /// Names of Doppler secrets: uppercase ASCII letters, digits and underscores.
+    pub const DOPPLER: NameRules = NameRules::new(
+        "Doppler",
+        None,
+        |c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_',
+        "uppercase ASCII letters, digits and underscores",
+    );
+
+    /// Names of HCP Vault Secrets secrets: up to 64 ASCII letters, digits and underscores.
+    pub const HCP_VAULT_SECRETS: NameRules = NameRules::new(
+        "HCP Vault Secrets",
+        Some(64),
+        |c| c.is_ascii_alphanumeric() || c == '_',
+        "ASCII letters, digits and underscores",
+    );
+
+    /// Keys of NATS JetStream KV buckets: ASCII letters, digits and `-/_=.`. Keys starting or
+    /// ending with a dot are rejected by the server.
+    pub const NATS_KV: NameRules = NameRules::new(
+        "NATS KV",
+        None,
+        |c| c.is_ascii_alphanumeric() || "-/_=.".contains(c),
+        "ASCII letters, digits and -/_=.",
+    );
+
+    /// Creates the rules of a backend. Names can never be empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Backend name, used in error messages.
+    /// * `max_len` - Maximum length of names, in characters.
+    /// * `allowed_chars` - Returns `true` for the characters allowed in names.
+    /// * `allowed_chars_description` - Description of the allowed characters, used in error
+    ///   messages. For example: `ASCII letters and digits`.
+    pub const fn new(
+        backend: &'static str,
+        max_len: Option<usize>,
+        allowed_chars: fn(char) -> bool,
+        allowed_chars_description: &'static str,
+    ) -> Self {
+        Self {
+            backend,
+            max_len,
+            allowed_chars,
+            allowed_chars_description,
+        }
+    }
+
+    /// Checks a secret name, returning an
+    /// [InvalidName](crate::SecretsProviderError::InvalidName) error explaining the first
+    /// violated rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Secret name.
+    pub fn validate(&self, name: &str) -> Result<()> {
+        let invalid = |reason: String| {
+            Err(SecretsProviderError::InvalidName(format!(
+                "{:?} is not a valid {} secret name: {}",
+                name, self.backend, reason
+            )))
+        };
+
+        if name.is_empty() {
+            return invalid("names can not be empty".to_string());
+        }
+
+        let len = name.chars().count();
+        if let Some(max_len) = self.max_len.filter(|max_len| len > *max_len) {
+            return invalid(format!(
+                "names are at most {} characters long, found {}",
+                max_len, len
+            ));
+        }
+
+        if let Some((position, c)) = name
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !(self.allowed_chars)(*c))
+        {
+            return invalid(format!(
+                "character {:?} at position {} is not allowed, only {} are",
+                c, position, self.allowed_chars_description
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Secrets Provider wrapper translating logical names with a [NamingConvention].
+///
+/// Returned secrets keep the logical name they were looked up with.
+///
+/// Listing secrets is not supported: backend names can not be translated back into logical
+/// names.
+#[derive(Clone, Debug)]
+pub struct NamingProvider<P> {
+    inner: P,
+    convention: NamingConvention,
+}
+
+impl<P> NamingProvider<P> {
+    /// Wraps a provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Wrapped Secrets Provider.
+    /// * `convention` - Naming convention of the wrapped provider's backend.
+    pub fn new(inner: P, convention: NamingConvention) -> Self {
+        Self { inner, convention }
+    }
+
+    /// Wrapped Secrets Provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Naming convention applied to lookups.
+    pub fn convention(&self) -> &NamingConvention {
+        &self.convention
+    }
+}
+
+/// Restores the logical name of a secret.
+fn with_name<T>(secret: Secret<T>, name: &str) -> Secret<T> {
+    Secret {
+        name: name.to_string(),
+        ..secret
+    }
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> SecretsProvider for NamingProvider<P> {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        Ok(self
+            .inner
+            .find(&self.convention.apply(secret_name))
+            .await?
+            .map(|s| with_name(s, secret_name)))
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        Ok(self
+            .inner
+            .find_with_version(&self.convention.apply(secret_name), version)
+            .await?
+            .map(|s| with_name(s, secret_name)))
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        Ok(self
+            .inner
+            .find_with_stage(&self.convention.apply(secret_name), stage)
+            .await?
+            .map(|s| with_name(s, secret_name)))
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        self.inner.exists(&self.convention.apply(secret_name)).await
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        self.inner
+            .list_secret_versions(&self.convention.apply(secret_name))
+            .await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        let translated: Vec<String> = secret_names
+            .iter()
+            .map(|name| self.convention.apply(name))
+            .collect();
+        let translated_refs: Vec<&str> = translated.iter().map(String::as_str).collect();
+
+        let mut retrieved = self.inner.batch_find::<T>(&translated_refs).await?;
+
+        // Secrets can not be cloned, so when several logical names translate to the same backend
+        // name, the secret is only returned for the first one
+        let mut found = HashMap::new();
+        for (name, translated) in secret_names.iter().zip(&translated) {
+            if let Some(secret) = retrieved.remove(translated.as_str()) {
+                found.insert(*name, with_name(secret, name));
+            }
+        }
+
+        Ok(found)
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.inner.backend()
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let canary = canary.map(|c| self.convention.apply(c));
+        self.inner.self_test(canary.as_deref()).await
+    }
+
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        let start = Instant::now();
+        let translated = self.convention.apply(secret_name);
+        let details = if translated == secret_name {
+            Vec::new()
+        } else {
+            vec![format!("Name translated to {}", translated)]
+        };
+
+        self.inner.resolve_trace(&translated).await.wrap(
+            "NamingProvider",
+            secret_name,
+            details,
+            start,
+        )
+    }
+}
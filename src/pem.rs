@@ -0,0 +1,77 @@
+//! TLS key material stored as PEM.
+//!
+//! With the `pem` feature, PEM secrets can be requested directly as the types used by rustls:
+//! a [PrivateKeyDer] (PKCS#8, PKCS#1 or SEC1 key), or a certificate chain as a
+//! `Vec<CertificateDer>`, leaf certificate first.
+//!
+//! ```rust,ignore
+//! let key = secrets_provider
+//!     .find::<PrivateKeyDer<'static>>("tls/key")
+//!     .await?
+//!     .expect("Secret not found")
+//!     .reveal();
+//! let chain = secrets_provider
+//!     .find::<Vec<CertificateDer<'static>>>("tls/chain")
+//!     .await?
+//!     .expect("Secret not found")
+//!     .reveal();
+//! ```
+//!
+//! Sections of other types are skipped, so the key and the chain can also be read from the same
+//! secret. Both string and binary secrets are decoded.
+use rustls_pki_types::pem::{self, PemObject};
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{wipe, wipe_shared, Decode, SecretData};
+use crate::Result;
+
+impl Decode for PrivateKeyDer<'static> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        from_pem(secret_name, secret_data, "private key", |pem| {
+            PrivateKeyDer::from_pem_slice(pem)
+        })
+    }
+}
+
+impl Decode for Vec<CertificateDer<'static>> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        from_pem(secret_name, secret_data, "certificate", |pem| {
+            let chain =
+                CertificateDer::pem_slice_iter(pem).collect::<std::result::Result<Vec<_>, _>>()?;
+            match chain.is_empty() {
+                true => Err(pem::Error::NoItemsFound),
+                false => Ok(chain),
+            }
+        })
+    }
+}
+
+fn from_pem<T>(
+    secret_name: &str,
+    secret_data: SecretData,
+    item: &str,
+    parse: impl FnOnce(&[u8]) -> std::result::Result<T, pem::Error>,
+) -> Result<T> {
+    let parsed = match secret_data {
+        SecretData::Str(mut s) => {
+            let parsed = parse(s.as_bytes());
+            wipe(&mut s);
+            parsed
+        }
+        SecretData::Bytes(mut b) => {
+            let parsed = parse(&b);
+            wipe_shared(&mut b);
+            parsed
+        }
+    };
+
+    // PEM errors may quote the invalid line, so they are not part of the error
+    parsed.map_err(|e| {
+        let reason = match e {
+            pem::Error::NoItemsFound => format!("no {} found", item),
+            _ => "invalid PEM document".to_string(),
+        };
+        SecretsProviderError::InvalidType(format!("{} ({})", secret_name, reason))
+    })
+}
@@ -0,0 +1,132 @@
+//! Promotion of secrets between environments.
+//!
+//! Configuration is usually validated in one environment (for example, staging) before being
+//! rolled out to the next one (production), each with its own provider. [promote] copies a
+//! secret from the provider of an environment to the writer of another one: its current version,
+//! or a pinned one. The promotion is described to a confirmation hook before anything is
+//! written, so runbooks can ask an operator, or check a change ticket, and decline it.
+//!
+//! ```rust,ignore
+//! let strategy = PromoteStrategy::Pinned(validated_version);
+//! let outcome = promote("database-password", &staging, &production, &strategy, |promotion| {
+//!     println!("Promote version {} of {}?", promotion.source_version, promotion.name);
+//!     operator_confirms()
+//! })
+//! .await?;
+//! ```
+//!
+//! Promotions are idempotent: a secret whose value is already current in the target is not
+//! written again, and the hook is not called.
+use crate::secret::SecretData;
+use crate::{Result, SecretsProvider, SecretsWriter};
+
+/// Version of the source secret a promotion copies.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PromoteStrategy {
+    /// Current version of the source secret.
+    #[default]
+    Latest,
+
+    /// Given version of the source secret, for example the one validated by a test run.
+    Pinned(String),
+}
+
+/// Promotion about to be written, given to the confirmation hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Promotion<'a> {
+    /// Name of the secret, in both environments.
+    pub name: &'a str,
+
+    /// Version of the secret in the source environment.
+    pub source_version: &'a str,
+
+    /// Current version of the secret in the target environment, `None` if it is created.
+    pub target_version: Option<&'a str>,
+}
+
+/// Result of a promotion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PromoteOutcome {
+    /// The secret was created in the target environment, with this version.
+    Created(String),
+
+    /// A new version was added to the secret of the target environment.
+    Updated(String),
+
+    /// The value is already current in the target environment, nothing was written.
+    Unchanged,
+
+    /// The confirmation hook declined the promotion, nothing was written.
+    Declined,
+}
+
+/// Copies a secret from the provider of an environment to another one.
+///
+/// Returns `None` if the secret, or its pinned version, does not exist in the source environment,
+/// or if the secret of the target environment was deleted during the promotion. Only the value
+/// is copied: tags and metadata of the target secret are kept.
+///
+/// # Arguments
+///
+/// * `name` - A string that contains the secret name, in both environments.
+/// * `from` - Provider of the source environment.
+/// * `to` - Writer of the target environment.
+/// * `strategy` - Version of the source secret to copy.
+/// * `confirm` - Confirmation hook, called before writing. The promotion is declined if it
+///   returns `false`.
+pub async fn promote<S, T, F>(
+    name: &str,
+    from: &S,
+    to: &T,
+    strategy: &PromoteStrategy,
+    confirm: F,
+) -> Result<Option<PromoteOutcome>>
+where
+    S: SecretsProvider + Sync,
+    T: SecretsProvider + SecretsWriter + Sync,
+    F: FnOnce(&Promotion<'_>) -> bool,
+{
+    let source = match strategy {
+        PromoteStrategy::Latest => from.find::<SecretData>(name).await?,
+        PromoteStrategy::Pinned(version) => {
+            from.find_with_version::<SecretData>(name, version).await?
+        }
+    };
+    let Some(source) = source else {
+        return Ok(None);
+    };
+
+    let target = to.find::<SecretData>(name).await?;
+    if target
+        .as_ref()
+        .is_some_and(|target| same_value(&source.secret, &target.secret))
+    {
+        return Ok(Some(PromoteOutcome::Unchanged));
+    }
+
+    let promotion = Promotion {
+        name,
+        source_version: &source.version,
+        target_version: target.as_ref().map(|target| target.version.as_str()),
+    };
+    if !confirm(&promotion) {
+        return Ok(Some(PromoteOutcome::Declined));
+    }
+
+    let outcome = match target {
+        None => PromoteOutcome::Created(to.create_secret(name, source.secret).await?),
+        Some(_) => match to.put_secret_value(name, source.secret).await? {
+            Some(version) => PromoteOutcome::Updated(version),
+            None => return Ok(None),
+        },
+    };
+    Ok(Some(outcome))
+}
+
+fn same_value(a: &SecretData, b: &SecretData) -> bool {
+    match (a, b) {
+        (SecretData::Str(a), SecretData::Str(b)) => a == b,
+        (SecretData::Bytes(a), SecretData::Bytes(b)) => a == b,
+        _ => false,
+    }
+}
@@ -0,0 +1,329 @@
+//! Per-caller quotas on a shared provider.
+//!
+//! Applications loading plugins (or serving several tenants) often share one provider between
+//! them. A misbehaving caller looking up secrets in a loop, or enumerating every secret it can
+//! guess, should not starve the others or exhaust the backend's rate limits. A [QuotaProvider]
+//! hands out a [CallerProvider] to each caller, identified by a caller token (for example, the
+//! name of the plugin). Lookups through a caller provider count against the quota of its caller,
+//! and lookups over the quota fail with a
+//! [QuotaExceeded](crate::SecretsProviderError::QuotaExceeded) error, without reaching the
+//! backend. Lookups are counted when they start, so a lookup dropped before it completes still
+//! counts.
+//!
+//! Callers without a quota of their own get the default one, unlimited unless configured, so
+//! core components keep unrestricted access.
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::ResolveTrace;
+use crate::{ListFilter, Result, SecretsPage, SecretsProvider, VersionStage};
+
+/// Limits of a caller. Every limit is disabled by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quota {
+    /// Maximum lookups per second, with bursts of up to one second of lookups. Batch lookups
+    /// count one lookup per secret, and listings one per page.
+    ///
+    /// A batch larger than one second of lookups is accepted once the caller has not looked up
+    /// anything for a second, and the lookups over the limit are paid back before the next
+    /// lookup is accepted.
+    pub max_lookups_per_second: Option<u32>,
+
+    /// Maximum number of distinct secrets looked up. Secrets already looked up can be looked up
+    /// again once the limit is reached.
+    pub max_distinct_secrets: Option<usize>,
+}
+
+/// Quota usage of a caller.
+struct CallerUsage {
+    tokens: f64,
+    last_refill: Instant,
+    secrets: HashSet<String>,
+}
+
+type Usage = Mutex<HashMap<String, CallerUsage>>;
+
+/// Shares a provider between callers, each one with its own quota.
+pub struct QuotaProvider<P> {
+    inner: Arc<P>,
+    default_quota: Quota,
+    quotas: HashMap<String, Quota>,
+    usage: Arc<Usage>,
+    clock: fn() -> Instant,
+}
+
+impl<P> QuotaProvider<P> {
+    /// Wraps a provider. Callers are not limited until quotas are set.
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Shared Secrets Provider.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            default_quota: Quota::default(),
+            quotas: HashMap::new(),
+            usage: Arc::new(Mutex::new(HashMap::new())),
+            clock: Instant::now,
+        }
+    }
+
+    /// Sets the quota of the callers without a quota of their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `quota` - Default quota.
+    pub fn with_default_quota(mut self, quota: Quota) -> Self {
+        self.default_quota = quota;
+        self
+    }
+
+    /// Sets the quota of a caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `caller` - Caller token.
+    /// * `quota` - Quota of the caller.
+    pub fn with_quota(mut self, caller: impl Into<String>, quota: Quota) -> Self {
+        self.quotas.insert(caller.into(), quota);
+        self
+    }
+
+    /// Sets the clock the lookup rates are measured with, instead of [Instant::now].
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Current time. For example, `|| tokio::time::Instant::now().into_std()` follows
+    ///   the paused clock of Tokio tests.
+    pub fn with_clock(mut self, clock: fn() -> Instant) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Shared Secrets Provider.
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// Quota of a caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `caller` - Caller token.
+    pub fn quota(&self, caller: &str) -> Quota {
+        self.quotas
+            .get(caller)
+            .copied()
+            .unwrap_or(self.default_quota)
+    }
+
+    /// Provider used by a caller. Every provider of the same caller shares its quota.
+    ///
+    /// # Arguments
+    ///
+    /// * `caller` - Caller token. For example, the name of a plugin.
+    pub fn caller(&self, caller: &str) -> CallerProvider<P> {
+        CallerProvider {
+            inner: self.inner.clone(),
+            caller: caller.into(),
+            quota: self.quota(caller),
+            usage: self.usage.clone(),
+            clock: self.clock,
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for QuotaProvider<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuotaProvider")
+            .field("default_quota", &self.default_quota)
+            .field("quotas", &self.quotas)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Secrets Provider used by a caller of a [QuotaProvider], enforcing its quota.
+pub struct CallerProvider<P> {
+    inner: Arc<P>,
+    caller: Arc<str>,
+    quota: Quota,
+    usage: Arc<Usage>,
+    clock: fn() -> Instant,
+}
+
+impl<P> CallerProvider<P> {
+    /// Caller token of the provider.
+    pub fn caller(&self) -> &str {
+        &self.caller
+    }
+
+    /// Quota of the caller.
+    pub fn quota(&self) -> Quota {
+        self.quota
+    }
+
+    /// Counts lookups against the quota of the caller, or fails if they would exceed it. Nothing
+    /// is counted when the quota is exceeded.
+    ///
+    /// # Arguments
+    ///
+    /// * `lookups` - Number of lookups.
+    /// * `secret_names` - Secrets looked up.
+    fn acquire(&self, lookups: usize, secret_names: &[&str]) -> Result<()> {
+        let mut usage = self.lock_usage();
+        let usage = usage
+            .entry(self.caller.to_string())
+            .or_insert_with(|| CallerUsage {
+                tokens: f64::from(self.quota.max_lookups_per_second.unwrap_or_default()),
+                last_refill: (self.clock)(),
+                secrets: HashSet::new(),
+            });
+
+        let mut tokens = usage.tokens;
+        if let Some(rate) = self.quota.max_lookups_per_second {
+            let now = (self.clock)();
+            let refilled = now.duration_since(usage.last_refill).as_secs_f64() * f64::from(rate);
+            tokens = (tokens + refilled).min(f64::from(rate));
+            usage.tokens = tokens;
+            usage.last_refill = now;
+
+            // Batches over the burst size only need a full bucket, and leave it in debt
+            if tokens < (lookups as f64).min(f64::from(rate)) {
+                return Err(SecretsProviderError::QuotaExceeded(format!(
+                    "{} is limited to {} lookups per second",
+                    self.caller, rate
+                )));
+            }
+            tokens -= lookups as f64;
+        }
+
+        // Secrets are only remembered for callers limited to distinct secrets
+        if let Some(max) = self.quota.max_distinct_secrets {
+            let new_secrets: HashSet<&str> = secret_names
+                .iter()
+                .copied()
+                .filter(|name| !usage.secrets.contains(*name))
+                .collect();
+            if usage.secrets.len() + new_secrets.len() > max {
+                return Err(SecretsProviderError::QuotaExceeded(format!(
+                    "{} is limited to {} distinct secrets",
+                    self.caller, max
+                )));
+            }
+            usage
+                .secrets
+                .extend(new_secrets.into_iter().map(str::to_string));
+        }
+
+        usage.tokens = tokens;
+        Ok(())
+    }
+
+    fn lock_usage(&self) -> MutexGuard<'_, HashMap<String, CallerUsage>> {
+        // Usage is updated in a single step, so a poisoned lock still holds consistent data
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<P> Clone for CallerProvider<P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            caller: self.caller.clone(),
+            quota: self.quota,
+            usage: self.usage.clone(),
+            clock: self.clock,
+        }
+    }
+}
+
+impl<P> std::fmt::Debug for CallerProvider<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallerProvider")
+            .field("caller", &self.caller)
+            .field("quota", &self.quota)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> SecretsProvider for CallerProvider<P> {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        self.acquire(1, &[secret_name])?;
+        self.inner.find(secret_name).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        self.acquire(1, &[secret_name])?;
+        self.inner.find_with_version(secret_name, version).await
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        self.acquire(1, &[secret_name])?;
+        self.inner.find_with_stage(secret_name, stage).await
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        self.acquire(1, &[secret_name])?;
+        self.inner.exists(secret_name).await
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        self.acquire(1, &[secret_name])?;
+        self.inner.list_secret_versions(secret_name).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        self.acquire(secret_names.len(), secret_names)?;
+        self.inner.batch_find(secret_names).await
+    }
+
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        self.acquire(1, &[])?;
+        self.inner.list_secrets(filter, page_token).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.inner.backend()
+    }
+
+    /// Self-tests are not counted against the quota.
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.inner.self_test(canary).await
+    }
+
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        let start = Instant::now();
+        if let Err(e) = self.acquire(1, &[secret_name]) {
+            return ResolveTrace::run("CallerProvider", secret_name, async { Err(e) }).await;
+        }
+
+        self.inner.resolve_trace(secret_name).await.wrap(
+            "CallerProvider",
+            secret_name,
+            vec![format!("Counted against the quota of {}", self.caller)],
+            start,
+        )
+    }
+}
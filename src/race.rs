@@ -0,0 +1,225 @@
+//! Hedged lookups across equivalent backends.
+//!
+//! When the same secrets are replicated to several backends (AWS Secrets Manager replicas in
+//! several regions, a Vault cluster and its performance standby...), a slow backend should not
+//! slow the application down. [RaceProvider] sends each lookup to the first backend, and to the
+//! next one if no answer came within the hedging delay, or as soon as a backend failed. The first
+//! successful answer is returned, and the lookups still running are dropped.
+//!
+//! With a zero hedging delay (the default), every backend is queried at once. A longer delay
+//! sends fewer requests: the other backends are only queried when the first one is slow.
+//!
+//! Backends must be equivalent: they hold the same secrets, with the same versions. A secret
+//! missing from the fastest backend is reported as missing, even if another backend has it.
+use std::collections::HashMap;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::{Decode, Secret, VersionInfo};
+use crate::self_test::SelfTestReport;
+use crate::trace::{ResolveTrace, TraceOutcome};
+use crate::{ListFilter, Result, SecretsPage, SecretsProvider, VersionStage};
+
+type Lookup<'a, R, E> = Pin<Box<dyn Future<Output = std::result::Result<R, E>> + Send + 'a>>;
+
+/// Secrets Provider wrapper racing lookups across equivalent backends.
+#[derive(Clone, Debug)]
+pub struct RaceProvider<P> {
+    providers: Vec<P>,
+    hedging_delay: Duration,
+}
+
+impl<P> RaceProvider<P> {
+    /// Wraps equivalent providers, queried in the given order. Lookups are sent to all of them
+    /// at once, until a hedging delay is set.
+    ///
+    /// Fails with an [Initialization](SecretsProviderError::Initialization) error if there are
+    /// no providers.
+    ///
+    /// # Arguments
+    ///
+    /// * `providers` - Wrapped Secrets Providers, the preferred one first.
+    pub fn new(providers: Vec<P>) -> Result<Self> {
+        if providers.is_empty() {
+            return Err(SecretsProviderError::Initialization(
+                "A race needs at least one provider".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            providers,
+            hedging_delay: Duration::ZERO,
+        })
+    }
+
+    /// Sets how long a lookup waits for a provider before it is also sent to the next one.
+    ///
+    /// # Arguments
+    ///
+    /// * `delay` - Hedging delay.
+    pub fn with_hedging_delay(mut self, delay: Duration) -> Self {
+        self.hedging_delay = delay;
+        self
+    }
+
+    /// Wrapped Secrets Providers, the preferred one first.
+    pub fn providers(&self) -> &[P] {
+        &self.providers
+    }
+
+    /// Races a lookup across the providers, returning the first success and the index of the
+    /// provider that answered. If every provider fails, the last error is returned.
+    async fn race<'a, R, E, F, Fut>(&'a self, lookup: F) -> std::result::Result<(usize, R), E>
+    where
+        F: Fn(&'a P) -> Fut,
+        Fut: Future<Output = std::result::Result<R, E>> + Send + 'a,
+    {
+        let mut running: Vec<(usize, Lookup<'a, R, E>)> = Vec::new();
+        let mut started = 0;
+        let mut hedge = Box::pin(tokio::time::sleep(Duration::ZERO));
+        let mut last_error = None;
+
+        poll_fn(|cx| loop {
+            // The next provider is queried when the hedging delay expired, or right away when
+            // no lookup is running anymore
+            let start_next = started < self.providers.len()
+                && (running.is_empty() || hedge.as_mut().poll(cx).is_ready());
+            if start_next {
+                running.push((started, Box::pin(lookup(&self.providers[started]))));
+                started += 1;
+                hedge
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + self.hedging_delay);
+            }
+
+            let mut index = 0;
+            while index < running.len() {
+                match running[index].1.as_mut().poll(cx) {
+                    Poll::Ready(Ok(result)) => return Poll::Ready(Ok((running[index].0, result))),
+                    Poll::Ready(Err(e)) => {
+                        last_error = Some(e);
+                        drop(running.remove(index));
+                    }
+                    Poll::Pending => index += 1,
+                }
+            }
+
+            if running.is_empty() && started == self.providers.len() {
+                // At least one provider was queried, so there is an error
+                return Poll::Ready(Err(last_error.take().expect("No provider was queried")));
+            }
+            // A failure with no lookup left running starts the next provider right away
+            if !start_next && !running.is_empty() {
+                return Poll::Pending;
+            }
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl<P: SecretsProvider + Send + Sync> SecretsProvider for RaceProvider<P> {
+    async fn find<T: Decode>(&self, secret_name: &str) -> Result<Option<Secret<T>>> {
+        let (_, secret) = self.race(|p| p.find(secret_name)).await?;
+        Ok(secret)
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>> {
+        let (_, secret) = self
+            .race(|p| p.find_with_version(secret_name, version))
+            .await?;
+        Ok(secret)
+    }
+
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>> {
+        let (_, secret) = self.race(|p| p.find_with_stage(secret_name, stage)).await?;
+        Ok(secret)
+    }
+
+    async fn exists(&self, secret_name: &str) -> Result<bool> {
+        let (_, exists) = self.race(|p| p.exists(secret_name)).await?;
+        Ok(exists)
+    }
+
+    async fn list_secret_versions(&self, secret_name: &str) -> Result<Vec<VersionInfo>> {
+        let (_, versions) = self.race(|p| p.list_secret_versions(secret_name)).await?;
+        Ok(versions)
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>> {
+        let (_, secrets) = self.race(|p| p.batch_find(secret_names)).await?;
+        Ok(secrets)
+    }
+
+    /// Listings are not raced: page tokens are specific to a backend, so secrets are listed
+    /// from the first provider.
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage> {
+        self.providers[0].list_secrets(filter, page_token).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.providers[0].backend()
+    }
+
+    /// Every provider is self-tested, in order. Check names are prefixed with the index of the
+    /// provider, for example: `provider 1: list_secrets`.
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        for (index, provider) in self.providers.iter().enumerate() {
+            for mut check in provider.self_test(canary).await.checks {
+                check.name = format!("provider {}: {}", index, check.name);
+                report.checks.push(check);
+            }
+        }
+
+        report
+    }
+
+    async fn resolve_trace(&self, secret_name: &str) -> ResolveTrace {
+        let start = Instant::now();
+        let traced = self
+            .race(|p| async move {
+                let trace = p.resolve_trace(secret_name).await;
+                match trace.outcome {
+                    TraceOutcome::Failed(_) => Err(trace),
+                    _ => Ok(trace),
+                }
+            })
+            .await;
+
+        match traced {
+            Ok((index, trace)) => trace.wrap(
+                "RaceProvider",
+                secret_name,
+                vec![format!("Answered by provider {}", index)],
+                start,
+            ),
+            Err(trace) => trace.wrap(
+                "RaceProvider",
+                secret_name,
+                vec!["Every provider failed".to_string()],
+                start,
+            ),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Host name resolution override for the HTTP-based implementations.
+//!
+//! Some networks reach the cloud APIs through private endpoints with split-horizon DNS, where the
+//! system resolver can not (or must not) be used. A [ResolveHost](crate::resolver::ResolveHost)
+//! lets the implementations resolve host names some other way while still connecting (and
+//! validating TLS certificates) with the original host name.
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use async_trait::async_trait;
+use hyper::client::connect::dns::{GaiResolver, Name};
+use hyper::client::HttpConnector;
+use tower_service::Service;
+
+/// Custom host name resolution.
+#[async_trait]
+pub trait ResolveHost: Send + Sync {
+    /// Resolves a host name into a list of IP addresses.
+    ///
+    /// Returning `Ok(None)` falls back to the system resolver for that host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Host name to resolve. For example: `secretsmanager.us-west-2.amazonaws.com`.
+    async fn resolve(&self, host: &str) -> io::Result<Option<Vec<IpAddr>>>;
+}
+
+/// Resolves host names from a static mapping, falling back to the system resolver for the hosts
+/// that are not mapped.
+///
+/// ```rust
+/// use secrets_provider::resolver::StaticResolver;
+///
+/// let resolver = StaticResolver::new().host(
+///     "secretsmanager.us-west-2.amazonaws.com",
+///     ["10.0.12.34".parse().unwrap()],
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StaticResolver {
+    hosts: HashMap<String, Vec<IpAddr>>,
+}
+
+impl StaticResolver {
+    /// Creates an empty static resolver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps a host name to a list of IP addresses, replacing any previous mapping for that host.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - Host name to map. It is compared case-insensitively.
+    /// * `addresses` - IP addresses the host name resolves to.
+    pub fn host(
+        mut self,
+        host: impl Into<String>,
+        addresses: impl IntoIterator<Item = IpAddr>,
+    ) -> Self {
+        self.hosts.insert(
+            host.into().to_ascii_lowercase(),
+            addresses.into_iter().collect(),
+        );
+        self
+    }
+}
+
+#[async_trait]
+impl ResolveHost for StaticResolver {
+    async fn resolve(&self, host: &str) -> io::Result<Option<Vec<IpAddr>>> {
+        Ok(self.hosts.get(&host.to_ascii_lowercase()).cloned())
+    }
+}
+
+/// Adapts a [ResolveHost](crate::resolver::ResolveHost) to the resolver interface of hyper's
+/// HTTP connector.
+#[derive(Clone)]
+pub(crate) struct HyperResolver {
+    resolver: Arc<dyn ResolveHost>,
+    system: GaiResolver,
+}
+
+impl Service<Name> for HyperResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let resolver = self.resolver.clone();
+        let mut system = self.system.clone();
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = match resolver.resolve(name.as_str()).await? {
+                // The connector sets the port of the URL on the resolved addresses
+                Some(ips) => ips.into_iter().map(|ip| SocketAddr::new(ip, 0)).collect(),
+                None => system.call(name).await?.collect(),
+            };
+            Ok(addresses.into_iter())
+        })
+    }
+}
+
+/// Creates an HTTP connector resolving host names with the given resolver. The connector accepts
+/// `https` URLs so it can be wrapped by a TLS connector.
+pub(crate) fn http_connector(resolver: Arc<dyn ResolveHost>) -> HttpConnector<HyperResolver> {
+    let mut connector = HttpConnector::new_with_resolver(HyperResolver {
+        resolver,
+        system: GaiResolver::new(),
+    });
+    connector.enforce_http(false);
+    connector
+}
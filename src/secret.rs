@@ -1,4 +1,8 @@
+use std::collections::BTreeMap;
 use std::fmt::{Debug, Display};
+use std::time::SystemTime;
+
+use bytes::Bytes;
 
 use crate::errors::SecretsProviderError;
 use crate::Result;
@@ -7,9 +11,43 @@ use crate::Result;
 ///
 /// We use this enum to know which datatype is the secret and to corretly downcast it when it is
 /// needed. The caller must know the secret's datatype before use it.
+///
+/// Binary secrets are held in a shared [Bytes] buffer, so providers keeping secrets in memory
+/// hand out their value without copying it.
+#[derive(Clone)]
 pub enum SecretData {
     Str(String),
-    Bytes(Vec<u8>),
+    Bytes(Bytes),
+}
+
+impl From<String> for SecretData {
+    fn from(value: String) -> Self {
+        SecretData::Str(value)
+    }
+}
+
+impl From<&str> for SecretData {
+    fn from(value: &str) -> Self {
+        SecretData::Str(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for SecretData {
+    fn from(value: Vec<u8>) -> Self {
+        SecretData::Bytes(value.into())
+    }
+}
+
+impl From<&[u8]> for SecretData {
+    fn from(value: &[u8]) -> Self {
+        SecretData::Bytes(Bytes::copy_from_slice(value))
+    }
+}
+
+impl From<Bytes> for SecretData {
+    fn from(value: Bytes) -> Self {
+        SecretData::Bytes(value)
+    }
 }
 
 /// Structure containing a secret retrieved from a secret manager.
@@ -26,6 +64,27 @@ pub struct Secret<T> {
 
     /// Secret itself
     pub(crate) secret: T,
+
+    /// Stages attached to the version by the backend. For example: `AWSCURRENT`.
+    pub(crate) version_stages: Vec<String>,
+
+    /// Whether the version is the current one, when the backend tells.
+    pub(crate) is_current: Option<bool>,
+
+    /// Backend the version comes from. See [SecretVersion].
+    pub(crate) backend: &'static str,
+
+    /// Tags of the secret, when the backend returned them with the value.
+    pub(crate) tags: Option<BTreeMap<String, String>>,
+
+    /// Creation time of the version, when the backend returned it with the value.
+    pub(crate) created_at: Option<SystemTime>,
+
+    /// Last rotation time of the secret, when the backend returned it with the value.
+    pub(crate) last_rotated_at: Option<SystemTime>,
+
+    /// Other metadata of the secret, when the backend returned any with the value.
+    pub(crate) metadata: Option<SecretMetadata>,
 }
 
 impl<T> Secret<T> {
@@ -56,6 +115,430 @@ impl<T> Secret<T> {
     pub fn reveal(self) -> T {
         self.secret
     }
+
+    /// Borrows the value of the secret, for callers needing it in several places while keeping
+    /// the secret and its metadata.
+    pub fn expose(&self) -> &T {
+        &self.secret
+    }
+
+    /// Splits the secret into its value and its metadata, kept in a secret without value.
+    ///
+    /// This example uses the `memory` feature.
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::{SecretsProvider, implementations::memory::MemorySecretsProvider};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider = MemorySecretsProvider::new();
+    ///
+    ///     let (password, metadata) = secrets_provider
+    ///         .find::<String>("database-password")
+    ///         .await
+    ///         .expect("There was an error getting the database password")
+    ///         .expect("Secret not found")
+    ///         .into_parts();
+    ///     println!("Using version {} of {}", metadata.version, metadata.name);
+    /// }
+    /// ```
+    pub fn into_parts(self) -> (T, Secret<()>) {
+        let Secret {
+            name,
+            version,
+            secret,
+            version_stages,
+            is_current,
+            backend,
+            tags,
+            created_at,
+            last_rotated_at,
+            metadata,
+        } = self;
+
+        let parts = Secret {
+            name,
+            version,
+            secret: (),
+            version_stages,
+            is_current,
+            backend,
+            tags,
+            created_at,
+            last_rotated_at,
+            metadata,
+        };
+        (secret, parts)
+    }
+
+    /// Stages attached to the secret version by the backend, like `AWSCURRENT` or `AWSPENDING`
+    /// for AWS Secrets Manager. Empty for backends without stages.
+    pub fn version_stages(&self) -> &[String] {
+        &self.version_stages
+    }
+
+    /// Tags (or labels) of the secret, for example to check that it belongs to the tenant using
+    /// it. `None` if the backend has no tags, or did not return them with the value; an empty
+    /// map if the secret has no tags.
+    pub fn tags(&self) -> Option<&BTreeMap<String, String>> {
+        self.tags.as_ref()
+    }
+
+    /// Creation time of the secret version, for example to detect credentials that should have
+    /// been rotated already. `None` if the backend did not return it with the value.
+    pub fn created_at(&self) -> Option<SystemTime> {
+        self.created_at
+    }
+
+    /// Last time the secret was rotated. `None` if the secret was never rotated, or if the
+    /// backend did not return it with the value.
+    pub fn last_rotated_at(&self) -> Option<SystemTime> {
+        self.last_rotated_at
+    }
+
+    /// Other metadata of the secret, like its resource identifier or description. `None` if the
+    /// backend did not return any with the value. Staging labels are returned by
+    /// [version_stages](Self::version_stages).
+    pub fn metadata(&self) -> Option<&SecretMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Returns whether the secret version is the current one, or `None` if the backend can not
+    /// tell without another request.
+    pub fn is_current(&self) -> Option<bool> {
+        self.is_current
+    }
+
+    /// Version of the secret, tagged with the backend it comes from.
+    pub fn secret_version(&self) -> SecretVersion {
+        SecretVersion::new(self.backend, self.version.clone())
+    }
+}
+
+/// With the `zeroize` feature, the value of a secret can be wiped in place.
+///
+/// Secrets do not wipe their value when they are dropped: Rust does not allow a `Drop`
+/// implementation for the values that can be wiped only, and one for every value would forbid
+/// [reveal](Secret::reveal) from moving the value out. Secrets wiped when dropped are retrieved as
+/// a `Secret<Zeroizing<T>>` instead, for example with `find::<Zeroizing<String>>`.
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize> zeroize::Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Secrets holding a value wiped when dropped, like a `Secret<Zeroizing<String>>`, are wiped when
+/// dropped: their other fields are not secret.
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::ZeroizeOnDrop> zeroize::ZeroizeOnDrop for Secret<T> {}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize> Secret<T> {
+    /// Reveals the secret, like [reveal](Self::reveal), in a wrapper wiping the value when it is
+    /// dropped, so the plaintext does not linger in freed memory.
+    pub fn reveal_zeroizing(self) -> zeroize::Zeroizing<T> {
+        zeroize::Zeroizing::new(self.secret)
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl<T: AsRef<[u8]>> Secret<T> {
+    /// Compares the value of the secret with another value in constant time, for example an API
+    /// token received in a request, so the comparison does not reveal how many leading bytes
+    /// match. The lengths of the values are not hidden.
+    ///
+    /// With the `ct-partial-eq` feature, secrets can also be compared with `==`, which uses this
+    /// function.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Value compared with the secret. For example: `token.as_bytes()`.
+    pub fn ct_eq(&self, other: impl AsRef<[u8]>) -> bool {
+        use subtle::ConstantTimeEq;
+
+        self.secret.as_ref().ct_eq(other.as_ref()).into()
+    }
+}
+
+/// With the `ct-partial-eq` feature, secrets are equal when their values are, whatever their
+/// names and versions. Values are compared in constant time.
+#[cfg(feature = "ct-partial-eq")]
+impl<T: AsRef<[u8]>, U: AsRef<[u8]>> PartialEq<Secret<U>> for Secret<T> {
+    fn eq(&self, other: &Secret<U>) -> bool {
+        self.ct_eq(&other.secret)
+    }
+}
+
+#[cfg(feature = "ct-partial-eq")]
+impl<T: AsRef<[u8]>> PartialEq<str> for Secret<T> {
+    fn eq(&self, other: &str) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+#[cfg(feature = "ct-partial-eq")]
+impl<T: AsRef<[u8]>> PartialEq<&str> for Secret<T> {
+    fn eq(&self, other: &&str) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+#[cfg(feature = "ct-partial-eq")]
+impl<T: AsRef<[u8]>> PartialEq<[u8]> for Secret<T> {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.ct_eq(other)
+    }
+}
+
+/// With the `serde` feature, secrets embedded in serialized structures (state snapshots, debug
+/// dumps...) are serialized like their [Debug] representation: name, version, and a placeholder
+/// instead of the value. The value is only serialized with
+/// [serialize_revealed](Self::serialize_revealed).
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for Secret<T> {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.serialize_with_value(serializer, &"*****")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> Secret<T> {
+    /// Serializes the secret with its value, instead of the placeholder used by its
+    /// [Serialize](serde::Serialize) implementation. It can be opted in per field:
+    ///
+    /// ```rust,ignore
+    /// #[derive(Serialize)]
+    /// struct Export {
+    ///     #[serde(serialize_with = "Secret::serialize_revealed")]
+    ///     password: Secret<String>,
+    /// }
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `serializer` - Serializer the secret is written to.
+    pub fn serialize_revealed<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        self.serialize_with_value(serializer, &self.secret)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Secret<T> {
+    fn serialize_with_value<S: serde::Serializer, V: serde::Serialize + ?Sized>(
+        &self,
+        serializer: S,
+        value: &V,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Secret", 3)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("version", &self.version)?;
+        state.serialize_field("secret", value)?;
+        state.end()
+    }
+}
+
+impl Secret<SecretData> {
+    /// Decodes a secret retrieved without a type, for example by a
+    /// [DynSecretsProvider](crate::DynSecretsProvider), keeping its metadata.
+    ///
+    /// This example uses the `memory` feature.
+    #[cfg_attr(not(feature = "memory"), doc = "```ignore")]
+    /// ```rust,no_run
+    /// use secrets_provider::implementations::memory::MemorySecretsProvider;
+    /// use secrets_provider::DynSecretsProvider;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let secrets_provider: Box<dyn DynSecretsProvider> = Box::new(MemorySecretsProvider::new());
+    ///
+    ///     let secret = secrets_provider
+    ///         .dyn_find("master_key_of_everything")
+    ///         .await
+    ///         .expect("There was an error getting the Master Key of Everything")
+    ///         .expect("Secret not found")
+    ///         .decode::<String>()
+    ///         .expect("The Master Key of Everything is not a string");
+    /// }
+    /// ```
+    pub fn decode<T: Decode>(self) -> Result<Secret<T>> {
+        self.try_map(T::decode)
+    }
+}
+
+impl<T> Secret<T> {
+    /// Converts the value of the secret, keeping its metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - Converts the value, given the name of the secret.
+    pub(crate) fn try_map<U>(self, f: impl FnOnce(&str, T) -> Result<U>) -> Result<Secret<U>> {
+        Ok(Secret {
+            secret: f(&self.name, self.secret)?,
+            name: self.name,
+            version: self.version,
+            version_stages: self.version_stages,
+            is_current: self.is_current,
+            backend: self.backend,
+            tags: self.tags,
+            created_at: self.created_at,
+            last_rotated_at: self.last_rotated_at,
+            metadata: self.metadata,
+        })
+    }
+}
+
+/// Wipes a buffer holding plaintext before it is freed, with the `zeroize` feature. Does nothing
+/// without it.
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe<Z: zeroize::Zeroize + ?Sized>(buffer: &mut Z) {
+    buffer.zeroize();
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn wipe<Z: ?Sized>(_buffer: &mut Z) {}
+
+/// Wipes a shared buffer like [wipe], unless other owners still use it. It is left empty.
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe_shared(buffer: &mut Bytes) {
+    if let Ok(mut unique) = std::mem::take(buffer).try_into_mut() {
+        wipe(&mut unique[..]);
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn wipe_shared(_buffer: &mut Bytes) {}
+
+/// Version of a secret, tagged with the backend it comes from.
+///
+/// Backends identify versions in different ways: AWS Secrets Manager uses UUIDs, HCP Vault
+/// Secrets and NATS use increasing numbers, the memory implementation uses UUIDs of its own...
+/// A plain string does not say which backend it belongs to, so code using several providers can
+/// easily look up a version in the wrong one. [SecretsProvider::find_with_secret_version](crate::SecretsProvider::find_with_secret_version)
+/// rejects versions of other backends with an
+/// [InvalidVersion](crate::SecretsProviderError::InvalidVersion) error instead.
+///
+/// The identifier is opaque: it is only meant to be given back to the same backend.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SecretVersion {
+    backend: &'static str,
+    id: String,
+}
+
+impl SecretVersion {
+    /// Tags a version identifier with its backend, for example to look up a version stored as a
+    /// string.
+    ///
+    /// # Arguments
+    ///
+    /// * `backend` - Backend of the version, as returned by
+    ///   [SecretsProvider::backend](crate::SecretsProvider::backend).
+    /// * `id` - Version identifier, as returned by the backend.
+    pub fn new(backend: &'static str, id: impl Into<String>) -> Self {
+        Self {
+            backend,
+            id: id.into(),
+        }
+    }
+
+    /// Backend the version comes from. For example: `AWS Secrets Manager`.
+    pub fn backend(&self) -> &'static str {
+        self.backend
+    }
+
+    /// Version identifier, as returned by the backend.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Display for SecretVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.id, self.backend)
+    }
+}
+
+/// Stage of a secret version: where it stands in the rotation of the secret. Stages are looked up
+/// with [SecretsProvider::find_with_stage](crate::SecretsProvider::find_with_stage), instead of
+/// opaque version identifiers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VersionStage {
+    /// Version returned by [find](crate::SecretsProvider::find). `AWSCURRENT` for AWS Secrets
+    /// Manager.
+    Current,
+
+    /// Version that was current before the current one. `AWSPREVIOUS` for AWS Secrets Manager.
+    Previous,
+
+    /// Version being rotated in, that is not current yet. `AWSPENDING` for AWS Secrets Manager.
+    Pending,
+
+    /// Custom staging label of the backend.
+    Label(String),
+}
+
+impl Display for VersionStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionStage::Current => write!(f, "current"),
+            VersionStage::Previous => write!(f, "previous"),
+            VersionStage::Pending => write!(f, "pending"),
+            VersionStage::Label(label) => write!(f, "{}", label),
+        }
+    }
+}
+
+/// Version of a secret, as listed by
+/// [SecretsProvider::list_secret_versions](crate::SecretsProvider::list_secret_versions).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// Version identifier, as accepted by
+    /// [find_with_version](crate::SecretsProvider::find_with_version).
+    pub id: String,
+
+    /// Stages of the version. Empty for versions that are no longer part of the rotation.
+    pub stages: Vec<VersionStage>,
+
+    /// Creation time of the version, `None` when the backend does not track it.
+    pub created_at: Option<SystemTime>,
+}
+
+impl VersionInfo {
+    /// Lists the versions of a backend without stages, where the last version is the current one.
+    ///
+    /// # Arguments
+    ///
+    /// * `versions` - Identifier and creation time of each version, oldest first.
+    #[cfg(any(
+        feature = "directory",
+        feature = "postgres",
+        feature = "redis",
+        feature = "sqlite"
+    ))]
+    pub(crate) fn last_current(
+        versions: impl IntoIterator<Item = (String, Option<SystemTime>)>,
+    ) -> Vec<Self> {
+        let mut versions: Vec<Self> = versions
+            .into_iter()
+            .map(|(id, created_at)| Self {
+                id,
+                stages: Vec::new(),
+                created_at,
+            })
+            .collect();
+        if let Some(current) = versions.last_mut() {
+            current.stages.push(VersionStage::Current);
+        }
+        versions
+    }
 }
 
 // We use this custom implementation of Display to prevent accidental secret leaking through
@@ -82,6 +565,103 @@ impl<T> Debug for Secret<T> {
     }
 }
 
+/// Metadata of a secret returned with its value, beyond the fields of [Secret].
+///
+/// Fields are `None` (or empty) when the backend does not supply them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecretMetadata {
+    /// Identifier of the secret as a backend resource. For example: the ARN of an AWS Secrets
+    /// Manager secret.
+    pub resource_id: Option<String>,
+
+    /// Description given to the secret.
+    pub description: Option<String>,
+
+    /// Backend-specific metadata, by name. For example: `kms_key_id` for AWS Secrets Manager.
+    pub extras: BTreeMap<String, String>,
+}
+
+/// Metadata of a secret, read without its value.
+///
+/// Fields are `None` when the backend does not track them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SecretDescription {
+    /// Name of the secret.
+    pub name: String,
+
+    /// Description given to the secret.
+    pub description: Option<String>,
+
+    /// Creation time of the secret.
+    pub created_at: Option<SystemTime>,
+
+    /// Last time the secret (or its value) was changed.
+    pub last_changed_at: Option<SystemTime>,
+
+    /// Last time the secret was rotated.
+    pub last_rotated_at: Option<SystemTime>,
+
+    /// Whether automatic rotation is enabled.
+    pub rotation_enabled: Option<bool>,
+
+    /// Tags (or labels) of the secret.
+    pub tags: Option<BTreeMap<String, String>>,
+}
+
+/// Secrets found under a path of a path-oriented backend.
+///
+/// Secrets and subtrees are indexed by the last component of their path, so a secret and a
+/// subtree can share the same key. The secrets keep their full name.
+pub struct SecretTree<T> {
+    /// Secrets directly under the path.
+    pub secrets: BTreeMap<String, Secret<T>>,
+
+    /// Subtrees directly under the path.
+    pub subtrees: BTreeMap<String, SecretTree<T>>,
+}
+
+impl<T> SecretTree<T> {
+    /// Returns the number of secrets in the tree, including its subtrees.
+    pub fn len(&self) -> usize {
+        self.secrets.len() + self.subtrees.values().map(SecretTree::len).sum::<usize>()
+    }
+
+    /// Returns `true` if there are no secrets in the tree, including its subtrees.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Finds a secret by its path relative to the tree. For example: `database/password`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the secret, with `/` separated components.
+    pub fn get(&self, path: &str) -> Option<&Secret<T>> {
+        match path.split_once('/') {
+            Some((subtree, rest)) => self.subtrees.get(subtree)?.get(rest),
+            None => self.secrets.get(path),
+        }
+    }
+}
+
+impl<T> Default for SecretTree<T> {
+    fn default() -> Self {
+        Self {
+            secrets: BTreeMap::new(),
+            subtrees: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Debug for SecretTree<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretTree")
+            .field("secrets", &self.secrets)
+            .field("subtrees", &self.subtrees)
+            .finish()
+    }
+}
+
 /// Trait used to cast a secret retrieved from a manager to its correct type. To support a new type
 /// T, T must implement Decode, and add a variant for T in the
 /// [SecretData](crate::secret::SecretData] enum.
@@ -95,6 +675,26 @@ pub trait Decode: Send {
     fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self>
     where
         Self: Sized;
+
+    /// Tries to cast raw bytes into its real datatype.
+    ///
+    /// This is used by implementations that can not tell string secrets apart from binary ones
+    /// (for example, secrets mounted as files). By default, valid UTF-8 content is decoded as a
+    /// string secret and anything else as a binary secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `raw` - The secret's content.
+    fn decode_raw(secret_name: &str, raw: Vec<u8>) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        match String::from_utf8(raw) {
+            Ok(s) => Self::decode(secret_name, SecretData::Str(s)),
+            Err(e) => Self::decode(secret_name, SecretData::Bytes(e.into_bytes().into())),
+        }
+    }
 }
 
 impl Decode for String {
@@ -106,11 +706,209 @@ impl Decode for String {
     }
 }
 
+/// Binary secret, taking over the buffer of the provider when nothing else shares it, and copying
+/// it otherwise.
 impl Decode for Vec<u8> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        match secret_data {
+            SecretData::Bytes(b) => Ok(b.into()),
+            _ => Err(SecretsProviderError::InvalidType(secret_name.to_string())),
+        }
+    }
+
+    fn decode_raw(_secret_name: &str, raw: Vec<u8>) -> Result<Self> {
+        Ok(raw)
+    }
+}
+
+/// String secret kept in a [secrecy::SecretString], zeroized when dropped.
+#[cfg(feature = "secrecy")]
+impl Decode for secrecy::SecretString {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        String::decode(secret_name, secret_data).map(Self::from)
+    }
+}
+
+/// Binary secret kept in a [secrecy::SecretSlice], zeroized when dropped.
+#[cfg(feature = "secrecy")]
+impl Decode for secrecy::SecretSlice<u8> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        Vec::<u8>::decode(secret_name, secret_data).map(Self::from)
+    }
+
+    fn decode_raw(_secret_name: &str, raw: Vec<u8>) -> Result<Self> {
+        Ok(Self::from(raw))
+    }
+}
+
+/// Binary secret in the [Bytes] buffer of the provider, without copying it. Lookups of a secret
+/// kept in memory, and clones of the value, share the same buffer, so large secrets (for example,
+/// certificate bundles) can be handed to several consumers without copying them.
+impl Decode for Bytes {
     fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
         match secret_data {
             SecretData::Bytes(b) => Ok(b),
             _ => Err(SecretsProviderError::InvalidType(secret_name.to_string())),
         }
     }
+
+    fn decode_raw(_secret_name: &str, raw: Vec<u8>) -> Result<Self> {
+        Ok(Self::from(raw))
+    }
+}
+
+/// Value wiped when it is dropped, so the plaintext does not linger in freed memory.
+#[cfg(feature = "zeroize")]
+impl<T: Decode + zeroize::Zeroize> Decode for zeroize::Zeroizing<T> {
+    fn decode(secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        T::decode(secret_name, secret_data).map(Self::new)
+    }
+
+    fn decode_raw(secret_name: &str, raw: Vec<u8>) -> Result<Self> {
+        T::decode_raw(secret_name, raw).map(Self::new)
+    }
+}
+
+// Used to read secrets without caring about their type (for example, in self-tests).
+impl Decode for SecretData {
+    fn decode(_secret_name: &str, secret_data: SecretData) -> Result<Self> {
+        Ok(secret_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret<T>(value: T) -> Secret<T> {
+        Secret {
+            name: "database-password".to_string(),
+            version: "1".to_string(),
+            secret: value,
+            version_stages: vec!["current".to_string()],
+            is_current: Some(true),
+            backend: "Test",
+            tags: None,
+            created_at: None,
+            last_rotated_at: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn secrets_can_be_borrowed_or_split() {
+        let secret = secret("hunter2".to_string());
+        assert_eq!("hunter2", secret.expose());
+
+        let (value, parts) = secret.into_parts();
+        assert_eq!("hunter2", value);
+        assert_eq!("database-password", parts.name);
+        assert_eq!("1", parts.version);
+        assert_eq!(Some(true), parts.is_current());
+        assert_eq!(parts.version_stages(), ["current"]);
+    }
+
+    #[cfg(feature = "secrecy")]
+    #[test]
+    fn secrets_can_be_kept_in_secrecy_wrappers() {
+        use secrecy::{ExposeSecret, SecretSlice, SecretString};
+
+        let string_secret = secret(SecretData::from("hunter2"))
+            .decode::<SecretString>()
+            .unwrap()
+            .reveal();
+        assert_eq!("hunter2", string_secret.expose_secret());
+
+        let binary_secret = secret(SecretData::from(vec![1, 2, 3]))
+            .decode::<SecretSlice<u8>>()
+            .unwrap()
+            .reveal();
+        assert_eq!([1, 2, 3], binary_secret.expose_secret());
+
+        assert!(matches!(
+            secret(SecretData::from(vec![1, 2, 3])).decode::<SecretString>(),
+            Err(SecretsProviderError::InvalidType(_))
+        ));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn secrets_can_be_zeroized() {
+        use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+        let revealed = secret("hunter2".to_string()).reveal_zeroizing();
+        assert_eq!("hunter2", revealed.as_str());
+
+        let mut zeroized = secret("hunter2".to_string());
+        zeroized.zeroize();
+        assert!(zeroized.reveal().is_empty());
+
+        // Values decoded in Zeroizing are wiped when the secret is dropped
+        fn wiped_on_drop<T: ZeroizeOnDrop>(_: &T) {}
+        let wrapped = secret(SecretData::from("hunter2"))
+            .decode::<Zeroizing<String>>()
+            .unwrap();
+        wiped_on_drop(&wrapped);
+        assert_eq!("hunter2", wrapped.reveal().as_str());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn secrets_are_redacted_when_serialized() {
+        #[derive(serde::Serialize)]
+        struct Snapshot {
+            secret: Secret<String>,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Export {
+            #[serde(serialize_with = "Secret::serialize_revealed")]
+            secret: Secret<String>,
+        }
+
+        let snapshot = serde_json::to_value(Snapshot {
+            secret: secret("hunter2".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "secret": { "name": "database-password", "version": "1", "secret": "*****" }
+            }),
+            snapshot
+        );
+
+        let export = serde_json::to_value(Export {
+            secret: secret("hunter2".to_string()),
+        })
+        .unwrap();
+        assert_eq!("hunter2", export["secret"]["secret"]);
+    }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn secrets_can_be_compared_in_constant_time() {
+        let token = secret("hunter2".to_string());
+        assert!(token.ct_eq("hunter2"));
+        assert!(!token.ct_eq("unter2"));
+        assert!(!token.ct_eq("hunter2!"));
+
+        let key = secret(vec![1u8, 2, 3]);
+        assert!(key.ct_eq([1, 2, 3]));
+        assert!(!key.ct_eq("hunter2"));
+    }
+
+    #[cfg(feature = "ct-partial-eq")]
+    #[test]
+    fn secrets_can_be_compared_with_eq() {
+        let token = secret("hunter2".to_string());
+        assert!(token == "hunter2");
+        assert!(token != *"another token");
+        assert!(token == *b"hunter2".as_slice());
+
+        // Only the values are compared, whatever their types
+        let copy = secret(b"hunter2".to_vec());
+        let key = secret(vec![1u8, 2, 3]);
+        assert!(token == copy);
+        assert!(token != key);
+    }
 }
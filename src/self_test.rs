@@ -0,0 +1,65 @@
+//! Structured report of a provider's self-test.
+//!
+//! See [SecretsProvider::self_test](crate::SecretsProvider::self_test).
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Outcome of a single self-test check.
+#[derive(Clone, Debug)]
+pub struct SelfTestCheck {
+    /// Name of the check. For example: `list_secrets`.
+    pub name: String,
+
+    /// How long the check took.
+    pub duration: Duration,
+
+    /// Error message if the check failed.
+    pub error: Option<String>,
+}
+
+impl SelfTestCheck {
+    /// Returns `true` if the check succeeded.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Runs a check, measuring how long it takes.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the check.
+    /// * `check` - Future resolving to the outcome of the check.
+    pub async fn run<E: Display>(
+        name: impl Into<String>,
+        check: impl Future<Output = std::result::Result<(), E>>,
+    ) -> Self {
+        let start = Instant::now();
+        let result = check.await;
+
+        Self {
+            name: name.into(),
+            duration: start.elapsed(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Report returned by [SecretsProvider::self_test](crate::SecretsProvider::self_test).
+#[derive(Clone, Debug, Default)]
+pub struct SelfTestReport {
+    /// Checks run by the self-test, in execution order.
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Returns `true` if every check succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(SelfTestCheck::passed)
+    }
+
+    /// Returns the checks that failed.
+    pub fn failed_checks(&self) -> impl Iterator<Item = &SelfTestCheck> {
+        self.checks.iter().filter(|c| !c.passed())
+    }
+}
@@ -0,0 +1,120 @@
+//! Golden mapping of backend failures onto lookup results.
+use std::fmt::Display;
+
+use crate::secret::SecretData;
+use crate::{SecretsProvider, SecretsProviderError};
+
+/// Version used when looking up a specific version. It should not exist in any backend, but it is
+/// formatted as a UUID so that backends validating version ids (like AWS) accept it.
+const CONFORMANCE_VERSION: &str = "00000000-0000-4000-8000-000000000000";
+
+/// Backend failures every implementation must map the same way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackendFault {
+    /// The secret (or the requested version) does not exist.
+    NotFound,
+
+    /// The credentials are not allowed to read the secret.
+    AccessDenied,
+
+    /// The backend rejected the request because of rate limiting.
+    Throttled,
+
+    /// The backend could not be reached.
+    Network,
+}
+
+impl BackendFault {
+    /// Result a lookup must have when the backend fails this way, according to
+    /// [GOLDEN_ERROR_MAPPING].
+    pub fn expected_outcome(self) -> ExpectedOutcome {
+        GOLDEN_ERROR_MAPPING
+            .iter()
+            .find(|(fault, _)| *fault == self)
+            .map(|(_, outcome)| *outcome)
+            .expect("Every backend fault has an expected outcome")
+    }
+}
+
+impl Display for BackendFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendFault::NotFound => write!(f, "not found"),
+            BackendFault::AccessDenied => write!(f, "access denied"),
+            BackendFault::Throttled => write!(f, "throttled"),
+            BackendFault::Network => write!(f, "network failure"),
+        }
+    }
+}
+
+/// Result of a lookup when the backend fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpectedOutcome {
+    /// The lookup succeeds without secret (`Ok(None)`).
+    Missing,
+
+    /// The lookup fails with [ProviderFailed](crate::SecretsProviderError::ProviderFailed).
+    ProviderFailed,
+}
+
+/// Reference mapping of backend failures onto lookup results.
+///
+/// Missing secrets are not errors. Any other failure is reported as
+/// [ProviderFailed](crate::SecretsProviderError::ProviderFailed), which callers may retry.
+/// [Initialization](crate::SecretsProviderError::Initialization) is reserved to provider creation,
+/// and [InvalidType](crate::SecretsProviderError::InvalidType) to secrets that do exist.
+pub const GOLDEN_ERROR_MAPPING: &[(BackendFault, ExpectedOutcome)] = &[
+    (BackendFault::NotFound, ExpectedOutcome::Missing),
+    (BackendFault::AccessDenied, ExpectedOutcome::ProviderFailed),
+    (BackendFault::Throttled, ExpectedOutcome::ProviderFailed),
+    (BackendFault::Network, ExpectedOutcome::ProviderFailed),
+];
+
+/// Checks that a provider whose backend fails with `fault` maps the failure as stated in
+/// [GOLDEN_ERROR_MAPPING], both when looking up the current version of a secret and a specific
+/// version.
+///
+/// Returns a description of the first mismatch.
+///
+/// # Arguments
+///
+/// * `provider` - Secrets Provider in front of the failing backend.
+/// * `secret_name` - Secret to look up. Its type does not matter.
+/// * `fault` - How the backend fails.
+pub async fn check_error_mapping<P: SecretsProvider + Sync + ?Sized>(
+    provider: &P,
+    secret_name: &str,
+    fault: BackendFault,
+) -> Result<(), String> {
+    let expected = fault.expected_outcome();
+
+    let current = provider.find::<SecretData>(secret_name).await;
+    check_outcome("find", fault, expected, current)?;
+
+    let versioned = provider
+        .find_with_version::<SecretData>(secret_name, CONFORMANCE_VERSION)
+        .await;
+    check_outcome("find_with_version", fault, expected, versioned)
+}
+
+fn check_outcome<T>(
+    lookup: &str,
+    fault: BackendFault,
+    expected: ExpectedOutcome,
+    result: crate::Result<Option<T>>,
+) -> Result<(), String> {
+    let actual = match &result {
+        Ok(None) => "Ok(None)".to_string(),
+        Ok(Some(_)) => "a secret".to_string(),
+        Err(e) => format!("{:?}", e),
+    };
+
+    match (expected, &result) {
+        (ExpectedOutcome::Missing, Ok(None)) => Ok(()),
+        (ExpectedOutcome::ProviderFailed, Err(SecretsProviderError::ProviderFailed(_))) => Ok(()),
+        _ => Err(format!(
+            "{} mapped backend failure \"{}\" to {}, expected {:?}",
+            lookup, fault, actual, expected
+        )),
+    }
+}
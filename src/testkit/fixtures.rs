@@ -0,0 +1,62 @@
+//! Secrets seeded by the conformance suite.
+use super::{SecretsProviderTestExt, TestkitError};
+
+pub const SECRET_1: &str = "84cd01f7f3e07756be8c3e133275616308921356f30fe0df63cd56fdf26da8ae";
+pub const SECRET_1_NAME: &str = "secret-1";
+pub const SECRET_2: &str = "4ce6a2e359976bfd186eb24c19fe0223a241add277b649bb9e5e8464ee36a9d7";
+pub const SECRET_2_NAME: &str = "secret-2";
+pub const SECRET_3: &str = "498181c80a3ecd8c2a9a05c5570f62990e5aae0e2d25743178300d7f5e9bf9d2";
+pub const SECRET_3_NAME: &str = "secret-3";
+pub const SECRET_4: &[u8; 64] = b"54a5d2d0ee46c477f4a5b4c2570099ac91aa98dcadd033c460f46853fc362f9d";
+pub const SECRET_4_NAME: &str = "secret-4";
+pub const SECRET_5: &[u8; 64] = b"9c98e5d1cd7582e11a32646216a64adf62a8c484901aa5c9fd722fc7465a19f0";
+pub const SECRET_5_NAME: &str = "secret-5";
+pub const SECRET_6: &[u8; 64] = b"0ae4b3a49454a3a8b9f7c1eed386c6c762283023d725d9591521718dfe9764a1";
+pub const SECRET_6_NAME: &str = "secret-6";
+
+pub const VERSIONED_SECRET_NAME: &str = "versioned-secret";
+pub const VERSIONED_SECRET_VERSION_1: &str =
+    "51cc0c173419b77cedcaf322411262018cd012a95920a3c4d7ae577ff76c4b92";
+pub const VERSIONED_SECRET_VERSION_2: &str =
+    "a329ca5df23159a7fa6400f919193fb02b59bc9cdc7d6527f1ca2cb7ed668121";
+
+/// Seeds a provider with the fixtures. The versioned secret is written twice, so it has two
+/// versions.
+///
+/// Fails with the first call that failed, and the error of its backend.
+pub async fn seed_secrets_provider(
+    provider: &mut (impl SecretsProviderTestExt + Send),
+) -> Result<(), TestkitError> {
+    let string_secrets = [
+        (SECRET_1_NAME, SECRET_1),
+        (SECRET_2_NAME, SECRET_2),
+        (SECRET_3_NAME, SECRET_3),
+    ];
+    for (name, value) in string_secrets {
+        provider
+            .add_string_secret(name, value)
+            .await
+            .map_err(|e| TestkitError::new("add_string_secret", name, e))?;
+    }
+
+    let binary_secrets = [
+        (SECRET_4_NAME, SECRET_4),
+        (SECRET_5_NAME, SECRET_5),
+        (SECRET_6_NAME, SECRET_6),
+    ];
+    for (name, value) in binary_secrets {
+        provider
+            .add_binary_secret(name, value)
+            .await
+            .map_err(|e| TestkitError::new("add_binary_secret", name, e))?;
+    }
+
+    for value in [VERSIONED_SECRET_VERSION_1, VERSIONED_SECRET_VERSION_2] {
+        provider
+            .add_string_secret(VERSIONED_SECRET_NAME, value)
+            .await
+            .map_err(|e| TestkitError::new("add_string_secret", VERSIONED_SECRET_NAME, e))?;
+    }
+
+    Ok(())
+}
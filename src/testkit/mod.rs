@@ -0,0 +1,34 @@
+//! Conformance helpers for Secrets Provider implementations.
+//!
+//! Every implementation talks to a different backend, but all of them must behave the same way,
+//! so callers can switch implementations without changing their code. This module contains the
+//! pieces used to check it:
+//!
+//! - [run_conformance_suite] seeds a provider with the [fixtures] and checks that lookups return
+//!   them as expected. It returns a structured report, so it can be driven from any test
+//!   framework, or even from a binary against a real backend.
+//! - [GOLDEN_ERROR_MAPPING] is the reference mapping of backend failures onto lookup results,
+//!   and [check_error_mapping] asserts that an implementation follows it. Implementations test it
+//!   by putting a provider in front of a failing backend (for example, a mocked API answering
+//!   `429 Too Many Requests`).
+//!
+#![cfg_attr(not(feature = "memory"), doc = "```ignore")]
+//! ```rust,no_run
+//! use secrets_provider::implementations::memory::MemorySecretsProvider;
+//! use secrets_provider::testkit::{run_conformance_suite, ConformanceOptions};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let mut provider = MemorySecretsProvider::new();
+//!     let report = run_conformance_suite(&mut provider, &ConformanceOptions::default()).await;
+//!     assert!(report.passed(), "Conformance suite failed: {report:?}");
+//! }
+//! ```
+mod error_mapping;
+pub mod fixtures;
+mod suite;
+mod test_ext;
+
+pub use error_mapping::{check_error_mapping, BackendFault, ExpectedOutcome, GOLDEN_ERROR_MAPPING};
+pub use suite::{run_conformance_suite, ConformanceOptions, ConformanceReport};
+pub use test_ext::{SecretsProviderTestExt, TestkitError};
@@ -0,0 +1,333 @@
+//! Conformance suite runner.
+use std::fmt::Debug;
+
+use super::fixtures::*;
+use super::{check_error_mapping, BackendFault, SecretsProviderTestExt};
+use crate::secret::{Decode, SecretData};
+use crate::self_test::SelfTestCheck;
+use crate::{SecretsProvider, SecretsProviderError, VersionStage};
+
+/// Cases run by [run_conformance_suite].
+#[derive(Clone, Debug)]
+pub struct ConformanceOptions {
+    /// Seeds the provider with the [fixtures](super::fixtures) before running the cases. Disable
+    /// it when the provider was seeded beforehand.
+    pub seed: bool,
+
+    /// Runs the cases reading previous versions. Disable it for implementations that only keep
+    /// the current version of each secret.
+    pub versions: bool,
+
+    /// Runs the cases checking that string secrets can not be read as binary ones, and the other
+    /// way around. Disable it for implementations that can not tell them apart.
+    pub strict_types: bool,
+}
+
+impl Default for ConformanceOptions {
+    fn default() -> Self {
+        Self {
+            seed: true,
+            versions: true,
+            strict_types: true,
+        }
+    }
+}
+
+/// Report returned by [run_conformance_suite].
+#[derive(Clone, Debug, Default)]
+pub struct ConformanceReport {
+    /// Cases run by the suite, in execution order.
+    pub cases: Vec<SelfTestCheck>,
+}
+
+impl ConformanceReport {
+    /// Returns `true` if every case succeeded.
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(SelfTestCheck::passed)
+    }
+
+    /// Returns the cases that failed.
+    pub fn failed_cases(&self) -> impl Iterator<Item = &SelfTestCheck> {
+        self.cases.iter().filter(|c| !c.passed())
+    }
+}
+
+/// Runs the conformance suite against a provider. These are the same cases as the generic
+/// tests, without any test framework: failures are reported instead of panicking.
+///
+/// # Arguments
+///
+/// * `provider` - Secrets Provider to check. It is seeded with the
+///   [fixtures](super::fixtures), unless disabled in the options.
+/// * `options` - Cases to run.
+pub async fn run_conformance_suite<P>(
+    provider: &mut P,
+    options: &ConformanceOptions,
+) -> ConformanceReport
+where
+    P: SecretsProvider + SecretsProviderTestExt + Send + Sync,
+{
+    let mut report = ConformanceReport::default();
+    // Every case reads the fixtures, so none of them can pass without them
+    if options.seed {
+        let seed = SelfTestCheck::run("seed", async {
+            seed_secrets_provider(provider)
+                .await
+                .map_err(|e| e.to_string())
+        })
+        .await;
+        let seeded = seed.passed();
+        report.cases.push(seed);
+        if !seeded {
+            return report;
+        }
+    }
+    let provider = &*provider;
+
+    report.cases.push(
+        SelfTestCheck::run("read_string_secrets", async {
+            expect_secret(provider, SECRET_1_NAME, SECRET_1.to_string()).await?;
+            expect_secret(provider, SECRET_2_NAME, SECRET_2.to_string()).await?;
+            expect_secret(provider, SECRET_3_NAME, SECRET_3.to_string()).await
+        })
+        .await,
+    );
+
+    report.cases.push(
+        SelfTestCheck::run("read_binary_secrets", async {
+            expect_secret(provider, SECRET_4_NAME, SECRET_4.to_vec()).await?;
+            expect_secret(provider, SECRET_5_NAME, SECRET_5.to_vec()).await?;
+            expect_secret(provider, SECRET_6_NAME, SECRET_6.to_vec()).await
+        })
+        .await,
+    );
+
+    if options.strict_types {
+        report.cases.push(
+            SelfTestCheck::run("read_binary_as_string", async {
+                expect_invalid_type(provider.find::<String>(SECRET_4_NAME).await)
+            })
+            .await,
+        );
+
+        report.cases.push(
+            SelfTestCheck::run("read_string_as_binary", async {
+                expect_invalid_type(provider.find::<Vec<u8>>(SECRET_1_NAME).await)
+            })
+            .await,
+        );
+    }
+
+    report.cases.push(
+        SelfTestCheck::run("find_missing_secret", async {
+            match provider.find::<SecretData>("non-existent-secret").await {
+                Ok(None) => Ok(()),
+                Ok(Some(_)) => Err("Found a secret that does not exist".to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        })
+        .await,
+    );
+
+    report.cases.push(
+        SelfTestCheck::run(
+            "error_mapping_not_found",
+            check_error_mapping(provider, "non-existent-secret", BackendFault::NotFound),
+        )
+        .await,
+    );
+
+    if options.versions {
+        report.cases.push(
+            SelfTestCheck::run("find_previous_versions", async {
+                let versions = provider
+                    .list_secret_versions(VERSIONED_SECRET_NAME)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let [previous, current] = versions.as_slice() else {
+                    return Err(format!(
+                        "Expected 2 versions of {}, found {:?}",
+                        VERSIONED_SECRET_NAME, versions
+                    ));
+                };
+                if !current.stages.contains(&VersionStage::Current) {
+                    return Err(format!(
+                        "The last version of {} is not current: {:?}",
+                        VERSIONED_SECRET_NAME, current
+                    ));
+                }
+
+                expect_version(provider, &previous.id, VERSIONED_SECRET_VERSION_1, false).await?;
+                expect_version(provider, &current.id, VERSIONED_SECRET_VERSION_2, true).await
+            })
+            .await,
+        );
+    }
+
+    report.cases.push(
+        SelfTestCheck::run("batch_find", async {
+            let mut retrieved = provider
+                .batch_find::<String>(&[SECRET_1_NAME, "non-existent-secret", SECRET_3_NAME])
+                .await
+                .map_err(|e| e.to_string())?;
+
+            for (name, expected) in [(SECRET_1_NAME, SECRET_1), (SECRET_3_NAME, SECRET_3)] {
+                match retrieved.remove(name) {
+                    Some(secret) if secret.secret == expected => (),
+                    Some(_) => return Err(format!("Unexpected value for secret {}", name)),
+                    None => return Err(format!("Secret {} not found", name)),
+                }
+            }
+
+            match retrieved.keys().next() {
+                Some(name) => Err(format!("Unexpected secret {} found", name)),
+                None => Ok(()),
+            }
+        })
+        .await,
+    );
+
+    if options.strict_types {
+        report.cases.push(
+            SelfTestCheck::run("batch_find_mixed_types", async {
+                // Secret 4 is binary, Secret 1 is string
+                match provider
+                    .batch_find::<String>(&[SECRET_1_NAME, SECRET_4_NAME])
+                    .await
+                {
+                    Err(_) => Ok(()),
+                    Ok(_) => Err("Read secrets of mixed types as strings".to_string()),
+                }
+            })
+            .await,
+        );
+    }
+
+    report.cases.push(
+        SelfTestCheck::run("batch_find_detailed", async {
+            // Secret 4 is binary
+            let names = [SECRET_1_NAME, "non-existent-secret", SECRET_4_NAME];
+            let names = if options.strict_types {
+                &names[..]
+            } else {
+                &names[..2]
+            };
+            let mut retrieved = provider.batch_find_detailed::<String>(names).await;
+
+            match retrieved.remove(SECRET_1_NAME) {
+                Some(Ok(Some(secret))) if secret.secret == SECRET_1 => (),
+                Some(Ok(Some(_))) => {
+                    return Err(format!("Unexpected value for secret {}", SECRET_1_NAME))
+                }
+                Some(Ok(None)) => return Err(format!("Secret {} not found", SECRET_1_NAME)),
+                Some(Err(e)) => return Err(e.to_string()),
+                None => return Err(format!("No result for secret {}", SECRET_1_NAME)),
+            }
+            match retrieved.remove("non-existent-secret") {
+                Some(Ok(None)) => (),
+                Some(Ok(Some(_))) => {
+                    return Err("Unexpected secret non-existent-secret found".to_string())
+                }
+                Some(Err(e)) => return Err(e.to_string()),
+                None => return Err("No result for secret non-existent-secret".to_string()),
+            }
+            match retrieved.remove(SECRET_4_NAME) {
+                Some(result) => expect_invalid_type(result),
+                None if options.strict_types => {
+                    Err(format!("No result for secret {}", SECRET_4_NAME))
+                }
+                None => Ok(()),
+            }
+        })
+        .await,
+    );
+
+    report.cases.push(
+        SelfTestCheck::run("self_test_canary", async {
+            let report = provider.self_test(Some(SECRET_4_NAME)).await;
+            let failed = report.failed_checks().next().map(|check| {
+                format!(
+                    "Self-test check {} failed: {}",
+                    check.name,
+                    check.error.as_deref().unwrap_or_default()
+                )
+            });
+
+            failed.map_or(Ok(()), Err)
+        })
+        .await,
+    );
+
+    report
+}
+
+/// Checks that a secret exists with the expected value.
+async fn expect_secret<P, T>(provider: &P, name: &str, expected: T) -> Result<(), String>
+where
+    P: SecretsProvider + Sync + ?Sized,
+    T: Decode + PartialEq,
+{
+    let secret = provider
+        .find::<T>(name)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Secret {} not found", name))?;
+
+    if secret.name != name {
+        return Err(format!("Secret {} returned as {}", name, secret.name));
+    }
+    if secret.reveal() != expected {
+        return Err(format!("Unexpected value for secret {}", name));
+    }
+
+    Ok(())
+}
+
+/// Checks that a version of the versioned secret has the expected value, and that it is not
+/// reported as current (or not) wrongly.
+async fn expect_version<P>(
+    provider: &P,
+    version: &str,
+    expected: &str,
+    current: bool,
+) -> Result<(), String>
+where
+    P: SecretsProvider + Sync + ?Sized,
+{
+    let secret = provider
+        .find_with_version::<String>(VERSIONED_SECRET_NAME, version)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Version {} of {} not found", version, VERSIONED_SECRET_NAME))?;
+
+    if secret
+        .is_current()
+        .is_some_and(|is_current| is_current != current)
+    {
+        return Err(format!(
+            "Version {} of {} reported as {}current",
+            version,
+            VERSIONED_SECRET_NAME,
+            if current { "not " } else { "" }
+        ));
+    }
+    if secret.reveal() != expected {
+        return Err(format!(
+            "Unexpected value for version {} of {}",
+            version, VERSIONED_SECRET_NAME
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks that a lookup failed because of the secret type.
+fn expect_invalid_type<T: Debug>(result: crate::Result<T>) -> Result<(), String> {
+    match result {
+        Err(SecretsProviderError::InvalidType(_)) => Ok(()),
+        r => Err(format!(
+            "Should have failed with InvalidType error: {:?}",
+            r
+        )),
+    }
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use thiserror::Error;
+
+use crate::errors::SecretsProviderError;
+use crate::Result;
+
+/// This trait defines some test extensions to seed a secrets provider before
+/// each test.
+///
+/// Methods fail with the error of the backend call that failed, so failures point at the
+/// backend instead of a panic inside the extension.
+#[async_trait]
+pub trait SecretsProviderTestExt {
+    /// Inserts or replaces a string secret in the secret manager.
+    async fn add_string_secret(&mut self, name: &str, value: &str) -> Result<()>;
+
+    /// Inserts or replaces a binary secret in the secret manager.
+    async fn add_binary_secret(&mut self, name: &str, value: &[u8]) -> Result<()>;
+}
+
+/// Failure of a [SecretsProviderTestExt] call made by the test helpers.
+#[derive(Error, Debug)]
+#[error("{call} failed: {error}")]
+pub struct TestkitError {
+    /// Call that failed, with its secret. For example: `add_string_secret("secret-1")`.
+    pub call: String,
+
+    /// Error of the backend.
+    #[source]
+    pub error: SecretsProviderError,
+}
+
+impl TestkitError {
+    /// Wraps the error of a call.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Method of [SecretsProviderTestExt] that failed.
+    /// * `secret_name` - Secret of the call.
+    /// * `error` - Error of the backend.
+    pub fn new(method: &str, secret_name: &str, error: SecretsProviderError) -> Self {
+        Self {
+            call: format!("{}({:?})", method, secret_name),
+            error,
+        }
+    }
+}
@@ -0,0 +1,323 @@
+//! rustls server configurations built from secrets.
+//!
+//! Servers terminating TLS with certificates kept in a secrets manager load them with a
+//! [ServerConfigLoader]: it reads a PEM certificate chain and a PEM private key (see the
+//! [pem](crate::pem) module) from any [SecretsProvider], and assembles a [ServerConfig] with
+//! them.
+//!
+//! ```rust,ignore
+//! let loader = ServerConfigLoader::new(Arc::clone(&secrets_provider), "tls/chain", "tls/key");
+//! let mut config = loader.load_reloading().await?;
+//! config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+//! let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+//! ```
+//!
+//! Configurations returned by [load_reloading](ServerConfigLoader::load_reloading) pick up
+//! rotated certificates without restarting the server: the secrets are polled, and new
+//! handshakes use the new certificate once the version of either secret changes. A rotation
+//! whose certificate and key do not match yet (for example, the chain was updated before the
+//! key) is retried at the next poll, and the previous certificate is used meanwhile.
+//!
+//! The chain and the key can be stored in the same secret, by passing its name twice.
+use std::sync::{Arc, RwLock, RwLockReadGuard, Weak};
+use std::time::Duration;
+
+use rustls::crypto::CryptoProvider;
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::ServerConfig;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::time::MissedTickBehavior;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::SecretData;
+use crate::{Result, SecretsProvider};
+
+/// Time between two lookups of the secrets of a reloading configuration, unless another one is
+/// configured.
+const DEFAULT_RELOAD_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Shortest time between two lookups of the secrets. Tokio intervals can not be zero.
+const MIN_RELOAD_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Loads rustls server configurations from a certificate chain secret and a private key secret.
+pub struct ServerConfigLoader<P> {
+    provider: Arc<P>,
+    certificate_chain: String,
+    private_key: String,
+    crypto_provider: Option<Arc<CryptoProvider>>,
+    interval: Duration,
+}
+
+impl<P> ServerConfigLoader<P>
+where
+    P: SecretsProvider + Send + Sync + 'static,
+{
+    /// Creates a loader using the process default crypto provider of rustls, and polling every
+    /// 5 minutes for rotated certificates.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Secrets Provider the secrets are read from, shared with the rest of the
+    ///   application.
+    /// * `certificate_chain` - Name of the secret holding the PEM certificate chain, leaf first.
+    /// * `private_key` - Name of the secret holding the PEM private key of the leaf certificate.
+    pub fn new(provider: Arc<P>, certificate_chain: &str, private_key: &str) -> Self {
+        Self {
+            provider,
+            certificate_chain: certificate_chain.to_string(),
+            private_key: private_key.to_string(),
+            crypto_provider: None,
+            interval: DEFAULT_RELOAD_INTERVAL,
+        }
+    }
+
+    /// Sets the crypto provider of the configurations, instead of the process default one.
+    ///
+    /// # Arguments
+    ///
+    /// * `crypto_provider` - rustls crypto provider, for example
+    ///   `rustls::crypto::aws_lc_rs::default_provider()`.
+    pub fn with_crypto_provider(mut self, crypto_provider: Arc<CryptoProvider>) -> Self {
+        self.crypto_provider = Some(crypto_provider);
+        self
+    }
+
+    /// Sets the time between two lookups of the secrets of reloading configurations.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between two lookups. Each lookup is a request to the backend per
+    ///   secret, so short intervals increase its cost. Intervals under 1 millisecond are raised
+    ///   to 1 millisecond.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(MIN_RELOAD_INTERVAL);
+        self
+    }
+
+    /// Provider the secrets are read from.
+    pub fn provider(&self) -> &Arc<P> {
+        &self.provider
+    }
+
+    /// Loads a configuration with the current certificate chain and private key, without client
+    /// authentication. The configuration keeps them for its whole life.
+    ///
+    /// Fails with a [ProviderFailed](SecretsProviderError::ProviderFailed) error if a secret does
+    /// not exist, and with an [InvalidType](SecretsProviderError::InvalidType) error if the
+    /// secrets are not valid PEM or the key does not match the certificate.
+    pub async fn load(&self) -> Result<ServerConfig> {
+        let crypto_provider = self.crypto_provider()?;
+        let loaded = self.find_current(&crypto_provider).await?;
+        let resolver = Arc::new(StaticCertResolver(loaded.certified_key));
+        server_config(crypto_provider, resolver)
+    }
+
+    /// Loads a configuration like [load](Self::load), reloading the certificate chain and
+    /// private key when the version of either secret changes.
+    ///
+    /// The secrets are polled in a task spawned in the current Tokio runtime, until the
+    /// configuration is dropped.
+    pub async fn load_reloading(&self) -> Result<ServerConfig> {
+        let crypto_provider = self.crypto_provider()?;
+        let resolver = self.load_resolver().await?;
+        server_config(crypto_provider, resolver)
+    }
+
+    /// Loads the current certificate chain and private key into a resolver reloading them when
+    /// the version of either secret changes, for configurations built by the application (for
+    /// example, with client authentication).
+    ///
+    /// The secrets are polled in a task spawned in the current Tokio runtime, until the resolver
+    /// is dropped.
+    pub async fn load_resolver(&self) -> Result<Arc<ReloadingCertResolver>> {
+        let crypto_provider = self.crypto_provider()?;
+        let loaded = self.find_current(&crypto_provider).await?;
+        let resolver = Arc::new(ReloadingCertResolver {
+            current: RwLock::new(loaded),
+        });
+
+        let poller = Poller {
+            provider: Arc::clone(&self.provider),
+            certificate_chain: self.certificate_chain.clone(),
+            private_key: self.private_key.clone(),
+            crypto_provider,
+        };
+        let resolver_ref = Arc::downgrade(&resolver);
+        let period = self.interval;
+        tokio::spawn(async move { poller.poll(resolver_ref, period).await });
+
+        Ok(resolver)
+    }
+
+    fn crypto_provider(&self) -> Result<Arc<CryptoProvider>> {
+        self.crypto_provider
+            .clone()
+            .or_else(|| CryptoProvider::get_default().cloned())
+            .ok_or_else(|| {
+                SecretsProviderError::Initialization(
+                    "No rustls crypto provider: install a process default one, or set it with \
+                     with_crypto_provider"
+                        .to_string(),
+                )
+            })
+    }
+
+    async fn find_current(&self, crypto_provider: &CryptoProvider) -> Result<LoadedKey> {
+        let chain = find_data(&*self.provider, &self.certificate_chain).await?;
+        let key = find_data(&*self.provider, &self.private_key).await?;
+        LoadedKey::decode(chain, key, crypto_provider)
+    }
+}
+
+impl<P> std::fmt::Debug for ServerConfigLoader<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerConfigLoader")
+            .field("certificate_chain", &self.certificate_chain)
+            .field("private_key", &self.private_key)
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Certificate resolver serving the latest certificate chain and private key read from the
+/// secrets. Created by [ServerConfigLoader::load_resolver].
+pub struct ReloadingCertResolver {
+    current: RwLock<LoadedKey>,
+}
+
+impl ReloadingCertResolver {
+    /// Certificate chain and private key served to new handshakes.
+    pub fn certified_key(&self) -> Arc<CertifiedKey> {
+        Arc::clone(&self.read_current().certified_key)
+    }
+
+    /// Versions of the certificate chain secret and the private key secret served to new
+    /// handshakes.
+    pub fn versions(&self) -> (String, String) {
+        let current = self.read_current();
+        (current.chain_version.clone(), current.key_version.clone())
+    }
+
+    fn read_current(&self) -> RwLockReadGuard<'_, LoadedKey> {
+        // The key is replaced in a single step, so a poisoned lock still holds consistent data
+        self.current.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key())
+    }
+}
+
+impl std::fmt::Debug for ReloadingCertResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadingCertResolver")
+            .field("versions", &self.versions())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct StaticCertResolver(Arc<CertifiedKey>);
+
+impl ResolvesServerCert for StaticCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(Arc::clone(&self.0))
+    }
+}
+
+/// Certificate chain and private key, with the versions of the secrets they were read from.
+struct LoadedKey {
+    chain_version: String,
+    key_version: String,
+    certified_key: Arc<CertifiedKey>,
+}
+
+impl LoadedKey {
+    fn decode(
+        chain: crate::Secret<SecretData>,
+        key: crate::Secret<SecretData>,
+        crypto_provider: &CryptoProvider,
+    ) -> Result<Self> {
+        let chain = chain.decode::<Vec<CertificateDer<'static>>>()?;
+        let key = key.decode::<PrivateKeyDer<'static>>()?;
+        let certified_key = CertifiedKey::from_der(chain.secret, key.secret, crypto_provider)
+            .map_err(|e| {
+                SecretsProviderError::InvalidType(format!(
+                    "{} and {} ({})",
+                    chain.name, key.name, e
+                ))
+            })?;
+
+        Ok(Self {
+            chain_version: chain.version,
+            key_version: key.version,
+            certified_key: Arc::new(certified_key),
+        })
+    }
+}
+
+/// Polls the secrets of a [ReloadingCertResolver].
+struct Poller<P> {
+    provider: Arc<P>,
+    certificate_chain: String,
+    private_key: String,
+    crypto_provider: Arc<CryptoProvider>,
+}
+
+impl<P: SecretsProvider + Send + Sync> Poller<P> {
+    async fn poll(self, resolver: Weak<ReloadingCertResolver>, period: Duration) {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes right away, and the secrets were just loaded
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(resolver) = resolver.upgrade() else {
+                break;
+            };
+
+            // Failed lookups and invalid rotations are retried at the next interval
+            if let Ok(Some(loaded)) = self.find_changed(resolver.versions()).await {
+                *resolver.current.write().unwrap_or_else(|e| e.into_inner()) = loaded;
+            }
+        }
+    }
+
+    /// Reads the secrets, and decodes them if the version of either one changed.
+    async fn find_changed(&self, versions: (String, String)) -> Result<Option<LoadedKey>> {
+        let chain = find_data(&*self.provider, &self.certificate_chain).await?;
+        let key = find_data(&*self.provider, &self.private_key).await?;
+        if (&chain.version, &key.version) == (&versions.0, &versions.1) {
+            return Ok(None);
+        }
+
+        LoadedKey::decode(chain, key, &self.crypto_provider).map(Some)
+    }
+}
+
+fn server_config(
+    crypto_provider: Arc<CryptoProvider>,
+    resolver: Arc<dyn ResolvesServerCert>,
+) -> Result<ServerConfig> {
+    let config = ServerConfig::builder_with_provider(crypto_provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| SecretsProviderError::Initialization(e.to_string()))?
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    Ok(config)
+}
+
+async fn find_data<P: SecretsProvider + Sync>(
+    provider: &P,
+    secret_name: &str,
+) -> Result<crate::Secret<SecretData>> {
+    provider
+        .find::<SecretData>(secret_name)
+        .await?
+        .ok_or_else(|| {
+            SecretsProviderError::ProviderFailed(format!("TLS secret {} not found", secret_name))
+        })
+}
@@ -0,0 +1,138 @@
+//! Structured trace of a secret lookup, to debug "wrong value" reports.
+//!
+//! See [SecretsProvider::resolve_trace](crate::SecretsProvider::resolve_trace).
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use crate::secret::{Secret, SecretData};
+use crate::Result;
+
+/// Work done by one layer of the provider stack (a wrapper or the backend implementation).
+#[derive(Clone, Debug)]
+pub struct TraceStep {
+    /// Name of the layer. For example: `NamingProvider`.
+    pub layer: String,
+
+    /// What the layer did, if it changed the lookup. For example: the translated secret name.
+    pub details: Vec<String>,
+
+    /// How long the layer took, including the layers below it.
+    pub duration: Duration,
+}
+
+/// Result of a traced lookup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceOutcome {
+    /// The secret was found. Its value is never part of the trace.
+    Found {
+        version: String,
+        version_stages: Vec<String>,
+        is_current: Option<bool>,
+    },
+
+    /// The secret does not exist.
+    NotFound,
+
+    /// The lookup failed with the given error message.
+    Failed(String),
+}
+
+/// Trace returned by [SecretsProvider::resolve_trace](crate::SecretsProvider::resolve_trace).
+#[derive(Clone, Debug)]
+pub struct ResolveTrace {
+    /// Secret name, as requested to the outermost layer.
+    pub name: String,
+
+    /// Layers the lookup went through, the outermost first.
+    pub steps: Vec<TraceStep>,
+
+    /// Result of the lookup.
+    pub outcome: TraceOutcome,
+}
+
+impl ResolveTrace {
+    /// Traces a lookup made by a backend implementation, the innermost layer of a stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Name of the implementation.
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `lookup` - Future resolving to the looked up secret.
+    pub async fn run(
+        layer: impl Into<String>,
+        secret_name: &str,
+        lookup: impl Future<Output = Result<Option<Secret<SecretData>>>>,
+    ) -> Self {
+        let start = Instant::now();
+        let outcome = TraceOutcome::from(lookup.await);
+
+        Self {
+            name: secret_name.to_string(),
+            steps: vec![TraceStep {
+                layer: layer.into(),
+                details: Vec::new(),
+                duration: start.elapsed(),
+            }],
+            outcome,
+        }
+    }
+
+    /// Records a wrapper around the layers traced so far. Wrappers call it once the wrapped
+    /// provider returned its trace.
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Name of the wrapper.
+    /// * `secret_name` - Secret name, as requested to the wrapper.
+    /// * `details` - What the wrapper did.
+    /// * `start` - When the wrapper started the lookup.
+    pub fn wrap(
+        mut self,
+        layer: impl Into<String>,
+        secret_name: &str,
+        details: Vec<String>,
+        start: Instant,
+    ) -> Self {
+        self.name = secret_name.to_string();
+        self.steps.insert(
+            0,
+            TraceStep {
+                layer: layer.into(),
+                details,
+                duration: start.elapsed(),
+            },
+        );
+        self
+    }
+
+    /// Total duration of the lookup.
+    pub fn duration(&self) -> Duration {
+        self.steps.first().map_or(Duration::ZERO, |s| s.duration)
+    }
+
+    /// Names of the layers the lookup went through, the outermost first.
+    pub fn layers(&self) -> impl Iterator<Item = &str> {
+        self.steps.iter().map(|s| s.layer.as_str())
+    }
+}
+
+impl From<Result<Option<Secret<SecretData>>>> for TraceOutcome {
+    fn from(result: Result<Option<Secret<SecretData>>>) -> Self {
+        match result {
+            Ok(Some(secret)) => TraceOutcome::Found {
+                version: secret.version,
+                version_stages: secret.version_stages,
+                is_current: secret.is_current,
+            },
+            Ok(None) => TraceOutcome::NotFound,
+            Err(e) => TraceOutcome::Failed(e.to_string()),
+        }
+    }
+}
+
+/// Name of a layer, from its type name without module paths and generic parameters.
+pub(crate) fn layer_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
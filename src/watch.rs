@@ -0,0 +1,334 @@
+//! Change notifications of secrets, safe for slow consumers.
+//!
+//! Providers that detect changes (for example, the
+//! [Vault Agent implementation](crate::implementations::vault_agent) reloading rendered files)
+//! publish a [SecretUpdate] through an [UpdateSender], and every subscriber receives it through
+//! its own [UpdateReceiver]. All of them follow the same semantics:
+//!
+//! - Updates are conflated: a receiver keeps at most one pending update per secret, the latest.
+//!   When a secret changes again before the previous update was received, the previous update is
+//!   replaced, keeping its place in the queue. Memory is bounded by the number of secrets, no
+//!   matter how slow the consumer is, and the sender never waits for consumers.
+//! - Updates do not carry values: consumers read the current value with
+//!   [find](crate::SecretsProvider::find) when they receive an update, so they never act on a
+//!   stale one.
+//! - Consumers can check how far behind they are with [UpdateReceiver::lag]: the number of
+//!   pending updates, and the number of updates replaced before being received.
+//! - Receivers are closed once the provider is dropped and their pending updates were received.
+//!
+//! Backends without change notifications are watched with a [SecretWatcher], which polls the
+//! current version of a secret and publishes its changes the same way.
+use std::collections::{HashMap, VecDeque};
+use std::pin::pin;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+
+use futures_util::future::{self, Either};
+use tokio::sync::Notify;
+use tokio::time::MissedTickBehavior;
+
+use crate::secret::SecretData;
+use crate::SecretsProvider;
+
+/// Time between two lookups of a watched secret, unless another one is configured.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Shortest time between two lookups of a watched secret. Tokio intervals can not be zero.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Change of a secret.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretUpdate {
+    /// Name of the secret.
+    pub name: String,
+
+    /// New current version of the secret, or `None` if it was removed.
+    pub version: Option<String>,
+}
+
+/// How far behind a receiver is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WatchLag {
+    /// Updates waiting to be received, at most one per secret.
+    pub pending: usize,
+
+    /// Updates replaced by a newer update of the same secret before being received, since the
+    /// receiver subscribed.
+    pub conflated: u64,
+}
+
+/// Pending updates of a receiver, in the order their secrets first changed.
+#[derive(Default)]
+struct Pending {
+    order: VecDeque<String>,
+    updates: HashMap<String, SecretUpdate>,
+    conflated: u64,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    pending: Mutex<Pending>,
+    notify: Notify,
+    /// Notified when the receiver is dropped, shared with the sender.
+    dropped: Arc<Notify>,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Pending> {
+        // Pending updates are changed in a single step, so a poisoned lock still holds
+        // consistent data
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Publishes updates to every subscribed receiver.
+#[derive(Default)]
+pub struct UpdateSender {
+    receivers: Mutex<Vec<Weak<Shared>>>,
+    receiver_dropped: Arc<Notify>,
+}
+
+impl UpdateSender {
+    /// Creates a sender without receivers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a receiver of the updates sent from now on.
+    pub fn subscribe(&self) -> UpdateReceiver {
+        let shared = Arc::new(Shared {
+            dropped: Arc::clone(&self.receiver_dropped),
+            ..Shared::default()
+        });
+        self.lock_receivers().push(Arc::downgrade(&shared));
+        UpdateReceiver { shared }
+    }
+
+    /// Sends an update to every receiver, replacing their pending update of the same secret.
+    /// It never waits for the receivers.
+    ///
+    /// # Arguments
+    ///
+    /// * `update` - Change of a secret.
+    pub fn send(&self, update: SecretUpdate) {
+        let mut receivers = self.lock_receivers();
+        // Dropped receivers are forgotten
+        receivers.retain(|receiver| {
+            let Some(shared) = receiver.upgrade() else {
+                return false;
+            };
+
+            let mut pending = shared.lock();
+            if pending
+                .updates
+                .insert(update.name.clone(), update.clone())
+                .is_some()
+            {
+                pending.conflated += 1;
+            } else {
+                pending.order.push_back(update.name.clone());
+            }
+            drop(pending);
+
+            shared.notify.notify_one();
+            true
+        });
+    }
+
+    /// Number of receivers that were not dropped.
+    pub fn receiver_count(&self) -> usize {
+        let mut receivers = self.lock_receivers();
+        receivers.retain(|receiver| receiver.strong_count() > 0);
+        receivers.len()
+    }
+
+    /// Waits until every receiver was dropped, for example to stop producing updates nobody
+    /// receives. Returns right away if there are no receivers.
+    pub async fn closed(&self) {
+        // A receiver dropped since the count was taken leaves a permit for this call
+        while self.receiver_count() > 0 {
+            self.receiver_dropped.notified().await;
+        }
+    }
+
+    fn lock_receivers(&self) -> MutexGuard<'_, Vec<Weak<Shared>>> {
+        self.receivers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for UpdateSender {
+    fn drop(&mut self) {
+        for shared in self.lock_receivers().iter().filter_map(Weak::upgrade) {
+            shared.lock().closed = true;
+            shared.notify.notify_one();
+        }
+    }
+}
+
+impl std::fmt::Debug for UpdateSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateSender")
+            .field("receivers", &self.receiver_count())
+            .finish()
+    }
+}
+
+/// Receives the updates published by a provider.
+pub struct UpdateReceiver {
+    shared: Arc<Shared>,
+}
+
+impl UpdateReceiver {
+    /// Waits for the next update. Returns `None` once the provider was dropped and every pending
+    /// update was received.
+    ///
+    /// It is cancellation safe: if the future is dropped, no update is lost.
+    pub async fn recv(&mut self) -> Option<SecretUpdate> {
+        loop {
+            {
+                let mut pending = self.shared.lock();
+                if let Some(update) = pop(&mut pending) {
+                    return Some(update);
+                }
+                if pending.closed {
+                    return None;
+                }
+            }
+
+            // A notification sent since the lock was released is kept for this call
+            self.shared.notify.notified().await;
+        }
+    }
+
+    /// Returns the next update if there is one, without waiting.
+    pub fn try_recv(&mut self) -> Option<SecretUpdate> {
+        pop(&mut self.shared.lock())
+    }
+
+    /// How far behind the receiver is.
+    pub fn lag(&self) -> WatchLag {
+        let pending = self.shared.lock();
+        WatchLag {
+            pending: pending.order.len(),
+            conflated: pending.conflated,
+        }
+    }
+}
+
+impl Drop for UpdateReceiver {
+    fn drop(&mut self) {
+        self.shared.dropped.notify_one();
+    }
+}
+
+impl std::fmt::Debug for UpdateReceiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdateReceiver")
+            .field("lag", &self.lag())
+            .finish_non_exhaustive()
+    }
+}
+
+fn pop(pending: &mut Pending) -> Option<SecretUpdate> {
+    let name = pending.order.pop_front()?;
+    pending.updates.remove(&name)
+}
+
+/// Watches secrets of a provider without change notifications (for example, AWS Secrets
+/// Manager), by polling their current version.
+///
+/// Each watched secret is looked up right away, and then once per interval. An update is sent
+/// when its current version differs from the previous lookup, so the first lookup only sets the
+/// version changes are compared with. Failed lookups are retried at the next interval. Polling
+/// stops as soon as every receiver of the secret was dropped, releasing the provider: dropping the
+/// receivers is how a watch is cancelled.
+///
+/// The watcher spawns a task per watched secret, so it must be used within a Tokio runtime.
+pub struct SecretWatcher<P> {
+    provider: Arc<P>,
+    interval: Duration,
+}
+
+impl<P> SecretWatcher<P>
+where
+    P: SecretsProvider + Send + Sync + 'static,
+{
+    /// Creates a watcher polling the secrets of a provider every 30 seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Secrets Provider to poll, shared with the rest of the application.
+    pub fn new(provider: Arc<P>) -> Self {
+        Self {
+            provider,
+            interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Sets the time between two lookups of a watched secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between two lookups. Each lookup is a request to the backend, so
+    ///   short intervals increase its cost. Intervals under 1 millisecond are raised to 1
+    ///   millisecond.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(MIN_POLL_INTERVAL);
+        self
+    }
+
+    /// Provider whose secrets are watched.
+    pub fn provider(&self) -> &Arc<P> {
+        &self.provider
+    }
+
+    /// Starts watching a secret, returning the receiver of its updates.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    pub fn watch(&self, secret_name: &str) -> UpdateReceiver {
+        let updates = UpdateSender::new();
+        let receiver = updates.subscribe();
+        let provider = Arc::clone(&self.provider);
+        let name = secret_name.to_string();
+        let period = self.interval;
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            // Version seen by the previous successful lookup, `None` before the first one
+            let mut seen: Option<Option<String>> = None;
+            loop {
+                let tick = pin!(interval.tick());
+                let closed = pin!(updates.closed());
+                if let Either::Right(_) = future::select(tick, closed).await {
+                    break;
+                }
+
+                let Ok(secret) = provider.find::<SecretData>(&name).await else {
+                    continue;
+                };
+                let version = secret.map(|secret| secret.version);
+                if seen.as_ref().is_some_and(|seen| *seen != version) {
+                    updates.send(SecretUpdate {
+                        name: name.clone(),
+                        version: version.clone(),
+                    });
+                }
+                seen = Some(version);
+            }
+        });
+
+        receiver
+    }
+}
+
+impl<P> std::fmt::Debug for SecretWatcher<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretWatcher")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,374 @@
+//! Write operations, for provisioning tooling and test harnesses.
+//!
+//! Most services only read secrets, so writing is a separate, optional trait: [SecretsWriter] is
+//! implemented by the backends that support it, next to [SecretsProvider](crate::SecretsProvider).
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+
+use crate::errors::SecretsProviderError;
+use crate::secret::SecretData;
+use crate::{trace, Result, SecretsProvider};
+
+/// How [SecretsWriter::delete_secret] deletes a secret.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeleteOptions {
+    /// Schedules the deletion after the backend's default recovery window. For example, 30 days
+    /// for AWS Secrets Manager.
+    #[default]
+    DefaultRecoveryWindow,
+
+    /// Schedules the deletion after a recovery window, in days. AWS Secrets Manager accepts 7 to
+    /// 30 days.
+    RecoveryWindowDays(u32),
+
+    /// Deletes the secret immediately, without recovery window: it can not be restored.
+    Force,
+}
+
+/// Options of the writes made with [SecretsWriter::create_secret_with_options] and
+/// [SecretsWriter::put_secret_value_with_options].
+///
+/// ```rust
+/// use secrets_provider::WriteOptions;
+///
+/// let options = WriteOptions::new().with_client_request_token("2f0b6c47-e1ad-4c07-a66b-6d2b");
+/// assert_eq!(Some("2f0b6c47-e1ad-4c07-a66b-6d2b"), options.client_request_token());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    client_request_token: Option<String>,
+}
+
+impl WriteOptions {
+    /// Writes without client request token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the write idempotent: retrying it with the same token and value does not create
+    /// another version, and returns the version written by the first attempt. Reusing a token
+    /// with another value fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - Unique token of the write, kept across its retries. For example: a UUID.
+    pub fn with_client_request_token(mut self, token: impl Into<String>) -> Self {
+        self.client_request_token = Some(token.into());
+        self
+    }
+
+    /// Client request token of the write, if any.
+    pub fn client_request_token(&self) -> Option<&str> {
+        self.client_request_token.as_deref()
+    }
+}
+
+/// Result of an entry of [SecretsWriter::put_many].
+#[derive(Debug)]
+pub enum PutResult {
+    /// The entry was written, with this version.
+    Written(String),
+
+    /// Writing the entry failed.
+    Failed(SecretsProviderError),
+
+    /// The entry was written, then rolled back because another entry failed.
+    RolledBack,
+
+    /// The entry was written, and rolling it back failed: it is left written.
+    RollbackFailed(SecretsProviderError),
+
+    /// The entry was not written, because another entry failed.
+    NotAttempted,
+}
+
+/// Report returned by [SecretsWriter::put_many], with the result of each entry in the order of the
+/// entries.
+#[derive(Debug, Default)]
+pub struct PutManyReport {
+    /// Secret name and result of each entry.
+    pub results: Vec<(String, PutResult)>,
+}
+
+impl PutManyReport {
+    /// Returns `true` if every entry was written.
+    pub fn committed(&self) -> bool {
+        self.results
+            .iter()
+            .all(|(_, result)| matches!(result, PutResult::Written(_)))
+    }
+}
+
+/// Secrets writer interface.
+#[async_trait]
+pub trait SecretsWriter {
+    /// Creates a secret with its first version, and returns that version.
+    ///
+    /// Fails with an [AlreadyExists](crate::SecretsProviderError::AlreadyExists) error if the
+    /// secret exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `value` - Value of the secret. For example: `"password".into()`.
+    async fn create_secret(&self, secret_name: &str, value: SecretData) -> Result<String>;
+
+    /// Adds a new version to a secret, and returns that version. The new version becomes the
+    /// current one.
+    ///
+    /// Returns `None` if the secret does not exist: it must be created with
+    /// [create_secret](Self::create_secret) first.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `value` - Value of the new version. For example: `b"binary".to_vec().into()`.
+    async fn put_secret_value(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+    ) -> Result<Option<String>>;
+
+    /// Deletes a secret, and returns the time it is permanently deleted. Until then, it can be
+    /// restored with [restore_secret](Self::restore_secret), it is not found by lookups and its
+    /// name can not be reused.
+    ///
+    /// Returns `None` if the secret does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `options` - Recovery window of the secret, or forced deletion.
+    async fn delete_secret(
+        &self,
+        secret_name: &str,
+        options: DeleteOptions,
+    ) -> Result<Option<SystemTime>>;
+
+    /// Cancels the deletion of a secret, within its recovery window. Restoring a secret that is
+    /// not deleted does nothing.
+    ///
+    /// Returns `false` if the secret does not exist, or was permanently deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    async fn restore_secret(&self, secret_name: &str) -> Result<bool>;
+
+    /// Creates a secret with its first version, like [create_secret](Self::create_secret), with
+    /// write options.
+    ///
+    /// The default implementation fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error if a client request token is
+    /// set, for backends that can not make writes idempotent.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn create_secret_with_options(
+    ///     &self,
+    ///     secret_name: &str,
+    ///     value: SecretData,
+    ///     options: &WriteOptions,
+    /// ) -> Result<String>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `value` - Value of the secret. For example: `"password".into()`.
+    /// * `options` - Client request token of the write.
+    async fn create_secret_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<String> {
+        match options.client_request_token() {
+            Some(_) => Err(idempotency_unsupported::<Self>()),
+            None => self.create_secret(secret_name, value).await,
+        }
+    }
+
+    /// Adds a new version to a secret, like [put_secret_value](Self::put_secret_value), with
+    /// write options.
+    ///
+    /// The default implementation fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error if a client request token is
+    /// set, for backends that can not make writes idempotent.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn put_secret_value_with_options(
+    ///     &self,
+    ///     secret_name: &str,
+    ///     value: SecretData,
+    ///     options: &WriteOptions,
+    /// ) -> Result<Option<String>>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `value` - Value of the new version. For example: `b"binary".to_vec().into()`.
+    /// * `options` - Client request token of the write.
+    async fn put_secret_value_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<Option<String>> {
+        match options.client_request_token() {
+            Some(_) => Err(idempotency_unsupported::<Self>()),
+            None => self.put_secret_value(secret_name, value).await,
+        }
+    }
+
+    /// Writes a set of secrets, creating the missing ones and adding a new version to the
+    /// others, so environments can be bootstrapped at once. Entries are written all or nothing
+    /// where the backend allows it.
+    ///
+    /// The default implementation writes the entries one by one, and stops at the first failure.
+    /// The written entries are then rolled back, as best effort: created secrets are deleted
+    /// without recovery window, and the previous value of the other ones is written again, as a
+    /// new version. Lookups running meanwhile can see part of the entries.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn put_many(&self, entries: Vec<(String, SecretData)>) -> PutManyReport;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `entries` - Name and value of each secret.
+    async fn put_many(&self, entries: Vec<(String, SecretData)>) -> PutManyReport
+    where
+        Self: SecretsProvider + Sync,
+    {
+        let mut report = PutManyReport::default();
+        // Index of each written entry, and its previous value (`None` if it was created)
+        let mut written = Vec::new();
+        let mut failed = false;
+
+        for (name, value) in entries {
+            let result = if failed {
+                PutResult::NotAttempted
+            } else {
+                match write_entry(self, &name, value).await {
+                    Ok((version, previous)) => {
+                        written.push((report.results.len(), previous));
+                        PutResult::Written(version)
+                    }
+                    Err(e) => {
+                        failed = true;
+                        PutResult::Failed(e)
+                    }
+                }
+            };
+            report.results.push((name, result));
+        }
+
+        if failed {
+            for (index, previous) in written.into_iter().rev() {
+                let name = report.results[index].0.clone();
+                let rollback = match previous {
+                    Some(previous) => self.put_secret_value(&name, previous).await.map(|_| ()),
+                    None => self
+                        .delete_secret(&name, DeleteOptions::Force)
+                        .await
+                        .map(|_| ()),
+                };
+                report.results[index].1 = match rollback {
+                    Ok(()) => PutResult::RolledBack,
+                    Err(e) => PutResult::RollbackFailed(e),
+                };
+            }
+        }
+
+        report
+    }
+
+    /// Adds tags to a secret, replacing the values of the tags it already has, so provisioning
+    /// pipelines can attach ownership or cost center tags.
+    ///
+    /// Returns `false` if the secret does not exist. The default implementation fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn tag_secret(&self, secret_name: &str, tags: BTreeMap<String, String>) -> Result<bool>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `tags` - Tag keys and values.
+    async fn tag_secret(
+        &self,
+        _secret_name: &str,
+        _tags: BTreeMap<String, String>,
+    ) -> Result<bool> {
+        Err(tags_unsupported::<Self>())
+    }
+
+    /// Removes tags from a secret. Keys the secret is not tagged with are ignored.
+    ///
+    /// Returns `false` if the secret does not exist. The default implementation fails with an
+    /// [Unsupported](crate::SecretsProviderError::Unsupported) error.
+    ///
+    /// We are using async_trait, and that is why the signature of this function is so strange.
+    /// The pretty-printed signature for this functions is:
+    /// ```rust,ignore
+    /// async fn untag_secret(&self, secret_name: &str, tag_keys: &[&str]) -> Result<bool>;
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `secret_name` - A string that contains the secret name.
+    /// * `tag_keys` - Keys of the tags to remove.
+    async fn untag_secret(&self, _secret_name: &str, _tag_keys: &[&str]) -> Result<bool> {
+        Err(tags_unsupported::<Self>())
+    }
+}
+
+fn tags_unsupported<W: ?Sized>() -> SecretsProviderError {
+    SecretsProviderError::Unsupported(format!(
+        "{} does not support tagging secrets",
+        trace::layer_name::<W>()
+    ))
+}
+
+fn idempotency_unsupported<W: ?Sized>() -> SecretsProviderError {
+    SecretsProviderError::Unsupported(format!(
+        "{} does not support client request tokens",
+        trace::layer_name::<W>()
+    ))
+}
+
+/// Writes an entry of [SecretsWriter::put_many], returning its version and the value it replaced.
+async fn write_entry<W>(
+    writer: &W,
+    name: &str,
+    value: SecretData,
+) -> Result<(String, Option<SecretData>)>
+where
+    W: SecretsWriter + SecretsProvider + Sync + ?Sized,
+{
+    match writer.find::<SecretData>(name).await? {
+        Some(previous) => {
+            let version = writer.put_secret_value(name, value).await?.ok_or_else(|| {
+                SecretsProviderError::ProviderFailed(format!(
+                    "{} was deleted while being written",
+                    name
+                ))
+            })?;
+            Ok((version, Some(previous.reveal())))
+        }
+        None => Ok((writer.create_secret(name, value).await?, None)),
+    }
+}
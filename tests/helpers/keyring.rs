@@ -0,0 +1,81 @@
+//! In-memory keyring store, shared by every entry of the process.
+//!
+//! The mock store of the `keyring` crate keeps each password in its entry, so a password set
+//! through an entry can not be read through another one, as the OS keyrings allow.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Once};
+
+use keyring::credential::{Credential, CredentialApi, CredentialBuilderApi};
+
+type Store = Arc<Mutex<HashMap<(String, String), Vec<u8>>>>;
+
+/// Makes the shared store the default keyring store.
+pub fn use_shared_keyring() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        keyring::set_default_credential_builder(Box::new(SharedKeyringBuilder::default()));
+    });
+}
+
+#[derive(Debug, Default)]
+struct SharedKeyringBuilder {
+    store: Store,
+}
+
+impl CredentialBuilderApi for SharedKeyringBuilder {
+    fn build(
+        &self,
+        _target: Option<&str>,
+        service: &str,
+        user: &str,
+    ) -> keyring::Result<Box<Credential>> {
+        Ok(Box::new(SharedKeyringCredential {
+            store: self.store.clone(),
+            id: (service.to_string(), user.to_string()),
+        }))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[derive(Debug)]
+struct SharedKeyringCredential {
+    store: Store,
+    id: (String, String),
+}
+
+impl CredentialApi for SharedKeyringCredential {
+    fn set_secret(&self, secret: &[u8]) -> keyring::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .insert(self.id.clone(), secret.to_vec());
+        Ok(())
+    }
+
+    fn get_secret(&self) -> keyring::Result<Vec<u8>> {
+        self.store
+            .lock()
+            .unwrap()
+            .get(&self.id)
+            .cloned()
+            .ok_or(keyring::Error::NoEntry)
+    }
+
+    fn delete_credential(&self) -> keyring::Result<()> {
+        self.store
+            .lock()
+            .unwrap()
+            .remove(&self.id)
+            .map(|_| ())
+            .ok_or(keyring::Error::NoEntry)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
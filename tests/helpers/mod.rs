@@ -1 +1,4 @@
 pub mod test_ext;
+
+#[cfg(feature = "keyring")]
+pub mod keyring;
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use ::age::secrecy::ExposeSecret;
+use ::age::x25519::Identity;
+use secrets_provider::implementations::age::{AgeIdentitySource, AgeSecretsProvider};
+use tempfile::TempDir;
+
+/// Encrypted bundle and identity files in a temporary directory. The directory is removed when
+/// the wrapper is dropped.
+pub struct AgeTestWrapper {
+    pub identity: Identity,
+    pub dir: TempDir,
+}
+
+impl AgeTestWrapper {
+    /// Encrypts `content` for the test identity and writes it to `file_name`.
+    pub fn write_bundle(&self, file_name: &str, content: &str, armor: bool) -> PathBuf {
+        let recipient = self.identity.to_public();
+        let path = self.dir.path().join(file_name);
+        let encrypted = if armor {
+            ::age::encrypt_and_armor(&recipient, content.as_bytes())
+                .unwrap()
+                .into_bytes()
+        } else {
+            ::age::encrypt(&recipient, content.as_bytes()).unwrap()
+        };
+        std::fs::write(&path, encrypted).unwrap();
+        path
+    }
+
+    /// Writes the test identity to an identity file.
+    pub fn write_identity_file(&self) -> PathBuf {
+        let path = self.dir.path().join("identity.txt");
+        std::fs::write(
+            &path,
+            format!(
+                "# test identity\n{}\n",
+                self.identity.to_string().expose_secret()
+            ),
+        )
+        .unwrap();
+        path
+    }
+
+    pub fn identity_string(&self) -> String {
+        self.identity.to_string().expose_secret().to_string()
+    }
+
+    pub fn load_provider(&self, bundle: &Path) -> AgeSecretsProvider {
+        AgeSecretsProvider::new(bundle, AgeIdentitySource::File(self.write_identity_file()))
+            .unwrap()
+    }
+}
+
+pub fn load_test_wrapper() -> AgeTestWrapper {
+    AgeTestWrapper {
+        identity: Identity::generate(),
+        dir: tempfile::tempdir().unwrap(),
+    }
+}
@@ -1,11 +1,14 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::SystemTime;
 
 use crate::helpers::test_ext::SecretsProviderTestExt;
 use async_trait::async_trait;
 use aws_config::{BehaviorVersion, Region};
-use aws_sdk_secretsmanager::{primitives::Blob, Client};
+use aws_sdk_secretsmanager::Client;
 use secrets_provider::{
-    implementations::aws::AwsSecretsProvider, Decode, Secret, SecretsProvider, SecretsProviderError,
+    implementations::aws::AwsSecretsProvider, self_test::SelfTestReport, Decode, DeleteOptions,
+    ListFilter, Secret, SecretData, SecretsPage, SecretsProvider, SecretsProviderError,
+    SecretsWriter, VersionInfo, VersionStage, WriteOptions,
 };
 use serde::Deserialize;
 
@@ -33,58 +36,130 @@ impl SecretsProvider for AwsTestWrapper {
         self.provider.find_with_version(secret_name, version).await
     }
 
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find_with_stage(secret_name, stage).await
+    }
+
+    async fn list_secret_versions(
+        &self,
+        secret_name: &str,
+    ) -> Result<Vec<VersionInfo>, SecretsProviderError> {
+        self.provider.list_secret_versions(secret_name).await
+    }
+
     async fn batch_find<'n, T: Decode>(
         &self,
         secret_names: &[&'n str],
     ) -> Result<HashMap<&'n str, Secret<T>>, SecretsProviderError> {
         self.provider.batch_find(secret_names).await
     }
+
+    async fn list_secrets(
+        &self,
+        filter: &ListFilter,
+        page_token: Option<&str>,
+    ) -> Result<SecretsPage, SecretsProviderError> {
+        self.provider.list_secrets(filter, page_token).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.provider.self_test(canary).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.provider.backend()
+    }
 }
 
 #[async_trait]
-impl SecretsProviderTestExt for AwsTestWrapper {
-    async fn add_string_secret(&mut self, name: &str, value: &str) {
-        self.create_if_not_exists(name).await;
-        self.client
-            .put_secret_value()
-            .secret_id(name)
-            .secret_string(value)
-            .send()
-            .await
-            .unwrap();
+impl SecretsWriter for AwsTestWrapper {
+    async fn create_secret(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+    ) -> Result<String, SecretsProviderError> {
+        self.provider.create_secret(secret_name, value).await
     }
 
-    async fn add_binary_secret(&mut self, name: &str, value: &[u8]) {
-        self.create_if_not_exists(name).await;
-        self.client
-            .put_secret_value()
-            .secret_id(name)
-            .secret_binary(Blob::new(value))
-            .send()
+    async fn put_secret_value(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+    ) -> Result<Option<String>, SecretsProviderError> {
+        self.provider.put_secret_value(secret_name, value).await
+    }
+
+    async fn delete_secret(
+        &self,
+        secret_name: &str,
+        options: DeleteOptions,
+    ) -> Result<Option<SystemTime>, SecretsProviderError> {
+        self.provider.delete_secret(secret_name, options).await
+    }
+
+    async fn restore_secret(&self, secret_name: &str) -> Result<bool, SecretsProviderError> {
+        self.provider.restore_secret(secret_name).await
+    }
+
+    async fn create_secret_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<String, SecretsProviderError> {
+        self.provider
+            .create_secret_with_options(secret_name, value, options)
             .await
-            .unwrap();
     }
 
-    async fn list_secret_versions(&self, name: &str) -> Vec<String> {
-        let secret_versions = self
-            .client
-            .list_secret_version_ids()
-            .secret_id(name)
-            .send()
+    async fn put_secret_value_with_options(
+        &self,
+        secret_name: &str,
+        value: SecretData,
+        options: &WriteOptions,
+    ) -> Result<Option<String>, SecretsProviderError> {
+        self.provider
+            .put_secret_value_with_options(secret_name, value, options)
             .await
-            .unwrap();
-
-        let mut versions = secret_versions.versions.unwrap();
-        versions.sort_by(|a, b| {
-            a.created_date
-                .unwrap()
-                .partial_cmp(&b.created_date.unwrap())
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        versions
-            .into_iter()
-            .map(|e| e.version_id.unwrap())
-            .collect()
+    }
+
+    async fn tag_secret(
+        &self,
+        secret_name: &str,
+        tags: BTreeMap<String, String>,
+    ) -> Result<bool, SecretsProviderError> {
+        self.provider.tag_secret(secret_name, tags).await
+    }
+
+    async fn untag_secret(
+        &self,
+        secret_name: &str,
+        tag_keys: &[&str],
+    ) -> Result<bool, SecretsProviderError> {
+        self.provider.untag_secret(secret_name, tag_keys).await
+    }
+}
+
+#[async_trait]
+impl SecretsProviderTestExt for AwsTestWrapper {
+    async fn add_string_secret(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), SecretsProviderError> {
+        self.create_or_put(name, value.into()).await
+    }
+
+    async fn add_binary_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
+        self.create_or_put(name, value.into()).await
     }
 }
 
@@ -134,13 +209,26 @@ impl AwsTestWrapper {
         }
     }
 
-    async fn create_if_not_exists(&self, name: &str) {
-        let _ = self
-            .client
-            .create_secret()
-            .name(name.to_string())
-            .send()
-            .await;
+    /// Creates the secret, or adds a version to it if it already exists.
+    async fn create_or_put(
+        &self,
+        name: &str,
+        value: SecretData,
+    ) -> Result<(), SecretsProviderError> {
+        match self.provider.create_secret(name, value.clone()).await {
+            Ok(_) => Ok(()),
+            Err(SecretsProviderError::AlreadyExists(_)) => {
+                match self.provider.put_secret_value(name, value).await? {
+                    Some(_) => Ok(()),
+                    // Deleted between both requests
+                    None => Err(SecretsProviderError::ProviderFailed(format!(
+                        "Secret {} not found",
+                        name
+                    ))),
+                }
+            }
+            Err(e) => Err(e),
+        }
     }
 }
 
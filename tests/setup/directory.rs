@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use secrets_provider::implementations::directory::DirectorySecretsProvider;
+use tempfile::TempDir;
+
+use crate::seeds::constants::*;
+
+/// Directory tree secrets provider reading from a temporary directory. The directory is removed
+/// when the wrapper is dropped.
+pub struct DirectoryTestWrapper {
+    pub provider: DirectorySecretsProvider,
+    pub dir: TempDir,
+}
+
+impl DirectoryTestWrapper {
+    /// Writes a secret file under the secrets directory. `name` may include a version suffix.
+    pub fn write_secret(&self, name: &str, value: &[u8]) {
+        let path = self.dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(path, value).unwrap();
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+pub fn load_test_provider() -> DirectoryTestWrapper {
+    let dir = tempfile::tempdir().unwrap();
+    let wrapper = DirectoryTestWrapper {
+        provider: DirectorySecretsProvider::new(dir.path()),
+        dir,
+    };
+
+    wrapper.write_secret(SECRET_1_NAME, SECRET_1.as_bytes());
+    wrapper.write_secret(&format!("{}.v2", SECRET_1_NAME), SECRET_2.as_bytes());
+    wrapper.write_secret(SECRET_4_NAME, SECRET_4);
+    wrapper
+}
@@ -0,0 +1,54 @@
+use secrets_provider::implementations::doppler::{
+    DopplerSecretsProvider, DopplerSecretsProviderBuilder,
+};
+use wiremock::matchers::{bearer_token, method, path, query_param};
+use wiremock::{Mock, MockBuilder, MockServer, ResponseTemplate};
+
+use crate::seeds::constants::*;
+
+pub const DOPPLER_TOKEN: &str = "dp.st.test.token";
+pub const DOPPLER_VERSION: &str = "0a1b2c3d";
+
+// Doppler only accepts uppercase names, so the shared fixture names can not be used
+pub const DOPPLER_SECRET_1_NAME: &str = "SECRET_1";
+pub const DOPPLER_SECRET_2_NAME: &str = "SECRET_2";
+
+/// Doppler provider reading from a mocked Doppler API. The server is stopped when the wrapper is
+/// dropped.
+pub struct DopplerTestWrapper {
+    pub provider: DopplerSecretsProvider,
+    _server: MockServer,
+}
+
+/// Matches config download requests.
+pub fn config_download_request() -> MockBuilder {
+    Mock::given(method("GET"))
+        .and(path("/v3/configs/config/secrets/download"))
+        .and(query_param("format", "json"))
+        .and(bearer_token(DOPPLER_TOKEN))
+}
+
+/// Mocked config download, for the seeded secrets.
+pub fn config_download() -> Mock {
+    config_download_request().respond_with(
+        ResponseTemplate::new(200)
+            .insert_header("ETag", format!("W/\"{}\"", DOPPLER_VERSION))
+            .set_body_json(serde_json::json!({
+                DOPPLER_SECRET_1_NAME: SECRET_1,
+                DOPPLER_SECRET_2_NAME: SECRET_2,
+            })),
+    )
+}
+
+pub async fn load_test_provider() -> DopplerTestWrapper {
+    let server = MockServer::start().await;
+    config_download().mount(&server).await;
+
+    DopplerTestWrapper {
+        provider: DopplerSecretsProviderBuilder::new(DOPPLER_TOKEN.to_string())
+            .endpoint_override(server.uri())
+            .build()
+            .unwrap(),
+        _server: server,
+    }
+}
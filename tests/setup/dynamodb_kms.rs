@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_dynamodb::primitives::Blob as DynamoDbBlob;
+use aws_sdk_dynamodb::types::{
+    AttributeDefinition, AttributeValue, BillingMode, KeySchemaElement, KeyType,
+    ScalarAttributeType,
+};
+use aws_sdk_kms::primitives::Blob;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use secrets_provider::implementations::dynamodb_kms::DynamoDbKmsSecretsProvider;
+use serde::Deserialize;
+use sha2::Sha256;
+
+const DEFAULT_AWS_REGION: &str = "us-west-2";
+pub const TEST_TABLE: &str = "secrets-provider-tests";
+
+pub struct DynamoDbKmsTestWrapper {
+    pub provider: DynamoDbKmsSecretsProvider,
+    pub dynamodb: aws_sdk_dynamodb::Client,
+    pub kms: aws_sdk_kms::Client,
+    pub key_id: String,
+}
+
+impl DynamoDbKmsTestWrapper {
+    /// Create a DynamoDbKmsSecretsProvider loading the default test configuration
+    /// from files .env.test and .env.test.local.
+    pub async fn load_default() -> Self {
+        dotenv::from_filename(".env.test.local").ok();
+        dotenv::from_filename(".env.test").ok();
+
+        #[derive(Deserialize)]
+        struct AwsProviderConfig {
+            endpoint: String,
+        }
+        let AwsProviderConfig { endpoint } =
+            envy::from_env::<AwsProviderConfig>().expect("Could not load configuration");
+
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(DEFAULT_AWS_REGION.to_string()))
+            .endpoint_url(&endpoint)
+            .load()
+            .await;
+        let dynamodb = aws_sdk_dynamodb::Client::new(&config);
+        let kms = aws_sdk_kms::Client::new(&config);
+
+        // The table is left over by previous runs
+        let _ = dynamodb
+            .create_table()
+            .table_name(TEST_TABLE)
+            .attribute_definitions(attribute("name"))
+            .attribute_definitions(attribute("version"))
+            .key_schema(key("name", KeyType::Hash))
+            .key_schema(key("version", KeyType::Range))
+            .billing_mode(BillingMode::PayPerRequest)
+            .send()
+            .await;
+        let key_id = kms
+            .create_key()
+            .send()
+            .await
+            .unwrap()
+            .key_metadata
+            .unwrap()
+            .key_id;
+
+        Self {
+            provider: DynamoDbKmsSecretsProvider::new_at_endpoint(
+                DEFAULT_AWS_REGION,
+                &endpoint,
+                TEST_TABLE,
+            )
+            .await,
+            dynamodb,
+            kms,
+            key_id,
+        }
+    }
+
+    /// Writes a secret version, encrypted like `credstash put` does.
+    pub async fn put_secret(&self, name: &str, version: u64, value: &[u8]) {
+        self.put_secret_with_context(name, version, value, HashMap::new())
+            .await
+    }
+
+    /// Writes a secret version, with the data key bound to a KMS encryption context.
+    pub async fn put_secret_with_context(
+        &self,
+        name: &str,
+        version: u64,
+        value: &[u8],
+        encryption_context: HashMap<String, String>,
+    ) {
+        let data_key = self
+            .kms
+            .generate_data_key()
+            .key_id(&self.key_id)
+            .number_of_bytes(64)
+            .set_encryption_context(Some(encryption_context))
+            .send()
+            .await
+            .unwrap();
+        let key = data_key.plaintext.unwrap().into_inner();
+        let (aes_key, hmac_key) = key.split_at(32);
+
+        let mut contents = value.to_vec();
+        let mut counter = [0; 16];
+        counter[15] = 1;
+        ctr::Ctr128BE::<aes::Aes256>::new(aes_key.into(), &counter.into())
+            .apply_keystream(&mut contents);
+        let mut mac = Hmac::<Sha256>::new_from_slice(hmac_key).unwrap();
+        mac.update(&contents);
+        let hmac: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        let encrypted_key = data_key.ciphertext_blob.unwrap().into_inner();
+        self.dynamodb
+            .put_item()
+            .table_name(TEST_TABLE)
+            .item("name", AttributeValue::S(name.to_string()))
+            .item("version", AttributeValue::S(format!("{:019}", version)))
+            .item("key", AttributeValue::S(STANDARD.encode(encrypted_key)))
+            .item("contents", AttributeValue::S(STANDARD.encode(contents)))
+            .item("hmac", AttributeValue::B(DynamoDbBlob::new(hmac)))
+            .item("digest", AttributeValue::S("SHA256".to_string()))
+            .send()
+            .await
+            .unwrap();
+    }
+
+    /// Replaces the HMAC of a secret version, as if its contents were tampered with.
+    pub async fn tamper_hmac(&self, name: &str, version: u64) {
+        self.dynamodb
+            .update_item()
+            .table_name(TEST_TABLE)
+            .key("name", AttributeValue::S(name.to_string()))
+            .key("version", AttributeValue::S(format!("{:019}", version)))
+            .update_expression("SET hmac = :hmac")
+            .expression_attribute_values(":hmac", AttributeValue::S("00".repeat(32)))
+            .send()
+            .await
+            .unwrap();
+    }
+}
+
+fn attribute(name: &str) -> AttributeDefinition {
+    AttributeDefinition::builder()
+        .attribute_name(name)
+        .attribute_type(ScalarAttributeType::S)
+        .build()
+        .unwrap()
+}
+
+fn key(name: &str, key_type: KeyType) -> KeySchemaElement {
+    KeySchemaElement::builder()
+        .attribute_name(name)
+        .key_type(key_type)
+        .build()
+        .unwrap()
+}
+
+pub async fn load_test_provider() -> DynamoDbKmsTestWrapper {
+    DynamoDbKmsTestWrapper::load_default().await
+}
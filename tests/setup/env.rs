@@ -0,0 +1,19 @@
+use secrets_provider::implementations::env::EnvSecretsProvider;
+
+use crate::seeds::constants::*;
+
+/// Creates an environment variables provider with a prefix unique to each test, so tests
+/// running in parallel do not see each other's variables.
+pub fn load_test_provider(test_prefix: &str) -> EnvSecretsProvider {
+    let provider = EnvSecretsProvider::new()
+        .with_prefix(test_prefix)
+        .with_base64_binaries();
+
+    std::env::set_var(provider.variable_name(SECRET_1_NAME), SECRET_1);
+    std::env::set_var(
+        provider.variable_name(SECRET_4_NAME),
+        // SECRET_4 encoded in base64
+        "base64:NTRhNWQyZDBlZTQ2YzQ3N2Y0YTViNGMyNTcwMDk5YWM5MWFhOThkY2FkZDAzM2M0NjBmNDY4NTNmYzM2MmY5ZA==",
+    );
+    provider
+}
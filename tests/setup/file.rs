@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use secrets_provider::implementations::file::FileSecretsProvider;
+use tempfile::TempDir;
+
+/// JSON secrets file with the seeded secrets. Secret 4 is encoded in base64.
+pub const JSON_SECRETS_FILE: &str = r#"{
+    "secret-1": "84cd01f7f3e07756be8c3e133275616308921356f30fe0df63cd56fdf26da8ae",
+    "secret-4": "binary:NTRhNWQyZDBlZTQ2YzQ3N2Y0YTViNGMyNTcwMDk5YWM5MWFhOThkY2FkZDAzM2M0NjBmNDY4NTNmYzM2MmY5ZA=="
+}"#;
+
+/// YAML secrets file with the seeded secrets. Secret 4 is encoded in base64.
+pub const YAML_SECRETS_FILE: &str = r#"
+secret-1: 84cd01f7f3e07756be8c3e133275616308921356f30fe0df63cd56fdf26da8ae
+secret-4: binary:NTRhNWQyZDBlZTQ2YzQ3N2Y0YTViNGMyNTcwMDk5YWM5MWFhOThkY2FkZDAzM2M0NjBmNDY4NTNmYzM2MmY5ZA==
+"#;
+
+/// Secrets file provider reading from a temporary directory. The directory is removed when the
+/// wrapper is dropped.
+pub struct FileTestWrapper {
+    pub provider: FileSecretsProvider,
+    pub path: PathBuf,
+    pub dir: TempDir,
+}
+
+pub fn load_test_provider(file_name: &str, content: &str) -> FileTestWrapper {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(file_name);
+    std::fs::write(&path, content).unwrap();
+
+    FileTestWrapper {
+        provider: FileSecretsProvider::new(&path).unwrap(),
+        path,
+        dir,
+    }
+}
@@ -0,0 +1,125 @@
+use secrets_provider::implementations::hcp::{
+    HcpVaultSecretsProvider, HcpVaultSecretsProviderBuilder,
+};
+use wiremock::matchers::{bearer_token, body_string_contains, method, path};
+use wiremock::{Mock, MockBuilder, MockServer, ResponseTemplate};
+
+use crate::seeds::constants::*;
+
+pub const HCP_CLIENT_ID: &str = "test-client-id";
+pub const HCP_CLIENT_SECRET: &str = "test-client-secret";
+pub const HCP_ACCESS_TOKEN: &str = "test-access-token";
+pub const HCP_APP_PATH: &str =
+    "/secrets/2023-11-28/organizations/test-org/projects/test-project/apps/test-app";
+
+// HCP Vault Secrets does not accept '-' in names, so the shared fixture names can not be used
+pub const HCP_SECRET_1_NAME: &str = "secret_1";
+pub const HCP_SECRET_2_NAME: &str = "secret_2";
+
+/// HCP Vault Secrets provider reading from mocked HCP APIs. The server is stopped when the
+/// wrapper is dropped.
+pub struct HcpTestWrapper {
+    pub provider: HcpVaultSecretsProvider,
+    _server: MockServer,
+}
+
+/// Mocked service principal authentication.
+pub fn token_request() -> Mock {
+    Mock::given(method("POST"))
+        .and(path("/oauth2/token"))
+        .and(body_string_contains("grant_type=client_credentials"))
+        .and(body_string_contains(format!(
+            "client_secret={}",
+            HCP_CLIENT_SECRET
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": HCP_ACCESS_TOKEN,
+            "expires_in": 3600,
+            "token_type": "Bearer",
+        })))
+}
+
+/// Matches authenticated requests to a path of the test application.
+pub fn app_request(app_path: &str) -> MockBuilder {
+    Mock::given(method("GET"))
+        .and(path(format!("{}{}", HCP_APP_PATH, app_path)))
+        .and(bearer_token(HCP_ACCESS_TOKEN))
+}
+
+/// Opened static secret, as returned by HCP.
+pub fn open_secret(
+    name: &str,
+    value: &str,
+    version: u64,
+    latest_version: u64,
+) -> serde_json::Value {
+    serde_json::json!({
+        "name": name,
+        "type": "kv",
+        "latest_version": latest_version,
+        "static_version": {
+            "version": version,
+            "value": value,
+            "created_at": "2024-01-01T00:00:00Z",
+        },
+    })
+}
+
+pub fn build_provider(server: &MockServer) -> HcpVaultSecretsProvider {
+    HcpVaultSecretsProviderBuilder::new(
+        HCP_CLIENT_ID.to_string(),
+        HCP_CLIENT_SECRET.to_string(),
+        "test-org".to_string(),
+        "test-project".to_string(),
+        "test-app".to_string(),
+    )
+    .endpoint_override(server.uri())
+    .auth_endpoint_override(server.uri())
+    .build()
+    .unwrap()
+}
+
+pub async fn load_test_provider() -> HcpTestWrapper {
+    let server = MockServer::start().await;
+    token_request().mount(&server).await;
+    app_request(&format!("/secrets/{}:open", HCP_SECRET_1_NAME))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "secret": open_secret(HCP_SECRET_1_NAME, SECRET_1, 2, 2),
+        })))
+        .mount(&server)
+        .await;
+    app_request(&format!("/secrets/{}/versions/1:open", HCP_SECRET_1_NAME))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "static_version": {
+                "version": 1,
+                "value": SECRET_2,
+                "created_at": "2023-12-01T00:00:00Z",
+            },
+        })))
+        .mount(&server)
+        .await;
+    app_request(&format!("/secrets/{}:open", HCP_SECRET_2_NAME))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "secret": {
+                "name": HCP_SECRET_2_NAME,
+                "type": "rotating",
+                "latest_version": 1,
+                "rotating_version": {"version": 1, "values": {"key": "value"}},
+            },
+        })))
+        .mount(&server)
+        .await;
+    // Any other secret or version does not exist
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(serde_json::json!({
+            "code": 5,
+            "message": "secret not found",
+        })))
+        .mount(&server)
+        .await;
+
+    HcpTestWrapper {
+        provider: build_provider(&server),
+        _server: server,
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use secrets_provider::{
+    implementations::keyring::KeyringSecretsProvider, self_test::SelfTestReport, Decode, Secret,
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::helpers::{keyring::use_shared_keyring, test_ext::SecretsProviderTestExt};
+
+/// Keyring provider reading from a service of its own, in the shared in-memory keyring store.
+pub struct KeyringTestWrapper {
+    pub provider: KeyringSecretsProvider,
+}
+
+impl KeyringTestWrapper {
+    pub fn new() -> Self {
+        static WRAPPERS: AtomicUsize = AtomicUsize::new(0);
+
+        use_shared_keyring();
+        let service = format!(
+            "secrets-provider-tests-{}",
+            WRAPPERS.fetch_add(1, Ordering::Relaxed)
+        );
+
+        Self {
+            provider: KeyringSecretsProvider::new(service),
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for KeyringTestWrapper {
+    async fn find<T: Decode>(
+        &self,
+        secret_name: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find(secret_name).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find_with_version(secret_name, version).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>, SecretsProviderError> {
+        self.provider.batch_find(secret_names).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.provider.self_test(canary).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.provider.backend()
+    }
+}
+
+#[async_trait]
+impl SecretsProviderTestExt for KeyringTestWrapper {
+    async fn add_string_secret(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), SecretsProviderError> {
+        self.provider.set_secret(name, value)
+    }
+
+    async fn add_binary_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
+        self.provider.set_secret(name, value)
+    }
+}
+
+pub fn load_test_provider() -> KeyringTestWrapper {
+    KeyringTestWrapper::new()
+}
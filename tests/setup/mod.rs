@@ -4,5 +4,53 @@ pub mod memory;
 #[cfg(feature = "legacy-rusoto-aws")]
 pub mod rusoto;
 
-#[cfg(feature = "aws")]
+#[cfg(feature = "aws-core")]
 pub mod aws;
+
+#[cfg(feature = "aws-s3-kms")]
+pub mod s3_kms;
+
+#[cfg(feature = "aws-dynamodb-kms")]
+pub mod dynamodb_kms;
+
+#[cfg(feature = "mounted")]
+pub mod mounted;
+
+#[cfg(feature = "systemd")]
+pub mod systemd;
+
+#[cfg(feature = "env")]
+pub mod env;
+
+#[cfg(feature = "file")]
+pub mod file;
+
+#[cfg(feature = "directory")]
+pub mod directory;
+
+#[cfg(feature = "age")]
+pub mod age;
+
+#[cfg(feature = "sealed")]
+pub mod sealed;
+
+#[cfg(feature = "doppler")]
+pub mod doppler;
+
+#[cfg(feature = "hcp")]
+pub mod hcp;
+
+#[cfg(feature = "nats")]
+pub mod nats;
+
+#[cfg(feature = "redis")]
+pub mod redis;
+
+#[cfg(feature = "keyring")]
+pub mod keyring;
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use secrets_provider::implementations::mounted::MountedSecretsProvider;
+use tempfile::TempDir;
+
+use crate::seeds::constants::*;
+
+/// Mounted secrets provider reading from a temporary directory. The directory is removed when
+/// the wrapper is dropped.
+pub struct MountedTestWrapper {
+    pub provider: MountedSecretsProvider,
+    pub dir: TempDir,
+}
+
+impl MountedTestWrapper {
+    /// Writes a secret file under the mounted directory.
+    pub fn write_secret(&self, name: &str, value: &[u8]) {
+        let path = self.dir.path().join(name);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(path, value).unwrap();
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+}
+
+pub fn load_test_provider() -> MountedTestWrapper {
+    let dir = tempfile::tempdir().unwrap();
+    let wrapper = MountedTestWrapper {
+        provider: MountedSecretsProvider::new(dir.path()),
+        dir,
+    };
+
+    wrapper.write_secret(SECRET_1_NAME, SECRET_1.as_bytes());
+    wrapper.write_secret(SECRET_4_NAME, SECRET_4);
+    wrapper
+}
@@ -0,0 +1,70 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_nats::jetstream::kv::{Config, Store};
+use secrets_provider::implementations::nats::NatsKvSecretsProvider;
+use serde::Deserialize;
+
+/// NATS KV provider reading from a bucket of its own, so tests do not see each other's secrets.
+pub struct NatsTestWrapper {
+    pub provider: NatsKvSecretsProvider,
+    pub store: Store,
+}
+
+impl NatsTestWrapper {
+    /// Create a NatsKvSecretsProvider loading the default test configuration from files
+    /// .env.test and .env.test.local.
+    pub async fn load_default() -> Self {
+        static WRAPPERS: AtomicUsize = AtomicUsize::new(0);
+
+        dotenv::from_filename(".env.test.local").ok();
+        dotenv::from_filename(".env.test").ok();
+
+        #[derive(Deserialize)]
+        struct NatsProviderConfig {
+            nats_url: String,
+        }
+        let NatsProviderConfig { nats_url } =
+            envy::from_env::<NatsProviderConfig>().expect("Could not load configuration");
+
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let bucket = format!(
+            "secrets-provider-tests-{}-{}",
+            started,
+            WRAPPERS.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let client = async_nats::connect(nats_url.as_str()).await.unwrap();
+        let store = async_nats::jetstream::new(client)
+            .create_key_value(Config {
+                bucket: bucket.clone(),
+                history: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        Self {
+            provider: NatsKvSecretsProvider::connect(&nats_url, &bucket)
+                .await
+                .unwrap(),
+            store,
+        }
+    }
+
+    /// Puts a value, returning its revision.
+    pub async fn put(&self, name: &str, value: &[u8]) -> String {
+        self.store
+            .put(name, value.to_vec().into())
+            .await
+            .unwrap()
+            .to_string()
+    }
+}
+
+pub async fn load_test_provider() -> NatsTestWrapper {
+    NatsTestWrapper::load_default().await
+}
@@ -0,0 +1,134 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use secrets_provider::{
+    implementations::postgres::PostgresSecretsProvider, self_test::SelfTestReport, Decode, Secret,
+    SecretsProvider, SecretsProviderError, VersionInfo,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+
+use crate::helpers::test_ext::SecretsProviderTestExt;
+
+/// PostgreSQL provider reading from a table of its own, so tests do not see each other's
+/// secrets.
+pub struct PostgresTestWrapper {
+    pub provider: PostgresSecretsProvider,
+    pub pool: PgPool,
+    pub table: String,
+}
+
+impl PostgresTestWrapper {
+    /// Create a PostgresSecretsProvider loading the default test configuration from files
+    /// .env.test and .env.test.local, and creates its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `encryption_key` - pgcrypto key, if values are encrypted.
+    pub async fn load_default(encryption_key: Option<&str>) -> Self {
+        static WRAPPERS: AtomicUsize = AtomicUsize::new(0);
+
+        dotenv::from_filename(".env.test.local").ok();
+        dotenv::from_filename(".env.test").ok();
+
+        #[derive(Deserialize)]
+        struct PostgresProviderConfig {
+            postgres_url: String,
+        }
+        let PostgresProviderConfig { postgres_url } =
+            envy::from_env::<PostgresProviderConfig>().expect("Could not load configuration");
+
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let table = format!(
+            "secrets_provider_tests_{}_{}",
+            started,
+            WRAPPERS.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let pool = PgPool::connect(&postgres_url).await.unwrap();
+        let mut provider = PostgresSecretsProvider::from(pool.clone()).with_table(&table);
+        if let Some(encryption_key) = encryption_key {
+            provider = provider.with_encryption_key(encryption_key);
+        }
+        provider.create_table().await.unwrap();
+
+        Self {
+            provider,
+            pool,
+            table,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for PostgresTestWrapper {
+    async fn find<T: Decode>(
+        &self,
+        secret_name: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find(secret_name).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find_with_version(secret_name, version).await
+    }
+
+    async fn list_secret_versions(
+        &self,
+        secret_name: &str,
+    ) -> Result<Vec<VersionInfo>, SecretsProviderError> {
+        self.provider.list_secret_versions(secret_name).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>, SecretsProviderError> {
+        self.provider.batch_find(secret_names).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.provider.self_test(canary).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.provider.backend()
+    }
+}
+
+#[async_trait]
+impl SecretsProviderTestExt for PostgresTestWrapper {
+    async fn add_string_secret(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), SecretsProviderError> {
+        self.provider.add_secret(name, value).await.map(|_| ())
+    }
+
+    async fn add_binary_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
+        self.provider.add_secret(name, value).await.map(|_| ())
+    }
+}
+
+pub async fn load_test_provider() -> PostgresTestWrapper {
+    PostgresTestWrapper::load_default(None).await
+}
+
+pub async fn load_encrypted_test_provider(encryption_key: &str) -> PostgresTestWrapper {
+    PostgresTestWrapper::load_default(Some(encryption_key)).await
+}
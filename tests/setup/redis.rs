@@ -0,0 +1,148 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+use redis::AsyncCommands;
+use secrets_provider::{
+    implementations::redis::RedisSecretsProvider, self_test::SelfTestReport, Decode, Secret,
+    SecretsProvider, SecretsProviderError, VersionInfo,
+};
+use serde::Deserialize;
+
+use crate::helpers::test_ext::SecretsProviderTestExt;
+
+/// Redis provider reading from a key namespace of its own, so tests do not see each other's
+/// secrets.
+pub struct RedisTestWrapper {
+    pub provider: RedisSecretsProvider,
+    pub connection: ConnectionManager,
+}
+
+impl RedisTestWrapper {
+    /// Create a RedisSecretsProvider loading the default test configuration from files
+    /// .env.test and .env.test.local.
+    pub async fn load_default() -> Self {
+        static WRAPPERS: AtomicUsize = AtomicUsize::new(0);
+
+        dotenv::from_filename(".env.test.local").ok();
+        dotenv::from_filename(".env.test").ok();
+
+        #[derive(Deserialize)]
+        struct RedisProviderConfig {
+            redis_url: String,
+        }
+        let RedisProviderConfig { redis_url } =
+            envy::from_env::<RedisProviderConfig>().expect("Could not load configuration");
+
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let key_prefix = format!(
+            "secrets-provider-tests:{}-{}:",
+            started,
+            WRAPPERS.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let connection = ConnectionManager::new(redis::Client::open(redis_url.as_str()).unwrap())
+            .await
+            .unwrap();
+
+        Self {
+            provider: RedisSecretsProvider::new(&redis_url)
+                .await
+                .unwrap()
+                .with_key_prefix(key_prefix),
+            connection,
+        }
+    }
+
+    /// Writes the next version of a secret.
+    pub async fn add_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
+        let version = self
+            .provider
+            .list_secret_version_ids(name)
+            .await?
+            .last()
+            .map_or(1, |v| v.parse::<u64>().unwrap() + 1);
+
+        self.connection
+            .hset::<_, _, _, ()>(self.key(name), version, value)
+            .await
+            .map_err(|e| SecretsProviderError::ProviderFailed(e.to_string()))
+    }
+
+    pub fn key(&self, name: &str) -> String {
+        format!("{}{}", self.provider.key_prefix(), name)
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for RedisTestWrapper {
+    async fn find<T: Decode>(
+        &self,
+        secret_name: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find(secret_name).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find_with_version(secret_name, version).await
+    }
+
+    async fn list_secret_versions(
+        &self,
+        secret_name: &str,
+    ) -> Result<Vec<VersionInfo>, SecretsProviderError> {
+        self.provider.list_secret_versions(secret_name).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>, SecretsProviderError> {
+        self.provider.batch_find(secret_names).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.provider.self_test(canary).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.provider.backend()
+    }
+}
+
+#[async_trait]
+impl SecretsProviderTestExt for RedisTestWrapper {
+    async fn add_string_secret(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), SecretsProviderError> {
+        self.add_secret(name, value.as_bytes()).await
+    }
+
+    async fn add_binary_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
+        self.add_secret(name, value).await
+    }
+}
+
+pub async fn load_test_provider() -> RedisTestWrapper {
+    RedisTestWrapper::load_default().await
+}
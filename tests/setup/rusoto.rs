@@ -4,12 +4,12 @@ use crate::helpers::test_ext::SecretsProviderTestExt;
 use async_trait::async_trait;
 use rusoto_core::Region;
 use rusoto_secretsmanager::{
-    CreateSecretRequest, DeleteSecretRequest, ListSecretVersionIdsRequest, ListSecretsRequest,
-    PutSecretValueRequest, SecretsManager, SecretsManagerClient,
+    CreateSecretRequest, DeleteSecretRequest, ListSecretsRequest, PutSecretValueRequest,
+    SecretsManager, SecretsManagerClient,
 };
 use secrets_provider::{
-    implementations::rusoto::AwsSecretsProvider, Decode, Secret, SecretsProvider,
-    SecretsProviderError,
+    implementations::rusoto::AwsSecretsProvider, self_test::SelfTestReport, Decode, Secret,
+    SecretsProvider, SecretsProviderError, VersionInfo, VersionStage,
 };
 use serde::Deserialize;
 
@@ -37,17 +37,44 @@ impl SecretsProvider for RusotoTestWrapper {
         self.provider.find_with_version(secret_name, version).await
     }
 
+    async fn find_with_stage<T: Decode>(
+        &self,
+        secret_name: &str,
+        stage: &VersionStage,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find_with_stage(secret_name, stage).await
+    }
+
+    async fn list_secret_versions(
+        &self,
+        secret_name: &str,
+    ) -> Result<Vec<VersionInfo>, SecretsProviderError> {
+        self.provider.list_secret_versions(secret_name).await
+    }
+
     async fn batch_find<'n, T: Decode>(
         &self,
         secret_names: &[&'n str],
     ) -> Result<HashMap<&'n str, Secret<T>>, SecretsProviderError> {
         self.provider.batch_find(secret_names).await
     }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.provider.self_test(canary).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.provider.backend()
+    }
 }
 
 #[async_trait]
 impl SecretsProviderTestExt for RusotoTestWrapper {
-    async fn add_string_secret(&mut self, name: &str, value: &str) {
+    async fn add_string_secret(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), SecretsProviderError> {
         self.create_if_not_exists(name).await;
         self.client
             .put_secret_value(PutSecretValueRequest {
@@ -57,10 +84,15 @@ impl SecretsProviderTestExt for RusotoTestWrapper {
                 ..Default::default()
             })
             .await
-            .unwrap();
+            .map(|_| ())
+            .map_err(|e| SecretsProviderError::ProviderFailed(e.to_string()))
     }
 
-    async fn add_binary_secret(&mut self, name: &str, value: &[u8]) {
+    async fn add_binary_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
         self.create_if_not_exists(name).await;
         self.client
             .put_secret_value(PutSecretValueRequest {
@@ -70,30 +102,8 @@ impl SecretsProviderTestExt for RusotoTestWrapper {
                 ..Default::default()
             })
             .await
-            .unwrap();
-    }
-
-    async fn list_secret_versions(&self, name: &str) -> Vec<String> {
-        let secret_versions = self
-            .client
-            .list_secret_version_ids(ListSecretVersionIdsRequest {
-                secret_id: name.into(),
-                ..Default::default()
-            })
-            .await
-            .unwrap();
-
-        let mut versions = secret_versions.versions.unwrap();
-        versions.sort_by(|a, b| {
-            a.created_date
-                .unwrap()
-                .partial_cmp(&b.created_date.unwrap())
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        versions
-            .into_iter()
-            .map(|e| e.version_id.unwrap())
-            .collect()
+            .map(|_| ())
+            .map_err(|e| SecretsProviderError::ProviderFailed(e.to_string()))
     }
 }
 
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use aes_gcm::aead::{Aead, AeadCore, OsRng};
+use aes_gcm::{Aes256Gcm, KeyInit};
+use aws_config::{BehaviorVersion, Region};
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_kms::types::DataKeySpec;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{BucketVersioningStatus, VersioningConfiguration};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use secrets_provider::implementations::s3_kms::S3KmsSecretsProvider;
+use serde::Deserialize;
+
+const DEFAULT_AWS_REGION: &str = "us-west-2";
+pub const TEST_BUCKET: &str = "secrets-provider-tests";
+
+pub struct S3KmsTestWrapper {
+    pub provider: S3KmsSecretsProvider,
+    pub s3: aws_sdk_s3::Client,
+    pub kms: aws_sdk_kms::Client,
+    pub key_id: String,
+}
+
+impl S3KmsTestWrapper {
+    /// Create a S3KmsSecretsProvider loading the default test configuration
+    /// from files .env.test and .env.test.local.
+    pub async fn load_default() -> Self {
+        dotenv::from_filename(".env.test.local").ok();
+        dotenv::from_filename(".env.test").ok();
+
+        #[derive(Deserialize)]
+        struct AwsProviderConfig {
+            endpoint: String,
+        }
+        let AwsProviderConfig { endpoint } =
+            envy::from_env::<AwsProviderConfig>().expect("Could not load configuration");
+
+        let config = aws_config::defaults(BehaviorVersion::latest())
+            .region(Region::new(DEFAULT_AWS_REGION.to_string()))
+            .endpoint_url(&endpoint)
+            .load()
+            .await;
+        let s3 = aws_sdk_s3::Client::from_conf(
+            aws_sdk_s3::config::Builder::from(&config)
+                .force_path_style(true)
+                .build(),
+        );
+        let kms = aws_sdk_kms::Client::new(&config);
+
+        // The bucket is left over by previous runs
+        let _ = s3.create_bucket().bucket(TEST_BUCKET).send().await;
+        s3.put_bucket_versioning()
+            .bucket(TEST_BUCKET)
+            .versioning_configuration(
+                VersioningConfiguration::builder()
+                    .status(BucketVersioningStatus::Enabled)
+                    .build(),
+            )
+            .send()
+            .await
+            .unwrap();
+        let key_id = kms
+            .create_key()
+            .send()
+            .await
+            .unwrap()
+            .key_metadata
+            .unwrap()
+            .key_id;
+
+        Self {
+            provider: S3KmsSecretsProvider::new_at_endpoint(
+                DEFAULT_AWS_REGION,
+                &endpoint,
+                TEST_BUCKET,
+            )
+            .await,
+            s3,
+            kms,
+            key_id,
+        }
+    }
+
+    /// Uploads a secret encrypted directly with KMS. Returns the object version.
+    pub async fn put_kms_ciphertext(&self, name: &str, value: &[u8]) -> String {
+        let ciphertext = self
+            .kms
+            .encrypt()
+            .key_id(&self.key_id)
+            .plaintext(Blob::new(value))
+            .send()
+            .await
+            .unwrap()
+            .ciphertext_blob
+            .unwrap()
+            .into_inner();
+
+        self.put_object(name, ciphertext, HashMap::new()).await
+    }
+
+    /// Uploads a secret with envelope encryption, laid out like the Amazon S3 Encryption Client
+    /// does. Returns the object version.
+    pub async fn put_envelope(&self, name: &str, value: &[u8]) -> String {
+        let encryption_context = HashMap::from([(
+            "aws:x-amz-cek-alg".to_string(),
+            "AES/GCM/NoPadding".to_string(),
+        )]);
+        let data_key = self
+            .kms
+            .generate_data_key()
+            .key_id(&self.key_id)
+            .key_spec(DataKeySpec::Aes256)
+            .set_encryption_context(Some(encryption_context.clone()))
+            .send()
+            .await
+            .unwrap();
+
+        let iv = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = Aes256Gcm::new_from_slice(data_key.plaintext.unwrap().as_ref())
+            .unwrap()
+            .encrypt(&iv, value)
+            .unwrap();
+
+        let encrypted_key = data_key.ciphertext_blob.unwrap().into_inner();
+        let metadata = HashMap::from([
+            ("x-amz-key-v2".to_string(), STANDARD.encode(encrypted_key)),
+            ("x-amz-iv".to_string(), STANDARD.encode(iv)),
+            ("x-amz-cek-alg".to_string(), "AES/GCM/NoPadding".to_string()),
+            ("x-amz-wrap-alg".to_string(), "kms+context".to_string()),
+            ("x-amz-tag-len".to_string(), "128".to_string()),
+            (
+                "x-amz-matdesc".to_string(),
+                serde_json::to_string(&encryption_context).unwrap(),
+            ),
+        ]);
+        self.put_object(name, ciphertext, metadata).await
+    }
+
+    async fn put_object(
+        &self,
+        name: &str,
+        body: Vec<u8>,
+        metadata: HashMap<String, String>,
+    ) -> String {
+        self.s3
+            .put_object()
+            .bucket(TEST_BUCKET)
+            .key(name)
+            .body(ByteStream::from(body))
+            .set_metadata(Some(metadata))
+            .send()
+            .await
+            .unwrap()
+            .version_id
+            .unwrap()
+    }
+}
+
+pub async fn load_test_provider() -> S3KmsTestWrapper {
+    S3KmsTestWrapper::load_default().await
+}
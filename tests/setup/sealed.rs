@@ -0,0 +1,41 @@
+use ::age::secrecy::ExposeSecret;
+use ::age::x25519::Identity;
+use secrets_provider::implementations::age::AgeIdentitySource;
+use secrets_provider::implementations::sealed::{seal_to_file, SealedSecretsProvider};
+use tempfile::TempDir;
+
+use crate::seeds::constants::*;
+
+/// Sealed secrets provider reading from a temporary directory. The directory is removed when the
+/// wrapper is dropped.
+pub struct SealedTestWrapper {
+    pub provider: SealedSecretsProvider,
+    pub identity: Identity,
+    pub dir: TempDir,
+}
+
+impl SealedTestWrapper {
+    /// Seals a secret for the test identity.
+    pub fn seal(&self, name: &str, value: &[u8]) {
+        let recipient = self.identity.to_public().to_string();
+        seal_to_file(self.dir.path(), name, value, &[&recipient]).unwrap();
+    }
+}
+
+pub fn load_test_provider() -> SealedTestWrapper {
+    let identity = Identity::generate();
+    let dir = tempfile::tempdir().unwrap();
+    let identity_file = dir.path().join("identity.txt");
+    std::fs::write(&identity_file, identity.to_string().expose_secret()).unwrap();
+    let provider =
+        SealedSecretsProvider::new(dir.path(), AgeIdentitySource::File(identity_file)).unwrap();
+    let wrapper = SealedTestWrapper {
+        provider,
+        identity,
+        dir,
+    };
+
+    wrapper.seal(SECRET_1_NAME, SECRET_1.as_bytes());
+    wrapper.seal(SECRET_4_NAME, SECRET_4);
+    wrapper
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use secrets_provider::{
+    implementations::sqlite::SqliteSecretsProvider, self_test::SelfTestReport, Decode, Secret,
+    SecretsProvider, SecretsProviderError, VersionInfo,
+};
+use tempfile::TempDir;
+
+use crate::helpers::test_ext::SecretsProviderTestExt;
+
+/// SQLite provider reading from a database in a temporary directory, removed when dropped.
+pub struct SqliteTestWrapper {
+    pub provider: SqliteSecretsProvider,
+    pub dir: TempDir,
+}
+
+impl SqliteTestWrapper {
+    pub async fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = SqliteSecretsProvider::open(dir.path().join("secrets.db"))
+            .await
+            .unwrap();
+
+        Self { provider, dir }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for SqliteTestWrapper {
+    async fn find<T: Decode>(
+        &self,
+        secret_name: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find(secret_name).await
+    }
+
+    async fn find_with_version<T: Decode>(
+        &self,
+        secret_name: &str,
+        version: &str,
+    ) -> Result<Option<Secret<T>>, SecretsProviderError> {
+        self.provider.find_with_version(secret_name, version).await
+    }
+
+    async fn list_secret_versions(
+        &self,
+        secret_name: &str,
+    ) -> Result<Vec<VersionInfo>, SecretsProviderError> {
+        self.provider.list_secret_versions(secret_name).await
+    }
+
+    async fn batch_find<'n, T: Decode>(
+        &self,
+        secret_names: &[&'n str],
+    ) -> Result<HashMap<&'n str, Secret<T>>, SecretsProviderError> {
+        self.provider.batch_find(secret_names).await
+    }
+
+    async fn self_test(&self, canary: Option<&str>) -> SelfTestReport {
+        self.provider.self_test(canary).await
+    }
+
+    fn backend(&self) -> Option<&'static str> {
+        self.provider.backend()
+    }
+}
+
+#[async_trait]
+impl SecretsProviderTestExt for SqliteTestWrapper {
+    async fn add_string_secret(
+        &mut self,
+        name: &str,
+        value: &str,
+    ) -> Result<(), SecretsProviderError> {
+        self.provider.add_secret(name, value).await.map(|_| ())
+    }
+
+    async fn add_binary_secret(
+        &mut self,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), SecretsProviderError> {
+        self.provider.add_secret(name, value).await.map(|_| ())
+    }
+}
+
+pub async fn load_test_provider() -> SqliteTestWrapper {
+    SqliteTestWrapper::new().await
+}
@@ -0,0 +1,30 @@
+use secrets_provider::implementations::systemd::SystemdCredentialsProvider;
+use tempfile::TempDir;
+
+use crate::seeds::constants::*;
+
+/// systemd credentials provider reading from a temporary directory. The directory is removed
+/// when the wrapper is dropped.
+pub struct SystemdTestWrapper {
+    pub provider: SystemdCredentialsProvider,
+    pub dir: TempDir,
+}
+
+impl SystemdTestWrapper {
+    /// Writes a credential file, as systemd does before starting the service.
+    pub fn write_credential(&self, name: &str, value: &[u8]) {
+        std::fs::write(self.dir.path().join(name), value).unwrap();
+    }
+}
+
+pub fn load_test_provider() -> SystemdTestWrapper {
+    let dir = tempfile::tempdir().unwrap();
+    let wrapper = SystemdTestWrapper {
+        provider: SystemdCredentialsProvider::new(dir.path()),
+        dir,
+    };
+
+    wrapper.write_credential(SECRET_1_NAME, SECRET_1.as_bytes());
+    wrapper.write_credential(SECRET_4_NAME, SECRET_4);
+    wrapper
+}
@@ -0,0 +1,130 @@
+//! Tests that are specific to the age-encrypted bundle implementation.
+//!
+//! Bundles are decrypted once and have a single version, so the generic tests do not apply to
+//! this implementation.
+
+use secrets_provider::{
+    implementations::age::{AgeIdentitySource, AgeSecretsProvider},
+    implementations::file::FileFormat,
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::{
+    seeds::constants::*,
+    setup::age::load_test_wrapper,
+    setup::file::{JSON_SECRETS_FILE, YAML_SECRETS_FILE},
+};
+
+#[tokio::test]
+async fn can_read_secrets_from_binary_and_armored_bundles() {
+    let wrapper = load_test_wrapper();
+
+    for (file_name, content, armor) in [
+        ("secrets.json.age", JSON_SECRETS_FILE, false),
+        ("secrets.yaml.age", YAML_SECRETS_FILE, true),
+    ] {
+        let bundle = wrapper.write_bundle(file_name, content, armor);
+        let provider = wrapper.load_provider(&bundle);
+
+        let secret_1 = provider
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(SECRET_1, secret_1.reveal());
+
+        let secret_4 = provider
+            .find::<Vec<u8>>(SECRET_4_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+    }
+}
+
+#[tokio::test]
+async fn can_read_identity_from_environment_variable() {
+    let wrapper = load_test_wrapper();
+    let bundle = wrapper.write_bundle("secrets.json.age", JSON_SECRETS_FILE, false);
+    std::env::set_var("AGE_TEST_IDENTITY", wrapper.identity_string());
+
+    let provider = AgeSecretsProvider::new(
+        &bundle,
+        AgeIdentitySource::Env("AGE_TEST_IDENTITY".to_string()),
+    )
+    .unwrap();
+
+    let secret_1 = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret_1.reveal());
+}
+
+#[tokio::test]
+async fn secrets_are_served_from_memory() {
+    let wrapper = load_test_wrapper();
+    let bundle = wrapper.write_bundle("secrets", JSON_SECRETS_FILE, false);
+    let provider = AgeSecretsProvider::with_format(
+        &bundle,
+        AgeIdentitySource::File(wrapper.write_identity_file()),
+        FileFormat::Json,
+    )
+    .unwrap();
+    std::fs::remove_file(&bundle).unwrap();
+
+    let secret_1 = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+
+    let same = provider
+        .find_with_version::<String>(SECRET_1_NAME, &secret_1.version)
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(SECRET_1, same.reveal());
+
+    let other = provider
+        .find_with_version::<String>(SECRET_1_NAME, "not-a-version")
+        .await
+        .unwrap();
+    assert!(other.is_none());
+
+    let missing = provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn wrong_identity_should_fail_initialization() {
+    let wrapper = load_test_wrapper();
+    let bundle = wrapper.write_bundle("secrets.json.age", JSON_SECRETS_FILE, false);
+
+    let other = load_test_wrapper();
+    match AgeSecretsProvider::new(
+        &bundle,
+        AgeIdentitySource::File(other.write_identity_file()),
+    ) {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn unknown_bundle_format_should_fail_initialization() {
+    let wrapper = load_test_wrapper();
+    let identity = wrapper.write_identity_file();
+
+    for file_name in ["secrets.json", "secrets.age", "secrets.txt.age"] {
+        let bundle = wrapper.write_bundle(file_name, JSON_SECRETS_FILE, false);
+        match AgeSecretsProvider::new(&bundle, AgeIdentitySource::File(identity.clone())) {
+            Err(SecretsProviderError::Initialization(_)) => (),
+            r => panic!("Should have failed with Initialization error: {:?}", r),
+        }
+    }
+}
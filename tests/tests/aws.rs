@@ -3,20 +3,32 @@
 //! Unless your tests needs to interact directly with the AWS client, you should
 //! create a generic test instead.
 
-use secrets_provider::SecretsProvider;
+use std::net::ToSocketAddrs;
+use std::time::Duration;
+
+use aws_sdk_secretsmanager::types::Tag;
+
+use secrets_provider::{
+    implementations::aws::{
+        hedging::HedgingPolicy, policy::Effect, replication::ReplicaRegion, AwsSecretsProvider,
+    },
+    resolver::StaticResolver,
+    DeleteOptions, SecretData, SecretsProvider, SecretsWriter, VersionStage,
+};
 
 use crate::{
-    generate_generic_tests,
+    generate_generic_tests, generate_generic_writer_tests,
     seeds::{constants::*, seeder::seed_secrets_provider},
 };
 
 // This line will include all generic tests using official AWS SDK implementation.
 generate_generic_tests!(crate::setup::aws::load_test_provider().await);
+generate_generic_writer_tests!(crate::setup::aws::load_test_provider().await);
 
 #[tokio::test]
 async fn test_can_retrieve_previous_and_current_aws_stages() {
     let mut secrets_provider = crate::setup::aws::load_test_provider().await;
-    seed_secrets_provider(&mut secrets_provider).await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
 
     // Read the secrets versions directly from the manager. We have to do this because every time
     // the emulator is seeded, the versions change. So, we read the version_id for each version of
@@ -47,8 +59,10 @@ async fn test_can_retrieve_previous_and_current_aws_stages() {
         .find_with_version::<String>(VERSIONED_SECRET_NAME, &previous_version)
         .await
         .unwrap()
-        .expect("Secret / version pair not found")
-        .reveal();
+        .expect("Secret / version pair not found");
+    assert_eq!(previous_secret.version_stages(), ["AWSPREVIOUS"]);
+    assert_eq!(previous_secret.is_current(), Some(false));
+    let previous_secret = previous_secret.reveal();
 
     assert_eq!(previous_secret, VERSIONED_SECRET_VERSION_1);
 
@@ -69,8 +83,392 @@ async fn test_can_retrieve_previous_and_current_aws_stages() {
         .find_with_version::<String>(VERSIONED_SECRET_NAME, &current_version)
         .await
         .unwrap()
-        .expect("Secret / version pair not found")
-        .reveal();
+        .expect("Secret / version pair not found");
+    assert_eq!(current_secret.is_current(), Some(true));
+    let current_secret = current_secret.reveal();
+
+    assert_eq!(current_secret, VERSIONED_SECRET_VERSION_2);
+}
+
+#[tokio::test]
+async fn test_can_find_versions_by_stage() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let previous_secret = secrets_provider
+        .provider
+        .find_with_stage::<String>(VERSIONED_SECRET_NAME, &VersionStage::Previous)
+        .await
+        .unwrap()
+        .expect("Previous version not found");
+    assert_eq!(previous_secret.version_stages(), ["AWSPREVIOUS"]);
+    assert!(previous_secret.created_at().is_some());
+    assert_eq!(previous_secret.reveal(), VERSIONED_SECRET_VERSION_1);
+
+    let current_secret = secrets_provider
+        .provider
+        .find_with_stage::<String>(
+            VERSIONED_SECRET_NAME,
+            &VersionStage::Label("AWSCURRENT".to_string()),
+        )
+        .await
+        .unwrap()
+        .expect("Current version not found");
+    assert_eq!(current_secret.is_current(), Some(true));
+    assert_eq!(current_secret.reveal(), VERSIONED_SECRET_VERSION_2);
 
+    let pending_secret = secrets_provider
+        .provider
+        .find_with_stage::<String>(VERSIONED_SECRET_NAME, &VersionStage::Pending)
+        .await
+        .unwrap();
+    assert!(pending_secret.is_none());
+}
+
+#[tokio::test]
+async fn test_can_find_version_current_at_a_given_time() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let current_secret = secrets_provider
+        .provider
+        .find_as_of::<String>(VERSIONED_SECRET_NAME, SystemTime::now())
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
     assert_eq!(current_secret, VERSIONED_SECRET_VERSION_2);
+
+    let secret = secrets_provider
+        .provider
+        .find_as_of::<String>(VERSIONED_SECRET_NAME, UNIX_EPOCH)
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn test_can_describe_many_secrets_at_once() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let described = secrets_provider
+        .provider
+        .batch_describe(&[SECRET_1_NAME, VERSIONED_SECRET_NAME, "non-existent-secret"])
+        .await
+        .unwrap();
+
+    assert_eq!(described.len(), 2);
+    let description = &described[SECRET_1_NAME];
+    assert_eq!(description.name, SECRET_1_NAME);
+    assert!(description.created_at.is_some());
+    assert!(described.contains_key(VERSIONED_SECRET_NAME));
+}
+
+#[tokio::test]
+async fn test_can_find_every_secret_with_an_empty_prefix() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    // "*" strips to an empty prefix, which must not be sent as a name filter
+    let found = secrets_provider
+        .provider
+        .find_by_prefix::<SecretData>("*")
+        .await
+        .unwrap();
+
+    assert!(found.contains_key(SECRET_1_NAME));
+    assert!(found.contains_key(VERSIONED_SECRET_NAME));
+}
+
+#[tokio::test]
+async fn test_batch_lookups_are_split_in_requests_of_20_secrets() {
+    let secrets_provider = crate::setup::aws::load_test_provider().await;
+    let names: Vec<String> = (0..25).map(|i| format!("batch-secret-{}", i)).collect();
+    for name in &names {
+        secrets_provider
+            .provider
+            .create_secret(name, SecretData::Str(format!("{}-value", name)))
+            .await
+            .unwrap();
+    }
+
+    let mut requested: Vec<&str> = names.iter().map(String::as_str).collect();
+    requested.push("non-existent-secret");
+    let mut secrets = secrets_provider
+        .provider
+        .batch_find::<String>(&requested)
+        .await
+        .unwrap();
+
+    assert_eq!(secrets.len(), 25);
+    for name in &names {
+        let secret = secrets.remove(name.as_str()).expect("Secret not found");
+        assert_eq!(secret.reveal(), format!("{}-value", name));
+    }
+}
+
+#[tokio::test]
+async fn test_can_retrieve_tags_with_values() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+    secrets_provider
+        .client
+        .tag_resource()
+        .secret_id(SECRET_1_NAME)
+        .tags(Tag::builder().key("tenant").value("acme").build())
+        .send()
+        .await
+        .unwrap();
+
+    let untagged = secrets_provider
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert!(untagged.tags().is_none());
+
+    let provider = secrets_provider.provider.clone().with_tags();
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(secret.tags().unwrap()["tenant"], "acme");
+
+    let described = provider.batch_describe(&[SECRET_1_NAME]).await.unwrap();
+    assert_eq!(described[SECRET_1_NAME].tags, secret.tags().cloned());
+}
+
+#[tokio::test]
+async fn test_can_retrieve_metadata_with_values() {
+    let secrets_provider = crate::setup::aws::load_test_provider().await;
+    let name = "secret-with-metadata";
+    secrets_provider
+        .client
+        .create_secret()
+        .name(name)
+        .description("Primary database password")
+        .secret_string(SECRET_1)
+        .send()
+        .await
+        .unwrap();
+
+    let secret = secrets_provider
+        .provider
+        .find::<String>(name)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    let metadata = secret.metadata().expect("Metadata not found");
+    assert!(metadata.resource_id.as_ref().unwrap().starts_with("arn:"));
+    assert!(metadata.description.is_none());
+
+    let provider = secrets_provider.provider.clone().with_metadata();
+    let secret = provider
+        .find::<String>(name)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    let metadata = secret.metadata().expect("Metadata not found");
+    assert_eq!(
+        Some("Primary database password"),
+        metadata.description.as_deref()
+    );
+
+    provider
+        .delete_secret(name, DeleteOptions::Force)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+}
+
+#[tokio::test]
+async fn test_can_delete_and_restore_secrets() {
+    let secrets_provider = crate::setup::aws::load_test_provider().await;
+    let provider = &secrets_provider.provider;
+    let name = "deleted-and-restored-secret";
+    provider.create_secret(name, SECRET_1.into()).await.unwrap();
+
+    let deletion_date = provider
+        .delete_secret(name, DeleteOptions::RecoveryWindowDays(7))
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert!(deletion_date > std::time::SystemTime::now());
+
+    assert!(provider.restore_secret(name).await.unwrap());
+    let restored = provider
+        .find::<String>(name)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, restored.reveal());
+
+    provider
+        .delete_secret(name, DeleteOptions::Force)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert!(!provider
+        .restore_secret("non-existent-secret")
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn test_hedged_lookups_are_capped() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    // A zero delay hedges every lookup, until the budget of a single second request is spent
+    let provider = secrets_provider
+        .provider
+        .clone()
+        .with_hedging(HedgingPolicy {
+            min_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+            burst: 1,
+            ..HedgingPolicy::default()
+        });
+    for _ in 0..3 {
+        let secret = provider
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(SECRET_1, secret.reveal());
+    }
+
+    let stats = provider.hedging_stats().unwrap();
+    assert_eq!(stats.requests, 3);
+    assert_eq!(stats.hedged, 1);
+    assert!(secrets_provider.provider.hedging_stats().is_none());
+}
+
+#[tokio::test]
+async fn test_can_resolve_endpoint_with_static_resolver() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    // Reach the emulator through a host name that only the static resolver knows about
+    let endpoint = std::env::var("ENDPOINT").unwrap();
+    let (scheme_and_host, port) = endpoint.rsplit_once(':').unwrap();
+    let host = scheme_and_host.trim_start_matches("http://");
+    let ip = (host, 0).to_socket_addrs().unwrap().next().unwrap().ip();
+
+    let provider = AwsSecretsProvider::new_with_resolver(
+        "us-west-2".to_string(),
+        Some(&format!("http://secrets.internal:{}", port)),
+        StaticResolver::new().host("secrets.internal", [ip]),
+    )
+    .await;
+
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[tokio::test]
+async fn test_can_inspect_resource_policies() {
+    let mut secrets_provider = crate::setup::aws::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let policy = secrets_provider
+        .provider
+        .get_resource_policy(SECRET_1_NAME)
+        .await
+        .unwrap();
+    assert!(policy.is_none());
+
+    secrets_provider
+        .client
+        .put_resource_policy()
+        .secret_id(SECRET_1_NAME)
+        .resource_policy(
+            r#"{
+                "Version": "2012-10-17",
+                "Statement": {
+                    "Effect": "Allow",
+                    "Principal": { "AWS": "arn:aws:iam::123456789012:root" },
+                    "Action": "secretsmanager:GetSecretValue",
+                    "Resource": "*"
+                }
+            }"#,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let policy = secrets_provider
+        .provider
+        .get_resource_policy(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Resource policy not found");
+
+    assert_eq!(policy.statements.len(), 1);
+    assert_eq!(policy.statements[0].effect, Effect::Allow);
+    assert_eq!(
+        policy.statements[0].action,
+        ["secretsmanager:GetSecretValue"]
+    );
+    assert_eq!(
+        policy.allowed_aws_principals().collect::<Vec<_>>(),
+        ["arn:aws:iam::123456789012:root"]
+    );
+
+    let missing = secrets_provider
+        .provider
+        .get_resource_policy("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn test_can_replicate_secrets_to_other_regions() {
+    let secrets_provider = crate::setup::aws::load_test_provider().await;
+    let provider = &secrets_provider.provider;
+    let name = "replicated-secret";
+    provider.create_secret(name, SECRET_1.into()).await.unwrap();
+
+    let statuses = provider
+        .replicate_secret(name, &[ReplicaRegion::new("eu-west-1")])
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(
+        statuses
+            .iter()
+            .map(|s| s.region.as_str())
+            .collect::<Vec<_>>(),
+        ["eu-west-1"]
+    );
+
+    let statuses = provider
+        .remove_replication(name, &["eu-west-1"])
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert!(statuses.is_empty());
+
+    assert!(provider
+        .replicate_secret("non-existent-secret", &[ReplicaRegion::new("eu-west-1")])
+        .await
+        .unwrap()
+        .is_none());
+
+    provider
+        .delete_secret(name, DeleteOptions::Force)
+        .await
+        .unwrap()
+        .expect("Secret not found");
 }
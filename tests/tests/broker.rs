@@ -0,0 +1,115 @@
+//! Tests of the secrets broker, serving the Memory implementation over a Unix domain socket.
+
+use std::path::Path;
+
+use secrets_provider::{
+    broker::{BrokerSecretsProvider, SecretsBroker},
+    implementations::memory::MemorySecretsProvider,
+    quota::{Quota, QuotaProvider},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret(SECRET_2_NAME.into(), SECRET_2.into());
+    provider.add_binary_secret(SECRET_4_NAME.into(), SECRET_4.to_vec());
+    provider
+}
+
+async fn wait_for_socket(path: &Path) {
+    while !path.exists() {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn clients_find_the_secrets_of_the_broker() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("secrets.sock");
+    let broker = tokio::spawn(SecretsBroker::new(load_test_provider()).listen(path.clone()));
+    wait_for_socket(&path).await;
+
+    let mode = std::os::unix::fs::PermissionsExt::mode(&path.metadata().unwrap().permissions());
+    assert_eq!(0o600, mode & 0o777);
+
+    let client = BrokerSecretsProvider::new(&path);
+    let secret = client
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    let version = secret.version.clone();
+    assert_eq!(SECRET_1, secret.reveal());
+
+    // The socket is bound in a private staging directory, removed once it was moved
+    let entries: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(vec![std::ffi::OsString::from("secrets.sock")], entries);
+
+    let secret = client
+        .find_with_version::<String>(SECRET_1_NAME, &version)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let secret = client
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret.reveal());
+
+    assert!(client
+        .find::<String>(SECRET_3_NAME)
+        .await
+        .unwrap()
+        .is_none());
+    match client.find::<String>(SECRET_4_NAME).await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+
+    broker.abort();
+}
+
+#[tokio::test]
+async fn errors_of_the_served_provider_reach_the_clients() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("secrets.sock");
+    let quotas = QuotaProvider::new(load_test_provider()).with_default_quota(Quota {
+        max_distinct_secrets: Some(1),
+        ..Quota::default()
+    });
+    let broker = tokio::spawn(SecretsBroker::new(quotas.caller("worker")).listen(path.clone()));
+    wait_for_socket(&path).await;
+
+    let client = BrokerSecretsProvider::new(&path);
+    assert!(client
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .is_some());
+    match client.find::<String>(SECRET_2_NAME).await {
+        Err(SecretsProviderError::QuotaExceeded(_)) => (),
+        r => panic!("Should have failed with QuotaExceeded error: {:?}", r),
+    }
+
+    broker.abort();
+}
+
+#[tokio::test]
+async fn unreachable_brokers_fail() {
+    let dir = tempfile::tempdir().unwrap();
+    let client = BrokerSecretsProvider::new(dir.path().join("missing.sock"));
+
+    match client.find::<String>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+}
@@ -0,0 +1,92 @@
+//! Tests dropping lookups mid-flight, using the chaos testing wrapper to slow down the Memory
+//! implementation.
+
+use std::time::{Duration, Instant};
+
+use secrets_provider::{
+    chaos::ChaosProvider, implementations::memory::MemorySecretsProvider, SecretsProvider,
+};
+
+use crate::seeds::constants::*;
+
+const LATENCY: Duration = Duration::from_millis(200);
+
+fn load_test_provider() -> ChaosProvider<MemorySecretsProvider> {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret(SECRET_2_NAME.into(), SECRET_2.into());
+    ChaosProvider::new(provider).with_latency(1.0, LATENCY)
+}
+
+#[tokio::test]
+async fn dropped_lookups_leave_the_provider_usable() {
+    let provider = load_test_provider();
+
+    let cancelled = tokio::time::timeout(
+        Duration::from_millis(10),
+        provider.find::<String>(SECRET_1_NAME),
+    )
+    .await;
+    assert!(cancelled.is_err(), "Lookup should have been cancelled");
+
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[tokio::test]
+async fn dropped_batches_leave_the_provider_usable() {
+    let provider = load_test_provider();
+
+    // The batch is dropped after its first lookup completed
+    let cancelled = tokio::time::timeout(
+        LATENCY + LATENCY / 2,
+        provider.batch_find_with_concurrency::<String>(&[SECRET_1_NAME, SECRET_2_NAME], 1),
+    )
+    .await;
+    assert!(cancelled.is_err(), "Batch should have been cancelled");
+
+    let secrets = provider
+        .batch_find::<String>(&[SECRET_1_NAME, SECRET_2_NAME])
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+}
+
+#[tokio::test]
+async fn lookups_losing_a_select_are_dropped() {
+    let provider = load_test_provider();
+
+    let winner = tokio::select! {
+        _ = provider.find::<String>(SECRET_1_NAME) => "lookup",
+        _ = tokio::time::sleep(Duration::from_millis(10)) => "timer",
+    };
+    assert_eq!("timer", winner);
+
+    let secret = provider.find::<String>(SECRET_2_NAME).await.unwrap();
+    assert!(secret.is_some());
+}
+
+#[tokio::test]
+async fn batch_lookups_run_concurrently() {
+    let provider = load_test_provider();
+
+    let start = Instant::now();
+    let secrets = provider
+        .batch_find::<String>(&[SECRET_1_NAME, SECRET_2_NAME, SECRET_3_NAME])
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+    assert!(start.elapsed() < LATENCY * 2);
+
+    let start = Instant::now();
+    let secrets = provider
+        .batch_find_with_concurrency::<String>(&[SECRET_1_NAME, SECRET_2_NAME, SECRET_3_NAME], 1)
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+    assert!(start.elapsed() >= LATENCY * 3);
+}
@@ -0,0 +1,23 @@
+//! Tests of the compile-time capabilities, using the Memory implementation.
+
+use secrets_provider::{
+    capabilities::{Capabilities, ProviderCapabilities},
+    implementations::memory::MemorySecretsProvider,
+    static_assert_supports,
+};
+
+static_assert_supports!(MemorySecretsProvider, versions, stages, list, write);
+
+#[test]
+fn memory_provider_declares_its_capabilities() {
+    assert_eq!(
+        Capabilities {
+            versions: true,
+            stages: true,
+            list: true,
+            write: true,
+            batch: false,
+        },
+        MemorySecretsProvider::CAPABILITIES
+    );
+}
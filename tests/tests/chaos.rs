@@ -0,0 +1,118 @@
+//! Tests of the chaos testing wrapper, using the Memory implementation as the wrapped provider.
+
+use std::time::{Duration, Instant};
+
+use secrets_provider::{
+    chaos::ChaosProvider, implementations::memory::MemorySecretsProvider, SecretsProvider,
+    SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_binary_secret(SECRET_4_NAME.into(), SECRET_4.to_vec());
+    provider
+}
+
+#[tokio::test]
+async fn misbehaviors_are_disabled_by_default() {
+    let provider = ChaosProvider::new(load_test_provider());
+
+    for _ in 0..10 {
+        let secret = provider
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(SECRET_1, secret.reveal());
+    }
+}
+
+#[tokio::test]
+async fn injects_errors() {
+    let provider = ChaosProvider::new(load_test_provider()).with_errors(1.0);
+
+    match provider.find::<String>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn injects_latency() {
+    let provider =
+        ChaosProvider::new(load_test_provider()).with_latency(1.0, Duration::from_millis(50));
+
+    let start = Instant::now();
+    provider.find::<String>(SECRET_1_NAME).await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn truncates_values() {
+    let provider = ChaosProvider::new(load_test_provider()).with_truncated_values(1.0);
+
+    let secret_1 = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(&SECRET_1[..SECRET_1.len() / 2], secret_1.reveal());
+
+    let secret_4 = provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(&SECRET_4[..SECRET_4.len() / 2], secret_4.reveal());
+}
+
+#[tokio::test]
+async fn returns_previously_seen_versions() {
+    let mut inner = load_test_provider();
+    let first = inner.add_string_secret(SECRET_2_NAME.into(), SECRET_2.into());
+    inner.add_string_secret(SECRET_2_NAME.into(), SECRET_3.into());
+    let provider = ChaosProvider::new(inner).with_stale_versions(1.0);
+
+    // Nothing was returned before, so there is no stale version yet
+    let current = provider
+        .find::<String>(SECRET_2_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_3, current.reveal());
+
+    provider
+        .find_with_version::<String>(SECRET_2_NAME, &first.version)
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+
+    let stale = provider
+        .find::<String>(SECRET_2_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(first.version, stale.version);
+    assert_eq!(SECRET_2, stale.reveal());
+}
+
+#[tokio::test]
+async fn seeded_providers_misbehave_the_same_way() {
+    let outcomes = |seed| async move {
+        let provider = ChaosProvider::new(load_test_provider())
+            .with_seed(seed)
+            .with_errors(0.5);
+        let mut outcomes = Vec::new();
+        for _ in 0..32 {
+            outcomes.push(provider.find::<String>(SECRET_1_NAME).await.is_ok());
+        }
+        outcomes
+    };
+
+    let first = outcomes(42).await;
+    assert_eq!(first, outcomes(42).await);
+    assert!(first.contains(&true) && first.contains(&false));
+}
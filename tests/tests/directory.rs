@@ -0,0 +1,218 @@
+//! Tests that are specific to the Directory tree implementation.
+//!
+//! Secret files can be read both as strings and binaries, and versions are created by writing
+//! suffixed files, so the generic tests do not apply to this implementation.
+
+use secrets_provider::{SecretsProvider, SecretsProviderError};
+
+use crate::{seeds::constants::*, setup::directory::load_test_provider};
+
+#[tokio::test]
+async fn can_read_files_as_string_and_binary() {
+    let wrapper = load_test_provider();
+
+    let secret_4 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4_NAME, secret_4.name);
+    assert_eq!("1", secret_4.version);
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+
+    let secret_1 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2.as_bytes(), secret_1.reveal());
+}
+
+#[tokio::test]
+async fn reading_non_utf8_file_as_string_should_fail() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret("non-utf8", &[0xff, 0xfe, 0x00]);
+
+    match wrapper.provider.find::<String>("non-utf8").await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn find_returns_highest_version() {
+    let wrapper = load_test_provider();
+
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("2", secret.version);
+    assert_eq!(SECRET_2, secret.reveal());
+
+    // Versions are compared as numbers
+    wrapper.write_secret(&format!("{}.v10", SECRET_1_NAME), SECRET_3.as_bytes());
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("10", secret.version);
+    assert_eq!(SECRET_3, secret.reveal());
+}
+
+#[tokio::test]
+async fn find_with_version_reads_suffixed_files() {
+    let wrapper = load_test_provider();
+
+    let first = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "1")
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(SECRET_1, first.reveal());
+
+    let second = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "2")
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(SECRET_2, second.reveal());
+
+    for version in ["3", "not-a-version"] {
+        let other = wrapper
+            .provider
+            .find_with_version::<String>(SECRET_1_NAME, version)
+            .await
+            .unwrap();
+        assert!(other.is_none());
+    }
+}
+
+#[tokio::test]
+async fn explicit_first_version_overrides_plain_file() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret(&format!("{}.v1", SECRET_1_NAME), SECRET_3.as_bytes());
+
+    let first = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "1")
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(SECRET_3, first.reveal());
+}
+
+#[tokio::test]
+async fn can_read_nested_versioned_files() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret("database/password.v3", SECRET_3.as_bytes());
+
+    let secret = wrapper
+        .provider
+        .find::<String>("database/password")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("3", secret.version);
+    assert_eq!(SECRET_3, secret.reveal());
+}
+
+#[tokio::test]
+async fn non_existent_secret_should_be_none() {
+    let wrapper = load_test_provider();
+
+    for name in ["non-existent-secret", "missing-directory/secret"] {
+        let secret = wrapper.provider.find::<String>(name).await.unwrap();
+        assert!(secret.is_none());
+    }
+}
+
+#[tokio::test]
+async fn names_escaping_the_root_should_fail() {
+    let wrapper = load_test_provider();
+    let outside = wrapper.root().parent().unwrap().join("outside-secret");
+
+    for name in ["../outside-secret", outside.to_str().unwrap(), ""] {
+        match wrapper.provider.find::<String>(name).await {
+            Err(SecretsProviderError::InvalidName(_)) => (),
+            r => panic!("Should have failed with InvalidName error: {:?}", r),
+        }
+    }
+}
+
+#[tokio::test]
+async fn list_secret_version_ids_sorts_versions() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret(&format!("{}.v10", SECRET_1_NAME), SECRET_3.as_bytes());
+    // Not versions of the secret
+    wrapper.write_secret(&format!("{}.vx", SECRET_1_NAME), SECRET_3.as_bytes());
+    wrapper.write_secret(&format!("{}-other.v3", SECRET_1_NAME), SECRET_3.as_bytes());
+
+    let versions = wrapper
+        .provider
+        .list_secret_version_ids(SECRET_1_NAME)
+        .unwrap();
+    assert_eq!(versions, ["1", "2", "10"]);
+
+    let versions = wrapper
+        .provider
+        .list_secret_version_ids("non-existent-secret")
+        .unwrap();
+    assert!(versions.is_empty());
+}
+
+#[test]
+fn find_tree_reads_nested_secrets() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret("app/database/password", SECRET_2.as_bytes());
+    wrapper.write_secret("app/database/password.v2", SECRET_3.as_bytes());
+    wrapper.write_secret("app/api-key", SECRET_1.as_bytes());
+    // A versioned secret and a subtree can share a name
+    wrapper.write_secret("app/database.v1", SECRET_1.as_bytes());
+
+    let tree = wrapper.provider.find_tree::<String>("app/").unwrap();
+    assert_eq!(3, tree.len());
+    assert_eq!(
+        vec!["api-key", "database"],
+        tree.secrets.keys().collect::<Vec<_>>()
+    );
+
+    let password = tree.get("database/password").expect("Secret not found");
+    assert_eq!("app/database/password", password.name);
+    assert_eq!("2", password.version);
+    assert_eq!("app/api-key", tree.get("api-key").unwrap().name);
+
+    // The whole tree is read with an empty prefix
+    let mut tree = wrapper.provider.find_tree::<Vec<u8>>("").unwrap();
+    assert_eq!(5, tree.len());
+    let secret_4 = tree
+        .secrets
+        .remove(SECRET_4_NAME)
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+}
+
+#[test]
+fn find_tree_of_missing_path_is_empty() {
+    let wrapper = load_test_provider();
+
+    let tree = wrapper.provider.find_tree::<String>("missing").unwrap();
+    assert!(tree.is_empty());
+
+    // Secrets are not trees
+    let tree = wrapper.provider.find_tree::<String>(SECRET_1_NAME).unwrap();
+    assert!(tree.is_empty());
+
+    assert!(matches!(
+        wrapper.provider.find_tree::<String>("../outside"),
+        Err(SecretsProviderError::InvalidName(_))
+    ));
+}
@@ -0,0 +1,227 @@
+//! Tests that are specific to the Doppler implementation, using a mocked Doppler API.
+//!
+//! Doppler configs only expose their current version and string secrets, so the generic tests do
+//! not apply to this implementation.
+
+use secrets_provider::{
+    implementations::doppler::DopplerSecretsProviderBuilder, SecretsProvider, SecretsProviderError,
+};
+use wiremock::matchers::{method, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::{
+    seeds::constants::*,
+    setup::doppler::{
+        config_download, config_download_request, load_test_provider, DOPPLER_SECRET_1_NAME,
+        DOPPLER_SECRET_2_NAME, DOPPLER_TOKEN, DOPPLER_VERSION,
+    },
+};
+
+#[tokio::test]
+async fn can_read_config_secrets() {
+    let wrapper = load_test_provider().await;
+
+    let secret = wrapper
+        .provider
+        .find::<String>(DOPPLER_SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(DOPPLER_SECRET_1_NAME, secret.name);
+    assert_eq!(DOPPLER_VERSION, secret.version);
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let missing = wrapper
+        .provider
+        .find::<String>("NON_EXISTENT_SECRET")
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn reading_as_binary_should_fail() {
+    let wrapper = load_test_provider().await;
+
+    match wrapper
+        .provider
+        .find::<Vec<u8>>(DOPPLER_SECRET_1_NAME)
+        .await
+    {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn find_with_version_only_returns_current_version() {
+    let wrapper = load_test_provider().await;
+
+    let secret = wrapper
+        .provider
+        .find_with_version::<String>(DOPPLER_SECRET_1_NAME, DOPPLER_VERSION)
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let other = wrapper
+        .provider
+        .find_with_version::<String>(DOPPLER_SECRET_1_NAME, "not-a-version")
+        .await
+        .unwrap();
+    assert!(other.is_none());
+}
+
+#[tokio::test]
+async fn batch_find_downloads_the_config_once() {
+    let server = MockServer::start().await;
+    config_download().expect(1).mount(&server).await;
+    let provider = DopplerSecretsProviderBuilder::new(DOPPLER_TOKEN.to_string())
+        .endpoint_override(server.uri())
+        .build()
+        .unwrap();
+
+    let mut secrets = provider
+        .batch_find::<String>(&[
+            DOPPLER_SECRET_1_NAME,
+            DOPPLER_SECRET_2_NAME,
+            "NON_EXISTENT_SECRET",
+        ])
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+    assert_eq!(
+        SECRET_2,
+        secrets.remove(DOPPLER_SECRET_2_NAME).unwrap().reveal()
+    );
+}
+
+#[tokio::test]
+async fn project_and_config_are_sent() {
+    let server = MockServer::start().await;
+    config_download_request()
+        .and(query_param("project", "backend"))
+        .and(query_param("config", "prd"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            DOPPLER_SECRET_1_NAME: SECRET_1,
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let provider = DopplerSecretsProviderBuilder::new(DOPPLER_TOKEN.to_string())
+        .project("backend".to_string())
+        .config("prd".to_string())
+        .endpoint_override(server.uri())
+        .build()
+        .unwrap();
+
+    let secret = provider
+        .find::<String>(DOPPLER_SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    // Without entity tag, the version is unknown
+    assert_eq!("unknown", secret.version);
+}
+
+#[tokio::test]
+async fn api_errors_should_fail() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "messages": ["Invalid Service token"],
+            "success": false,
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = DopplerSecretsProviderBuilder::new("dp.st.invalid".to_string())
+        .endpoint_override(server.uri())
+        .build()
+        .unwrap();
+
+    match provider.find::<String>(DOPPLER_SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(e)) => {
+            assert!(
+                e.contains("Invalid Service token"),
+                "Unexpected error: {}",
+                e
+            )
+        }
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+
+    let report = provider.self_test(None).await;
+    let failed: Vec<_> = report.failed_checks().map(|c| c.name.as_str()).collect();
+    assert_eq!(failed, ["download_config"]);
+}
+
+#[tokio::test]
+async fn invalid_names_fail_before_any_request() {
+    let server = MockServer::start().await;
+    config_download().expect(0).mount(&server).await;
+    let provider = DopplerSecretsProviderBuilder::new(DOPPLER_TOKEN.to_string())
+        .endpoint_override(server.uri())
+        .build()
+        .unwrap();
+
+    match provider.find::<String>("secret-1").await {
+        Err(SecretsProviderError::InvalidName(e)) => {
+            assert!(e.contains("'s' at position 0"), "Unexpected error: {}", e)
+        }
+        r => panic!("Should have failed with InvalidName error: {:?}", r),
+    }
+
+    let result = provider
+        .batch_find::<String>(&[DOPPLER_SECRET_1_NAME, ""])
+        .await;
+    assert!(
+        matches!(result, Err(SecretsProviderError::InvalidName(_))),
+        "Should have failed with InvalidName error: {:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn backend_errors_follow_golden_error_mapping() {
+    use secrets_provider::testkit::{check_error_mapping, BackendFault};
+
+    let wrapper = load_test_provider().await;
+    check_error_mapping(
+        &wrapper.provider,
+        "NON_EXISTENT_SECRET",
+        BackendFault::NotFound,
+    )
+    .await
+    .unwrap();
+
+    for (status, fault) in [
+        (403, BackendFault::AccessDenied),
+        (429, BackendFault::Throttled),
+    ] {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+        let provider = DopplerSecretsProviderBuilder::new(DOPPLER_TOKEN.to_string())
+            .endpoint_override(server.uri())
+            .build()
+            .unwrap();
+
+        check_error_mapping(&provider, DOPPLER_SECRET_1_NAME, fault)
+            .await
+            .unwrap();
+    }
+
+    // Nothing listens on the discard port
+    let provider = DopplerSecretsProviderBuilder::new(DOPPLER_TOKEN.to_string())
+        .endpoint_override("http://127.0.0.1:9".to_string())
+        .build()
+        .unwrap();
+    check_error_mapping(&provider, DOPPLER_SECRET_1_NAME, BackendFault::Network)
+        .await
+        .unwrap();
+}
@@ -0,0 +1,78 @@
+//! Tests of object-safe Secrets Providers, using the Memory implementation as the boxed provider.
+
+use std::sync::Arc;
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider, DynSecretsProvider, SecretData,
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_binary_secret(SECRET_4_NAME.into(), SECRET_4.to_vec());
+    provider
+}
+
+#[tokio::test]
+async fn boxed_providers_return_untyped_secrets() {
+    let providers: Vec<Box<dyn DynSecretsProvider>> = vec![Box::new(load_test_provider())];
+
+    for provider in &providers {
+        let secret = provider
+            .dyn_find(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert!(matches!(secret.reveal(), SecretData::Str(s) if s == SECRET_1));
+
+        let secret = provider
+            .dyn_find(SECRET_4_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(Some("Memory"), provider.dyn_backend());
+        let version = secret.version.clone();
+        assert_eq!(
+            SECRET_4.to_vec(),
+            secret.decode::<Vec<u8>>().unwrap().reveal()
+        );
+
+        let same = provider
+            .dyn_find_with_version(SECRET_4_NAME, &version)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        match same.decode::<String>() {
+            Err(SecretsProviderError::InvalidType(_)) => (),
+            r => panic!("Should have failed with InvalidType error: {:?}", r),
+        }
+    }
+}
+
+#[tokio::test]
+async fn boxed_providers_are_secrets_providers() {
+    async fn find_secret_1(provider: &impl SecretsProvider) -> String {
+        provider
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found")
+            .reveal()
+    }
+
+    let boxed: Box<dyn DynSecretsProvider> = Box::new(load_test_provider());
+    assert_eq!(SECRET_1, find_secret_1(&boxed).await);
+
+    let shared: Arc<dyn DynSecretsProvider> = Arc::new(load_test_provider());
+    assert_eq!(SECRET_1, find_secret_1(&shared).await);
+
+    let secrets = shared
+        .batch_find::<Vec<u8>>(&[SECRET_4_NAME, SECRET_2_NAME])
+        .await
+        .unwrap();
+    assert_eq!(1, secrets.len());
+    assert!(secrets.contains_key(SECRET_4_NAME));
+}
@@ -0,0 +1,159 @@
+//! Tests that are specific to the AWS DynamoDB + KMS implementation.
+//!
+//! Decrypted values can be read both as strings and binaries, so the generic tests do not apply
+//! to this implementation.
+
+use std::collections::HashMap;
+
+use secrets_provider::{SecretsProvider, SecretsProviderError};
+
+use crate::{seeds::constants::*, setup::dynamodb_kms::load_test_provider};
+
+#[tokio::test]
+async fn can_read_credstash_secrets() {
+    let wrapper = load_test_provider().await;
+    wrapper
+        .put_secret(SECRET_1_NAME, 1, SECRET_1.as_bytes())
+        .await;
+
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("0000000000000000001", secret.version);
+    assert_eq!(Some(true), secret.is_current());
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[tokio::test]
+async fn can_read_binary_secrets() {
+    let wrapper = load_test_provider().await;
+    wrapper.put_secret(SECRET_4_NAME, 1, SECRET_4).await;
+
+    let secret = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret.reveal());
+}
+
+#[tokio::test]
+async fn the_highest_version_is_current() {
+    let wrapper = load_test_provider().await;
+    wrapper
+        .put_secret(
+            VERSIONED_SECRET_NAME,
+            9,
+            VERSIONED_SECRET_VERSION_1.as_bytes(),
+        )
+        .await;
+    wrapper
+        .put_secret(
+            VERSIONED_SECRET_NAME,
+            10,
+            VERSIONED_SECRET_VERSION_2.as_bytes(),
+        )
+        .await;
+
+    let secret = wrapper
+        .provider
+        .find::<String>(VERSIONED_SECRET_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("0000000000000000010", secret.version);
+    assert_eq!(VERSIONED_SECRET_VERSION_2, secret.reveal());
+
+    // Versions can be requested without their padding
+    for version in ["9", "0000000000000000009"] {
+        let secret = wrapper
+            .provider
+            .find_with_version::<String>(VERSIONED_SECRET_NAME, version)
+            .await
+            .unwrap()
+            .expect("Secret version not found");
+        assert_eq!("0000000000000000009", secret.version);
+        assert_eq!(None, secret.is_current());
+        assert_eq!(VERSIONED_SECRET_VERSION_1, secret.reveal());
+    }
+}
+
+#[tokio::test]
+async fn data_keys_are_decrypted_with_the_encryption_context() {
+    let wrapper = load_test_provider().await;
+    let encryption_context = HashMap::from([("environment".to_string(), "production".to_string())]);
+    wrapper
+        .put_secret_with_context(
+            "context-secret",
+            1,
+            SECRET_2.as_bytes(),
+            encryption_context.clone(),
+        )
+        .await;
+
+    let result = wrapper.provider.find::<String>("context-secret").await;
+    assert!(
+        matches!(result, Err(SecretsProviderError::ProviderFailed(_))),
+        "{:?}",
+        result
+    );
+
+    let secret = wrapper
+        .provider
+        .clone()
+        .with_encryption_context(encryption_context)
+        .find::<String>("context-secret")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, secret.reveal());
+}
+
+#[tokio::test]
+async fn tampered_secrets_are_rejected() {
+    let wrapper = load_test_provider().await;
+    wrapper
+        .put_secret("tampered-secret", 1, SECRET_3.as_bytes())
+        .await;
+    wrapper.tamper_hmac("tampered-secret", 1).await;
+
+    let result = wrapper.provider.find::<String>("tampered-secret").await;
+    assert!(
+        matches!(result, Err(SecretsProviderError::ProviderFailed(ref e)) if e.contains("HMAC")),
+        "{:?}",
+        result
+    );
+}
+
+#[tokio::test]
+async fn missing_secrets_and_versions_are_not_found() {
+    let wrapper = load_test_provider().await;
+    wrapper
+        .put_secret(SECRET_1_NAME, 1, SECRET_1.as_bytes())
+        .await;
+
+    assert!(wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "not-a-version")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn self_test_checks_the_table() {
+    let wrapper = load_test_provider().await;
+
+    let report = wrapper.provider.self_test(None).await;
+    assert!(report.is_healthy(), "{:?}", report);
+}
@@ -0,0 +1,83 @@
+//! Tests of the structured secrets packed in binary formats, using the Memory implementation.
+
+use secrets_provider::{
+    encoded::Encoded, implementations::memory::MemorySecretsProvider, SecretsProvider,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::seeds::constants::*;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct EdgeConfig {
+    endpoint: String,
+    port: u16,
+    token: Vec<u8>,
+}
+
+fn edge_config() -> EdgeConfig {
+    EdgeConfig {
+        endpoint: "edge.example.com".to_string(),
+        port: 8443,
+        token: SECRET_4.to_vec(),
+    }
+}
+
+fn load_test_provider(payload: Vec<u8>) -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_binary_secret("edge-config".into(), payload);
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider
+}
+
+#[cfg(feature = "cbor")]
+#[tokio::test]
+async fn can_decode_cbor_secrets() {
+    use secrets_provider::encoded::Cbor;
+
+    let mut payload = Vec::new();
+    ciborium::ser::into_writer(&edge_config(), &mut payload).unwrap();
+    let provider = load_test_provider(payload);
+
+    let config = provider
+        .find::<Encoded<EdgeConfig, Cbor>>("edge-config")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(8443, config.port);
+    assert_eq!(edge_config(), config.into_inner());
+}
+
+#[cfg(feature = "msgpack")]
+#[tokio::test]
+async fn can_decode_message_pack_secrets() {
+    use secrets_provider::encoded::MessagePack;
+
+    let provider = load_test_provider(rmp_serde::to_vec(&edge_config()).unwrap());
+
+    let config = provider
+        .find::<Encoded<EdgeConfig, MessagePack>>("edge-config")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(edge_config(), config.into_inner());
+}
+
+#[cfg(feature = "cbor")]
+#[tokio::test]
+async fn invalid_payloads_and_string_secrets_are_rejected() {
+    use secrets_provider::{encoded::Cbor, SecretsProviderError};
+
+    let provider = load_test_provider(vec![0xff, 0x00, 0x13]);
+
+    let result = provider
+        .find::<Encoded<EdgeConfig, Cbor>>("edge-config")
+        .await;
+    assert!(matches!(result, Err(SecretsProviderError::InvalidType(_))));
+
+    let result = provider
+        .find::<Encoded<EdgeConfig, Cbor>>(SECRET_1_NAME)
+        .await;
+    assert!(matches!(result, Err(SecretsProviderError::InvalidType(_))));
+}
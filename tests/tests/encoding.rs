@@ -0,0 +1,97 @@
+//! Tests of the binary secrets stored as text, using the Memory implementation.
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider, SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+#[cfg(feature = "base64")]
+#[tokio::test]
+async fn can_decode_base64_secrets() {
+    use secrets_provider::encoding::Base64;
+
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret("signing-key".into(), "AAEC/w==\n".into());
+    provider.add_string_secret("password".into(), "aHVudGVyMg==".into());
+
+    let key = provider
+        .find::<Base64>("signing-key")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!([0x00, 0x01, 0x02, 0xff], key.as_slice());
+
+    let password = provider
+        .find::<Base64<String>>("password")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal()
+        .into_inner();
+    assert_eq!("hunter2", password);
+}
+
+#[cfg(feature = "base64")]
+#[tokio::test]
+async fn invalid_base64_and_binary_secrets_are_rejected() {
+    use secrets_provider::encoding::Base64;
+
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), "not base64!".into());
+    provider.add_binary_secret(SECRET_4_NAME.into(), SECRET_4.to_vec());
+
+    for name in [SECRET_1_NAME, SECRET_4_NAME] {
+        assert!(matches!(
+            provider.find::<Base64>(name).await,
+            Err(SecretsProviderError::InvalidType(_))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn can_decode_hex_secrets() {
+    use secrets_provider::encoding::Hex;
+
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(
+        SECRET_1_NAME.into(),
+        std::str::from_utf8(SECRET_4).unwrap().into(),
+    );
+    provider.add_string_secret("uppercase".into(), "00FFaB\n".into());
+
+    let key = provider
+        .find::<Hex>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(32, key.len());
+    assert_eq!([0x54, 0xa5, 0xd2], key[..3]);
+
+    let bytes = provider
+        .find::<Hex>("uppercase")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal()
+        .into_inner();
+    assert_eq!(vec![0x00, 0xff, 0xab], bytes);
+}
+
+#[tokio::test]
+async fn malformed_hex_secrets_are_rejected() {
+    use secrets_provider::encoding::Hex;
+
+    let mut provider = MemorySecretsProvider::new();
+    for (name, value) in [("odd", "abc"), ("sign", "+f"), ("letters", "zz")] {
+        provider.add_string_secret(name.into(), value.into());
+        match provider.find::<Hex>(name).await {
+            Err(SecretsProviderError::InvalidType(message)) => {
+                assert!(!message.contains(value), "Secret leaked: {message}")
+            }
+            other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Tests that are specific to the Environment variables implementation.
+//!
+//! Environment variables are not versioned, so the generic tests do not apply to this
+//! implementation.
+
+use secrets_provider::{
+    implementations::env::{default_name_transform, EnvSecretsProvider, ENV_SECRET_VERSION},
+    SecretsProvider, SecretsProviderError, VersionStage,
+};
+
+use crate::{seeds::constants::*, setup::env::load_test_provider};
+
+#[tokio::test]
+async fn can_read_string_and_binary_secrets() {
+    let provider = load_test_provider("ENV_READ_");
+
+    let secret_1 = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1_NAME, secret_1.name);
+    assert_eq!(ENV_SECRET_VERSION, secret_1.version);
+    assert_eq!(SECRET_1, secret_1.reveal());
+
+    let secret_4 = provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+}
+
+#[tokio::test]
+async fn reading_with_wrong_type_should_fail() {
+    let provider = load_test_provider("ENV_TYPES_");
+
+    match provider.find::<Vec<u8>>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+
+    match provider.find::<String>(SECRET_4_NAME).await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn base64_prefix_is_a_string_unless_enabled() {
+    std::env::set_var("ENV_NO_BASE64_SECRET", "base64:aGVsbG8=");
+    let provider = EnvSecretsProvider::new().with_prefix("ENV_NO_BASE64_");
+
+    let secret = provider
+        .find::<String>("secret")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("base64:aGVsbG8=", secret.reveal());
+}
+
+#[tokio::test]
+async fn malformed_base64_should_fail() {
+    let provider = load_test_provider("ENV_MALFORMED_");
+    std::env::set_var(provider.variable_name("malformed"), "base64:not base64!");
+
+    match provider.find::<Vec<u8>>("malformed").await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn non_existent_secret_should_be_none() {
+    let provider = load_test_provider("ENV_MISSING_");
+
+    let secret = provider.find::<String>("non-existent-secret").await.unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn find_with_version_only_returns_current_version() {
+    let provider = load_test_provider("ENV_VERSIONS_");
+
+    let secret = provider
+        .find_with_version::<String>(SECRET_1_NAME, ENV_SECRET_VERSION)
+        .await
+        .unwrap();
+    assert!(secret.is_some());
+
+    let secret = provider
+        .find_with_version::<String>(SECRET_1_NAME, "previous")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn only_the_current_stage_is_supported() {
+    let provider = load_test_provider("ENV_STAGES_");
+
+    let secret = provider
+        .find_with_stage::<String>(SECRET_1_NAME, &VersionStage::Current)
+        .await
+        .unwrap();
+    assert!(secret.is_some());
+
+    match provider
+        .find_with_stage::<String>(SECRET_1_NAME, &VersionStage::Previous)
+        .await
+    {
+        Err(SecretsProviderError::Unsupported(_)) => (),
+        r => panic!("Should have failed with Unsupported error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn name_transform_can_be_customized() {
+    assert_eq!("DATABASE_PASSWORD_1", default_name_transform("database/password-1"));
+
+    std::env::set_var("ENV_CUSTOM_secret.1", SECRET_2);
+    let provider = EnvSecretsProvider::new()
+        .with_prefix("ENV_CUSTOM_")
+        .with_name_transform(|name| name.replace('-', "."));
+    assert_eq!("ENV_CUSTOM_secret.1", provider.variable_name("secret-1"));
+
+    let secret = provider
+        .find::<String>("secret-1")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, secret.reveal());
+}
@@ -0,0 +1,130 @@
+//! Tests of the error mapping wrapper, using the chaos testing wrapper to make the Memory
+//! implementation fail.
+
+use std::sync::{Arc, Mutex};
+
+use secrets_provider::{
+    chaos::ChaosProvider,
+    error_mapping::{ErrorContext, ErrorMappingProvider, MappedError},
+    implementations::memory::MemorySecretsProvider,
+    trace::TraceOutcome,
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> ChaosProvider<MemorySecretsProvider> {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    ChaosProvider::new(provider).with_errors(1.0)
+}
+
+/// Treats failures of `legacy/` secrets as missing secrets.
+fn legacy_not_found(context: &ErrorContext<'_>, error: SecretsProviderError) -> MappedError {
+    match error {
+        SecretsProviderError::ProviderFailed(_)
+            if context
+                .secret_names
+                .iter()
+                .all(|name| name.starts_with("legacy/")) =>
+        {
+            MappedError::NotFound
+        }
+        error => MappedError::Error(error),
+    }
+}
+
+#[tokio::test]
+async fn errors_can_be_mapped_to_missing_secrets() {
+    let provider = ErrorMappingProvider::new(load_test_provider(), legacy_not_found);
+
+    assert!(provider
+        .find::<String>("legacy/database")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(provider
+        .find_with_version::<String>("legacy/database", "1")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(provider
+        .batch_find::<String>(&["legacy/database", "legacy/api-key"])
+        .await
+        .unwrap()
+        .is_empty());
+
+    match provider.find::<String>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+    match provider
+        .batch_find::<String>(&["legacy/database", SECRET_1_NAME])
+        .await
+    {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn errors_can_be_replaced() {
+    let contexts = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&contexts);
+    let provider = ErrorMappingProvider::new(
+        load_test_provider(),
+        move |context: &ErrorContext<'_>, _| {
+            seen.lock().unwrap().push((
+                context.secret_names.join(","),
+                context.version.map(str::to_string),
+                context.backend,
+            ));
+            MappedError::Error(SecretsProviderError::InvalidName(
+                context.secret_names.join(","),
+            ))
+        },
+    );
+
+    match provider
+        .find_with_version::<String>(SECRET_1_NAME, "1")
+        .await
+    {
+        Err(SecretsProviderError::InvalidName(name)) => assert_eq!(SECRET_1_NAME, name),
+        r => panic!("Should have failed with InvalidName error: {:?}", r),
+    }
+    assert_eq!(
+        vec![(
+            SECRET_1_NAME.to_string(),
+            Some("1".to_string()),
+            Some("Memory")
+        )],
+        *contexts.lock().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn successful_lookups_are_not_mapped() {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    let provider =
+        ErrorMappingProvider::new(provider, |_: &ErrorContext<'_>, _| MappedError::NotFound);
+
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[tokio::test]
+async fn traces_show_the_original_error() {
+    let provider = ErrorMappingProvider::new(load_test_provider(), legacy_not_found);
+
+    let trace = provider.resolve_trace("legacy/database").await;
+    assert_eq!(
+        "ErrorMappingProvider",
+        trace.layers().next().unwrap_or_default()
+    );
+    assert!(matches!(trace.outcome, TraceOutcome::Failed(_)));
+}
@@ -0,0 +1,149 @@
+//! Tests of the External Secrets Operator export.
+
+use std::time::Duration;
+
+use secrets_provider::{
+    eso::{DeclaredSecret, EsoExport, EsoProvider, SecretManifest},
+    SecretsProviderError,
+};
+
+const MANIFEST: &str = r#"
+secrets:
+  - name: database/password
+    key: DATABASE_PASSWORD
+  - name: api-token
+    version: AWSPREVIOUS
+"#;
+
+#[test]
+fn can_parse_a_secret_manifest() {
+    let manifest = SecretManifest::from_yaml(MANIFEST).unwrap();
+
+    assert_eq!(vec!["database/password", "api-token"], manifest.names());
+    assert_eq!(manifest.secrets[0].key(), "DATABASE_PASSWORD");
+    assert_eq!(manifest.secrets[1].key(), "api-token");
+    assert_eq!(Some("AWSPREVIOUS"), manifest.secrets[1].version.as_deref());
+
+    let result = SecretManifest::from_yaml("secrets: 42");
+    assert!(matches!(
+        result,
+        Err(SecretsProviderError::Initialization(_))
+    ));
+}
+
+#[test]
+fn can_generate_aws_manifests() {
+    let manifest = SecretManifest::from_yaml(MANIFEST).unwrap();
+    let generated = EsoExport::new(
+        "my-app",
+        EsoProvider::AwsSecretsManager {
+            region: "us-west-2".to_string(),
+        },
+    )
+    .with_namespace("apps")
+    .with_refresh_interval(Duration::from_secs(600))
+    .generate(&manifest)
+    .unwrap();
+
+    let documents = generated
+        .split("---\n")
+        .map(|document| serde_yaml::from_str::<serde_yaml::Value>(document).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(2, documents.len());
+
+    let store = &documents[0];
+    assert_eq!(store["apiVersion"], "external-secrets.io/v1beta1");
+    assert_eq!(store["kind"], "SecretStore");
+    assert_eq!(store["metadata"]["name"], "my-app");
+    assert_eq!(store["metadata"]["namespace"], "apps");
+    assert_eq!(
+        store["spec"]["provider"]["aws"]["service"],
+        "SecretsManager"
+    );
+    assert_eq!(store["spec"]["provider"]["aws"]["region"], "us-west-2");
+
+    let external_secret = &documents[1];
+    assert_eq!(external_secret["kind"], "ExternalSecret");
+    let spec = &external_secret["spec"];
+    assert_eq!(spec["refreshInterval"], "600s");
+    assert_eq!(spec["secretStoreRef"]["name"], "my-app");
+    assert_eq!(spec["target"]["name"], "my-app");
+    assert_eq!(spec["data"][0]["secretKey"], "DATABASE_PASSWORD");
+    assert_eq!(spec["data"][0]["remoteRef"]["key"], "database/password");
+    assert!(spec["data"][0]["remoteRef"].get("version").is_none());
+    assert_eq!(spec["data"][1]["secretKey"], "api-token");
+    assert_eq!(spec["data"][1]["remoteRef"]["version"], "AWSPREVIOUS");
+}
+
+#[test]
+fn can_generate_doppler_manifests() {
+    let manifest = SecretManifest::from_yaml(MANIFEST).unwrap();
+    let generated = EsoExport::new(
+        "my-app",
+        EsoProvider::Doppler {
+            token_secret_name: "doppler-token".to_string(),
+            token_secret_key: "token".to_string(),
+            project: Some("backend".to_string()),
+            config: None,
+        },
+    )
+    .with_target("my-app-secrets")
+    .generate(&manifest)
+    .unwrap();
+
+    let documents = generated
+        .split("---\n")
+        .map(|document| serde_yaml::from_str::<serde_yaml::Value>(document).unwrap())
+        .collect::<Vec<_>>();
+
+    let doppler = &documents[0]["spec"]["provider"]["doppler"];
+    let token = &doppler["auth"]["secretRef"]["dopplerToken"];
+    assert_eq!(token["name"], "doppler-token");
+    assert_eq!(token["key"], "token");
+    assert_eq!(doppler["project"], "backend");
+    assert!(doppler.get("config").is_none());
+    assert!(documents[0]
+        .get("metadata")
+        .unwrap()
+        .get("namespace")
+        .is_none());
+
+    let spec = &documents[1]["spec"];
+    assert_eq!(spec["refreshInterval"], "3600s");
+    assert_eq!(spec["target"]["name"], "my-app-secrets");
+}
+
+#[test]
+fn invalid_and_duplicated_keys_are_rejected() {
+    let export = EsoExport::new(
+        "my-app",
+        EsoProvider::AwsSecretsManager {
+            region: "us-west-2".to_string(),
+        },
+    );
+    let declared = |name: &str, key: Option<&str>| DeclaredSecret {
+        name: name.to_string(),
+        key: key.map(str::to_string),
+        version: None,
+    };
+
+    // Secret names with '/' need an explicit key
+    let manifest = SecretManifest {
+        secrets: vec![declared("database/password", None)],
+    };
+    assert!(matches!(
+        export.generate(&manifest),
+        Err(SecretsProviderError::InvalidName(_))
+    ));
+
+    let manifest = SecretManifest {
+        secrets: vec![
+            declared("database/password", Some("password")),
+            declared("password", None),
+        ],
+    };
+    assert!(matches!(
+        export.generate(&manifest),
+        Err(SecretsProviderError::InvalidName(_))
+    ));
+}
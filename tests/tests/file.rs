@@ -0,0 +1,132 @@
+//! Tests that are specific to the Secrets file implementation.
+//!
+//! Secrets files have a single version, so the generic tests do not apply to this
+//! implementation.
+
+use std::time::{Duration, SystemTime};
+
+use secrets_provider::{
+    implementations::file::{FileFormat, FileSecretsProvider},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::{
+    seeds::constants::*,
+    setup::file::{load_test_provider, JSON_SECRETS_FILE, YAML_SECRETS_FILE},
+};
+
+#[tokio::test]
+async fn can_read_json_and_yaml_files() {
+    for (file_name, content) in [
+        ("secrets.json", JSON_SECRETS_FILE),
+        ("secrets.yaml", YAML_SECRETS_FILE),
+    ] {
+        let wrapper = load_test_provider(file_name, content);
+
+        let secret_1 = wrapper
+            .provider
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(SECRET_1_NAME, secret_1.name);
+        assert_eq!(SECRET_1, secret_1.reveal());
+
+        let secret_4 = wrapper
+            .provider
+            .find::<Vec<u8>>(SECRET_4_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+    }
+}
+
+#[tokio::test]
+async fn reading_with_wrong_type_should_fail() {
+    let wrapper = load_test_provider("secrets.json", JSON_SECRETS_FILE);
+
+    match wrapper.provider.find::<Vec<u8>>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+
+    match wrapper.provider.find::<String>(SECRET_4_NAME).await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn non_existent_secret_should_be_none() {
+    let wrapper = load_test_provider("secrets.json", JSON_SECRETS_FILE);
+
+    let secret = wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn reloads_the_file_when_it_changes() {
+    let wrapper = load_test_provider("secrets.json", JSON_SECRETS_FILE);
+
+    let before = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+
+    std::fs::write(&wrapper.path, format!(r#"{{ "secret-1": "{}" }}"#, SECRET_2)).unwrap();
+    // Make sure the modification time changes, whatever the file system resolution is
+    std::fs::File::options()
+        .write(true)
+        .open(&wrapper.path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+
+    let after = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_ne!(before.version, after.version);
+    assert_eq!(SECRET_2, after.reveal());
+
+    let previous = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, &before.version)
+        .await
+        .unwrap();
+    assert!(previous.is_none());
+}
+
+#[tokio::test]
+async fn invalid_files_should_fail() {
+    let wrapper = load_test_provider("secrets.json", JSON_SECRETS_FILE);
+
+    let unknown_format = wrapper.dir.path().join("secrets.txt");
+    std::fs::write(&unknown_format, JSON_SECRETS_FILE).unwrap();
+    match FileSecretsProvider::new(&unknown_format) {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+    assert!(FileSecretsProvider::with_format(&unknown_format, FileFormat::Json).is_ok());
+
+    let invalid_base64 = wrapper.dir.path().join("invalid.yaml");
+    std::fs::write(&invalid_base64, "secret: binary:not base64!").unwrap();
+    match FileSecretsProvider::new(&invalid_base64) {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+
+    match FileSecretsProvider::new(wrapper.dir.path().join("missing.json")) {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+}
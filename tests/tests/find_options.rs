@@ -0,0 +1,141 @@
+//! Tests of lookups with options, using the Memory implementation, wrapped by the chaos testing
+//! wrapper for slow lookups.
+
+use std::time::{Duration, Instant};
+
+use secrets_provider::{
+    chaos::ChaosProvider, implementations::memory::MemorySecretsProvider, DecodeHint, FindOptions,
+    SecretsProvider, SecretsProviderError, VersionStage,
+};
+
+use crate::seeds::constants::*;
+
+#[tokio::test]
+async fn finds_the_requested_version() {
+    let mut provider = MemorySecretsProvider::new();
+    let first = provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_2.into());
+
+    let current = provider
+        .find_with_options::<String>(SECRET_1_NAME, &FindOptions::new())
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, current.reveal());
+
+    let options = FindOptions::new().with_version(first.version.clone());
+    let by_version = provider
+        .find_with_options::<String>(SECRET_1_NAME, &options)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(first.version, by_version.version);
+
+    // The last target set wins
+    let options = options.with_stage(VersionStage::Previous);
+    assert_eq!(None, options.version());
+    let by_stage = provider
+        .find_with_options::<String>(SECRET_1_NAME, &options)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, by_stage.reveal());
+}
+
+#[tokio::test]
+async fn decodes_values_as_hinted() {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_binary_secret(SECRET_4_NAME.into(), SECRET_4.to_vec());
+    provider.add_binary_secret(SECRET_5_NAME.into(), vec![0xff, 0xfe]);
+
+    let options = FindOptions::new().with_decode_hint(DecodeHint::Bytes);
+    let bytes = provider
+        .find_with_options::<Vec<u8>>(SECRET_1_NAME, &options)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1.as_bytes(), bytes.reveal());
+
+    let options = FindOptions::new().with_decode_hint(DecodeHint::Str);
+    let string = provider
+        .find_with_options::<String>(SECRET_4_NAME, &options)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.as_slice(), string.reveal().as_bytes());
+
+    match provider
+        .find_with_options::<String>(SECRET_5_NAME, &options)
+        .await
+    {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!(
+            "Should have failed with InvalidType error: {:?}",
+            r.map(|_| ())
+        ),
+    }
+}
+
+#[tokio::test]
+async fn slow_lookups_time_out() {
+    let mut inner = MemorySecretsProvider::new();
+    inner.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    let provider = ChaosProvider::new(inner).with_latency(1.0, Duration::from_secs(5));
+
+    let start = Instant::now();
+    let options = FindOptions::new().with_timeout(Duration::from_millis(50));
+    match provider
+        .find_with_options::<String>(SECRET_1_NAME, &options)
+        .await
+    {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!(
+            "Should have failed with ProviderFailed error: {:?}",
+            r.map(|_| ())
+        ),
+    }
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Lookups completing in time are not affected
+    let options = FindOptions::new().with_timeout(Duration::from_secs(5));
+    let secret = provider
+        .inner()
+        .find_with_options::<String>(SECRET_1_NAME, &options)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[tokio::test]
+async fn concurrent_lookups_time_out_independently() {
+    let mut inner = MemorySecretsProvider::new();
+    inner.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    let provider = ChaosProvider::new(inner).with_latency(1.0, Duration::from_millis(500));
+
+    // The short deadline is added after a longer one, and still expires first
+    let long = FindOptions::new().with_timeout(Duration::from_secs(5));
+    let short = FindOptions::new().with_timeout(Duration::from_millis(50));
+    let start = Instant::now();
+    let (completed, timed_out) = tokio::join!(
+        provider.find_with_options::<String>(SECRET_1_NAME, &long),
+        async {
+            let result = provider
+                .find_with_options::<String>(SECRET_1_NAME, &short)
+                .await;
+            (result, start.elapsed())
+        }
+    );
+
+    assert_eq!(
+        SECRET_1,
+        completed.unwrap().expect("Secret not found").reveal()
+    );
+    let (result, elapsed) = timed_out;
+    assert!(matches!(
+        result,
+        Err(SecretsProviderError::ProviderFailed(_))
+    ));
+    assert!(elapsed < Duration::from_millis(500), "{elapsed:?}");
+}
@@ -22,7 +22,7 @@ macro_rules! generate_generic_tests {
 
             async fn get_secrets_provider() -> impl SecretsProvider + SecretsProviderTestExt {
                 let mut provider = $setup_fn;
-                seed_secrets_provider(&mut provider).await;
+                seed_secrets_provider(&mut provider).await.unwrap();
                 provider
             }
 
@@ -126,15 +126,28 @@ macro_rules! generate_generic_tests {
                 assert!(non_existent_secret.is_none())
             }
 
+            #[tokio::test]
+            async fn checks_whether_secrets_exist() {
+                let secrets_provider = get_secrets_provider().await;
+
+                assert!(secrets_provider.exists(SECRET_1_NAME).await.unwrap());
+                assert!(secrets_provider.exists(SECRET_4_NAME).await.unwrap());
+                assert!(!secrets_provider
+                    .exists("non-existent-secret")
+                    .await
+                    .unwrap());
+            }
+
             #[tokio::test]
             async fn get_versioned_secrets_correctly() {
                 let secrets_provider = get_secrets_provider().await;
                 let secret_versions = secrets_provider
                     .list_secret_versions(VERSIONED_SECRET_NAME)
-                    .await;
+                    .await
+                    .unwrap();
 
                 let previous_secret = secrets_provider
-                    .find_with_version::<String>(VERSIONED_SECRET_NAME, &secret_versions[0])
+                    .find_with_version::<String>(VERSIONED_SECRET_NAME, &secret_versions[0].id)
                     .await
                     .unwrap()
                     .expect("Secret / version pair not found")
@@ -143,7 +156,7 @@ macro_rules! generate_generic_tests {
                 assert_eq!(previous_secret, VERSIONED_SECRET_VERSION_1);
 
                 let current_secret = secrets_provider
-                    .find_with_version::<String>(VERSIONED_SECRET_NAME, &secret_versions[1])
+                    .find_with_version::<String>(VERSIONED_SECRET_NAME, &secret_versions[1].id)
                     .await
                     .unwrap()
                     .expect("Secret / version pair not found")
@@ -152,6 +165,70 @@ macro_rules! generate_generic_tests {
                 assert_eq!(current_secret, VERSIONED_SECRET_VERSION_2);
             }
 
+            #[tokio::test]
+            async fn versions_report_whether_they_are_current() {
+                let secrets_provider = get_secrets_provider().await;
+                let secret_versions = secrets_provider
+                    .list_secret_versions(VERSIONED_SECRET_NAME)
+                    .await
+                    .unwrap();
+
+                // Backends that can not tell are not checked
+                let current_secret = secrets_provider
+                    .find::<String>(VERSIONED_SECRET_NAME)
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                assert_ne!(current_secret.is_current(), Some(false));
+
+                let previous_secret = secrets_provider
+                    .find_with_version::<String>(VERSIONED_SECRET_NAME, &secret_versions[0].id)
+                    .await
+                    .unwrap()
+                    .expect("Secret / version pair not found");
+                assert_ne!(previous_secret.is_current(), Some(true));
+            }
+
+            #[tokio::test]
+            async fn secret_versions_are_tagged_with_their_backend() {
+                use secrets_provider::{SecretVersion, SecretsProviderError};
+
+                let secrets_provider = get_secrets_provider().await;
+                let secret_versions = secrets_provider
+                    .list_secret_versions(VERSIONED_SECRET_NAME)
+                    .await
+                    .unwrap();
+
+                let previous_secret = secrets_provider
+                    .find_with_version::<String>(VERSIONED_SECRET_NAME, &secret_versions[0].id)
+                    .await
+                    .unwrap()
+                    .expect("Secret / version pair not found");
+                let version = previous_secret.secret_version();
+                assert_eq!(version.id(), secret_versions[0].id);
+
+                let same_secret = secrets_provider
+                    .find_with_secret_version::<String>(VERSIONED_SECRET_NAME, &version)
+                    .await
+                    .unwrap()
+                    .expect("Secret / version pair not found");
+                assert_eq!(same_secret.reveal(), VERSIONED_SECRET_VERSION_1);
+
+                // Providers that do not tag their versions accept any of them
+                if secrets_provider.backend().is_some() {
+                    assert_eq!(secrets_provider.backend(), Some(version.backend()));
+
+                    let foreign = SecretVersion::new("Another backend", version.id());
+                    match secrets_provider
+                        .find_with_secret_version::<String>(VERSIONED_SECRET_NAME, &foreign)
+                        .await
+                    {
+                        Err(SecretsProviderError::InvalidVersion(_)) => (),
+                        r => panic!("Should have failed with InvalidVersion error: {:?}", r),
+                    }
+                }
+            }
+
             #[tokio::test]
             async fn find_inexistent_secret() {
                 let secrets_provider = get_secrets_provider().await;
@@ -222,6 +299,480 @@ macro_rules! generate_generic_tests {
                     "Should've failed, instead returned: {retrieved:?}"
                 );
             }
+
+            #[tokio::test]
+            async fn finds_versions_by_stage() {
+                use secrets_provider::VersionStage;
+
+                let secrets_provider = get_secrets_provider().await;
+                let find = |stage: VersionStage| {
+                    let secrets_provider = &secrets_provider;
+                    async move {
+                        secrets_provider
+                            .find_with_stage::<String>(VERSIONED_SECRET_NAME, &stage)
+                            .await
+                            .map(|secret| secret.map(|s| s.reveal()))
+                    }
+                };
+
+                let current = find(VersionStage::Current).await.unwrap();
+                assert_eq!(Some(VERSIONED_SECRET_VERSION_2.to_string()), current);
+
+                // Backends without stages are not checked
+                match find(VersionStage::Previous).await {
+                    Err(SecretsProviderError::Unsupported(_)) => return,
+                    previous => {
+                        assert_eq!(
+                            Some(VERSIONED_SECRET_VERSION_1.to_string()),
+                            previous.unwrap()
+                        )
+                    }
+                }
+                assert_eq!(None, find(VersionStage::Pending).await.unwrap());
+            }
+
+            #[tokio::test]
+            async fn lists_versions_with_their_stages() {
+                use secrets_provider::VersionStage;
+
+                let secrets_provider = get_secrets_provider().await;
+                let versions = secrets_provider
+                    .list_secret_versions(VERSIONED_SECRET_NAME)
+                    .await
+                    .unwrap();
+
+                assert_eq!(2, versions.len());
+                assert!(versions[0].stages.contains(&VersionStage::Previous));
+                assert!(versions[1].stages.contains(&VersionStage::Current));
+                if let (Some(previous), Some(current)) =
+                    (versions[0].created_at, versions[1].created_at)
+                {
+                    assert!(previous <= current);
+                }
+
+                let missing = secrets_provider
+                    .list_secret_versions("non-existent-secret")
+                    .await
+                    .unwrap();
+                assert!(missing.is_empty());
+            }
+
+            #[tokio::test]
+            async fn lists_secrets_in_pages() {
+                use secrets_provider::ListFilter;
+
+                let secrets_provider = get_secrets_provider().await;
+                let filter = ListFilter {
+                    name_prefix: Some("secret-".to_string()),
+                    page_size: Some(4),
+                };
+
+                let mut names = Vec::new();
+                let mut page_token = None;
+                loop {
+                    // Backends that can not list are not checked
+                    let page = match secrets_provider
+                        .list_secrets(&filter, page_token.as_deref())
+                        .await
+                    {
+                        Err(SecretsProviderError::Unsupported(_)) => return,
+                        page => page.unwrap(),
+                    };
+                    assert!(page.secrets.len() <= 4);
+                    names.extend(page.secrets.into_iter().map(|s| s.name));
+
+                    page_token = page.next_page_token;
+                    if page_token.is_none() {
+                        break;
+                    }
+                }
+
+                names.sort();
+                assert_eq!(
+                    vec![
+                        SECRET_1_NAME,
+                        SECRET_2_NAME,
+                        SECRET_3_NAME,
+                        SECRET_4_NAME,
+                        SECRET_5_NAME,
+                        SECRET_6_NAME
+                    ],
+                    names
+                );
+            }
+
+            #[tokio::test]
+            async fn lists_secrets_as_a_stream() {
+                use futures_util::TryStreamExt;
+                use secrets_provider::ListFilter;
+
+                let secrets_provider = get_secrets_provider().await;
+                let filter = ListFilter {
+                    name_prefix: Some("secret-".to_string()),
+                    page_size: Some(2),
+                };
+
+                // Backends that can not list are not checked
+                let mut names: Vec<String> = match secrets_provider
+                    .list_secrets_stream(&filter)
+                    .map_ok(|secret| secret.name)
+                    .try_collect()
+                    .await
+                {
+                    Err(SecretsProviderError::Unsupported(_)) => return,
+                    names => names.unwrap(),
+                };
+
+                names.sort();
+                assert_eq!(
+                    vec![
+                        SECRET_1_NAME,
+                        SECRET_2_NAME,
+                        SECRET_3_NAME,
+                        SECRET_4_NAME,
+                        SECRET_5_NAME,
+                        SECRET_6_NAME
+                    ],
+                    names
+                );
+            }
+
+            #[tokio::test]
+            async fn finds_secrets_by_prefix() {
+                use secrets_provider::SecretData;
+
+                let secrets_provider = get_secrets_provider().await;
+
+                // Backends that can not list are not checked
+                let mut secrets = match secrets_provider
+                    .find_by_prefix::<String>("versioned-*")
+                    .await
+                {
+                    Err(SecretsProviderError::Unsupported(_)) => return,
+                    secrets => secrets.unwrap(),
+                };
+                assert_eq!(1, secrets.len());
+                let secret = secrets
+                    .remove(VERSIONED_SECRET_NAME)
+                    .expect("Secret not found");
+                assert_eq!(VERSIONED_SECRET_VERSION_2, secret.reveal());
+
+                let all = secrets_provider
+                    .find_by_prefix::<SecretData>("secret-")
+                    .await
+                    .unwrap();
+                assert_eq!(6, all.len());
+                assert!(all.contains_key(SECRET_4_NAME));
+            }
+
+            #[tokio::test]
+            async fn self_test_reads_canary() {
+                let secrets_provider = get_secrets_provider().await;
+
+                let report = secrets_provider.self_test(Some(SECRET_4_NAME)).await;
+                assert!(report.is_healthy(), "Self-test failed: {report:?}");
+
+                let report = secrets_provider.self_test(Some("missing")).await;
+                let failed: Vec<_> = report.failed_checks().map(|c| c.name.as_str()).collect();
+                assert_eq!(failed, ["find_canary"]);
+            }
+
+            #[tokio::test]
+            async fn missing_secret_follows_golden_error_mapping() {
+                use secrets_provider::testkit::{check_error_mapping, BackendFault};
+
+                let secrets_provider = get_secrets_provider().await;
+
+                check_error_mapping(
+                    &secrets_provider,
+                    "non-existent-secret",
+                    BackendFault::NotFound,
+                )
+                .await
+                .unwrap();
+            }
+        }
+    };
+}
+
+/// Generate writer tests for a given implementation.
+///
+/// Usage:
+///     generate_generic_writer_tests(get_mock_secrets_provider())
+///
+/// It will generate a new module called `generic_writer` and call
+/// `get_mock_secrets_provider()` before each test to get an instance
+/// of the secrets provider, like [generate_generic_tests].
+///
+/// Any expression resulting in an
+/// `impl SecretsProvider + SecretsWriter + SecretsProviderTestExt` can be
+/// used in the place of `get_mock_secrets_provider()`. The expression can
+/// be `async`.
+#[macro_export]
+macro_rules! generate_generic_writer_tests {
+    ($setup_fn:expr) => {
+        mod generic_writer {
+            use std::collections::BTreeMap;
+
+            use secrets_provider::{
+                DeleteOptions, PutResult, SecretsProvider, SecretsProviderError, SecretsWriter,
+                WriteOptions,
+            };
+            use $crate::{
+                helpers::test_ext::SecretsProviderTestExt,
+                seeds::{constants::*, seeder::seed_secrets_provider},
+            };
+
+            const NEW_SECRET_NAME: &str = "new-secret";
+
+            async fn get_secrets_provider(
+            ) -> impl SecretsProvider + SecretsWriter + SecretsProviderTestExt + Sync {
+                let mut provider = $setup_fn;
+                seed_secrets_provider(&mut provider).await.unwrap();
+                provider
+            }
+
+            #[tokio::test]
+            async fn creates_secrets_and_adds_versions() {
+                let secrets_provider = get_secrets_provider().await;
+
+                let first = secrets_provider
+                    .create_secret(NEW_SECRET_NAME, SECRET_1.into())
+                    .await
+                    .unwrap();
+                match secrets_provider
+                    .create_secret(NEW_SECRET_NAME, SECRET_2.into())
+                    .await
+                {
+                    Err(SecretsProviderError::AlreadyExists(_)) => (),
+                    r => panic!("Should have failed with AlreadyExists error: {:?}", r),
+                }
+
+                let second = secrets_provider
+                    .put_secret_value(NEW_SECRET_NAME, SECRET_2.into())
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                let versions = secrets_provider
+                    .list_secret_versions(NEW_SECRET_NAME)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    vec![first, second.clone()],
+                    versions.into_iter().map(|v| v.id).collect::<Vec<_>>()
+                );
+
+                let current = secrets_provider
+                    .find::<String>(NEW_SECRET_NAME)
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                assert_eq!(second, current.version);
+                assert_eq!(SECRET_2, current.reveal());
+
+                assert!(secrets_provider
+                    .put_secret_value("non-existent-secret", SECRET_4.as_slice().into())
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+
+            #[tokio::test]
+            async fn retried_writes_with_the_same_token_are_idempotent() {
+                // Tokens are long enough for AWS Secrets Manager, which wants 32 characters
+                const CREATE_TOKEN: &str = "3f1c9a2e-5b7d-4e8f-9a0b-1c2d3e4f5a6b";
+                const PUT_TOKEN: &str = "7a8b9c0d-1e2f-4a3b-8c4d-5e6f7a8b9c0d";
+
+                let secrets_provider = get_secrets_provider().await;
+
+                // Backends that can not make writes idempotent are not checked
+                let create = WriteOptions::new().with_client_request_token(CREATE_TOKEN);
+                let first = match secrets_provider
+                    .create_secret_with_options(NEW_SECRET_NAME, SECRET_1.into(), &create)
+                    .await
+                {
+                    Err(SecretsProviderError::Unsupported(_)) => return,
+                    first => first.unwrap(),
+                };
+                assert_eq!(CREATE_TOKEN, first);
+                let retried = secrets_provider
+                    .create_secret_with_options(NEW_SECRET_NAME, SECRET_1.into(), &create)
+                    .await
+                    .unwrap();
+                assert_eq!(first, retried);
+
+                let put = WriteOptions::new().with_client_request_token(PUT_TOKEN);
+                for _ in 0..2 {
+                    let version = secrets_provider
+                        .put_secret_value_with_options(NEW_SECRET_NAME, SECRET_2.into(), &put)
+                        .await
+                        .unwrap();
+                    assert_eq!(Some(PUT_TOKEN.to_string()), version);
+                }
+                let versions = secrets_provider
+                    .list_secret_versions(NEW_SECRET_NAME)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    vec![CREATE_TOKEN, PUT_TOKEN],
+                    versions.iter().map(|v| v.id.as_str()).collect::<Vec<_>>()
+                );
+
+                // Reusing a token with another value fails
+                match secrets_provider
+                    .put_secret_value_with_options(NEW_SECRET_NAME, SECRET_3.into(), &put)
+                    .await
+                {
+                    Err(SecretsProviderError::InvalidVersion(_)) => (),
+                    r => panic!("Should have failed with InvalidVersion error: {:?}", r),
+                }
+            }
+
+            #[tokio::test]
+            async fn deletes_and_restores_secrets() {
+                let secrets_provider = get_secrets_provider().await;
+
+                let deletion_date = secrets_provider
+                    .delete_secret(SECRET_1_NAME, DeleteOptions::RecoveryWindowDays(7))
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                assert!(deletion_date > std::time::SystemTime::now());
+                assert!(secrets_provider
+                    .find::<String>(SECRET_1_NAME)
+                    .await
+                    .unwrap()
+                    .is_none());
+                // The name is reserved during the recovery window
+                assert!(secrets_provider
+                    .create_secret(SECRET_1_NAME, SECRET_2.into())
+                    .await
+                    .is_err());
+
+                assert!(secrets_provider
+                    .restore_secret(SECRET_1_NAME)
+                    .await
+                    .unwrap());
+                let restored = secrets_provider
+                    .find::<String>(SECRET_1_NAME)
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                assert_eq!(SECRET_1, restored.reveal());
+
+                secrets_provider
+                    .delete_secret(SECRET_1_NAME, DeleteOptions::Force)
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                assert!(!secrets_provider
+                    .restore_secret(SECRET_1_NAME)
+                    .await
+                    .unwrap());
+                secrets_provider
+                    .create_secret(SECRET_1_NAME, SECRET_2.into())
+                    .await
+                    .unwrap();
+
+                assert!(secrets_provider
+                    .delete_secret("non-existent-secret", DeleteOptions::default())
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+
+            #[tokio::test]
+            async fn writes_many_secrets_all_or_nothing() {
+                let secrets_provider = get_secrets_provider().await;
+
+                let report = secrets_provider
+                    .put_many(vec![
+                        (SECRET_1_NAME.to_string(), SECRET_2.into()),
+                        (NEW_SECRET_NAME.to_string(), SECRET_3.into()),
+                    ])
+                    .await;
+                assert!(report.committed());
+                for (name, value) in [(SECRET_1_NAME, SECRET_2), (NEW_SECRET_NAME, SECRET_3)] {
+                    let secret = secrets_provider
+                        .find::<String>(name)
+                        .await
+                        .unwrap()
+                        .expect("Secret not found");
+                    assert_eq!(value, secret.reveal());
+                }
+
+                // The name of a deleted secret is reserved, so none of the entries are kept
+                secrets_provider
+                    .delete_secret(NEW_SECRET_NAME, DeleteOptions::default())
+                    .await
+                    .unwrap();
+                let report = secrets_provider
+                    .put_many(vec![
+                        (SECRET_1_NAME.to_string(), SECRET_3.into()),
+                        (NEW_SECRET_NAME.to_string(), SECRET_1.into()),
+                    ])
+                    .await;
+                assert!(!report.committed());
+                assert!(matches!(
+                    report.results[0].1,
+                    PutResult::NotAttempted | PutResult::RolledBack
+                ));
+                assert!(matches!(report.results[1].1, PutResult::Failed(_)));
+                let secret = secrets_provider
+                    .find::<String>(SECRET_1_NAME)
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                assert_eq!(SECRET_2, secret.reveal());
+            }
+
+            #[tokio::test]
+            async fn tags_and_untags_secrets() {
+                let secrets_provider = get_secrets_provider().await;
+
+                let tags = BTreeMap::from([
+                    ("owner".to_string(), "payments".to_string()),
+                    ("cost-center".to_string(), "1234".to_string()),
+                ]);
+                // Backends that can not tag secrets are not checked
+                match secrets_provider.tag_secret(SECRET_1_NAME, tags).await {
+                    Err(SecretsProviderError::Unsupported(_)) => return,
+                    tagged => assert!(tagged.unwrap()),
+                }
+                assert!(!secrets_provider
+                    .tag_secret("non-existent-secret", BTreeMap::new())
+                    .await
+                    .unwrap());
+
+                assert!(secrets_provider
+                    .untag_secret(SECRET_1_NAME, &["cost-center", "unknown"])
+                    .await
+                    .unwrap());
+                assert!(!secrets_provider
+                    .untag_secret("non-existent-secret", &["owner"])
+                    .await
+                    .unwrap());
+
+                // Tags are kept while the secret is deleted, and backends that do not return
+                // tags with values are not checked
+                secrets_provider
+                    .delete_secret(SECRET_1_NAME, DeleteOptions::default())
+                    .await
+                    .unwrap();
+                assert!(secrets_provider
+                    .restore_secret(SECRET_1_NAME)
+                    .await
+                    .unwrap());
+                let secret = secrets_provider
+                    .find::<String>(SECRET_1_NAME)
+                    .await
+                    .unwrap()
+                    .expect("Secret not found");
+                if let Some(tags) = secret.tags() {
+                    let expected = BTreeMap::from([("owner".to_string(), "payments".to_string())]);
+                    assert_eq!(&expected, tags);
+                }
+            }
         }
     };
 }
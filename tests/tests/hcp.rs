@@ -0,0 +1,217 @@
+//! Tests that are specific to the HCP Vault Secrets implementation, using mocked HCP APIs.
+//!
+//! HCP Vault Secrets only stores string secrets, with names that the shared fixtures do not
+//! follow, so the generic tests do not apply to this implementation.
+
+use secrets_provider::{SecretsProvider, SecretsProviderError};
+use wiremock::matchers::{method, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+use crate::{
+    seeds::constants::*,
+    setup::hcp::{
+        app_request, build_provider, load_test_provider, open_secret, token_request,
+        HCP_SECRET_1_NAME, HCP_SECRET_2_NAME,
+    },
+};
+
+#[tokio::test]
+async fn can_read_current_and_previous_versions() {
+    let wrapper = load_test_provider().await;
+
+    let secret = wrapper
+        .provider
+        .find::<String>(HCP_SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(HCP_SECRET_1_NAME, secret.name);
+    assert_eq!("2", secret.version);
+    assert_eq!(Some(true), secret.is_current());
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let previous = wrapper
+        .provider
+        .find_with_version::<String>(HCP_SECRET_1_NAME, "1")
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!("1", previous.version);
+    assert_eq!(SECRET_2, previous.reveal());
+
+    for version in ["3", "not-a-version"] {
+        let missing = wrapper
+            .provider
+            .find_with_version::<String>(HCP_SECRET_1_NAME, version)
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    let missing = wrapper
+        .provider
+        .find::<String>("missing_secret")
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn only_static_string_secrets_can_be_read() {
+    let wrapper = load_test_provider().await;
+
+    let result = wrapper.provider.find::<Vec<u8>>(HCP_SECRET_1_NAME).await;
+    assert!(matches!(result, Err(SecretsProviderError::InvalidType(_))));
+
+    let result = wrapper.provider.find::<String>(HCP_SECRET_2_NAME).await;
+    assert!(matches!(result, Err(SecretsProviderError::InvalidType(_))));
+}
+
+#[tokio::test]
+async fn access_token_is_reused() {
+    let server = MockServer::start().await;
+    token_request().expect(1).mount(&server).await;
+    app_request(&format!("/secrets/{}:open", HCP_SECRET_1_NAME))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "secret": open_secret(HCP_SECRET_1_NAME, SECRET_1, 1, 1),
+        })))
+        .expect(3)
+        .mount(&server)
+        .await;
+    let provider = build_provider(&server);
+
+    for _ in 0..3 {
+        provider
+            .find::<String>(HCP_SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found");
+    }
+}
+
+#[tokio::test]
+async fn batch_find_opens_every_page_of_secrets() {
+    let server = MockServer::start().await;
+    token_request().mount(&server).await;
+    app_request("/secrets:open")
+        .and(query_param("pagination.next_page_token", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "secrets": [open_secret(HCP_SECRET_2_NAME, SECRET_2, 1, 1)],
+            "pagination": {"next_page_token": ""},
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    app_request("/secrets:open")
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "secrets": [
+                open_secret(HCP_SECRET_1_NAME, SECRET_1, 1, 1),
+                open_secret("unrequested_secret", SECRET_3, 1, 1),
+            ],
+            "pagination": {"next_page_token": "page-2"},
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let provider = build_provider(&server);
+
+    let mut secrets = provider
+        .batch_find::<String>(&[HCP_SECRET_1_NAME, HCP_SECRET_2_NAME, "missing_secret"])
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+    assert_eq!(
+        SECRET_1,
+        secrets.remove(HCP_SECRET_1_NAME).unwrap().reveal()
+    );
+    assert_eq!(
+        SECRET_2,
+        secrets.remove(HCP_SECRET_2_NAME).unwrap().reveal()
+    );
+}
+
+#[tokio::test]
+async fn authentication_errors_should_fail() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "error": "invalid_client",
+            "error_description": "Unauthorized",
+        })))
+        .mount(&server)
+        .await;
+    let provider = build_provider(&server);
+
+    match provider.find::<String>(HCP_SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(e)) => {
+            assert!(e.contains("Unauthorized"), "Unexpected error: {}", e)
+        }
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+
+    let report = provider.self_test(None).await;
+    let failed: Vec<_> = report.failed_checks().map(|c| c.name.as_str()).collect();
+    assert_eq!(failed, ["authenticate"]);
+}
+
+#[tokio::test]
+async fn invalid_names_fail_before_any_request() {
+    let server = MockServer::start().await;
+    token_request().expect(0).mount(&server).await;
+    let provider = build_provider(&server);
+
+    match provider.find::<String>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::InvalidName(e)) => {
+            assert!(e.contains("'-' at position 6"), "Unexpected error: {}", e)
+        }
+        r => panic!("Should have failed with InvalidName error: {:?}", r),
+    }
+
+    let result = provider
+        .batch_find::<String>(&[HCP_SECRET_1_NAME, ""])
+        .await;
+    assert!(matches!(result, Err(SecretsProviderError::InvalidName(_))));
+}
+
+#[tokio::test]
+async fn backend_errors_follow_golden_error_mapping() {
+    use secrets_provider::testkit::{check_error_mapping, BackendFault};
+
+    let wrapper = load_test_provider().await;
+    check_error_mapping(&wrapper.provider, "missing_secret", BackendFault::NotFound)
+        .await
+        .unwrap();
+
+    for (status, fault) in [
+        (403, BackendFault::AccessDenied),
+        (429, BackendFault::Throttled),
+    ] {
+        let server = MockServer::start().await;
+        token_request().mount(&server).await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(status))
+            .mount(&server)
+            .await;
+
+        check_error_mapping(&build_provider(&server), HCP_SECRET_1_NAME, fault)
+            .await
+            .unwrap();
+    }
+
+    // Nothing listens on the discard port
+    let server = MockServer::start().await;
+    let provider = secrets_provider::implementations::hcp::HcpVaultSecretsProviderBuilder::new(
+        "id".to_string(),
+        "secret".to_string(),
+        "test-org".to_string(),
+        "test-project".to_string(),
+        "test-app".to_string(),
+    )
+    .endpoint_override(server.uri())
+    .auth_endpoint_override("http://127.0.0.1:9".to_string())
+    .build()
+    .unwrap();
+    check_error_mapping(&provider, HCP_SECRET_1_NAME, BackendFault::Network)
+        .await
+        .unwrap();
+}
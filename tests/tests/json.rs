@@ -0,0 +1,121 @@
+//! Tests of the structured secrets stored as JSON documents, using the Memory implementation.
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider, json::Json, SecretsProvider,
+    SecretsProviderError,
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct DatabaseCredentials {
+    username: String,
+    password: String,
+    host: String,
+}
+
+const CREDENTIALS: &str = r#"{"username": "admin", "password": "hunter2", "host": "db.internal"}"#;
+
+#[tokio::test]
+async fn can_decode_json_secrets() {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret("database".into(), CREDENTIALS.into());
+    provider.add_binary_secret("database-binary".into(), CREDENTIALS.as_bytes().to_vec());
+
+    let expected = DatabaseCredentials {
+        username: "admin".to_string(),
+        password: "hunter2".to_string(),
+        host: "db.internal".to_string(),
+    };
+    for name in ["database", "database-binary"] {
+        let credentials = provider
+            .find::<Json<DatabaseCredentials>>(name)
+            .await
+            .unwrap()
+            .expect("Secret not found")
+            .reveal();
+        assert_eq!("admin", credentials.username);
+        assert_eq!(expected, credentials.into_inner());
+    }
+}
+
+#[tokio::test]
+async fn invalid_documents_are_rejected_without_their_content() {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(
+        "database".into(),
+        r#"{"username": "admin", "password": 1234}"#.into(),
+    );
+
+    let result = provider.find::<Json<DatabaseCredentials>>("database").await;
+    match result {
+        Err(SecretsProviderError::InvalidType(message)) => {
+            assert!(message.starts_with("database"));
+            assert!(!message.contains("1234"), "Secret leaked: {message}");
+        }
+        other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[tokio::test]
+async fn can_decode_key_value_secrets() {
+    use std::collections::HashMap;
+
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret("database".into(), CREDENTIALS.into());
+    provider.add_string_secret("nested".into(), r#"{"database": {"port": 5432}}"#.into());
+
+    let values = provider
+        .find::<HashMap<String, String>>("database")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(3, values.len());
+    assert_eq!("hunter2", values["password"]);
+
+    // Only string values are supported
+    assert!(matches!(
+        provider.find::<HashMap<String, String>>("nested").await,
+        Err(SecretsProviderError::InvalidType(_))
+    ));
+}
+
+#[tokio::test]
+async fn can_find_a_single_field() {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(
+        "cluster".into(),
+        r#"{"password": "hunter2", "replicas": [{"host": "db-1", "port": 5432}]}"#.into(),
+    );
+
+    let password = provider
+        .find_field::<String>("cluster", "/password")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("cluster", password.name);
+    assert_eq!("hunter2", password.reveal());
+
+    let port = provider
+        .find_field::<u16>("cluster", "/replicas/0/port")
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(5432, port);
+
+    assert!(provider
+        .find_field::<String>("missing", "/password")
+        .await
+        .unwrap()
+        .is_none());
+    for (pointer, expected) in [("/username", "no field"), ("/password", "invalid field")] {
+        match provider.find_field::<u16>("cluster", pointer).await {
+            Err(SecretsProviderError::InvalidType(message)) => {
+                assert!(message.contains(expected), "Unexpected error: {message}");
+                assert!(!message.contains("hunter2"), "Secret leaked: {message}");
+            }
+            other => panic!("Unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+}
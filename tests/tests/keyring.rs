@@ -0,0 +1,76 @@
+//! Tests that are specific to the OS keyring implementation.
+//!
+//! They run against an in-memory keyring store shared by the whole test process. Keyring values
+//! are bytes and are not versioned, so the conformance suite is run without the previous
+//! versions and strict types cases.
+
+use secrets_provider::{
+    implementations::keyring::KEYRING_SECRET_VERSION,
+    testkit::{run_conformance_suite, ConformanceOptions},
+    SecretsProvider,
+};
+
+use crate::{seeds::constants::*, setup::keyring::load_test_provider};
+
+#[tokio::test]
+async fn conformance_suite_passes() {
+    let mut wrapper = load_test_provider();
+
+    let options = ConformanceOptions {
+        versions: false,
+        strict_types: false,
+        ..Default::default()
+    };
+    let report = run_conformance_suite(&mut wrapper, &options).await;
+    assert!(report.passed(), "Conformance suite failed: {report:?}");
+}
+
+#[tokio::test]
+async fn secrets_can_be_replaced_and_deleted() {
+    let wrapper = load_test_provider();
+    wrapper
+        .provider
+        .set_secret(SECRET_1_NAME, SECRET_1)
+        .unwrap();
+    wrapper
+        .provider
+        .set_secret(SECRET_1_NAME, SECRET_2)
+        .unwrap();
+
+    let secret = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, KEYRING_SECRET_VERSION)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, secret.reveal());
+
+    let secret = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "1")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+
+    assert!(wrapper.provider.delete_secret(SECRET_1_NAME).unwrap());
+    assert!(!wrapper.provider.delete_secret(SECRET_1_NAME).unwrap());
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn services_do_not_share_secrets() {
+    let wrapper = load_test_provider();
+    let other = load_test_provider();
+    wrapper
+        .provider
+        .set_secret(SECRET_1_NAME, SECRET_1)
+        .unwrap();
+
+    let secret = other.provider.find::<String>(SECRET_1_NAME).await.unwrap();
+    assert!(secret.is_none());
+}
@@ -0,0 +1,144 @@
+//! Tests of rotating signing key sets, using the Memory implementation to store the keys.
+
+use std::time::Duration;
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider,
+    keyset::{HmacSha256Key, KeySet},
+    SecretsProviderError, SecretsWriter,
+};
+
+const KEY_NAME: &str = "webhook-signing-key";
+const MESSAGE: &[u8] = b"{\"event\": \"payment.succeeded\"}";
+
+async fn load_test_key_set() -> KeySet<HmacSha256Key, MemorySecretsProvider> {
+    let provider = MemorySecretsProvider::new();
+    provider
+        .create_secret(KEY_NAME, "first key".into())
+        .await
+        .unwrap();
+    KeySet::load(provider, KEY_NAME).await.unwrap()
+}
+
+async fn rotate(key_set: &KeySet<HmacSha256Key, MemorySecretsProvider>, key: &str) -> String {
+    key_set
+        .provider()
+        .put_secret_value(KEY_NAME, key.into())
+        .await
+        .unwrap()
+        .expect("Key not found")
+}
+
+#[tokio::test]
+async fn signs_with_the_current_key() {
+    let key_set = load_test_key_set().await;
+
+    let signature = key_set.sign(MESSAGE);
+    assert_eq!(key_set.current_version(), signature.key_version);
+    assert_eq!(32, signature.bytes.len());
+    assert_eq!(
+        Some(signature.key_version.clone()),
+        key_set.verify(MESSAGE, &signature.bytes)
+    );
+    assert_eq!(None, key_set.verify(b"tampered", &signature.bytes));
+}
+
+#[tokio::test]
+async fn verifies_with_previous_keys_after_rotation() {
+    let key_set = load_test_key_set().await;
+    let first = key_set.sign(MESSAGE);
+
+    let second_version = rotate(&key_set, "second key").await;
+    assert!(key_set.refresh().await.unwrap());
+    assert!(!key_set.refresh().await.unwrap());
+
+    let second = key_set.sign(MESSAGE);
+    assert_eq!(second_version, second.key_version);
+    assert_ne!(first.bytes, second.bytes);
+    assert_eq!(
+        Some(first.key_version.clone()),
+        key_set.verify(MESSAGE, &first.bytes)
+    );
+
+    // Only one previous key is kept by default
+    rotate(&key_set, "third key").await;
+    key_set.refresh().await.unwrap();
+    assert_eq!(2, key_set.versions().len());
+    assert_eq!(None, key_set.verify(MESSAGE, &first.bytes));
+    assert!(key_set.verify(MESSAGE, &second.bytes).is_some());
+}
+
+#[tokio::test]
+async fn unknown_signatures_refresh_the_key_set() {
+    let key_set = load_test_key_set()
+        .await
+        .with_min_refresh_interval(Duration::ZERO);
+
+    // Another instance signs with the rotated key before this one refreshed
+    let rotated_version = rotate(&key_set, "rotated key").await;
+    let signer = MemorySecretsProvider::new();
+    signer
+        .create_secret(KEY_NAME, "rotated key".into())
+        .await
+        .unwrap();
+    let signature = KeySet::<HmacSha256Key, _>::load(signer, KEY_NAME)
+        .await
+        .unwrap()
+        .sign(MESSAGE);
+
+    assert_eq!(None, key_set.verify(MESSAGE, &signature.bytes));
+    assert_eq!(
+        Some(rotated_version),
+        key_set
+            .verify_or_refresh(MESSAGE, &signature.bytes)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn refreshes_triggered_by_signatures_are_rate_limited() {
+    let key_set = load_test_key_set().await;
+    rotate(&key_set, "rotated key").await;
+
+    assert_eq!(
+        None,
+        key_set
+            .verify_or_refresh(MESSAGE, b"invalid signature")
+            .await
+            .unwrap()
+    );
+    assert_eq!(1, key_set.versions().len());
+}
+
+#[tokio::test]
+async fn previous_versions_can_be_added() {
+    let provider = MemorySecretsProvider::new();
+    let first_version = provider
+        .create_secret(KEY_NAME, "first key".into())
+        .await
+        .unwrap();
+    provider
+        .put_secret_value(KEY_NAME, "second key".into())
+        .await
+        .unwrap();
+
+    let key_set = KeySet::<HmacSha256Key, _>::load(provider, KEY_NAME)
+        .await
+        .unwrap();
+    assert!(key_set.add_previous_version(&first_version).await.unwrap());
+    assert!(!key_set.add_previous_version("unknown").await.unwrap());
+    assert_eq!(
+        vec![key_set.current_version(), first_version],
+        key_set.versions()
+    );
+}
+
+#[tokio::test]
+async fn missing_keys_fail_to_load() {
+    let result = KeySet::<HmacSha256Key, _>::load(MemorySecretsProvider::new(), KEY_NAME).await;
+    assert!(matches!(
+        result,
+        Err(SecretsProviderError::ProviderFailed(_))
+    ));
+}
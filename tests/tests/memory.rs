@@ -3,7 +3,173 @@
 //! It's very unlikely that you'd need to create a specific test for this implementation.
 //! Create a generic test instead.
 
-use crate::generate_generic_tests;
+use secrets_provider::testkit::{run_conformance_suite, ConformanceOptions};
+
+use crate::{generate_generic_tests, generate_generic_writer_tests};
 
 // Include all generic tests using Memory implementation.
 generate_generic_tests!(crate::setup::memory::load_test_provider());
+generate_generic_writer_tests!(crate::setup::memory::load_test_provider());
+
+#[tokio::test]
+async fn conformance_suite_passes() {
+    let mut provider = crate::setup::memory::load_test_provider();
+
+    let report = run_conformance_suite(&mut provider, &ConformanceOptions::default()).await;
+    assert!(report.passed(), "Conformance suite failed: {report:?}");
+    assert!(report
+        .cases
+        .iter()
+        .any(|c| c.name == "find_previous_versions"));
+}
+
+#[test]
+fn find_as_of_selects_version_current_at_the_time() {
+    use std::thread::sleep;
+    use std::time::{Duration, SystemTime};
+
+    use crate::seeds::constants::*;
+
+    let mut provider = secrets_provider::implementations::memory::MemorySecretsProvider::new();
+    let before = SystemTime::now();
+    sleep(Duration::from_millis(5));
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    sleep(Duration::from_millis(5));
+    let between = SystemTime::now();
+    sleep(Duration::from_millis(5));
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_2.into());
+
+    let secret = provider
+        .find_as_of::<String>(SECRET_1_NAME, between)
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let secret = provider
+        .find_as_of::<String>(SECRET_1_NAME, SystemTime::now())
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, secret.reveal());
+
+    // The secret did not exist yet
+    let secret = provider
+        .find_as_of::<String>(SECRET_1_NAME, before)
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[test]
+fn batch_describe_reports_version_times() {
+    use crate::seeds::constants::*;
+
+    let mut provider = secrets_provider::implementations::memory::MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_2.into());
+    provider.add_string_secret(SECRET_2_NAME.into(), SECRET_2.into());
+
+    let described = provider.batch_describe(&[SECRET_1_NAME, "non-existent-secret"]);
+
+    assert_eq!(1, described.len());
+    let description = &described[SECRET_1_NAME];
+    assert_eq!(SECRET_1_NAME, description.name);
+    assert!(description.created_at.unwrap() <= description.last_changed_at.unwrap());
+    assert_eq!(None, description.rotation_enabled);
+}
+
+#[tokio::test]
+async fn versions_know_whether_they_are_current() {
+    use secrets_provider::SecretsProvider;
+
+    use crate::seeds::constants::*;
+
+    let mut provider = secrets_provider::implementations::memory::MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_2.into());
+    let versions = provider.list_secret_version_ids(SECRET_1_NAME).unwrap();
+
+    let previous = provider
+        .find_with_version::<String>(SECRET_1_NAME, &versions[0])
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(Some(false), previous.is_current());
+    assert_eq!(previous.version_stages(), ["previous"]);
+
+    let current = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(Some(true), current.is_current());
+    assert_eq!(current.version_stages(), ["current"]);
+    assert!(previous.created_at().unwrap() <= current.created_at().unwrap());
+
+    // Older versions have no stage
+    let added = provider.add_string_secret(SECRET_1_NAME.into(), SECRET_3.into());
+    assert_eq!(added.version_stages(), ["current"]);
+    let oldest = provider
+        .find_with_version::<String>(SECRET_1_NAME, &versions[0])
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert!(oldest.version_stages().is_empty());
+    assert_eq!(None, current.last_rotated_at());
+}
+
+#[tokio::test]
+async fn tags_are_returned_with_values() {
+    use std::collections::BTreeMap;
+
+    use secrets_provider::SecretsProvider;
+
+    use crate::seeds::constants::*;
+
+    let mut provider = secrets_provider::implementations::memory::MemorySecretsProvider::new();
+    let created = provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    assert_eq!(Some(&BTreeMap::new()), created.tags());
+    assert!(provider.add_tags(SECRET_1_NAME, [("tenant".into(), "acme".into())]));
+    assert!(!provider.add_tags("missing", [("tenant".into(), "acme".into())]));
+
+    let expected = BTreeMap::from([("tenant".to_string(), "acme".to_string())]);
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(Some(&expected), secret.tags());
+    let described = provider.batch_describe(&[SECRET_1_NAME]);
+    assert_eq!(Some(&expected), described[SECRET_1_NAME].tags.as_ref());
+}
+
+#[tokio::test]
+async fn binary_secrets_are_read_without_copies() {
+    use bytes::Bytes;
+    use secrets_provider::{SecretsProvider, SecretsProviderError};
+
+    use crate::seeds::constants::*;
+
+    let mut provider = secrets_provider::implementations::memory::MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_binary_secret(SECRET_4_NAME.into(), SECRET_4.to_vec());
+
+    let first = provider
+        .find::<Bytes>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    let second = provider
+        .find::<Bytes>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(SECRET_4, &first[..]);
+    // Both lookups share the buffer stored by the provider
+    assert_eq!(first.as_ptr(), second.as_ptr());
+
+    assert!(matches!(
+        provider.find::<Bytes>(SECRET_1_NAME).await,
+        Err(SecretsProviderError::InvalidType(_))
+    ));
+}
@@ -1,9 +1,84 @@
-#[cfg(feature = "aws")]
+#[cfg(feature = "age")]
+mod age;
+#[cfg(feature = "aws-core")]
 mod aws;
+#[cfg(all(feature = "broker", feature = "memory"))]
+mod broker;
+#[cfg(all(feature = "chaos", feature = "memory"))]
+mod cancellation;
+#[cfg(feature = "memory")]
+mod capabilities;
+#[cfg(all(feature = "chaos", feature = "memory"))]
+mod chaos;
+#[cfg(feature = "directory")]
+mod directory;
+#[cfg(feature = "doppler")]
+mod doppler;
+#[cfg(feature = "memory")]
+mod dynamic;
+#[cfg(feature = "aws-dynamodb-kms")]
+mod dynamodb_kms;
+#[cfg(all(feature = "memory", any(feature = "cbor", feature = "msgpack")))]
+mod encoded;
+#[cfg(feature = "memory")]
+mod encoding;
+#[cfg(feature = "env")]
+mod env;
+#[cfg(all(feature = "chaos", feature = "memory"))]
+mod error_mapping;
+#[cfg(feature = "eso")]
+mod eso;
+#[cfg(feature = "file")]
+mod file;
+#[cfg(all(feature = "chaos", feature = "memory"))]
+mod find_options;
+#[cfg(feature = "hcp")]
+mod hcp;
+#[cfg(all(feature = "json", feature = "memory"))]
+mod json;
+#[cfg(feature = "keyring")]
+mod keyring;
+#[cfg(all(feature = "keyset", feature = "memory"))]
+mod keyset;
 #[cfg(feature = "memory")]
 mod memory;
+#[cfg(feature = "mounted")]
+mod mounted;
+#[cfg(feature = "memory")]
+mod namespace;
+mod naming;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(all(feature = "pem", feature = "memory"))]
+mod pem;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "memory")]
+mod promote;
+#[cfg(feature = "memory")]
+mod quota;
+#[cfg(all(feature = "race", feature = "chaos", feature = "memory"))]
+mod race;
+#[cfg(feature = "redis")]
+mod redis;
 #[cfg(feature = "legacy-rusoto-aws")]
 mod rusoto;
+#[cfg(feature = "aws-s3-kms")]
+mod s3_kms;
+#[cfg(feature = "sealed")]
+mod sealed;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "systemd")]
+mod systemd;
+#[cfg(all(feature = "rustls", feature = "memory"))]
+mod tls;
+#[cfg(feature = "memory")]
+mod trace;
+#[cfg(feature = "vault-agent")]
+mod vault_agent;
+#[cfg(feature = "watch")]
+mod watch;
 
 #[macro_use]
 mod generic;
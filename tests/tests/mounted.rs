@@ -0,0 +1,130 @@
+//! Tests that are specific to the Mounted files implementation.
+//!
+//! Mounted files have a single version and can be read both as strings and binaries, so the
+//! generic tests do not apply to this implementation.
+
+use secrets_provider::{
+    implementations::mounted::MountedSecretsProvider, SecretsProvider, SecretsProviderError,
+};
+
+use crate::{seeds::constants::*, setup::mounted::load_test_provider};
+
+#[tokio::test]
+async fn can_read_files_as_string_and_binary() {
+    let wrapper = load_test_provider();
+
+    let secret_1 = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1_NAME, secret_1.name);
+    assert_eq!(SECRET_1, secret_1.reveal());
+
+    let secret_1 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1.as_bytes(), secret_1.reveal());
+
+    let secret_4 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+}
+
+#[tokio::test]
+async fn reading_non_utf8_file_as_string_should_fail() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret("non-utf8", &[0xff, 0xfe, 0x00]);
+
+    match wrapper.provider.find::<String>("non-utf8").await {
+        Err(SecretsProviderError::InvalidType(_)) => (),
+        r => panic!("Should have failed with InvalidType error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn can_read_nested_files() {
+    let wrapper = load_test_provider();
+    wrapper.write_secret("database/password", SECRET_2.as_bytes());
+
+    let secret = wrapper
+        .provider
+        .find::<String>("database/password")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, secret.reveal());
+}
+
+#[tokio::test]
+async fn non_existent_secret_should_be_none() {
+    let wrapper = load_test_provider();
+
+    let secret = wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn names_escaping_the_root_should_fail() {
+    let wrapper = load_test_provider();
+    let outside = wrapper.root().parent().unwrap().join("outside-secret");
+
+    for name in ["../outside-secret", outside.to_str().unwrap(), ""] {
+        match wrapper.provider.find::<String>(name).await {
+            Err(SecretsProviderError::InvalidName(_)) => (),
+            r => panic!("Should have failed with InvalidName error: {:?}", r),
+        }
+    }
+}
+
+#[tokio::test]
+async fn find_with_version_only_returns_current_version() {
+    let wrapper = load_test_provider();
+
+    let current = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+
+    let same = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, &current.version)
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(SECRET_1, same.reveal());
+
+    let other = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "not-a-version")
+        .await
+        .unwrap();
+    assert!(other.is_none());
+}
+
+#[tokio::test]
+async fn self_test_checks_root_directory() {
+    let wrapper = load_test_provider();
+
+    let report = wrapper.provider.self_test(Some(SECRET_1_NAME)).await;
+    assert!(report.is_healthy(), "Self-test failed: {report:?}");
+
+    let missing_root = MountedSecretsProvider::new(wrapper.root().join("missing"));
+    let report = missing_root.self_test(None).await;
+    let failed: Vec<_> = report.failed_checks().map(|c| c.name.as_str()).collect();
+    assert_eq!(failed, ["read_root"]);
+}
@@ -0,0 +1,192 @@
+//! Tests of secret namespaces and of the scoped wrapper, using the Memory implementation as the
+//! backend.
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider,
+    namespace::{NamespaceLayout, Scope, ScopedProvider, SecretPath},
+    ListFilter, SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret("payments/production/database".into(), SECRET_1.into());
+    provider.add_string_secret("payments/staging/database".into(), SECRET_2.into());
+    provider.add_string_secret("production_api_key".into(), SECRET_3.into());
+    provider
+}
+
+#[test]
+fn paths_are_parsed_and_displayed() {
+    let path = SecretPath::parse("payments/production/database/password").unwrap();
+    assert_eq!("payments", path.project());
+    assert_eq!(vec!["production", "database"], path.folders());
+    assert_eq!("password", path.name());
+    assert_eq!("payments/production/database/password", path.to_string());
+    assert_eq!(
+        path,
+        SecretPath::new("payments", "password")
+            .with_folder("production")
+            .with_folder("database")
+    );
+
+    for invalid in ["password", "payments//password", "payments/../password"] {
+        assert!(
+            matches!(
+                SecretPath::parse(invalid),
+                Err(SecretsProviderError::InvalidName(_))
+            ),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn layouts_map_paths_to_backend_names() {
+    let path = SecretPath::new("payments", "api-key").with_folder("production");
+
+    assert_eq!(
+        "payments/production/api-key",
+        path.backend_name(NamespaceLayout::Path)
+    );
+    assert_eq!(
+        "production_api-key",
+        path.backend_name(NamespaceLayout::ProjectPerProvider('_'))
+    );
+}
+
+#[test]
+fn scopes_contain_their_subfolders() {
+    let scope = Scope::new("payments").folder("production");
+    assert_eq!("payments/production", scope.to_string());
+
+    assert!(scope.contains(&SecretPath::parse("payments/production/database").unwrap()));
+    assert!(scope.contains(&SecretPath::parse("payments/production/db/password").unwrap()));
+    assert!(!scope.contains(&SecretPath::parse("payments/staging/database").unwrap()));
+    assert!(!scope.contains(&SecretPath::parse("billing/production/database").unwrap()));
+}
+
+#[tokio::test]
+async fn scoped_lookups_use_relative_names() {
+    let provider = ScopedProvider::new(
+        load_test_provider(),
+        Scope::new("payments").folder("production"),
+        NamespaceLayout::Path,
+    );
+
+    let secret = provider
+        .find::<String>("database")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("database", secret.name);
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let found = provider
+        .batch_find::<String>(&["database", "missing"])
+        .await
+        .unwrap();
+    assert_eq!(vec![&"database"], found.keys().collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn scoped_lookups_can_not_escape_the_scope() {
+    let provider = ScopedProvider::new(
+        load_test_provider(),
+        Scope::new("payments").folder("production"),
+        NamespaceLayout::Path,
+    );
+
+    for name in ["../staging/database", "/database", ""] {
+        let result = provider.find::<String>(name).await;
+        assert!(
+            matches!(result, Err(SecretsProviderError::InvalidName(_))),
+            "{}: {:?}",
+            name,
+            result
+        );
+    }
+}
+
+#[tokio::test]
+async fn projects_can_be_selected_by_the_provider() {
+    let provider = ScopedProvider::new(
+        load_test_provider(),
+        Scope::new("payments").folder("production"),
+        NamespaceLayout::ProjectPerProvider('_'),
+    );
+
+    assert_eq!(
+        "production_api_key",
+        provider.backend_name("api_key").unwrap()
+    );
+    let secret = provider
+        .find::<String>("api_key")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_3, secret.reveal());
+}
+
+#[tokio::test]
+async fn traces_show_the_scoped_name() {
+    let provider = ScopedProvider::new(
+        load_test_provider(),
+        Scope::new("payments").folder("staging"),
+        NamespaceLayout::Path,
+    );
+
+    let trace = provider.resolve_trace("database").await;
+    assert_eq!(
+        vec!["ScopedProvider", "MemorySecretsProvider"],
+        trace.layers().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["Name scoped to payments/staging/database".to_string()],
+        trace.steps[0].details
+    );
+}
+
+#[tokio::test]
+async fn scoped_listings_only_show_the_scope() {
+    let provider = ScopedProvider::new(
+        load_test_provider(),
+        Scope::new("payments").folder("production"),
+        NamespaceLayout::Path,
+    );
+    assert_eq!(
+        "payments/production/",
+        provider.scope().backend_prefix(NamespaceLayout::Path)
+    );
+
+    let page = provider
+        .list_secrets(&ListFilter::default(), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        vec!["database"],
+        page.secrets.iter().map(|s| &s.name).collect::<Vec<_>>()
+    );
+    assert!(provider
+        .find::<String>(&page.secrets[0].name)
+        .await
+        .unwrap()
+        .is_some());
+
+    let provider = ScopedProvider::new(
+        load_test_provider(),
+        Scope::new("payments").folder("production"),
+        NamespaceLayout::ProjectPerProvider('_'),
+    );
+    let filter = ListFilter {
+        name_prefix: Some("api".to_string()),
+        ..Default::default()
+    };
+    let page = provider.list_secrets(&filter, None).await.unwrap();
+    assert_eq!(
+        vec!["api_key"],
+        page.secrets.iter().map(|s| &s.name).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,166 @@
+//! Tests of the naming conventions and of the wrapper applying them.
+
+use secrets_provider::naming::{CaseFolding, Escape, NameRules, NameStyle, NamingConvention};
+use secrets_provider::SecretsProviderError;
+
+#[test]
+fn presets_translate_logical_names() {
+    let name = "database.primary-password";
+
+    assert_eq!(
+        "DATABASE_PRIMARY_PASSWORD",
+        NamingConvention::env().apply(name)
+    );
+    assert_eq!(
+        "database/primary/password",
+        NamingConvention::path().apply(name)
+    );
+    assert_eq!(name, NamingConvention::aws().apply(name));
+    assert_eq!("api-key-v2", NamingConvention::aws().apply("api key#v2"));
+    assert_eq!(name, NamingConvention::new().apply(name));
+}
+
+#[test]
+fn styles_ignore_empty_words() {
+    let convention = NamingConvention::new()
+        .with_style(NameStyle::Kebab)
+        .with_case(CaseFolding::Lower);
+
+    assert_eq!(
+        "database-password",
+        convention.apply("/Database__Password/")
+    );
+}
+
+#[test]
+fn escapes_illegal_characters_as_hex() {
+    let convention = NamingConvention::new()
+        .with_allowed_chars(|c| c.is_ascii_alphanumeric() || c == '-')
+        .with_escape(Escape::Hex('%'));
+
+    assert_eq!("caf%C3%A9-key%2B1", convention.apply("café-key+1"));
+}
+
+#[test]
+fn name_rules_accept_valid_names() {
+    NameRules::AWS.validate("prod/database.password").unwrap();
+    NameRules::AWS_ARN
+        .validate("arn:aws:secretsmanager:us-west-2:123456789012:secret:prod/db-a1b2c3")
+        .unwrap();
+    NameRules::DOPPLER.validate("DATABASE_PASSWORD").unwrap();
+    NameRules::NATS_KV
+        .validate("prod/database.password")
+        .unwrap();
+}
+
+#[test]
+fn name_rules_explain_invalid_names() {
+    let too_long = "a".repeat(513);
+    for (rules, name, reason) in [
+        (NameRules::AWS, "", "can not be empty"),
+        (
+            NameRules::AWS,
+            too_long.as_str(),
+            "at most 512 characters long, found 513",
+        ),
+        (
+            NameRules::AWS,
+            "prod/db password",
+            "' ' at position 7 is not allowed",
+        ),
+        (
+            NameRules::DOPPLER,
+            "DATABASE-PASSWORD",
+            "'-' at position 8 is not allowed",
+        ),
+        (
+            NameRules::NATS_KV,
+            "prod/db password",
+            "' ' at position 7 is not allowed",
+        ),
+    ] {
+        match rules.validate(name) {
+            Err(SecretsProviderError::InvalidName(e)) => {
+                assert!(e.contains(reason), "Unexpected error: {}", e)
+            }
+            r => panic!("Should have failed with InvalidName error: {:?}", r),
+        }
+    }
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn wrapper_looks_up_translated_names() {
+    use secrets_provider::{
+        implementations::memory::MemorySecretsProvider, naming::NamingProvider, SecretsProvider,
+    };
+
+    use crate::seeds::constants::*;
+
+    let mut inner = MemorySecretsProvider::new();
+    let stored = inner.add_string_secret("DATABASE_PASSWORD".into(), SECRET_1.into());
+    inner.add_string_secret("API_KEY".into(), SECRET_2.into());
+    let provider = NamingProvider::new(inner, NamingConvention::env());
+
+    let secret = provider
+        .find::<String>("database.password")
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("database.password", secret.name);
+    assert_eq!(SECRET_1, secret.reveal());
+
+    let secret = provider
+        .find_with_version::<String>("database-password", &stored.version)
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!("database-password", secret.name);
+
+    let mut secrets = provider
+        .batch_find::<String>(&["database/password", "api-key", "missing"])
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+    assert_eq!(SECRET_2, secrets.remove("api-key").unwrap().reveal());
+
+    let report = provider.self_test(Some("api.key")).await;
+    assert!(report.is_healthy(), "Self-test failed: {report:?}");
+
+    // Backend names can not be translated back into logical names
+    assert!(matches!(
+        provider.list_secrets(&Default::default(), None).await,
+        Err(SecretsProviderError::Unsupported(_))
+    ));
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn wrapper_resolves_names_from_runtime_context() {
+    use secrets_provider::{
+        implementations::memory::MemorySecretsProvider, naming::NamingProvider, SecretsProvider,
+    };
+
+    use crate::seeds::constants::*;
+
+    tokio::task_local! {
+        static TENANT: &'static str;
+    }
+
+    let mut inner = MemorySecretsProvider::new();
+    inner.add_string_secret("tenant-a/database.password".into(), SECRET_1.into());
+    inner.add_string_secret("tenant-b/database.password".into(), SECRET_2.into());
+    let convention = NamingConvention::new()
+        .with_resolver(|name| format!("{}/{}", TENANT.with(|tenant| *tenant), name));
+    let provider = NamingProvider::new(inner, convention);
+
+    for (tenant, expected) in [("tenant-a", SECRET_1), ("tenant-b", SECRET_2)] {
+        let secret = TENANT
+            .scope(tenant, provider.find::<String>("database.password"))
+            .await
+            .unwrap()
+            .expect("Secret not found");
+        assert_eq!("database.password", secret.name);
+        assert_eq!(expected, secret.reveal());
+    }
+}
@@ -0,0 +1,115 @@
+//! Tests that are specific to the NATS JetStream KV implementation.
+//!
+//! Values can be read both as strings and binaries, so the generic tests do not apply to this
+//! implementation.
+
+use secrets_provider::{SecretsProvider, SecretsProviderError};
+
+use crate::{seeds::constants::*, setup::nats::load_test_provider};
+
+#[tokio::test]
+async fn can_read_current_values() {
+    let wrapper = load_test_provider().await;
+    let revision = wrapper.put(SECRET_1_NAME, SECRET_1.as_bytes()).await;
+    wrapper.put(SECRET_4_NAME, SECRET_4).await;
+
+    let secret_1 = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(revision, secret_1.version);
+    assert_eq!(Some(true), secret_1.is_current());
+    assert_eq!(SECRET_1, secret_1.reveal());
+
+    let secret_4 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+}
+
+#[tokio::test]
+async fn can_read_previous_revisions() {
+    let wrapper = load_test_provider().await;
+    let previous = wrapper
+        .put(VERSIONED_SECRET_NAME, VERSIONED_SECRET_VERSION_1.as_bytes())
+        .await;
+    wrapper
+        .put(VERSIONED_SECRET_NAME, VERSIONED_SECRET_VERSION_2.as_bytes())
+        .await;
+
+    let secret = wrapper
+        .provider
+        .find_with_version::<String>(VERSIONED_SECRET_NAME, &previous)
+        .await
+        .unwrap()
+        .expect("Secret revision not found");
+    assert_eq!(previous, secret.version);
+    assert_eq!(VERSIONED_SECRET_VERSION_1, secret.reveal());
+
+    let secret = wrapper
+        .provider
+        .find::<String>(VERSIONED_SECRET_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(VERSIONED_SECRET_VERSION_2, secret.reveal());
+}
+
+#[tokio::test]
+async fn revisions_of_other_keys_are_not_found() {
+    let wrapper = load_test_provider().await;
+    let other = wrapper.put(SECRET_2_NAME, SECRET_2.as_bytes()).await;
+    wrapper.put(SECRET_1_NAME, SECRET_1.as_bytes()).await;
+
+    for version in [other.as_str(), "not-a-revision"] {
+        let secret = wrapper
+            .provider
+            .find_with_version::<String>(SECRET_1_NAME, version)
+            .await
+            .unwrap();
+        assert!(secret.is_none());
+    }
+}
+
+#[tokio::test]
+async fn deleted_keys_are_not_found() {
+    let wrapper = load_test_provider().await;
+    wrapper.put(SECRET_1_NAME, SECRET_1.as_bytes()).await;
+    wrapper.store.delete(SECRET_1_NAME).await.unwrap();
+
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+    let secret = wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn invalid_keys_are_rejected() {
+    let wrapper = load_test_provider().await;
+
+    match wrapper.provider.find::<String>("database password").await {
+        Err(SecretsProviderError::InvalidName(_)) => (),
+        r => panic!("Should have failed with InvalidName error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn self_test_checks_the_bucket() {
+    let wrapper = load_test_provider().await;
+
+    let report = wrapper.provider.self_test(None).await;
+    assert!(report.is_healthy(), "{:?}", report);
+}
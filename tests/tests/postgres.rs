@@ -0,0 +1,137 @@
+//! Tests that are specific to the PostgreSQL implementation.
+//!
+//! Values are stored as bytes, so string secrets can also be read as binaries, and the generic
+//! tests do not apply to this implementation. The conformance suite is run without the strict
+//! types cases instead.
+
+use secrets_provider::{
+    implementations::postgres::PostgresSecretsProvider,
+    testkit::{run_conformance_suite, ConformanceOptions},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::{
+    seeds::constants::*,
+    setup::postgres::{load_encrypted_test_provider, load_test_provider},
+};
+
+fn options() -> ConformanceOptions {
+    ConformanceOptions {
+        strict_types: false,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn conformance_suite_passes() {
+    let mut wrapper = load_test_provider().await;
+
+    let report = run_conformance_suite(&mut wrapper, &options()).await;
+    assert!(report.passed(), "Conformance suite failed: {report:?}");
+}
+
+#[tokio::test]
+async fn conformance_suite_passes_with_encryption() {
+    let mut wrapper = load_encrypted_test_provider("test-encryption-key").await;
+
+    let report = run_conformance_suite(&mut wrapper, &options()).await;
+    assert!(report.passed(), "Conformance suite failed: {report:?}");
+}
+
+#[tokio::test]
+async fn versions_are_numbered_from_one() {
+    let wrapper = load_test_provider().await;
+
+    let first = wrapper.provider.add_secret(SECRET_1_NAME, SECRET_1).await;
+    let second = wrapper.provider.add_secret(SECRET_1_NAME, SECRET_2).await;
+    assert_eq!("1", first.unwrap());
+    assert_eq!("2", second.unwrap());
+
+    let secret = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "1")
+        .await
+        .unwrap()
+        .expect("Secret / version pair not found");
+    assert_eq!(Some(false), secret.is_current());
+    assert_eq!(SECRET_1, secret.reveal());
+
+    for version in ["latest", "0", "-1"] {
+        let secret = wrapper
+            .provider
+            .find_with_version::<String>(SECRET_1_NAME, version)
+            .await
+            .unwrap();
+        assert!(secret.is_none());
+    }
+}
+
+#[tokio::test]
+async fn values_are_encrypted_at_rest() {
+    let wrapper = load_encrypted_test_provider("test-encryption-key").await;
+    wrapper
+        .provider
+        .add_secret(SECRET_1_NAME, SECRET_1)
+        .await
+        .unwrap();
+
+    let stored: Vec<u8> = sqlx::query_scalar(&format!(
+        "SELECT value FROM \"{}\" WHERE name = $1",
+        wrapper.table
+    ))
+    .bind(SECRET_1_NAME)
+    .fetch_one(&wrapper.pool)
+    .await
+    .unwrap();
+    assert!(!stored
+        .windows(SECRET_1.len())
+        .any(|w| w == SECRET_1.as_bytes()));
+
+    // Reading with another key fails instead of returning garbage
+    let provider = PostgresSecretsProvider::from(wrapper.pool.clone())
+        .with_table(&wrapper.table)
+        .with_encryption_key("another-key");
+    match provider.find::<String>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+}
+
+#[tokio::test]
+async fn table_names_are_quoted() {
+    let wrapper = load_test_provider().await;
+    let provider = PostgresSecretsProvider::from(wrapper.pool.clone())
+        .with_table(&format!("public.{}\"; DROP TABLE x; --", wrapper.table));
+
+    provider.create_table().await.unwrap();
+    provider.add_secret(SECRET_1_NAME, SECRET_1).await.unwrap();
+
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+
+    // The table of the wrapper is still empty
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn unreachable_server_should_fail() {
+    // Nothing listens on the discard port
+    match PostgresSecretsProvider::new("postgres://127.0.0.1:9/secrets").await {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+
+    match PostgresSecretsProvider::new("not-a-url").await {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+}
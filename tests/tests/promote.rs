@@ -0,0 +1,135 @@
+//! Tests of promotions between environments, using the Memory implementation for both.
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider,
+    promote::{promote, PromoteOutcome, PromoteStrategy},
+    SecretsProvider, SecretsWriter,
+};
+
+use crate::seeds::constants::*;
+
+#[tokio::test]
+async fn secrets_are_promoted_once_confirmed() {
+    let staging = MemorySecretsProvider::new();
+    let production = MemorySecretsProvider::new();
+    let version = staging
+        .create_secret(SECRET_1_NAME, SECRET_1.into())
+        .await
+        .unwrap();
+
+    let mut confirmed = Vec::new();
+    let outcome = promote(
+        SECRET_1_NAME,
+        &staging,
+        &production,
+        &PromoteStrategy::Latest,
+        |promotion| {
+            confirmed.push((
+                promotion.source_version.to_string(),
+                promotion.target_version.is_none(),
+            ));
+            true
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(outcome, Some(PromoteOutcome::Created(_))));
+    assert_eq!(vec![(version, true)], confirmed);
+    let promoted = production
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(SECRET_1, promoted.reveal());
+
+    // Promoting it again writes nothing, without asking
+    let outcome = promote(
+        SECRET_1_NAME,
+        &staging,
+        &production,
+        &PromoteStrategy::Latest,
+        |_| panic!("Unchanged promotions are not confirmed"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(Some(PromoteOutcome::Unchanged), outcome);
+}
+
+#[tokio::test]
+async fn declined_promotions_write_nothing() {
+    let staging = MemorySecretsProvider::new();
+    let production = MemorySecretsProvider::new();
+    staging
+        .create_secret(SECRET_1_NAME, SECRET_1.into())
+        .await
+        .unwrap();
+
+    let outcome = promote(
+        SECRET_1_NAME,
+        &staging,
+        &production,
+        &PromoteStrategy::Latest,
+        |_| false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(Some(PromoteOutcome::Declined), outcome);
+    assert!(!production.exists(SECRET_1_NAME).await.unwrap());
+}
+
+#[tokio::test]
+async fn pinned_versions_are_promoted() {
+    let staging = MemorySecretsProvider::new();
+    let production = MemorySecretsProvider::new();
+    let validated = staging
+        .create_secret(SECRET_1_NAME, SECRET_1.into())
+        .await
+        .unwrap();
+    staging
+        .put_secret_value(SECRET_1_NAME, SECRET_2.into())
+        .await
+        .unwrap();
+    production
+        .create_secret(SECRET_1_NAME, SECRET_3.into())
+        .await
+        .unwrap();
+
+    let outcome = promote(
+        SECRET_1_NAME,
+        &staging,
+        &production,
+        &PromoteStrategy::Pinned(validated),
+        |_| true,
+    )
+    .await
+    .unwrap();
+    assert!(matches!(outcome, Some(PromoteOutcome::Updated(_))));
+    let promoted = production
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(SECRET_1, promoted.reveal());
+
+    // Missing secrets and versions are not promoted
+    let missing = promote(
+        SECRET_1_NAME,
+        &staging,
+        &production,
+        &PromoteStrategy::Pinned("missing".to_string()),
+        |_| true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(None, missing);
+    let missing = promote(
+        SECRET_2_NAME,
+        &staging,
+        &production,
+        &PromoteStrategy::Latest,
+        |_| true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(None, missing);
+}
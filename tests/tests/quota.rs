@@ -0,0 +1,132 @@
+//! Tests of per-caller quotas, using the Memory implementation as the shared provider.
+
+use std::time::Duration;
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider,
+    quota::{Quota, QuotaProvider},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret(SECRET_2_NAME.into(), SECRET_2.into());
+    provider.add_string_secret(SECRET_3_NAME.into(), SECRET_3.into());
+    provider
+}
+
+fn assert_quota_exceeded<T>(result: Result<T, SecretsProviderError>) {
+    match result {
+        Err(SecretsProviderError::QuotaExceeded(_)) => (),
+        Err(e) => panic!("Should have failed with QuotaExceeded error: {:?}", e),
+        Ok(_) => panic!("Should have failed with QuotaExceeded error"),
+    }
+}
+
+#[tokio::test]
+async fn lookups_are_rate_limited_per_caller() {
+    let quotas = QuotaProvider::new(load_test_provider()).with_quota(
+        "plugin",
+        Quota {
+            max_lookups_per_second: Some(2),
+            ..Quota::default()
+        },
+    );
+    let plugin = quotas.caller("plugin");
+    let core = quotas.caller("core");
+
+    for _ in 0..2 {
+        assert!(plugin
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .is_some());
+    }
+    assert_quota_exceeded(plugin.find::<String>(SECRET_1_NAME).await);
+    // Providers of the same caller share the quota
+    assert_quota_exceeded(quotas.caller("plugin").find::<String>(SECRET_1_NAME).await);
+
+    // Other callers are not limited
+    for _ in 0..10 {
+        assert!(core.find::<String>(SECRET_1_NAME).await.unwrap().is_some());
+    }
+}
+
+#[tokio::test]
+async fn batch_lookups_count_every_secret() {
+    let quotas = QuotaProvider::new(load_test_provider()).with_default_quota(Quota {
+        max_lookups_per_second: Some(3),
+        ..Quota::default()
+    });
+    let plugin = quotas.caller("plugin");
+
+    let secrets = plugin
+        .batch_find::<String>(&[SECRET_1_NAME, SECRET_2_NAME])
+        .await
+        .unwrap();
+    assert_eq!(2, secrets.len());
+    assert_quota_exceeded(
+        plugin
+            .batch_find::<String>(&[SECRET_1_NAME, SECRET_2_NAME])
+            .await,
+    );
+    // Rejected lookups are not counted
+    assert!(plugin
+        .find::<String>(SECRET_3_NAME)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test(start_paused = true)]
+async fn batch_lookups_over_the_rate_are_paid_back() {
+    let quotas = QuotaProvider::new(load_test_provider())
+        .with_default_quota(Quota {
+            max_lookups_per_second: Some(2),
+            ..Quota::default()
+        })
+        .with_clock(|| tokio::time::Instant::now().into_std());
+    let plugin = quotas.caller("plugin");
+
+    // A batch larger than the rate is accepted with a full quota
+    let secrets = plugin
+        .batch_find::<String>(&[SECRET_1_NAME, SECRET_2_NAME, SECRET_3_NAME])
+        .await
+        .unwrap();
+    assert_eq!(3, secrets.len());
+
+    // The lookup over the rate is paid back first
+    assert_quota_exceeded(plugin.find::<String>(SECRET_1_NAME).await);
+    tokio::time::advance(Duration::from_millis(999)).await;
+    assert_quota_exceeded(plugin.find::<String>(SECRET_1_NAME).await);
+    tokio::time::advance(Duration::from_millis(1)).await;
+    assert!(plugin
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn distinct_secrets_are_limited() {
+    let quotas = QuotaProvider::new(load_test_provider()).with_default_quota(Quota {
+        max_distinct_secrets: Some(2),
+        ..Quota::default()
+    });
+    let plugin = quotas.caller("plugin");
+
+    plugin.find::<String>(SECRET_1_NAME).await.unwrap();
+    plugin.find::<String>(SECRET_2_NAME).await.unwrap();
+    assert_quota_exceeded(plugin.find::<String>(SECRET_3_NAME).await);
+
+    // Secrets already looked up are still available
+    let secret = plugin
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+}
@@ -0,0 +1,124 @@
+//! Tests of the hedged lookups wrapper, using the chaos testing wrapper to make Memory
+//! implementations slow or failing.
+
+use std::time::{Duration, Instant};
+
+use secrets_provider::{
+    chaos::ChaosProvider, implementations::memory::MemorySecretsProvider, race::RaceProvider,
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::seeds::constants::*;
+
+const SLOW: Duration = Duration::from_millis(500);
+
+fn load_test_provider() -> ChaosProvider<MemorySecretsProvider> {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    ChaosProvider::new(provider)
+}
+
+fn slow_provider() -> ChaosProvider<MemorySecretsProvider> {
+    load_test_provider().with_latency(1.0, SLOW)
+}
+
+#[tokio::test]
+async fn the_fastest_provider_answers() {
+    let provider = RaceProvider::new(vec![slow_provider(), load_test_provider()]).unwrap();
+
+    let start = Instant::now();
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+    assert!(start.elapsed() < SLOW, "{:?}", start.elapsed());
+
+    let trace = provider.resolve_trace(SECRET_1_NAME).await;
+    assert_eq!(
+        vec!["Answered by provider 1".to_string()],
+        trace.steps[0].details
+    );
+}
+
+#[tokio::test]
+async fn the_next_provider_is_queried_after_the_hedging_delay() {
+    let hedging_delay = Duration::from_millis(100);
+    let provider = RaceProvider::new(vec![slow_provider(), load_test_provider()])
+        .unwrap()
+        .with_hedging_delay(hedging_delay);
+
+    let start = Instant::now();
+    let secrets = provider
+        .batch_find::<String>(&[SECRET_1_NAME])
+        .await
+        .unwrap();
+    assert_eq!(1, secrets.len());
+    assert!(start.elapsed() >= hedging_delay, "{:?}", start.elapsed());
+    assert!(start.elapsed() < SLOW, "{:?}", start.elapsed());
+
+    // The preferred provider answers if it is fast enough
+    let provider = RaceProvider::new(vec![load_test_provider(), slow_provider()])
+        .unwrap()
+        .with_hedging_delay(hedging_delay);
+    let trace = provider.resolve_trace(SECRET_1_NAME).await;
+    assert_eq!(
+        vec!["Answered by provider 0".to_string()],
+        trace.steps[0].details
+    );
+}
+
+#[tokio::test]
+async fn failures_query_the_next_provider_right_away() {
+    let provider = RaceProvider::new(vec![
+        load_test_provider().with_errors(1.0),
+        load_test_provider(),
+    ])
+    .unwrap()
+    .with_hedging_delay(Duration::from_secs(30));
+
+    let start = Instant::now();
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+    assert!(start.elapsed() < SLOW, "{:?}", start.elapsed());
+}
+
+#[tokio::test]
+async fn fails_when_every_provider_fails() {
+    let provider = RaceProvider::new(vec![
+        load_test_provider().with_errors(1.0),
+        load_test_provider().with_errors(1.0),
+    ])
+    .unwrap();
+
+    match provider.find::<String>(SECRET_1_NAME).await {
+        Err(SecretsProviderError::ProviderFailed(_)) => (),
+        r => panic!("Should have failed with ProviderFailed error: {:?}", r),
+    }
+
+    assert!(matches!(
+        RaceProvider::<MemorySecretsProvider>::new(Vec::new()),
+        Err(SecretsProviderError::Initialization(_))
+    ));
+}
+
+#[tokio::test]
+async fn every_provider_is_self_tested() {
+    let provider = RaceProvider::new(vec![load_test_provider(), load_test_provider()]).unwrap();
+
+    let report = provider.self_test(Some(SECRET_1_NAME)).await;
+    assert!(report.is_healthy(), "Self-test failed: {report:?}");
+    assert_eq!(
+        vec!["provider 0: find_canary", "provider 1: find_canary"],
+        report
+            .checks
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>()
+    );
+}
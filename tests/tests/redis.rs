@@ -0,0 +1,116 @@
+//! Tests that are specific to the Redis implementation.
+//!
+//! Redis values are binary safe, so string secrets can also be read as binaries, and the generic
+//! tests do not apply to this implementation. The conformance suite is run without the strict
+//! types cases instead.
+
+use redis::AsyncCommands;
+use secrets_provider::{
+    implementations::redis::RedisSecretsProvider,
+    testkit::{run_conformance_suite, ConformanceOptions},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::{seeds::constants::*, setup::redis::load_test_provider};
+
+#[tokio::test]
+async fn conformance_suite_passes() {
+    let mut wrapper = load_test_provider().await;
+
+    let options = ConformanceOptions {
+        strict_types: false,
+        ..Default::default()
+    };
+    let report = run_conformance_suite(&mut wrapper, &options).await;
+    assert!(report.passed(), "Conformance suite failed: {report:?}");
+}
+
+#[tokio::test]
+async fn can_read_values_as_string_and_binary() {
+    let mut wrapper = load_test_provider().await;
+    wrapper
+        .add_secret(SECRET_1_NAME, SECRET_1.as_bytes())
+        .await
+        .unwrap();
+
+    let secret = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("1", secret.version);
+    assert_eq!(SECRET_1.as_bytes(), secret.reveal());
+}
+
+#[tokio::test]
+async fn fields_that_are_not_versions_are_ignored() {
+    let mut wrapper = load_test_provider().await;
+    let key = wrapper.key(SECRET_1_NAME);
+    wrapper
+        .connection
+        .hset_multiple::<_, _, _, ()>(
+            &key,
+            &[("1", SECRET_1), ("latest", SECRET_2), ("02", SECRET_3)],
+        )
+        .await
+        .unwrap();
+
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("1", secret.version);
+    assert_eq!(SECRET_1, secret.reveal());
+
+    for version in ["latest", "02"] {
+        let secret = wrapper
+            .provider
+            .find_with_version::<String>(SECRET_1_NAME, version)
+            .await
+            .unwrap();
+        assert!(secret.is_none());
+    }
+
+    let versions = wrapper
+        .provider
+        .list_secret_version_ids(SECRET_1_NAME)
+        .await
+        .unwrap();
+    assert_eq!(versions, ["1"]);
+}
+
+#[tokio::test]
+async fn keys_are_prefixed() {
+    let mut wrapper = load_test_provider().await;
+    wrapper
+        .add_secret(SECRET_1_NAME, SECRET_1.as_bytes())
+        .await
+        .unwrap();
+
+    let value: Option<String> = wrapper
+        .connection
+        .hget(wrapper.key(SECRET_1_NAME), "1")
+        .await
+        .unwrap();
+    assert_eq!(Some(SECRET_1), value.as_deref());
+    assert!(wrapper
+        .key(SECRET_1_NAME)
+        .starts_with(wrapper.provider.key_prefix()));
+}
+
+#[tokio::test]
+async fn unreachable_server_should_fail() {
+    // Nothing listens on the discard port
+    match RedisSecretsProvider::new("redis://127.0.0.1:9").await {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+
+    match RedisSecretsProvider::new("not-a-url").await {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+}
@@ -4,7 +4,11 @@
 //! create a generic test instead.
 
 use rusoto_secretsmanager::{ListSecretVersionIdsRequest, SecretsManager as _};
-use secrets_provider::SecretsProvider;
+use secrets_provider::{
+    implementations::rusoto::AwsSecretsProviderBuilder, resolver::StaticResolver, SecretsProvider,
+    VersionStage,
+};
+use std::net::ToSocketAddrs;
 
 use crate::{
     generate_generic_tests,
@@ -17,7 +21,7 @@ generate_generic_tests!(crate::setup::rusoto::load_test_provider().await);
 #[tokio::test]
 async fn test_can_retrieve_previous_and_current_aws_stages() {
     let mut secrets_provider = crate::setup::rusoto::load_test_provider().await;
-    seed_secrets_provider(&mut secrets_provider).await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
 
     // Read the secrets versions directly from the manager. We have to do this because every time
     // the emulator is seeded, the versions change. So, we read the version_id for each version of
@@ -51,8 +55,10 @@ async fn test_can_retrieve_previous_and_current_aws_stages() {
         .find_with_version::<String>(VERSIONED_SECRET_NAME, &previous_version)
         .await
         .unwrap()
-        .expect("Secret / version pair not found")
-        .reveal();
+        .expect("Secret / version pair not found");
+    assert_eq!(previous_secret.version_stages(), ["AWSPREVIOUS"]);
+    assert_eq!(previous_secret.is_current(), Some(false));
+    let previous_secret = previous_secret.reveal();
 
     assert_eq!(previous_secret, VERSIONED_SECRET_VERSION_1);
 
@@ -73,8 +79,112 @@ async fn test_can_retrieve_previous_and_current_aws_stages() {
         .find_with_version::<String>(VERSIONED_SECRET_NAME, &current_version)
         .await
         .unwrap()
-        .expect("Secret / version pair not found")
-        .reveal();
+        .expect("Secret / version pair not found");
+    assert_eq!(current_secret.is_current(), Some(true));
+    let current_secret = current_secret.reveal();
 
     assert_eq!(current_secret, VERSIONED_SECRET_VERSION_2);
 }
+
+#[tokio::test]
+async fn test_can_find_versions_by_stage() {
+    let mut secrets_provider = crate::setup::rusoto::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let previous_secret = secrets_provider
+        .provider
+        .find_with_stage::<String>(VERSIONED_SECRET_NAME, &VersionStage::Previous)
+        .await
+        .unwrap()
+        .expect("Previous version not found");
+    assert_eq!(previous_secret.version_stages(), ["AWSPREVIOUS"]);
+    assert!(previous_secret.created_at().is_some());
+    assert_eq!(previous_secret.reveal(), VERSIONED_SECRET_VERSION_1);
+
+    let current_secret = secrets_provider
+        .provider
+        .find_with_stage::<String>(
+            VERSIONED_SECRET_NAME,
+            &VersionStage::Label("AWSCURRENT".to_string()),
+        )
+        .await
+        .unwrap()
+        .expect("Current version not found");
+    assert_eq!(current_secret.is_current(), Some(true));
+    assert_eq!(current_secret.reveal(), VERSIONED_SECRET_VERSION_2);
+
+    let pending_secret = secrets_provider
+        .provider
+        .find_with_stage::<String>(VERSIONED_SECRET_NAME, &VersionStage::Pending)
+        .await
+        .unwrap();
+    assert!(pending_secret.is_none());
+}
+
+#[tokio::test]
+async fn test_can_find_version_current_at_a_given_time() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let mut secrets_provider = crate::setup::rusoto::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let current_secret = secrets_provider
+        .provider
+        .find_as_of::<String>(VERSIONED_SECRET_NAME, SystemTime::now())
+        .await
+        .unwrap()
+        .expect("Secret not found")
+        .reveal();
+    assert_eq!(current_secret, VERSIONED_SECRET_VERSION_2);
+
+    let secret = secrets_provider
+        .provider
+        .find_as_of::<String>(VERSIONED_SECRET_NAME, UNIX_EPOCH)
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn test_can_describe_many_secrets_at_once() {
+    let mut secrets_provider = crate::setup::rusoto::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    let described = secrets_provider
+        .provider
+        .batch_describe(&[SECRET_1_NAME, VERSIONED_SECRET_NAME, "non-existent-secret"])
+        .await
+        .unwrap();
+
+    assert_eq!(described.len(), 2);
+    let description = &described[SECRET_1_NAME];
+    assert_eq!(description.name, SECRET_1_NAME);
+    assert!(description.created_at.is_some());
+    assert!(described.contains_key(VERSIONED_SECRET_NAME));
+}
+
+#[tokio::test]
+async fn test_can_resolve_endpoint_with_static_resolver() {
+    let mut secrets_provider = crate::setup::rusoto::load_test_provider().await;
+    seed_secrets_provider(&mut secrets_provider).await.unwrap();
+
+    // Reach the emulator through a host name that only the static resolver knows about
+    let endpoint = std::env::var("ENDPOINT").unwrap();
+    let (scheme_and_host, port) = endpoint.rsplit_once(':').unwrap();
+    let host = scheme_and_host.trim_start_matches("http://");
+    let ip = (host, 0).to_socket_addrs().unwrap().next().unwrap().ip();
+
+    let provider = AwsSecretsProviderBuilder::new("us-west-2".to_string())
+        .endpoint_override(format!("http://secrets.internal:{}", port))
+        .resolver(StaticResolver::new().host("secrets.internal", [ip]))
+        .build()
+        .unwrap();
+
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+
+    assert_eq!(SECRET_1, secret.reveal());
+}
@@ -0,0 +1,98 @@
+//! Tests that are specific to the AWS S3 + KMS implementation.
+//!
+//! Decrypted objects can be read both as strings and binaries, so the generic tests do not apply
+//! to this implementation.
+
+use secrets_provider::SecretsProvider;
+
+use crate::{seeds::constants::*, setup::s3_kms::load_test_provider};
+
+#[tokio::test]
+async fn can_read_kms_ciphertext_objects() {
+    let wrapper = load_test_provider().await;
+    let version = wrapper
+        .put_kms_ciphertext(SECRET_1_NAME, SECRET_1.as_bytes())
+        .await;
+
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(version, secret.version);
+    assert_eq!(Some(true), secret.is_current());
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[tokio::test]
+async fn can_read_envelope_encrypted_objects() {
+    let wrapper = load_test_provider().await;
+    wrapper.put_envelope(SECRET_4_NAME, SECRET_4).await;
+
+    let secret = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret.reveal());
+}
+
+#[tokio::test]
+async fn can_read_previous_object_versions() {
+    let wrapper = load_test_provider().await;
+    let previous = wrapper
+        .put_envelope(VERSIONED_SECRET_NAME, VERSIONED_SECRET_VERSION_1.as_bytes())
+        .await;
+    wrapper
+        .put_kms_ciphertext(VERSIONED_SECRET_NAME, VERSIONED_SECRET_VERSION_2.as_bytes())
+        .await;
+
+    let secret = wrapper
+        .provider
+        .find_with_version::<String>(VERSIONED_SECRET_NAME, &previous)
+        .await
+        .unwrap()
+        .expect("Secret version not found");
+    assert_eq!(previous, secret.version);
+    assert_eq!(None, secret.is_current());
+    assert_eq!(VERSIONED_SECRET_VERSION_1, secret.reveal());
+
+    let secret = wrapper
+        .provider
+        .find::<String>(VERSIONED_SECRET_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(VERSIONED_SECRET_VERSION_2, secret.reveal());
+}
+
+#[tokio::test]
+async fn missing_objects_and_versions_are_not_found() {
+    let wrapper = load_test_provider().await;
+    wrapper
+        .put_kms_ciphertext(SECRET_1_NAME, SECRET_1.as_bytes())
+        .await;
+
+    assert!(wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "not-a-version")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn self_test_checks_the_bucket() {
+    let wrapper = load_test_provider().await;
+
+    let report = wrapper.provider.self_test(None).await;
+    assert!(report.is_healthy(), "{:?}", report);
+}
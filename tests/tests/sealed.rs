@@ -0,0 +1,87 @@
+//! Tests that are specific to the sealed secrets implementation.
+//!
+//! Sealed secrets have a single version and can be read both as strings and binaries, so the
+//! generic tests do not apply to this implementation.
+
+use ::age::x25519::Identity;
+use secrets_provider::{
+    implementations::age::AgeIdentitySource,
+    implementations::sealed::{seal, SealedSecretsProvider},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::{seeds::constants::*, setup::sealed::load_test_provider};
+
+#[tokio::test]
+async fn can_unseal_secrets_as_string_and_binary() {
+    let wrapper = load_test_provider();
+
+    let secret_1 = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1_NAME, secret_1.name);
+    assert_eq!(SECRET_1, secret_1.reveal());
+
+    let secret_4 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+
+    let secret = wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn sealed_files_do_not_contain_the_secret() {
+    let wrapper = load_test_provider();
+
+    let sealed =
+        std::fs::read_to_string(wrapper.dir.path().join(format!("{}.age", SECRET_1_NAME))).unwrap();
+    assert!(sealed.starts_with("-----BEGIN AGE ENCRYPTED FILE-----"));
+    assert!(!sealed.contains(SECRET_1));
+}
+
+#[tokio::test]
+async fn another_identity_can_not_unseal_secrets() {
+    let wrapper = load_test_provider();
+    let other = Identity::generate().to_public().to_string();
+    std::fs::write(
+        wrapper.dir.path().join("other.age"),
+        seal(SECRET_1, &[&other]).unwrap(),
+    )
+    .unwrap();
+
+    let result = wrapper.provider.find::<String>("other").await;
+    assert!(matches!(
+        result,
+        Err(SecretsProviderError::ProviderFailed(_))
+    ));
+}
+
+#[tokio::test]
+async fn invalid_recipients_and_identities_are_rejected() {
+    let result = seal(SECRET_1, &["not-a-recipient"]);
+    assert!(matches!(
+        result,
+        Err(SecretsProviderError::ProviderFailed(_))
+    ));
+
+    let dir = tempfile::tempdir().unwrap();
+    let identity_file = dir.path().join("identity.txt");
+    std::fs::write(&identity_file, "# no identities\n").unwrap();
+    let result = SealedSecretsProvider::new(dir.path(), AgeIdentitySource::File(identity_file));
+    assert!(matches!(
+        result,
+        Err(SecretsProviderError::Initialization(_))
+    ));
+}
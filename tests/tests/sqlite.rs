@@ -0,0 +1,156 @@
+//! Tests that are specific to the SQLite implementation.
+//!
+//! Values are stored as bytes, so string secrets can also be read as binaries, and the generic
+//! tests do not apply to this implementation. The conformance suite is run without the strict
+//! types cases instead.
+
+use secrets_provider::{
+    implementations::sqlite::SqliteSecretsProvider,
+    testkit::{run_conformance_suite, ConformanceOptions},
+    SecretsProvider,
+};
+
+use crate::{seeds::constants::*, setup::sqlite::load_test_provider};
+
+#[tokio::test]
+async fn conformance_suite_passes() {
+    let mut wrapper = load_test_provider().await;
+
+    let options = ConformanceOptions {
+        strict_types: false,
+        ..Default::default()
+    };
+    let report = run_conformance_suite(&mut wrapper, &options).await;
+    assert!(report.passed(), "Conformance suite failed: {report:?}");
+}
+
+#[tokio::test]
+async fn secrets_are_kept_when_reopened() {
+    let wrapper = load_test_provider().await;
+    wrapper
+        .provider
+        .add_secret(SECRET_1_NAME, SECRET_1)
+        .await
+        .unwrap();
+    let version = wrapper
+        .provider
+        .add_secret(SECRET_1_NAME, SECRET_2)
+        .await
+        .unwrap();
+    assert_eq!("2", version);
+
+    let provider = SqliteSecretsProvider::open(wrapper.dir.path().join("secrets.db"))
+        .await
+        .unwrap();
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!("2", secret.version);
+    assert_eq!(SECRET_2, secret.reveal());
+
+    for version in ["latest", "0"] {
+        let secret = provider
+            .find_with_version::<String>(SECRET_1_NAME, version)
+            .await
+            .unwrap();
+        assert!(secret.is_none());
+    }
+}
+
+#[tokio::test]
+async fn versions_know_when_they_were_created() {
+    use std::time::{Duration, SystemTime};
+
+    let wrapper = load_test_provider().await;
+    // SQLite timestamps are in seconds
+    let before = SystemTime::now() - Duration::from_secs(1);
+    wrapper
+        .provider
+        .add_secret(SECRET_1_NAME, SECRET_1)
+        .await
+        .unwrap();
+
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    let created_at = secret.created_at().expect("Creation time not returned");
+    assert!(created_at >= before && created_at <= SystemTime::now());
+    assert_eq!(None, secret.last_rotated_at());
+}
+
+#[cfg(feature = "sqlcipher")]
+#[tokio::test]
+async fn encrypted_databases_need_the_passphrase() {
+    use secrets_provider::SecretsProviderError;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("secrets.db");
+
+    let provider = SqliteSecretsProvider::open_encrypted(&path, "it's a passphrase")
+        .await
+        .unwrap();
+    provider.add_secret(SECRET_1_NAME, SECRET_1).await.unwrap();
+    drop(provider);
+
+    let stored = std::fs::read(&path).unwrap();
+    assert!(!stored
+        .windows(SECRET_1.len())
+        .any(|w| w == SECRET_1.as_bytes()));
+
+    for result in [
+        SqliteSecretsProvider::open(&path).await,
+        SqliteSecretsProvider::open_encrypted(&path, "another passphrase").await,
+    ] {
+        match result {
+            Err(SecretsProviderError::Initialization(_)) => (),
+            r => panic!("Should have failed with Initialization error: {:?}", r),
+        }
+    }
+
+    let provider = SqliteSecretsProvider::open_encrypted(&path, "it's a passphrase")
+        .await
+        .unwrap();
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+}
+
+#[cfg(all(feature = "sqlcipher", feature = "keyring"))]
+#[tokio::test]
+async fn master_key_is_kept_in_the_keyring() {
+    crate::helpers::keyring::use_shared_keyring();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("secrets.db");
+
+    let provider =
+        SqliteSecretsProvider::open_with_keyring(&path, "secrets-provider-tests", "sqlite")
+            .await
+            .unwrap();
+    provider.add_secret(SECRET_1_NAME, SECRET_1).await.unwrap();
+    drop(provider);
+
+    let provider =
+        SqliteSecretsProvider::open_with_keyring(&path, "secrets-provider-tests", "sqlite")
+            .await
+            .unwrap();
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1, secret.reveal());
+
+    // Another keyring entry holds another master key
+    let result =
+        SqliteSecretsProvider::open_with_keyring(&path, "secrets-provider-tests", "other").await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,95 @@
+//! Tests that are specific to the systemd credentials implementation.
+//!
+//! Credentials have a single version and can be read both as strings and binaries, so the
+//! generic tests do not apply to this implementation.
+
+use secrets_provider::{
+    implementations::systemd::{SystemdCredentialsProvider, CREDENTIALS_DIRECTORY_VAR},
+    SecretsProvider, SecretsProviderError,
+};
+
+use crate::{seeds::constants::*, setup::systemd::load_test_provider};
+
+#[tokio::test]
+async fn can_read_credentials_as_string_and_binary() {
+    let wrapper = load_test_provider();
+
+    let secret_1 = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_1_NAME, secret_1.name);
+    assert_eq!(SECRET_1, secret_1.reveal());
+
+    let secret_4 = wrapper
+        .provider
+        .find::<Vec<u8>>(SECRET_4_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_4.to_vec(), secret_4.reveal());
+
+    let secret = wrapper
+        .provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(secret.is_none());
+}
+
+#[tokio::test]
+async fn only_the_current_version_can_be_found() {
+    let wrapper = load_test_provider();
+    let secret = wrapper
+        .provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+
+    let same = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, &secret.version)
+        .await
+        .unwrap();
+    assert!(same.is_some());
+
+    let other = wrapper
+        .provider
+        .find_with_version::<String>(SECRET_1_NAME, "1")
+        .await
+        .unwrap();
+    assert!(other.is_none());
+}
+
+#[tokio::test]
+async fn names_that_are_not_file_names_should_fail() {
+    let wrapper = load_test_provider();
+
+    for name in ["database/password", "../secret", "/etc/passwd", "..", ""] {
+        match wrapper.provider.find::<String>(name).await {
+            Err(SecretsProviderError::InvalidName(_)) => (),
+            r => panic!("Should have failed with InvalidName error: {:?}", r),
+        }
+    }
+}
+
+#[tokio::test]
+async fn directory_is_read_from_the_environment() {
+    let wrapper = load_test_provider();
+
+    std::env::remove_var(CREDENTIALS_DIRECTORY_VAR);
+    match SystemdCredentialsProvider::from_env() {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+
+    std::env::set_var(CREDENTIALS_DIRECTORY_VAR, wrapper.dir.path());
+    let provider = SystemdCredentialsProvider::from_env().unwrap();
+    assert_eq!(wrapper.dir.path(), provider.directory());
+
+    let report = provider.self_test(Some(SECRET_1_NAME)).await;
+    assert!(report.is_healthy(), "Self-test failed: {report:?}");
+}
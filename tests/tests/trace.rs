@@ -0,0 +1,80 @@
+//! Tests of lookup traces, using the Memory implementation as the backend.
+
+use secrets_provider::{
+    implementations::memory::MemorySecretsProvider,
+    naming::{NamingConvention, NamingProvider},
+    trace::TraceOutcome,
+    SecretsProvider,
+};
+
+use crate::seeds::constants::*;
+
+fn load_test_provider() -> MemorySecretsProvider {
+    let mut provider = MemorySecretsProvider::new();
+    provider.add_string_secret(SECRET_1_NAME.into(), SECRET_1.into());
+    provider.add_string_secret("SECRET_2".into(), SECRET_2.into());
+    provider
+}
+
+#[tokio::test]
+async fn traces_lookups_of_implementations() {
+    let provider = load_test_provider();
+
+    let trace = provider.resolve_trace(SECRET_1_NAME).await;
+    assert_eq!(SECRET_1_NAME, trace.name);
+    assert_eq!(
+        vec!["MemorySecretsProvider"],
+        trace.layers().collect::<Vec<_>>()
+    );
+    assert!(matches!(
+        trace.outcome,
+        TraceOutcome::Found {
+            is_current: Some(true),
+            ..
+        }
+    ));
+
+    let trace = provider.resolve_trace("non-existent-secret").await;
+    assert_eq!(TraceOutcome::NotFound, trace.outcome);
+}
+
+#[tokio::test]
+async fn traces_every_layer_of_wrapped_providers() {
+    let provider = NamingProvider::new(load_test_provider(), NamingConvention::env());
+
+    let trace = provider.resolve_trace("secret.2").await;
+    assert_eq!("secret.2", trace.name);
+    assert_eq!(
+        vec!["NamingProvider", "MemorySecretsProvider"],
+        trace.layers().collect::<Vec<_>>()
+    );
+    assert_eq!(vec!["Name translated to SECRET_2"], trace.steps[0].details);
+    assert!(trace.steps[1].details.is_empty());
+    assert!(trace.duration() >= trace.steps[1].duration);
+    assert!(matches!(trace.outcome, TraceOutcome::Found { .. }));
+}
+
+#[cfg(feature = "chaos")]
+#[tokio::test]
+async fn traces_chaos_misbehaviors() {
+    use secrets_provider::chaos::ChaosProvider;
+
+    let provider = ChaosProvider::new(load_test_provider()).with_errors(1.0);
+
+    let trace = provider.resolve_trace(SECRET_1_NAME).await;
+    assert_eq!(vec!["ChaosProvider"], trace.layers().collect::<Vec<_>>());
+    assert_eq!(vec!["Injected a failure"], trace.steps[0].details);
+    assert!(matches!(trace.outcome, TraceOutcome::Failed(_)));
+
+    let provider = ChaosProvider::new(load_test_provider()).with_truncated_values(1.0);
+
+    let trace = provider.resolve_trace(SECRET_1_NAME).await;
+    assert_eq!(
+        vec!["ChaosProvider", "MemorySecretsProvider"],
+        trace.layers().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec!["Truncated the value to half of its length"],
+        trace.steps[0].details
+    );
+}
@@ -0,0 +1,154 @@
+//! Tests that are specific to the Vault Agent rendered files implementation.
+//!
+//! Rendered files have a single version and values can be read both as strings and binaries, so
+//! the generic tests do not apply to this implementation.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use secrets_provider::{
+    implementations::file::FileFormat,
+    implementations::vault_agent::{RenderedFile, VaultAgentSecretsProvider},
+    SecretsProvider, SecretsProviderError,
+};
+use tempfile::TempDir;
+
+use crate::seeds::constants::*;
+
+/// Renders a file the way Vault Agent does: written aside, then renamed over the destination.
+fn render(path: &Path, content: &str) {
+    let rendered = path.with_extension("tmp");
+    std::fs::write(&rendered, content).unwrap();
+    std::fs::rename(rendered, path).unwrap();
+}
+
+fn load_test_provider() -> (VaultAgentSecretsProvider, TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    render(&dir.path().join("secret-1"), SECRET_1);
+    render(
+        &dir.path().join("config.json"),
+        &format!(r#"{{"{}": "{}"}}"#, SECRET_2_NAME, SECRET_2),
+    );
+
+    let provider = VaultAgentSecretsProvider::new(vec![
+        RenderedFile::value(SECRET_1_NAME, dir.path().join("secret-1")),
+        RenderedFile::document(dir.path().join("config.json"), FileFormat::Json),
+    ])
+    .unwrap();
+    (provider, dir)
+}
+
+#[tokio::test]
+async fn can_read_rendered_values_and_documents() {
+    let (provider, _dir) = load_test_provider();
+
+    let secret_1 = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(Some(true), secret_1.is_current());
+    assert_eq!(SECRET_1, secret_1.reveal());
+
+    let secret_2 = provider
+        .find::<String>(SECRET_2_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    let version = secret_2.version.clone();
+    assert_eq!(SECRET_2, secret_2.reveal());
+
+    let secret_2 = provider
+        .find_with_version::<String>(SECRET_2_NAME, &version)
+        .await
+        .unwrap();
+    assert!(secret_2.is_some());
+    let secret_2 = provider
+        .find_with_version::<String>(SECRET_2_NAME, "other")
+        .await
+        .unwrap();
+    assert!(secret_2.is_none());
+
+    let missing = provider
+        .find::<String>("non-existent-secret")
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
+#[tokio::test]
+async fn reloads_files_when_they_are_rendered_again() {
+    let (provider, dir) = load_test_provider();
+
+    render(&dir.path().join("secret-1"), SECRET_3);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let secret = provider
+            .find::<String>(SECRET_1_NAME)
+            .await
+            .unwrap()
+            .expect("Secret not found")
+            .reveal();
+        if secret == SECRET_3 {
+            break;
+        }
+        assert!(Instant::now() < deadline, "Rendered file was not reloaded");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+}
+
+#[tokio::test]
+async fn publishes_changes_of_rendered_files() {
+    let (provider, dir) = load_test_provider();
+    let mut updates = provider.subscribe();
+
+    render(&dir.path().join("secret-1"), SECRET_3);
+
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.recv())
+        .await
+        .expect("No update was published")
+        .expect("Updates were closed");
+    assert_eq!(SECRET_1_NAME, update.name);
+
+    let secret = provider
+        .find::<String>(SECRET_1_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(update.version, Some(secret.version.clone()));
+    assert_eq!(SECRET_3, secret.reveal());
+}
+
+#[tokio::test]
+async fn keeps_previous_secrets_if_rendered_files_are_invalid() {
+    let (provider, dir) = load_test_provider();
+
+    render(&dir.path().join("config.json"), "{ not json");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let secret_2 = provider
+        .find::<String>(SECRET_2_NAME)
+        .await
+        .unwrap()
+        .expect("Secret not found");
+    assert_eq!(SECRET_2, secret_2.reveal());
+}
+
+#[tokio::test]
+async fn secrets_rendered_twice_are_rejected() {
+    let (_, dir) = load_test_provider();
+    render(
+        &dir.path().join("other.json"),
+        &format!(r#"{{"{}": "{}"}}"#, SECRET_1_NAME, SECRET_1),
+    );
+
+    let result = VaultAgentSecretsProvider::new(vec![
+        RenderedFile::value(SECRET_1_NAME, dir.path().join("secret-1")),
+        RenderedFile::document(dir.path().join("other.json"), FileFormat::Json),
+    ]);
+    match result {
+        Err(SecretsProviderError::Initialization(_)) => (),
+        r => panic!("Should have failed with Initialization error: {:?}", r),
+    }
+}
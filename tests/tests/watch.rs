@@ -0,0 +1,188 @@
+//! Tests of the change notifications semantics.
+
+use std::time::Duration;
+
+use secrets_provider::watch::{SecretUpdate, UpdateSender, WatchLag};
+
+fn update(name: &str, version: &str) -> SecretUpdate {
+    SecretUpdate {
+        name: name.to_string(),
+        version: Some(version.to_string()),
+    }
+}
+
+#[tokio::test]
+async fn updates_of_the_same_secret_are_conflated() {
+    let sender = UpdateSender::new();
+    let mut receiver = sender.subscribe();
+
+    sender.send(update("a", "1"));
+    sender.send(update("b", "1"));
+    sender.send(update("a", "2"));
+    sender.send(update("a", "3"));
+    assert_eq!(
+        WatchLag {
+            pending: 2,
+            conflated: 2
+        },
+        receiver.lag()
+    );
+
+    // The latest update of a keeps the place of the first one
+    assert_eq!(Some(update("a", "3")), receiver.recv().await);
+    assert_eq!(Some(update("b", "1")), receiver.recv().await);
+    assert_eq!(None, receiver.try_recv());
+    assert_eq!(0, receiver.lag().pending);
+}
+
+#[tokio::test]
+async fn receivers_are_woken_up_by_updates() {
+    let sender = UpdateSender::new();
+    let mut receiver = sender.subscribe();
+
+    let waiting = tokio::spawn(async move { receiver.recv().await });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    sender.send(update("a", "1"));
+
+    let received = tokio::time::timeout(Duration::from_secs(5), waiting)
+        .await
+        .expect("Receiver was not woken up")
+        .unwrap();
+    assert_eq!(Some(update("a", "1")), received);
+}
+
+#[tokio::test]
+async fn receivers_are_closed_when_the_sender_is_dropped() {
+    let sender = UpdateSender::new();
+    let mut receiver = sender.subscribe();
+    let dropped = sender.subscribe();
+    drop(dropped);
+    assert_eq!(1, sender.receiver_count());
+
+    sender.send(update("a", "1"));
+    drop(sender);
+
+    // Pending updates are still received
+    assert_eq!(Some(update("a", "1")), receiver.recv().await);
+    assert_eq!(None, receiver.recv().await);
+}
+
+#[tokio::test]
+async fn sender_is_closed_once_every_receiver_is_dropped() {
+    let sender = UpdateSender::new();
+    let first = sender.subscribe();
+    let second = sender.subscribe();
+
+    let closed = tokio::time::timeout(Duration::from_millis(50), sender.closed()).await;
+    assert!(closed.is_err(), "The sender still has receivers");
+
+    drop(first);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(second);
+    });
+    tokio::time::timeout(Duration::from_secs(5), sender.closed())
+        .await
+        .expect("The sender was not closed");
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn watcher_polls_version_changes() {
+    use std::sync::Arc;
+
+    use secrets_provider::implementations::memory::MemorySecretsProvider;
+    use secrets_provider::watch::SecretWatcher;
+    use secrets_provider::{DeleteOptions, SecretsWriter};
+
+    let provider = Arc::new(MemorySecretsProvider::new());
+    provider
+        .create_secret("database-password", "first".into())
+        .await
+        .unwrap();
+    let watcher =
+        SecretWatcher::new(Arc::clone(&provider)).with_interval(Duration::from_millis(10));
+    let mut receiver = watcher.watch("database-password");
+
+    // The first lookup is not an update
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(None, receiver.try_recv());
+
+    let version = provider
+        .put_secret_value("database-password", "second".into())
+        .await
+        .unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .expect("No update received");
+    assert_eq!(
+        Some(SecretUpdate {
+            name: "database-password".to_string(),
+            version,
+        }),
+        received
+    );
+
+    provider
+        .delete_secret("database-password", DeleteOptions::Force)
+        .await
+        .unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .expect("No update received");
+    assert_eq!(None, received.unwrap().version);
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn watcher_with_a_zero_interval_still_polls() {
+    use std::sync::Arc;
+
+    use secrets_provider::implementations::memory::MemorySecretsProvider;
+    use secrets_provider::watch::SecretWatcher;
+    use secrets_provider::SecretsWriter;
+
+    let provider = Arc::new(MemorySecretsProvider::new());
+    provider
+        .create_secret("database-password", "first".into())
+        .await
+        .unwrap();
+    let watcher = SecretWatcher::new(Arc::clone(&provider)).with_interval(Duration::ZERO);
+    let mut receiver = watcher.watch("database-password");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let version = provider
+        .put_secret_value("database-password", "second".into())
+        .await
+        .unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+        .await
+        .expect("No update received");
+    assert_eq!(version, received.unwrap().version);
+}
+
+#[cfg(feature = "memory")]
+#[tokio::test]
+async fn watcher_releases_the_provider_once_receivers_are_dropped() {
+    use std::sync::Arc;
+
+    use secrets_provider::implementations::memory::MemorySecretsProvider;
+    use secrets_provider::watch::SecretWatcher;
+
+    let provider = Arc::new(MemorySecretsProvider::new());
+    let watcher = SecretWatcher::new(Arc::clone(&provider)).with_interval(Duration::from_secs(60));
+    let receiver = watcher.watch("database-password");
+    drop(watcher);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(2, Arc::strong_count(&provider));
+
+    // Polling stops without waiting for the next lookup
+    drop(receiver);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while Arc::strong_count(&provider) > 1 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("The provider was not released");
+}